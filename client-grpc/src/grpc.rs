@@ -14,6 +14,944 @@ pub struct ReadyResponse {
     #[prost(bool, tag = "1")]
     pub ready: bool,
 }
+/// A point of a planned flight path
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PathPoint {
+    /// Degrees of latitude
+    #[prost(double, tag = "1")]
+    pub latitude: f64,
+    /// Degrees of longitude
+    #[prost(double, tag = "2")]
+    pub longitude: f64,
+    /// Altitude in meters (WGS84)
+    #[prost(float, tag = "3")]
+    pub altitude_meters: f32,
+}
+/// A flight plan
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightPlan {
+    /// Unique identifier, used as the operational intent ID
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// Identifier of the flight reporting telemetry for this plan,
+    ///   defaults to the flight plan ID
+    #[prost(string, optional, tag = "2")]
+    pub session_id: ::core::option::Option<::prost::alloc::string::String>,
+    /// Priority of the flight, higher values take precedence
+    #[prost(int32, tag = "3")]
+    pub priority: i32,
+    /// State of the flight plan
+    #[prost(enumeration = "FlightPlanState", tag = "4")]
+    pub state: i32,
+    /// Planned departure time
+    #[prost(message, optional, tag = "5")]
+    pub time_start: ::core::option::Option<::prost_types::Timestamp>,
+    /// Planned arrival time
+    #[prost(message, optional, tag = "6")]
+    pub time_end: ::core::option::Option<::prost_types::Timestamp>,
+    /// Planned path, from departure to arrival
+    #[prost(message, repeated, tag = "7")]
+    pub path: ::prost::alloc::vec::Vec<PathPoint>,
+    /// Vertiport the flight departs from, if any
+    #[prost(string, optional, tag = "8")]
+    pub origin_vertiport_id: ::core::option::Option<::prost::alloc::string::String>,
+    /// Vertiport the flight arrives at, if any
+    #[prost(string, optional, tag = "9")]
+    pub target_vertiport_id: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Put Flight Plan Response object
+#[derive(Eq, Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PutFlightPlanResponse {
+    /// Version of the operational intent after the update
+    #[prost(int32, tag = "1")]
+    pub version: i32,
+}
+/// Delete Flight Plan Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteFlightPlanRequest {
+    /// ID of the flight plan to remove
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Delete Flight Plan Response object
+#[derive(Eq, Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteFlightPlanResponse {
+    /// True if the flight plan was found and removed
+    #[prost(bool, tag = "1")]
+    pub deleted: bool,
+}
+/// A point of an area
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Coordinates {
+    /// Degrees of latitude
+    #[prost(double, tag = "1")]
+    pub latitude: f64,
+    /// Degrees of longitude
+    #[prost(double, tag = "2")]
+    pub longitude: f64,
+}
+/// A restricted area, for example a no-fly zone or a closed vertiport
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Constraint {
+    /// Unique identifier, used as the constraint ID
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// Kind of restriction, for example "NoFlyZone" or "VertiportClosure"
+    #[prost(string, optional, tag = "2")]
+    pub constraint_type: ::core::option::Option<::prost::alloc::string::String>,
+    /// Outline of the restricted area (at least 3 vertices)
+    #[prost(message, repeated, tag = "3")]
+    pub vertices: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Lower bound of the restricted area in meters (WGS84)
+    #[prost(float, tag = "4")]
+    pub altitude_meters_min: f32,
+    /// Upper bound of the restricted area in meters (WGS84)
+    #[prost(float, tag = "5")]
+    pub altitude_meters_max: f32,
+    /// Start of the restriction
+    #[prost(message, optional, tag = "6")]
+    pub time_start: ::core::option::Option<::prost_types::Timestamp>,
+    /// End of the restriction
+    #[prost(message, optional, tag = "7")]
+    pub time_end: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// Put Constraint Response object
+#[derive(Eq, Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PutConstraintResponse {
+    /// Version of the constraint after the update
+    #[prost(int32, tag = "1")]
+    pub version: i32,
+}
+/// Delete Constraint Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteConstraintRequest {
+    /// ID of the constraint to withdraw
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Delete Constraint Response object
+#[derive(Eq, Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteConstraintResponse {
+    /// True if the constraint was found and withdrawn
+    #[prost(bool, tag = "1")]
+    pub deleted: bool,
+}
+/// The hours a vertiport is open on a day of the week
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OperatingHours {
+    /// Day of the week, for example "Monday"
+    #[prost(string, tag = "1")]
+    pub day: ::prost::alloc::string::String,
+    /// Opening time in UTC, format 'HH:MM'
+    #[prost(string, tag = "2")]
+    pub open: ::prost::alloc::string::String,
+    /// Closing time in UTC, format 'HH:MM'
+    #[prost(string, tag = "3")]
+    pub close: ::prost::alloc::string::String,
+}
+/// A vertiport of a partner network
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Vertiport {
+    /// Unique identifier, of format 'provider:id'
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// Identifier of the partner provider of the vertiport
+    #[prost(string, tag = "2")]
+    pub provider: ::prost::alloc::string::String,
+    /// Human readable name
+    #[prost(string, tag = "3")]
+    pub name: ::prost::alloc::string::String,
+    /// Center of the vertiport
+    #[prost(message, optional, tag = "4")]
+    pub location: ::core::option::Option<Coordinates>,
+    /// Outline of the vertiport
+    #[prost(message, repeated, tag = "5")]
+    pub footprint: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Number of landing pads
+    #[prost(uint32, tag = "6")]
+    pub pad_count: u32,
+    /// Opening hours, the vertiport is closed on days without hours
+    #[prost(message, repeated, tag = "7")]
+    pub operating_hours: ::prost::alloc::vec::Vec<OperatingHours>,
+    /// Current status
+    #[prost(enumeration = "VertiportStatus", tag = "8")]
+    pub status: i32,
+}
+/// Get Partner Vertiports Request object
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetPartnerVertiportsRequest {
+    /// First corner of the area of interest
+    #[prost(message, optional, tag = "1")]
+    pub corner_1: ::core::option::Option<Coordinates>,
+    /// Opposite corner of the area of interest
+    #[prost(message, optional, tag = "2")]
+    pub corner_2: ::core::option::Option<Coordinates>,
+}
+/// Get Partner Vertiports Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetPartnerVertiportsResponse {
+    /// The vertiports in the area, ordered by ID
+    #[prost(message, repeated, tag = "1")]
+    pub vertiports: ::prost::alloc::vec::Vec<Vertiport>,
+}
+/// Wind reported by a meteorological report
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Wind {
+    /// Direction the wind blows from in degrees true, unset if variable
+    #[prost(uint32, optional, tag = "1")]
+    pub direction_degrees: ::core::option::Option<u32>,
+    /// Mean wind speed in knots
+    #[prost(float, tag = "2")]
+    pub speed_knots: f32,
+    /// Gust speed in knots, if reported
+    #[prost(float, optional, tag = "3")]
+    pub gust_knots: ::core::option::Option<f32>,
+}
+/// A parsed METAR or TAF
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WeatherReport {
+    /// Kind of report
+    #[prost(enumeration = "WeatherReportType", tag = "1")]
+    pub report_type: i32,
+    /// ICAO identifier of the reporting station
+    #[prost(string, tag = "2")]
+    pub station: ::prost::alloc::string::String,
+    /// Time of the observation (METAR) or of issue (TAF)
+    #[prost(message, optional, tag = "3")]
+    pub issued: ::core::option::Option<::prost_types::Timestamp>,
+    /// Start of the validity of a forecast
+    #[prost(message, optional, tag = "4")]
+    pub valid_from: ::core::option::Option<::prost_types::Timestamp>,
+    /// End of the validity of a forecast
+    #[prost(message, optional, tag = "5")]
+    pub valid_to: ::core::option::Option<::prost_types::Timestamp>,
+    /// The (prevailing) wind
+    #[prost(message, optional, tag = "6")]
+    pub wind: ::core::option::Option<Wind>,
+    /// Prevailing visibility in meters, 10000 or more is reported as 10000
+    #[prost(float, optional, tag = "7")]
+    pub visibility_meters: ::core::option::Option<f32>,
+    /// Height of the lowest broken or overcast layer, or vertical visibility, in feet
+    #[prost(uint32, optional, tag = "8")]
+    pub ceiling_feet: ::core::option::Option<u32>,
+    /// Altimeter setting in hectopascal
+    #[prost(float, optional, tag = "9")]
+    pub qnh_hpa: ::core::option::Option<f32>,
+    /// Air temperature in degrees Celsius
+    #[prost(sint32, optional, tag = "10")]
+    pub temperature_celsius: ::core::option::Option<i32>,
+    /// Dew point in degrees Celsius
+    #[prost(sint32, optional, tag = "11")]
+    pub dew_point_celsius: ::core::option::Option<i32>,
+    /// The report as received
+    #[prost(string, tag = "12")]
+    pub raw: ::prost::alloc::string::String,
+}
+/// The latest reports of a station
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StationWeather {
+    /// ICAO identifier of the station
+    #[prost(string, tag = "1")]
+    pub station: ::prost::alloc::string::String,
+    /// Location of the station, if known
+    #[prost(message, optional, tag = "2")]
+    pub location: ::core::option::Option<Coordinates>,
+    /// The latest observation
+    #[prost(message, optional, tag = "3")]
+    pub metar: ::core::option::Option<WeatherReport>,
+    /// The latest forecast
+    #[prost(message, optional, tag = "4")]
+    pub taf: ::core::option::Option<WeatherReport>,
+}
+/// Get Weather Request object, by station or by area
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetWeatherRequest {
+    /// ICAO identifier of the station, takes precedence over the area
+    #[prost(string, optional, tag = "1")]
+    pub station: ::core::option::Option<::prost::alloc::string::String>,
+    /// First corner of the area of interest
+    #[prost(message, optional, tag = "2")]
+    pub corner_1: ::core::option::Option<Coordinates>,
+    /// Opposite corner of the area of interest
+    #[prost(message, optional, tag = "3")]
+    pub corner_2: ::core::option::Option<Coordinates>,
+}
+/// Get Weather Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetWeatherResponse {
+    /// The matching stations, ordered by station
+    #[prost(message, repeated, tag = "1")]
+    pub stations: ::prost::alloc::vec::Vec<StationWeather>,
+}
+/// Get Broadcast Frames Request object
+/// The diagonal of the area may not exceed 7 km
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetBroadcastFramesRequest {
+    /// First corner of the area of interest
+    #[prost(message, optional, tag = "1")]
+    pub corner_1: ::core::option::Option<Coordinates>,
+    /// Opposite corner of the area of interest
+    #[prost(message, optional, tag = "2")]
+    pub corner_2: ::core::option::Option<Coordinates>,
+}
+/// An Open Drone ID message pack of a flight
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BroadcastFrame {
+    /// The ID of the flight
+    #[prost(string, tag = "1")]
+    pub flight_id: ::prost::alloc::string::String,
+    /// The Basic ID and Location/Vector messages of the flight,
+    ///   as a message pack of ASTM F3411 Broadcast Remote ID
+    #[prost(bytes = "vec", tag = "2")]
+    pub message_pack: ::prost::alloc::vec::Vec<u8>,
+}
+/// Get Broadcast Frames Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetBroadcastFramesResponse {
+    /// The frames of the flights in the area, ordered by flight ID
+    #[prost(message, repeated, tag = "1")]
+    pub frames: ::prost::alloc::vec::Vec<BroadcastFrame>,
+}
+/// A state of a partner aircraft
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AircraftState {
+    /// Time of the state
+    #[prost(message, optional, tag = "1")]
+    pub timestamp: ::core::option::Option<::prost_types::Timestamp>,
+    /// Operational status of the aircraft
+    #[prost(enumeration = "OperationalStatus", tag = "2")]
+    pub status: i32,
+    /// Position of the aircraft
+    #[prost(message, optional, tag = "3")]
+    pub position: ::core::option::Option<Coordinates>,
+    /// Geodetic altitude in meters, -1000 if unknown
+    #[prost(float, tag = "4")]
+    pub altitude_meters: f32,
+    /// Track in degrees clockwise from true north, 361 if unknown
+    #[prost(float, tag = "5")]
+    pub track_degrees: f32,
+    /// Ground speed in meters per second, 255 if unknown
+    #[prost(float, tag = "6")]
+    pub ground_speed_mps: f32,
+    /// Vertical speed in meters per second, positive up, 63 if unknown
+    #[prost(float, tag = "7")]
+    pub vertical_speed_mps: f32,
+}
+/// Push Telemetry Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PushTelemetryRequest {
+    /// Identifier of the pushing provider, optional, must be the provider
+    ///   of the bearer token in the authorization metadata if set
+    #[prost(string, tag = "1")]
+    pub provider_id: ::prost::alloc::string::String,
+    /// Identifier of the flight, unique to the provider
+    #[prost(string, tag = "2")]
+    pub flight_id: ::prost::alloc::string::String,
+    /// Type of the aircraft
+    #[prost(enumeration = "AircraftType", tag = "3")]
+    pub aircraft_type: i32,
+    /// If this is a simulated flight
+    #[prost(bool, tag = "4")]
+    pub simulated: bool,
+    /// States of the aircraft, oldest first
+    #[prost(message, repeated, tag = "5")]
+    pub states: ::prost::alloc::vec::Vec<AircraftState>,
+}
+/// Push Telemetry Response object
+#[derive(Eq, Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PushTelemetryResponse {
+    /// Number of new states
+    #[prost(uint32, tag = "1")]
+    pub accepted: u32,
+    /// Number of states that were already received
+    #[prost(uint32, tag = "2")]
+    pub duplicates: u32,
+}
+/// Position of a Remote ID aircraft
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RidAircraftPosition {
+    /// Degrees of latitude
+    #[prost(double, tag = "1")]
+    pub latitude: f64,
+    /// Degrees of longitude
+    #[prost(double, tag = "2")]
+    pub longitude: f64,
+    /// Geodetic altitude in meters, -1000 if unknown
+    #[prost(float, tag = "3")]
+    pub altitude_meters: f32,
+    /// Horizontal accuracy of the position
+    #[prost(enumeration = "HorizontalAccuracy", tag = "4")]
+    pub accuracy_h: i32,
+    /// Vertical accuracy of the position
+    #[prost(enumeration = "VerticalAccuracy", tag = "5")]
+    pub accuracy_v: i32,
+    /// If the position is extrapolated rather than reported
+    #[prost(bool, tag = "6")]
+    pub extrapolated: bool,
+    /// Pressure altitude in meters, -1000 if unknown
+    #[prost(float, tag = "7")]
+    pub pressure_altitude_meters: f32,
+    /// Height in meters, -1000 if unknown
+    #[prost(float, tag = "8")]
+    pub height_meters: f32,
+    /// Reference of the height
+    #[prost(enumeration = "HeightReference", tag = "9")]
+    pub height_reference: i32,
+}
+/// State of a Remote ID aircraft
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RidAircraftState {
+    /// Time of the state
+    #[prost(message, optional, tag = "1")]
+    pub timestamp: ::core::option::Option<::prost_types::Timestamp>,
+    /// Accuracy of the time in seconds
+    #[prost(float, tag = "2")]
+    pub timestamp_accuracy: f32,
+    /// Operational status of the aircraft
+    #[prost(enumeration = "OperationalStatus", tag = "3")]
+    pub status: i32,
+    /// Position of the aircraft
+    #[prost(message, optional, tag = "4")]
+    pub position: ::core::option::Option<RidAircraftPosition>,
+    /// Track in degrees clockwise from true north, 361 if unknown
+    #[prost(float, tag = "5")]
+    pub track_degrees: f32,
+    /// Ground speed in meters per second, 255 if unknown
+    #[prost(float, tag = "6")]
+    pub ground_speed_mps: f32,
+    /// Accuracy of the speed
+    #[prost(enumeration = "SpeedAccuracy", tag = "7")]
+    pub speed_accuracy: i32,
+    /// Vertical speed in meters per second, positive up, 63 if unknown
+    #[prost(float, tag = "8")]
+    pub vertical_speed_mps: f32,
+}
+/// A recent position of a Remote ID aircraft
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RidRecentPosition {
+    /// Time of the position
+    #[prost(message, optional, tag = "1")]
+    pub time: ::core::option::Option<::prost_types::Timestamp>,
+    /// Position of the aircraft
+    #[prost(message, optional, tag = "2")]
+    pub position: ::core::option::Option<RidAircraftPosition>,
+}
+/// An altitude with its reference and units
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VolumeAltitude {
+    /// Value of the altitude
+    #[prost(double, tag = "1")]
+    pub value: f64,
+    /// Reference of the altitude, for example "W84"
+    #[prost(string, tag = "2")]
+    pub reference: ::prost::alloc::string::String,
+    /// Units of the altitude, for example "M"
+    #[prost(string, tag = "3")]
+    pub units: ::prost::alloc::string::String,
+}
+/// A polygon outline
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OutlinePolygon {
+    /// Vertices of the polygon
+    #[prost(message, repeated, tag = "1")]
+    pub vertices: ::prost::alloc::vec::Vec<Coordinates>,
+}
+/// A circle outline
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OutlineCircle {
+    /// Center of the circle
+    #[prost(message, optional, tag = "1")]
+    pub center: ::core::option::Option<Coordinates>,
+    /// Radius of the circle
+    #[prost(float, tag = "2")]
+    pub radius: f32,
+    /// Units of the radius, for example "M"
+    #[prost(string, tag = "3")]
+    pub radius_units: ::prost::alloc::string::String,
+}
+/// A volume of the operating area of a flight
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OperatingVolume {
+    /// Polygon outline of the volume, if any
+    #[prost(message, optional, tag = "1")]
+    pub outline_polygon: ::core::option::Option<OutlinePolygon>,
+    /// Circle outline of the volume, if any
+    #[prost(message, optional, tag = "2")]
+    pub outline_circle: ::core::option::Option<OutlineCircle>,
+    /// Lower bound of the volume, if any
+    #[prost(message, optional, tag = "3")]
+    pub altitude_lower: ::core::option::Option<VolumeAltitude>,
+    /// Upper bound of the volume, if any
+    #[prost(message, optional, tag = "4")]
+    pub altitude_upper: ::core::option::Option<VolumeAltitude>,
+    /// Start of the volume
+    #[prost(message, optional, tag = "5")]
+    pub time_start: ::core::option::Option<::prost_types::Timestamp>,
+    /// End of the volume
+    #[prost(message, optional, tag = "6")]
+    pub time_end: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// A flight reported by Remote ID
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RidFlight {
+    /// Identifier of the flight
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// Type of the aircraft
+    #[prost(enumeration = "AircraftType", tag = "2")]
+    pub aircraft_type: i32,
+    /// Current state of the aircraft
+    #[prost(message, optional, tag = "3")]
+    pub current_state: ::core::option::Option<RidAircraftState>,
+    /// Number of aircraft in the operating area
+    #[prost(int32, tag = "4")]
+    pub aircraft_count: i32,
+    /// Volumes of the operating area
+    #[prost(message, repeated, tag = "5")]
+    pub volumes: ::prost::alloc::vec::Vec<OperatingVolume>,
+    /// If this is a simulated flight
+    #[prost(bool, tag = "6")]
+    pub simulated: bool,
+    /// Recent positions of the aircraft, oldest first
+    #[prost(message, repeated, tag = "7")]
+    pub recent_positions: ::prost::alloc::vec::Vec<RidRecentPosition>,
+}
+/// Flights in an area, the Protobuf encoding of the REST flight queries
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetFlightsResponse {
+    /// Time of the response
+    #[prost(message, optional, tag = "1")]
+    pub timestamp: ::core::option::Option<::prost_types::Timestamp>,
+    /// Flights in the area
+    #[prost(message, repeated, tag = "2")]
+    pub flights: ::prost::alloc::vec::Vec<RidFlight>,
+    /// If no ISAs are present in the area
+    #[prost(bool, tag = "3")]
+    pub no_isas_present: bool,
+}
+/// State of a flight plan, mirrors the ASTM F3548 operational intent states
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FlightPlanState {
+    /// Accepted, the flight has not started
+    Accepted = 0,
+    /// The flight is in progress and conforming to its plan
+    Activated = 1,
+    /// The flight left its planned path but is expected to return
+    Nonconforming = 2,
+    /// The flight left its planned path and will not return
+    Contingent = 3,
+}
+impl FlightPlanState {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            FlightPlanState::Accepted => "ACCEPTED",
+            FlightPlanState::Activated => "ACTIVATED",
+            FlightPlanState::Nonconforming => "NONCONFORMING",
+            FlightPlanState::Contingent => "CONTINGENT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ACCEPTED" => Some(Self::Accepted),
+            "ACTIVATED" => Some(Self::Activated),
+            "NONCONFORMING" => Some(Self::Nonconforming),
+            "CONTINGENT" => Some(Self::Contingent),
+            _ => None,
+        }
+    }
+}
+/// Status of a vertiport
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum VertiportStatus {
+    /// The vertiport accepts arrivals and departures
+    Open = 0,
+    /// The vertiport accepts emergency landings only
+    Restricted = 1,
+    /// The vertiport is closed
+    Closed = 2,
+}
+impl VertiportStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            VertiportStatus::Open => "OPEN",
+            VertiportStatus::Restricted => "RESTRICTED",
+            VertiportStatus::Closed => "CLOSED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "OPEN" => Some(Self::Open),
+            "RESTRICTED" => Some(Self::Restricted),
+            "CLOSED" => Some(Self::Closed),
+            _ => None,
+        }
+    }
+}
+/// Kind of a meteorological report
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum WeatherReportType {
+    /// Observed weather (METAR or SPECI)
+    Metar = 0,
+    /// Forecast weather (TAF)
+    Taf = 1,
+}
+impl WeatherReportType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            WeatherReportType::Metar => "METAR",
+            WeatherReportType::Taf => "TAF",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "METAR" => Some(Self::Metar),
+            "TAF" => Some(Self::Taf),
+            _ => None,
+        }
+    }
+}
+/// Type of an aircraft, mirrors the ASTM F3411 UA types
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AircraftType {
+    NotDeclared = 0,
+    Aeroplane = 1,
+    Helicopter = 2,
+    Gyroplane = 3,
+    HybridLift = 4,
+    Ornithopter = 5,
+    Glider = 6,
+    Kite = 7,
+    FreeBalloon = 8,
+    CaptiveBalloon = 9,
+    Airship = 10,
+    FreeFallOrParachute = 11,
+    Rocket = 12,
+    TetheredPoweredAircraft = 13,
+    GroundObstacle = 14,
+    Other = 15,
+}
+impl AircraftType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            AircraftType::NotDeclared => "NOT_DECLARED",
+            AircraftType::Aeroplane => "AEROPLANE",
+            AircraftType::Helicopter => "HELICOPTER",
+            AircraftType::Gyroplane => "GYROPLANE",
+            AircraftType::HybridLift => "HYBRID_LIFT",
+            AircraftType::Ornithopter => "ORNITHOPTER",
+            AircraftType::Glider => "GLIDER",
+            AircraftType::Kite => "KITE",
+            AircraftType::FreeBalloon => "FREE_BALLOON",
+            AircraftType::CaptiveBalloon => "CAPTIVE_BALLOON",
+            AircraftType::Airship => "AIRSHIP",
+            AircraftType::FreeFallOrParachute => "FREE_FALL_OR_PARACHUTE",
+            AircraftType::Rocket => "ROCKET",
+            AircraftType::TetheredPoweredAircraft => "TETHERED_POWERED_AIRCRAFT",
+            AircraftType::GroundObstacle => "GROUND_OBSTACLE",
+            AircraftType::Other => "OTHER",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "NOT_DECLARED" => Some(Self::NotDeclared),
+            "AEROPLANE" => Some(Self::Aeroplane),
+            "HELICOPTER" => Some(Self::Helicopter),
+            "GYROPLANE" => Some(Self::Gyroplane),
+            "HYBRID_LIFT" => Some(Self::HybridLift),
+            "ORNITHOPTER" => Some(Self::Ornithopter),
+            "GLIDER" => Some(Self::Glider),
+            "KITE" => Some(Self::Kite),
+            "FREE_BALLOON" => Some(Self::FreeBalloon),
+            "CAPTIVE_BALLOON" => Some(Self::CaptiveBalloon),
+            "AIRSHIP" => Some(Self::Airship),
+            "FREE_FALL_OR_PARACHUTE" => Some(Self::FreeFallOrParachute),
+            "ROCKET" => Some(Self::Rocket),
+            "TETHERED_POWERED_AIRCRAFT" => Some(Self::TetheredPoweredAircraft),
+            "GROUND_OBSTACLE" => Some(Self::GroundObstacle),
+            "OTHER" => Some(Self::Other),
+            _ => None,
+        }
+    }
+}
+/// Operational status of an aircraft, mirrors the ASTM F3411 statuses
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum OperationalStatus {
+    Undeclared = 0,
+    Ground = 1,
+    Airborne = 2,
+    Emergency = 3,
+    RemoteIdSystemFailure = 4,
+}
+impl OperationalStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            OperationalStatus::Undeclared => "UNDECLARED",
+            OperationalStatus::Ground => "GROUND",
+            OperationalStatus::Airborne => "AIRBORNE",
+            OperationalStatus::Emergency => "EMERGENCY",
+            OperationalStatus::RemoteIdSystemFailure => "REMOTE_ID_SYSTEM_FAILURE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "UNDECLARED" => Some(Self::Undeclared),
+            "GROUND" => Some(Self::Ground),
+            "AIRBORNE" => Some(Self::Airborne),
+            "EMERGENCY" => Some(Self::Emergency),
+            "REMOTE_ID_SYSTEM_FAILURE" => Some(Self::RemoteIdSystemFailure),
+            _ => None,
+        }
+    }
+}
+/// Horizontal accuracy of a Remote ID position, mirrors the ASTM F3411 values
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum HorizontalAccuracy {
+    HaUnknown = 0,
+    Ha10nmPlus = 1,
+    Ha10nm = 2,
+    Ha4nm = 3,
+    Ha2nm = 4,
+    Ha1nm = 5,
+    Ha05nm = 6,
+    Ha03nm = 7,
+    Ha01nm = 8,
+    Ha005nm = 9,
+    Ha30m = 10,
+    Ha10m = 11,
+    Ha3m = 12,
+    Ha1m = 13,
+}
+impl HorizontalAccuracy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            HorizontalAccuracy::HaUnknown => "HA_UNKNOWN",
+            HorizontalAccuracy::Ha10nmPlus => "HA_10NM_PLUS",
+            HorizontalAccuracy::Ha10nm => "HA_10NM",
+            HorizontalAccuracy::Ha4nm => "HA_4NM",
+            HorizontalAccuracy::Ha2nm => "HA_2NM",
+            HorizontalAccuracy::Ha1nm => "HA_1NM",
+            HorizontalAccuracy::Ha05nm => "HA_05NM",
+            HorizontalAccuracy::Ha03nm => "HA_03NM",
+            HorizontalAccuracy::Ha01nm => "HA_01NM",
+            HorizontalAccuracy::Ha005nm => "HA_005NM",
+            HorizontalAccuracy::Ha30m => "HA_30M",
+            HorizontalAccuracy::Ha10m => "HA_10M",
+            HorizontalAccuracy::Ha3m => "HA_3M",
+            HorizontalAccuracy::Ha1m => "HA_1M",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "HA_UNKNOWN" => Some(Self::HaUnknown),
+            "HA_10NM_PLUS" => Some(Self::Ha10nmPlus),
+            "HA_10NM" => Some(Self::Ha10nm),
+            "HA_4NM" => Some(Self::Ha4nm),
+            "HA_2NM" => Some(Self::Ha2nm),
+            "HA_1NM" => Some(Self::Ha1nm),
+            "HA_05NM" => Some(Self::Ha05nm),
+            "HA_03NM" => Some(Self::Ha03nm),
+            "HA_01NM" => Some(Self::Ha01nm),
+            "HA_005NM" => Some(Self::Ha005nm),
+            "HA_30M" => Some(Self::Ha30m),
+            "HA_10M" => Some(Self::Ha10m),
+            "HA_3M" => Some(Self::Ha3m),
+            "HA_1M" => Some(Self::Ha1m),
+            _ => None,
+        }
+    }
+}
+/// Vertical accuracy of a Remote ID position, mirrors the ASTM F3411 values
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum VerticalAccuracy {
+    VaUnknown = 0,
+    Va150mPlus = 1,
+    Va150m = 2,
+    Va45m = 3,
+    Va25m = 4,
+    Va10m = 5,
+    Va3m = 6,
+    Va1m = 7,
+}
+impl VerticalAccuracy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            VerticalAccuracy::VaUnknown => "VA_UNKNOWN",
+            VerticalAccuracy::Va150mPlus => "VA_150M_PLUS",
+            VerticalAccuracy::Va150m => "VA_150M",
+            VerticalAccuracy::Va45m => "VA_45M",
+            VerticalAccuracy::Va25m => "VA_25M",
+            VerticalAccuracy::Va10m => "VA_10M",
+            VerticalAccuracy::Va3m => "VA_3M",
+            VerticalAccuracy::Va1m => "VA_1M",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "VA_UNKNOWN" => Some(Self::VaUnknown),
+            "VA_150M_PLUS" => Some(Self::Va150mPlus),
+            "VA_150M" => Some(Self::Va150m),
+            "VA_45M" => Some(Self::Va45m),
+            "VA_25M" => Some(Self::Va25m),
+            "VA_10M" => Some(Self::Va10m),
+            "VA_3M" => Some(Self::Va3m),
+            "VA_1M" => Some(Self::Va1m),
+            _ => None,
+        }
+    }
+}
+/// Speed accuracy of a Remote ID state, mirrors the ASTM F3411 values
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SpeedAccuracy {
+    SaUnknown = 0,
+    Sa10mpsPlus = 1,
+    Sa10mps = 2,
+    Sa3mps = 3,
+    Sa1mps = 4,
+    Sa03mps = 5,
+}
+impl SpeedAccuracy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            SpeedAccuracy::SaUnknown => "SA_UNKNOWN",
+            SpeedAccuracy::Sa10mpsPlus => "SA_10MPS_PLUS",
+            SpeedAccuracy::Sa10mps => "SA_10MPS",
+            SpeedAccuracy::Sa3mps => "SA_3MPS",
+            SpeedAccuracy::Sa1mps => "SA_1MPS",
+            SpeedAccuracy::Sa03mps => "SA_03MPS",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "SA_UNKNOWN" => Some(Self::SaUnknown),
+            "SA_10MPS_PLUS" => Some(Self::Sa10mpsPlus),
+            "SA_10MPS" => Some(Self::Sa10mps),
+            "SA_3MPS" => Some(Self::Sa3mps),
+            "SA_1MPS" => Some(Self::Sa1mps),
+            "SA_03MPS" => Some(Self::Sa03mps),
+            _ => None,
+        }
+    }
+}
+/// Reference of the height of a Remote ID position
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum HeightReference {
+    TakeoffLocation = 0,
+    GroundLevel = 1,
+}
+impl HeightReference {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            HeightReference::TakeoffLocation => "TAKEOFF_LOCATION",
+            HeightReference::GroundLevel => "GROUND_LEVEL",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "TAKEOFF_LOCATION" => Some(Self::TakeoffLocation),
+            "GROUND_LEVEL" => Some(Self::GroundLevel),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 #[cfg(not(tarpaulin_include))]
 pub mod rpc_service_client {
@@ -103,5 +1041,168 @@ pub mod rpc_service_client {
             let path = http::uri::PathAndQuery::from_static("/grpc.RpcService/isReady");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// Shares a flight plan with other USSs as an ASTM F3548 operational intent
+        pub async fn put_flight_plan(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FlightPlan>,
+        ) -> Result<tonic::Response<super::PutFlightPlanResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/putFlightPlan",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Stops sharing a flight plan, for example when it was cancelled or completed
+        pub async fn delete_flight_plan(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteFlightPlanRequest>,
+        ) -> Result<tonic::Response<super::DeleteFlightPlanResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/deleteFlightPlan",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Publishes a restriction, such as a no-fly zone, as an ASTM F3548 constraint
+        pub async fn put_constraint(
+            &mut self,
+            request: impl tonic::IntoRequest<super::Constraint>,
+        ) -> Result<tonic::Response<super::PutConstraintResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/putConstraint",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Withdraws a published constraint
+        pub async fn delete_constraint(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteConstraintRequest>,
+        ) -> Result<tonic::Response<super::DeleteConstraintResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/deleteConstraint",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Finds the vertiports of partner networks in an area
+        pub async fn get_partner_vertiports(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetPartnerVertiportsRequest>,
+        ) -> Result<
+            tonic::Response<super::GetPartnerVertiportsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/getPartnerVertiports",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Gets the latest meteorological reports of a station or of an area
+        pub async fn get_weather(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetWeatherRequest>,
+        ) -> Result<tonic::Response<super::GetWeatherResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/getWeather",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Gets the flights in an area encoded as Open Drone ID message packs
+        pub async fn get_broadcast_frames(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetBroadcastFramesRequest>,
+        ) -> Result<tonic::Response<super::GetBroadcastFramesResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/getBroadcastFrames",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Pushes a batch of states of a partner flight
+        pub async fn push_telemetry(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PushTelemetryRequest>,
+        ) -> Result<tonic::Response<super::PushTelemetryResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/pushTelemetry",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
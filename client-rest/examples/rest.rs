@@ -36,6 +36,7 @@ async fn uss(
         let data = GetFlightsRequest {
             view: "0.0,0.0,0.0,0.0".to_string(),
             recent_positions_duration: 10.,
+            ..Default::default()
         };
 
         let uri = format!(
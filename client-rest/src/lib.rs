@@ -4,3 +4,19 @@
 pub mod types {
     include!("../../openapi/types.rs");
 }
+
+/// Encodings of flight responses, to decode the Protobuf, MessagePack
+///  and CBOR responses of the flight queries
+pub mod encodings {
+    use crate::types::*;
+    use svc_discovery_client_grpc::client as pb;
+
+    include!("../../openapi/encodings.rs");
+}
+
+/// KML and CZML exports of flights
+pub mod exports {
+    use crate::types::*;
+
+    include!("../../openapi/exports.rs");
+}
@@ -0,0 +1,354 @@
+/// Encodings of flight responses
+///
+/// JSON is the default encoding. Protobuf encodes the `GetFlightsResponse`
+///  message of the gRPC API, MessagePack and CBOR encode the same fields
+///  as JSON by name.
+
+use lib_common::time::{DateTime, Utc};
+use prost::Message;
+use std::fmt::{self, Display, Formatter};
+use strum::IntoEnumIterator;
+
+/// The media type of Protobuf encoded responses
+pub const PROTOBUF_MEDIA_TYPE: &str = "application/x-protobuf";
+
+/// The media type of MessagePack encoded responses
+pub const MESSAGEPACK_MEDIA_TYPE: &str = "application/msgpack";
+
+/// The media type of CBOR encoded responses
+pub const CBOR_MEDIA_TYPE: &str = "application/cbor";
+
+/// An encoding of flight responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// JSON
+    Json,
+
+    /// Protobuf
+    Protobuf,
+
+    /// MessagePack
+    MessagePack,
+
+    /// CBOR (RFC 8949)
+    Cbor,
+}
+
+impl Encoding {
+    /// The media type of the encoding
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            Encoding::Json => "application/json",
+            Encoding::Protobuf => PROTOBUF_MEDIA_TYPE,
+            Encoding::MessagePack => MESSAGEPACK_MEDIA_TYPE,
+            Encoding::Cbor => CBOR_MEDIA_TYPE,
+        }
+    }
+
+    /// The encoding of a media type, such as the Content-Type of a
+    ///  response. Parameters and the common aliases are accepted.
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        let essence = media_type.split(';').next().unwrap_or_default();
+        match essence.trim().to_ascii_lowercase().as_str() {
+            "application/json" => Some(Encoding::Json),
+            "application/x-protobuf" | "application/protobuf" | "application/vnd.google.protobuf" => {
+                Some(Encoding::Protobuf)
+            }
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Encoding::MessagePack)
+            }
+            "application/cbor" => Some(Encoding::Cbor),
+            _ => None,
+        }
+    }
+}
+
+/// Errors encoding or decoding flight responses
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EncodingError {
+    /// The response could not be encoded
+    Encode,
+
+    /// The bytes are not a response in the encoding
+    Decode,
+}
+
+impl std::error::Error for EncodingError {}
+
+impl Display for EncodingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodingError::Encode => write!(f, "Could not encode flights"),
+            EncodingError::Decode => write!(f, "Could not decode flights"),
+        }
+    }
+}
+
+/// The Protobuf value of an enum, by its position
+fn enum_index<T: IntoEnumIterator + PartialEq>(value: T) -> i32 {
+    T::iter().position(|v| v == value).unwrap_or_default() as i32
+}
+
+/// The enum value of a Protobuf value, by its position
+fn enum_value<T: IntoEnumIterator>(index: i32) -> Result<T, EncodingError> {
+    let index = usize::try_from(index).map_err(|_| EncodingError::Decode)?;
+    T::iter().nth(index).ok_or(EncodingError::Decode)
+}
+
+/// Convert a time to a Protobuf timestamp, None if the time is invalid
+fn time_to_proto(time: &Time) -> Option<prost_types::Timestamp> {
+    time.to_datetime().map(|dt| prost_types::Timestamp {
+        seconds: dt.timestamp(),
+        nanos: dt.timestamp_subsec_nanos() as i32,
+    })
+}
+
+/// Convert a Protobuf timestamp to a time, fails if missing or invalid
+fn time_from_proto(timestamp: Option<prost_types::Timestamp>) -> Result<Time, EncodingError> {
+    let timestamp = timestamp.ok_or(EncodingError::Decode)?;
+    let nanos = u32::try_from(timestamp.nanos).map_err(|_| EncodingError::Decode)?;
+    DateTime::<Utc>::from_timestamp(timestamp.seconds, nanos)
+        .map(Time::from)
+        .ok_or(EncodingError::Decode)
+}
+
+/// Convert a point to Protobuf coordinates
+fn point_to_proto(point: &LatLngPoint) -> pb::Coordinates {
+    pb::Coordinates {
+        latitude: point.lat,
+        longitude: point.lng,
+    }
+}
+
+/// Convert Protobuf coordinates to a point
+fn point_from_proto(coordinates: pb::Coordinates) -> LatLngPoint {
+    LatLngPoint {
+        lat: coordinates.latitude,
+        lng: coordinates.longitude,
+    }
+}
+
+/// Convert an altitude to Protobuf
+fn altitude_to_proto(altitude: &Altitude) -> pb::VolumeAltitude {
+    pb::VolumeAltitude {
+        value: altitude.value,
+        reference: altitude.reference.clone(),
+        units: altitude.units.clone(),
+    }
+}
+
+/// Convert a Protobuf altitude
+fn altitude_from_proto(altitude: pb::VolumeAltitude) -> Altitude {
+    Altitude {
+        value: altitude.value,
+        reference: altitude.reference,
+        units: altitude.units,
+    }
+}
+
+/// Convert a volume of an operating area to Protobuf
+fn volume_to_proto(volume: &Volume4D) -> pb::OperatingVolume {
+    let outline = &volume.volume;
+    pb::OperatingVolume {
+        outline_polygon: outline
+            .outline_polygon
+            .as_ref()
+            .map(|polygon| pb::OutlinePolygon {
+                vertices: polygon.vertices.iter().map(point_to_proto).collect(),
+            }),
+        outline_circle: outline
+            .outline_circle
+            .as_ref()
+            .map(|circle| pb::OutlineCircle {
+                center: Some(point_to_proto(&circle.center)),
+                radius: circle.radius.value,
+                radius_units: circle.radius.units.clone(),
+            }),
+        altitude_lower: outline.altitude_lower.as_ref().map(altitude_to_proto),
+        altitude_upper: outline.altitude_upper.as_ref().map(altitude_to_proto),
+        time_start: time_to_proto(&volume.time_start),
+        time_end: time_to_proto(&volume.time_end),
+    }
+}
+
+/// Convert a Protobuf volume of an operating area
+fn volume_from_proto(volume: pb::OperatingVolume) -> Result<Volume4D, EncodingError> {
+    let outline_circle = match volume.outline_circle {
+        Some(circle) => Some(Circle {
+            center: point_from_proto(circle.center.ok_or(EncodingError::Decode)?),
+            radius: Radius {
+                value: circle.radius,
+                units: circle.radius_units,
+            },
+        }),
+        None => None,
+    };
+
+    Ok(Volume4D {
+        volume: Volume3D {
+            outline_circle,
+            outline_polygon: volume.outline_polygon.map(|polygon| Polygon {
+                vertices: polygon.vertices.into_iter().map(point_from_proto).collect(),
+            }),
+            altitude_lower: volume.altitude_lower.map(altitude_from_proto),
+            altitude_upper: volume.altitude_upper.map(altitude_from_proto),
+        },
+        time_start: time_from_proto(volume.time_start)?,
+        time_end: time_from_proto(volume.time_end)?,
+    })
+}
+
+/// Convert a position to Protobuf
+fn position_to_proto(position: &RIDAircraftPosition) -> pb::RidAircraftPosition {
+    pb::RidAircraftPosition {
+        latitude: position.lat,
+        longitude: position.lng,
+        altitude_meters: position.alt,
+        accuracy_h: enum_index(position.accuracy_h),
+        accuracy_v: enum_index(position.accuracy_v),
+        extrapolated: position.extrapolated,
+        pressure_altitude_meters: position.pressure_alt,
+        height_meters: position.height.distance,
+        height_reference: enum_index(position.height.reference),
+    }
+}
+
+/// Convert a Protobuf position, fails if missing or invalid
+fn position_from_proto(
+    position: Option<pb::RidAircraftPosition>,
+) -> Result<RIDAircraftPosition, EncodingError> {
+    let position = position.ok_or(EncodingError::Decode)?;
+    Ok(RIDAircraftPosition {
+        lat: position.latitude,
+        lng: position.longitude,
+        alt: position.altitude_meters,
+        accuracy_h: enum_value(position.accuracy_h)?,
+        accuracy_v: enum_value(position.accuracy_v)?,
+        extrapolated: position.extrapolated,
+        pressure_alt: position.pressure_altitude_meters,
+        height: RIDHeight {
+            distance: position.height_meters,
+            reference: enum_value(position.height_reference)?,
+        },
+    })
+}
+
+/// Convert a flight to Protobuf
+fn flight_to_proto(flight: &RIDFlight) -> pb::RidFlight {
+    let state = &flight.current_state;
+    pb::RidFlight {
+        id: flight.id.clone(),
+        aircraft_type: enum_index(flight.aircraft_type),
+        current_state: Some(pb::RidAircraftState {
+            timestamp: time_to_proto(&state.timestamp),
+            timestamp_accuracy: state.timestamp_accuracy,
+            status: enum_index(state.operational_status),
+            position: Some(position_to_proto(&state.position)),
+            track_degrees: state.track,
+            ground_speed_mps: state.speed,
+            speed_accuracy: enum_index(state.speed_accuracy),
+            vertical_speed_mps: state.vertical_speed,
+        }),
+        aircraft_count: flight.operating_area.aircraft_count,
+        volumes: flight
+            .operating_area
+            .volumes
+            .iter()
+            .map(volume_to_proto)
+            .collect(),
+        simulated: flight.simulated,
+        recent_positions: flight
+            .recent_positions
+            .iter()
+            .map(|recent| pb::RidRecentPosition {
+                time: time_to_proto(&recent.time),
+                position: Some(position_to_proto(&recent.position)),
+            })
+            .collect(),
+    }
+}
+
+/// Convert a Protobuf flight, fails if a field is missing or invalid
+fn flight_from_proto(flight: pb::RidFlight) -> Result<RIDFlight, EncodingError> {
+    let state = flight.current_state.ok_or(EncodingError::Decode)?;
+    let current_state = RIDAircraftState {
+        timestamp: time_from_proto(state.timestamp)?,
+        timestamp_accuracy: state.timestamp_accuracy,
+        operational_status: enum_value(state.status)?,
+        position: position_from_proto(state.position)?,
+        track: state.track_degrees,
+        speed: state.ground_speed_mps,
+        speed_accuracy: enum_value(state.speed_accuracy)?,
+        vertical_speed: state.vertical_speed_mps,
+    };
+
+    Ok(RIDFlight {
+        id: flight.id,
+        aircraft_type: enum_value(flight.aircraft_type)?,
+        current_state,
+        operating_area: OperatingArea {
+            aircraft_count: flight.aircraft_count,
+            volumes: flight
+                .volumes
+                .into_iter()
+                .map(volume_from_proto)
+                .collect::<Result<Vec<Volume4D>, EncodingError>>()?,
+        },
+        simulated: flight.simulated,
+        recent_positions: flight
+            .recent_positions
+            .into_iter()
+            .map(|recent| {
+                Ok(RIDRecentAircraftPosition {
+                    time: time_from_proto(recent.time)?,
+                    position: position_from_proto(recent.position)?,
+                })
+            })
+            .collect::<Result<Vec<RIDRecentAircraftPosition>, EncodingError>>()?,
+    })
+}
+
+/// Encode a flights response
+pub fn encode_flights(
+    encoding: Encoding,
+    response: &GetFlightsResponse,
+) -> Result<Vec<u8>, EncodingError> {
+    match encoding {
+        Encoding::Json => serde_json::to_vec(response).map_err(|_| EncodingError::Encode),
+        Encoding::Protobuf => Ok(pb::GetFlightsResponse {
+            timestamp: time_to_proto(&response.timestamp),
+            flights: response.flights.iter().map(flight_to_proto).collect(),
+            no_isas_present: response.no_isas_present,
+        }
+        .encode_to_vec()),
+        Encoding::MessagePack => {
+            rmp_serde::to_vec_named(response).map_err(|_| EncodingError::Encode)
+        }
+        Encoding::Cbor => serde_cbor::to_vec(response).map_err(|_| EncodingError::Encode),
+    }
+}
+
+/// Decode a flights response
+pub fn decode_flights(
+    encoding: Encoding,
+    bytes: &[u8],
+) -> Result<GetFlightsResponse, EncodingError> {
+    match encoding {
+        Encoding::Json => serde_json::from_slice(bytes).map_err(|_| EncodingError::Decode),
+        Encoding::Protobuf => {
+            let response =
+                pb::GetFlightsResponse::decode(bytes).map_err(|_| EncodingError::Decode)?;
+            Ok(GetFlightsResponse {
+                timestamp: time_from_proto(response.timestamp)?,
+                flights: response
+                    .flights
+                    .into_iter()
+                    .map(flight_from_proto)
+                    .collect::<Result<Vec<RIDFlight>, EncodingError>>()?,
+                no_isas_present: response.no_isas_present,
+            })
+        }
+        Encoding::MessagePack => rmp_serde::from_slice(bytes).map_err(|_| EncodingError::Decode),
+        Encoding::Cbor => serde_cbor::from_slice(bytes).map_err(|_| EncodingError::Decode),
+    }
+}
@@ -0,0 +1,261 @@
+/// KML and CZML exports of flights for 3D visualisation tools
+///
+/// Each flight is a time-tagged track of its recent positions and current
+///  state. Tracks are colored by operational status, with half transparent
+///  colors for simulated flights, and get an icon by aircraft type.
+
+use lib_common::time::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Value};
+
+/// Location of the icons of the standard KML shapes
+const ICONS_URL: &str = "http://maps.google.com/mapfiles/kml/shapes";
+
+/// The media type of KML documents
+pub const KML_MEDIA_TYPE: &str = "application/vnd.google-earth.kml+xml";
+
+/// The media type of CZML documents
+pub const CZML_MEDIA_TYPE: &str = "application/czml+json";
+
+/// The color of a flight as RGBA
+pub fn flight_color(status: RIDOperationalStatus, simulated: bool) -> [u8; 4] {
+    let alpha = if simulated { 128 } else { 255 };
+    match status {
+        RIDOperationalStatus::Undeclared => [255, 255, 255, alpha],
+        RIDOperationalStatus::Ground => [158, 158, 158, alpha],
+        RIDOperationalStatus::Airborne => [0, 200, 83, alpha],
+        RIDOperationalStatus::Emergency => [213, 0, 0, alpha],
+        RIDOperationalStatus::RemoteIDSystemFailure => [255, 145, 0, alpha],
+    }
+}
+
+/// The URL of the icon of an aircraft type
+pub fn aircraft_icon(aircraft_type: UAType) -> String {
+    let shape = match aircraft_type {
+        UAType::Aeroplane | UAType::Glider | UAType::Ornithopter => "airports",
+        UAType::Helicopter | UAType::Gyroplane | UAType::HybridLift => "heliport",
+        UAType::Kite | UAType::FreeBalloon | UAType::CaptiveBalloon | UAType::Airship => {
+            "open-diamond"
+        }
+        UAType::FreeFallOrParachute | UAType::Rocket => "triangle",
+        UAType::TetheredPoweredAircraft => "target",
+        UAType::GroundObstacle => "caution",
+        UAType::NotDeclared | UAType::Other => "placemark_circle",
+    };
+
+    format!("{}/{}.png", ICONS_URL, shape)
+}
+
+/// The positions of a flight by time, oldest first, ending with the
+///  current state. Positions with an invalid time are left out.
+fn flight_track(flight: &RIDFlight) -> Vec<(DateTime<Utc>, &RIDAircraftPosition)> {
+    let mut track = flight
+        .recent_positions
+        .iter()
+        .map(|recent| (&recent.time, &recent.position))
+        .chain(std::iter::once((
+            &flight.current_state.timestamp,
+            &flight.current_state.position,
+        )))
+        .filter_map(|(time, position)| Some((time.to_datetime()?, position)))
+        .collect::<Vec<(DateTime<Utc>, &RIDAircraftPosition)>>();
+
+    // the current state is preferred over a recent position at that time
+    track.reverse();
+    track.sort_by_key(|(time, _)| *time);
+    track.dedup_by_key(|(time, _)| *time);
+    track
+}
+
+/// If every position of a track has a known altitude
+fn has_altitudes(track: &[(DateTime<Utc>, &RIDAircraftPosition)]) -> bool {
+    track
+        .iter()
+        .all(|(_, position)| position.alt != UNKNOWN_ALTITUDE)
+}
+
+/// Format a time for KML and CZML
+fn export_time(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Escape text for XML
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// The KML Placemark of a flight, if it has a position with a valid time
+fn kml_placemark(flight: &RIDFlight) -> Option<String> {
+    let track = flight_track(flight);
+    if track.is_empty() {
+        return None;
+    }
+
+    let state = &flight.current_state;
+    let [r, g, b, a] = flight_color(state.operational_status, flight.simulated);
+    let color = format!("{:02x}{:02x}{:02x}{:02x}", a, b, g, r);
+    let altitudes = has_altitudes(&track);
+    let altitude_mode = if altitudes { "absolute" } else { "clampToGround" };
+
+    let mut placemark = format!(
+        "<Placemark><name>{}</name>\
+<Style><IconStyle><color>{}</color><Icon><href>{}</href></Icon></IconStyle>\
+<LineStyle><color>{}</color><width>2</width></LineStyle></Style>\
+<ExtendedData>",
+        escape_xml(&flight.id),
+        color,
+        aircraft_icon(flight.aircraft_type),
+        color
+    );
+
+    let data = [
+        ("aircraft_type", flight.aircraft_type.to_string()),
+        ("operational_status", state.operational_status.to_string()),
+        ("simulated", flight.simulated.to_string()),
+        ("track", state.track.to_string()),
+        ("speed", state.speed.to_string()),
+        ("vertical_speed", state.vertical_speed.to_string()),
+    ];
+    for (name, value) in data {
+        placemark.push_str(&format!(
+            "<Data name=\"{}\"><value>{}</value></Data>",
+            name,
+            escape_xml(&value)
+        ));
+    }
+
+    placemark.push_str(&format!(
+        "</ExtendedData><gx:Track><altitudeMode>{}</altitudeMode>",
+        altitude_mode
+    ));
+    for (time, _) in &track {
+        placemark.push_str(&format!("<when>{}</when>", export_time(time)));
+    }
+    for (_, position) in &track {
+        let alt = if altitudes { position.alt } else { 0.0 };
+        placemark.push_str(&format!(
+            "<gx:coord>{} {} {}</gx:coord>",
+            position.lng, position.lat, alt
+        ));
+    }
+    placemark.push_str("</gx:Track></Placemark>");
+
+    Some(placemark)
+}
+
+/// Render flights as a KML document of time-tagged tracks, for Google
+///  Earth
+pub fn flights_to_kml(response: &GetFlightsResponse) -> String {
+    let mut kml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+<kml xmlns=\"http://www.opengis.net/kml/2.2\" xmlns:gx=\"http://www.google.com/kml/ext/2.2\">\
+<Document><name>Flights at {}</name>",
+        escape_xml(&response.timestamp.value)
+    );
+
+    for placemark in response.flights.iter().filter_map(kml_placemark) {
+        kml.push_str(&placemark);
+    }
+
+    kml.push_str("</Document></kml>");
+    kml
+}
+
+/// The CZML packet of the track of a flight, available until the end of
+///  the document
+fn czml_packet(
+    flight: &RIDFlight,
+    track: &[(DateTime<Utc>, &RIDAircraftPosition)],
+    end: &DateTime<Utc>,
+) -> Option<Value> {
+    let (epoch, _) = track.first()?;
+    let altitudes = has_altitudes(track);
+    let height_reference = if altitudes { "NONE" } else { "CLAMP_TO_GROUND" };
+
+    let mut samples = Vec::with_capacity(track.len() * 4);
+    for (time, position) in track {
+        let seconds = (*time - *epoch).num_milliseconds() as f64 / 1000.0;
+        let alt = if altitudes { position.alt as f64 } else { 0.0 };
+        samples.extend([seconds, position.lng, position.lat, alt]);
+    }
+
+    let state = &flight.current_state;
+    let color = flight_color(state.operational_status, flight.simulated);
+    Some(json!({
+        "id": flight.id,
+        "name": flight.id,
+        "availability": format!("{}/{}", export_time(epoch), export_time(end)),
+        "position": {
+            "epoch": export_time(epoch),
+            "cartographicDegrees": samples
+        },
+        "billboard": {
+            "image": aircraft_icon(flight.aircraft_type),
+            "color": { "rgba": color },
+            "scale": 0.5,
+            "heightReference": height_reference
+        },
+        "label": {
+            "text": flight.id,
+            "font": "12px sans-serif",
+            "pixelOffset": { "cartesian2": [0, -24] }
+        },
+        "path": {
+            "material": { "solidColor": { "color": { "rgba": color } } },
+            "width": 2,
+            "leadTime": 0
+        },
+        "properties": {
+            "aircraft_type": flight.aircraft_type,
+            "operational_status": state.operational_status,
+            "simulated": flight.simulated,
+            "track": state.track,
+            "speed": state.speed,
+            "vertical_speed": state.vertical_speed
+        }
+    }))
+}
+
+/// Render flights as a CZML document of time-tagged tracks, for Cesium.
+/// The clock of the document starts at the oldest position and ends at
+///  the time of the response, or at the latest position if later.
+pub fn flights_to_czml(response: &GetFlightsResponse) -> Value {
+    let tracks = response
+        .flights
+        .iter()
+        .map(|flight| (flight, flight_track(flight)))
+        .filter(|(_, track)| !track.is_empty())
+        .collect::<Vec<(&RIDFlight, Vec<(DateTime<Utc>, &RIDAircraftPosition)>)>>();
+
+    let time = response.timestamp.to_datetime().unwrap_or_else(Utc::now);
+    let times = tracks
+        .iter()
+        .flat_map(|(_, track)| [track.first(), track.last()])
+        .flatten()
+        .map(|(time, _)| *time);
+    let start = times.clone().min().unwrap_or(time).min(time);
+    let end = times.max().unwrap_or(time).max(time);
+
+    let mut packets = vec![json!({
+        "id": "document",
+        "name": format!("Flights at {}", response.timestamp.value),
+        "version": "1.0",
+        "clock": {
+            "interval": format!("{}/{}", export_time(&start), export_time(&end)),
+            "currentTime": export_time(&start),
+            "multiplier": 1,
+            "range": "CLAMPED"
+        }
+    })];
+
+    packets.extend(
+        tracks
+            .iter()
+            .filter_map(|(flight, track)| czml_packet(flight, track, &end)),
+    );
+
+    Value::Array(packets)
+}
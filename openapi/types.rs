@@ -2,23 +2,118 @@
 
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
-use lib_common::time::{SecondsFormat, Utc};
+use lib_common::time::{DateTime, SecondsFormat, Utc};
 use std::fmt::Debug;
 use strum::{EnumString, Display, EnumIter};
 
 /// RFC3339 format enum
 pub const RFC3339_FORMAT_STRING: &str = "RFC3339";
 
+/// Reported altitude when the altitude is unknown
+pub const UNKNOWN_ALTITUDE: f32 = -1000.0;
+
+/// Approximate length of one degree of latitude in meters
+pub const METERS_PER_DEGREE: f64 = 111_320.0;
+
 /// Example Request Body Information Type
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, IntoParams)]
 pub struct GetFlightsRequest {
     /// The area of this view as a string of format "lat1,lon1,lat2,lon2"
     pub view: String,
 
+    /// Recent positions duration
+    pub recent_positions_duration: f32,
+
+    /// Only report these aircraft types, as a comma separated list
+    ///  of UAType values, for example "Helicopter,HybridLift"
+    pub aircraft_types: Option<String>,
+
+    /// Do not report these aircraft types, as a comma separated list
+    ///  of UAType values, for example "FreeBalloon,GroundObstacle"
+    pub exclude_aircraft_types: Option<String>,
+
+    /// Only report aircraft with these operational statuses, as a comma
+    ///  separated list of RIDOperationalStatus values, for example "Emergency"
+    pub operational_statuses: Option<String>,
+
+    /// Only report simulated (true) or real (false) flights
+    pub simulated: Option<bool>,
+
+    /// Lowest geodetic altitude of the reported aircraft in meters
+    pub altitude_min: Option<f32>,
+
+    /// Highest geodetic altitude of the reported aircraft in meters
+    pub altitude_max: Option<f32>,
+
+    /// Format of the response, "json", "geojson", "kml", "czml",
+    ///  "protobuf", "msgpack" or "cbor", overriding the Accept header
+    pub format: Option<String>
+}
+
+/// A request for the flights in a polygon or circle area
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetAreaFlightsRequest {
+    /// The area of interest, with optional altitude bounds in meters
+    pub area: Volume3D,
+
     /// Recent positions duration
     pub recent_positions_duration: f32
 }
 
+/// A request for the flights nearest to a point
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetNearbyFlightsRequest {
+    /// Latitude of the point
+    pub lat: f64,
+
+    /// Longitude of the point
+    pub lng: f64,
+
+    /// Geodetic altitude of the point in meters, flights are sorted by 3D
+    ///  distance if provided and by horizontal distance otherwise
+    pub alt: Option<f32>,
+
+    /// Radius around the point in meters
+    pub radius_m: f64,
+
+    /// Maximum number of reported flights, 10 if not provided
+    pub limit: Option<u32>,
+
+    /// Recent positions duration, 5 seconds if not provided
+    pub recent_positions_duration: Option<f32>
+}
+
+/// A flight with its distance and bearing from a point
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct NearbyFlight {
+    /// The flight
+    pub flight: RIDFlight,
+
+    /// Distance from the point to the current position in meters, 3D if
+    ///  the altitudes of the point and of the aircraft are known
+    pub distance_m: f64,
+
+    /// Horizontal distance from the point to the current position in meters
+    pub horizontal_distance_m: f64,
+
+    /// Bearing from the point to the current position in degrees, clockwise
+    ///  from true north
+    pub bearing_deg: f64
+}
+
+/// The flights nearest to a point
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetNearbyFlightsResponse {
+    /// The time of the response
+    pub timestamp: Time,
+
+    /// The flights within the radius, nearest first
+    pub flights: Vec<NearbyFlight>,
+
+    /// If no ISAs are present, this will be true
+    pub no_isas_present: bool
+}
+
 /// A time in RFC3339 format
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
 pub struct Time {
@@ -39,6 +134,24 @@ impl Default for Time {
     }
 }
 
+impl From<DateTime<Utc>> for Time {
+    fn from(dt: DateTime<Utc>) -> Time {
+        Time {
+            value: dt.to_rfc3339_opts(SecondsFormat::Millis, true),
+            format: RFC3339_FORMAT_STRING.to_string()
+        }
+    }
+}
+
+impl Time {
+    /// Parse the RFC3339 time string, returns None if the string is invalid
+    pub fn to_datetime(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.value)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+}
+
 /// An altitude with variable reference and units
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
 pub struct Altitude {
@@ -76,6 +189,13 @@ pub struct LatLngPoint {
     pub lat: f64
 }
 
+impl LatLngPoint {
+    /// Check if the coordinates are within the valid range
+    pub fn is_valid(&self) -> bool {
+        (-90.0..=90.0).contains(&self.lat) && (-180.0..=180.0).contains(&self.lng)
+    }
+}
+
 /// Defines a 2D area around a point
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
 pub struct Radius {
@@ -116,16 +236,18 @@ pub struct Polygon {
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
 pub struct Volume3D {
     /// The outline of the volume as a circle
-    outline_circle: Circle,
+    /// Exactly one of `outline_circle` or `outline_polygon` must be specified
+    pub outline_circle: Option<Circle>,
 
     /// The outline of the volume as a polygon
-    outline_polygon: Polygon,
+    /// Exactly one of `outline_circle` or `outline_polygon` must be specified
+    pub outline_polygon: Option<Polygon>,
 
     /// The altitude of the lower bound of the volume
-    altitude_lower: Altitude,
+    pub altitude_lower: Option<Altitude>,
 
     /// The altitude of the upper bound of the volume
-    altitude_upper: Altitude
+    pub altitude_upper: Option<Altitude>
 }
 
 /// A 4D volume defined by a 3D volume and a start and end time
@@ -466,3 +588,1116 @@ pub enum RIDOperationalStatus {
     /// Remote ID System Failure
     RemoteIDSystemFailure,
 }
+
+/// An Identification Service Area, announcing that a USS has flights
+/// in the given area and time range
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct IdentificationServiceArea {
+    /// Unique identifier of this ISA
+    pub id: String,
+
+    /// Assigned by the DSS based on the creating client's identity
+    pub owner: String,
+
+    /// Base URL of the USS providing flights in this ISA
+    pub uss_base_url: String,
+
+    /// Beginning time of this ISA
+    pub time_start: Time,
+
+    /// End time of this ISA
+    pub time_end: Time,
+
+    /// Opaque version of this ISA, changes on every update
+    pub version: String
+}
+
+/// A subscription to changes of ISAs in an area
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct Subscription {
+    /// Unique identifier of this subscription
+    pub id: String,
+
+    /// Assigned by the DSS based on the creating client's identity
+    pub owner: String,
+
+    /// Base URL of the USS to notify of ISA changes
+    pub uss_base_url: String,
+
+    /// Incremented every time a notification is triggered for this subscription
+    pub notification_index: i32,
+
+    /// Beginning time of this subscription
+    pub time_start: Time,
+
+    /// End time of this subscription
+    pub time_end: Time,
+
+    /// Opaque version of this subscription, changes on every update
+    pub version: String
+}
+
+/// The notification state of a single subscription
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct SubscriptionState {
+    /// The subscription that triggered the notification
+    pub subscription_id: String,
+
+    /// The notification index of the subscription after this change
+    pub notification_index: i32
+}
+
+/// Subscriptions of a single USS that must be notified of a change
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct SubscriberToNotify {
+    /// The subscriptions of this USS affected by the change
+    pub subscriptions: Vec<SubscriptionState>,
+
+    /// Base URL of the USS to notify
+    pub url: String
+}
+
+/// Parameters to create or update an ISA
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct CreateIdentificationServiceAreaParameters {
+    /// The area and time range covered by the ISA
+    pub extents: Volume4D,
+
+    /// Base URL of the USS providing flights in this ISA
+    pub uss_base_url: String
+}
+
+/// Response to a successful ISA creation, update or deletion
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct PutIdentificationServiceAreaResponse {
+    /// Subscribers which are notified of this change
+    pub subscribers: Vec<SubscriberToNotify>,
+
+    /// The resulting ISA
+    pub service_area: IdentificationServiceArea
+}
+
+/// Response to a request for a single ISA
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetIdentificationServiceAreaResponse {
+    /// The requested ISA
+    pub service_area: IdentificationServiceArea
+}
+
+/// Parameters to search for ISAs or subscriptions
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct SearchAreaRequest {
+    /// The area of interest as a closed polygon of format "lat1,lng1,lat2,lng2,lat3,lng3,..."
+    pub area: String,
+
+    /// Only return results that end after this RFC3339 time
+    pub earliest_time: Option<String>,
+
+    /// Only return results that start before this RFC3339 time
+    pub latest_time: Option<String>
+}
+
+/// Response to an ISA search
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct SearchIdentificationServiceAreasResponse {
+    /// The ISAs intersecting the area of interest
+    pub service_areas: Vec<IdentificationServiceArea>
+}
+
+/// Parameters to create or update a subscription
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct CreateSubscriptionParameters {
+    /// The area and time range covered by the subscription
+    pub extents: Volume4D,
+
+    /// Base URL of the USS to notify of ISA changes
+    pub uss_base_url: String
+}
+
+/// Response to a successful subscription creation or update
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct PutSubscriptionResponse {
+    /// ISAs currently intersecting the subscription
+    pub service_areas: Vec<IdentificationServiceArea>,
+
+    /// The resulting subscription
+    pub subscription: Subscription
+}
+
+/// Response to a request for a single subscription, or its deletion
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetSubscriptionResponse {
+    /// The requested subscription
+    pub subscription: Subscription
+}
+
+/// Response to a subscription search
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct SearchSubscriptionsResponse {
+    /// The subscriptions intersecting the area of interest
+    pub subscriptions: Vec<Subscription>
+}
+
+/// Notification sent to subscribers when an ISA changes
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct PutIdentificationServiceAreaNotificationParameters {
+    /// The subscriptions of the receiving USS that triggered this notification
+    pub subscriptions: Vec<SubscriptionState>,
+
+    /// The ISA after the change, absent if it was deleted
+    pub service_area: Option<IdentificationServiceArea>,
+
+    /// The extents of the ISA after the change, absent if it was deleted
+    pub extents: Option<Volume4D>
+}
+
+/// The availability of a USS as reported to the DSS
+#[derive(Debug, Display, Copy, Clone, EnumString, EnumIter, Serialize, Deserialize, PartialEq)]
+#[derive(ToSchema)]
+#[schema(default="Unknown")]
+pub enum UssAvailabilityState {
+    /// No report of the USS availability is known
+    Unknown,
+
+    /// The USS is operating normally
+    Normal,
+
+    /// The USS is down and its operational intents must be treated as
+    /// unavailable
+    Down,
+}
+
+/// The state of an operational intent
+#[derive(Debug, Display, Copy, Clone, EnumString, EnumIter, Serialize, Deserialize, PartialEq)]
+#[derive(ToSchema)]
+#[schema(default="Accepted")]
+pub enum OperationalIntentState {
+    /// The operational intent was accepted, but the flight has not started
+    Accepted,
+
+    /// The flight is in progress and conforming to its intent
+    Activated,
+
+    /// The flight left its nominal volumes but is expected to return
+    Nonconforming,
+
+    /// The flight left its nominal volumes and will not return
+    Contingent,
+}
+
+/// Shared information about an operational intent, as stored in the DSS
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct OperationalIntentReference {
+    /// Unique identifier of the operational intent
+    pub id: String,
+
+    /// The USS managing the operational intent
+    pub manager: String,
+
+    /// The availability of the managing USS
+    pub uss_availability: UssAvailabilityState,
+
+    /// Incremented each time the operational intent changes
+    pub version: i32,
+
+    /// The state of the operational intent
+    pub state: OperationalIntentState,
+
+    /// Opaque version number, only provided to the managing USS
+    pub ovn: Option<String>,
+
+    /// The beginning of the operational intent
+    pub time_start: Time,
+
+    /// The end of the operational intent
+    pub time_end: Time,
+
+    /// Base URL of the USS managing the operational intent
+    pub uss_base_url: String,
+
+    /// The subscription keeping the managing USS informed of changes
+    pub subscription_id: String
+}
+
+/// Details of an operational intent, only shared between USSs
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct OperationalIntentDetails {
+    /// The volumes the flight is planned to stay within
+    pub volumes: Vec<Volume4D>,
+
+    /// The volumes occupied while the flight is nonconforming or contingent
+    pub off_nominal_volumes: Vec<Volume4D>,
+
+    /// Priority of the operation, higher values take precedence
+    pub priority: i32
+}
+
+/// An operational intent, combining the shared reference and its details
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct OperationalIntent {
+    /// The reference of the operational intent
+    pub reference: OperationalIntentReference,
+
+    /// The details of the operational intent
+    pub details: OperationalIntentDetails
+}
+
+/// Response to a request for the details of an operational intent
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetOperationalIntentDetailsResponse {
+    /// The requested operational intent
+    pub operational_intent: OperationalIntent
+}
+
+/// The position of a vehicle
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct Position {
+    /// Degrees of longitude ([-180, 180])
+    pub longitude: f64,
+
+    /// Degrees of latitude ([-90, 90])
+    pub latitude: f64,
+
+    /// horizontal accuracy in meters
+    pub accuracy_h: HorizontalAccuracy,
+
+    /// vertical accuracy in meters
+    pub accuracy_v: VerticalAccuracy,
+
+    /// is extrapolated rather than reported
+    pub extrapolated: bool,
+
+    /// The altitude of the vehicle
+    pub altitude: Altitude
+}
+
+/// The velocity of a vehicle
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct Velocity {
+    /// The speed of the vehicle
+    pub speed: f32,
+
+    /// The units of the speed (MetersPerSecond)
+    pub units_speed: String,
+
+    /// The track of the vehicle with respect to true north
+    pub track: f32,
+
+    /// The reference of the speed (Ground or Air)
+    pub speed_type: String
+}
+
+/// A telemetry report of a vehicle
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct VehicleTelemetry {
+    /// The time at which the telemetry was measured
+    pub time_measured: Time,
+
+    /// The position of the vehicle
+    pub position: Position,
+
+    /// The velocity of the vehicle
+    pub velocity: Velocity
+}
+
+/// Response to a request for the telemetry of an operational intent
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetOperationalIntentTelemetryResponse {
+    /// The operational intent the telemetry belongs to
+    pub operational_intent_id: String,
+
+    /// The most recent telemetry, absent if none is available
+    pub telemetry: Option<VehicleTelemetry>,
+
+    /// The time at which new telemetry is expected, absent if none is expected
+    pub next_telemetry_opportunity: Option<Time>
+}
+
+/// Notification sent by a USS when one of its operational intents changes
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct PutOperationalIntentDetailsParameters {
+    /// The operational intent that changed
+    pub operational_intent_id: String,
+
+    /// The operational intent after the change, absent if it was removed
+    pub operational_intent: Option<OperationalIntent>,
+
+    /// The subscriptions of the receiving USS that triggered this notification
+    pub subscriptions: Vec<SubscriptionState>
+}
+
+/// Shared information about a constraint, as stored in the DSS
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct ConstraintReference {
+    /// Unique identifier of the constraint
+    pub id: String,
+
+    /// The USS managing the constraint
+    pub manager: String,
+
+    /// The availability of the managing USS
+    pub uss_availability: UssAvailabilityState,
+
+    /// Incremented each time the constraint changes
+    pub version: i32,
+
+    /// Opaque version number, only provided to the managing USS
+    pub ovn: Option<String>,
+
+    /// The beginning of the constraint
+    pub time_start: Time,
+
+    /// The end of the constraint
+    pub time_end: Time,
+
+    /// Base URL of the USS managing the constraint
+    pub uss_base_url: String
+}
+
+/// Details of a constraint, only shared between USSs
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct ConstraintDetails {
+    /// The volumes in which the restriction applies
+    pub volumes: Vec<Volume4D>,
+
+    /// The kind of restriction, for example "NoFlyZone" or "VertiportClosure"
+    #[serde(rename = "type")]
+    pub constraint_type: Option<String>
+}
+
+/// A constraint, combining the shared reference and its details
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct Constraint {
+    /// The reference of the constraint
+    pub reference: ConstraintReference,
+
+    /// The details of the constraint
+    pub details: ConstraintDetails
+}
+
+/// Response to a request for the details of a constraint
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetConstraintDetailsResponse {
+    /// The requested constraint
+    pub constraint: Constraint
+}
+
+/// Notification sent by a USS when one of its constraints changes
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct PutConstraintDetailsParameters {
+    /// The constraint that changed
+    pub constraint_id: String,
+
+    /// The constraint after the change, absent if it was removed
+    pub constraint: Option<Constraint>,
+
+    /// The subscriptions of the receiving USS that triggered this notification
+    pub subscriptions: Vec<SubscriptionState>
+}
+
+/// The role of the USS that recorded an exchange
+#[derive(Debug, Display, Copy, Clone, EnumString, EnumIter, Serialize, Deserialize, PartialEq)]
+#[derive(ToSchema)]
+#[schema(default="Client")]
+pub enum RecorderRole {
+    /// The USS sent the request
+    Client,
+
+    /// The USS received the request
+    Server,
+}
+
+/// A request and its response between two USSs, as recorded by one of them
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct ExchangeRecord {
+    /// Full URL of the request
+    pub url: String,
+
+    /// HTTP method of the request
+    pub method: String,
+
+    /// HTTP headers of the request, one "name: value" per entry
+    pub headers: Vec<String>,
+
+    /// The role of the USS that recorded the exchange
+    pub recorder_role: RecorderRole,
+
+    /// The time the request was sent or received
+    pub request_time: Time,
+
+    /// Body of the request
+    pub request_body: Option<String>,
+
+    /// The time the response was sent or received
+    pub response_time: Option<Time>,
+
+    /// Body of the response
+    pub response_body: Option<String>,
+
+    /// HTTP status code of the response
+    pub response_code: Option<i32>,
+
+    /// Human-readable description of the problem
+    pub problem: Option<String>
+}
+
+/// A report of an erroneous or unexpected exchange with a USS
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct ErrorReport {
+    /// Assigned by the receiving USS, absent when submitting the report
+    pub report_id: Option<String>,
+
+    /// The exchange being reported
+    pub exchange: ExchangeRecord
+}
+
+/// A report as stored by this USS
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct StoredErrorReport {
+    /// The time the report was received
+    pub received: Time,
+
+    /// The received report
+    pub report: ErrorReport
+}
+
+/// Parameters to query the received reports
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct QueryErrorReportsRequest {
+    /// Only return reports received at or after this RFC3339 time
+    pub since: Option<String>,
+
+    /// Only return reports received at or before this RFC3339 time
+    pub until: Option<String>,
+
+    /// Maximum number of reports to return, most recent first
+    pub limit: Option<usize>
+}
+
+/// Response to a query of the received reports
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct QueryErrorReportsResponse {
+    /// The matching reports, most recent first
+    pub reports: Vec<StoredErrorReport>
+}
+
+/// The availability of a single USS
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct UssAvailabilityStatus {
+    /// The USS the availability applies to
+    pub uss: String,
+
+    /// The availability of the USS
+    pub availability: UssAvailabilityState
+}
+
+/// Response to a request for, or an update of, the availability of a USS
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct UssAvailabilityStatusResponse {
+    /// The availability of the USS
+    pub status: UssAvailabilityStatus,
+
+    /// Version of the availability, required to change it
+    pub version: String
+}
+
+/// Parameters to change the availability of a USS
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct SetUssAvailabilityStatusParameters {
+    /// The current version of the availability, empty if it was never set
+    pub old_version: String,
+
+    /// The new availability of the USS
+    pub availability: UssAvailabilityState
+}
+
+/// A service offered by a service provider
+#[derive(Debug, Display, Copy, Clone, EnumString, EnumIter, Serialize, Deserialize, PartialEq)]
+#[derive(ToSchema)]
+#[schema(default="Rid")]
+pub enum ProviderService {
+    /// ASTM F3411 Remote ID
+    Rid,
+
+    /// ASTM F3548 strategic coordination
+    F3548,
+
+    /// Weather information
+    Weather,
+
+    /// Vertiport information, the `/vertiports` API of this service
+    Vertiports
+}
+
+/// The role of a service provider in the U-space
+#[derive(Debug, Display, Copy, Clone, EnumString, EnumIter, Serialize, Deserialize, PartialEq)]
+#[derive(ToSchema)]
+#[schema(default="Ussp")]
+pub enum ProviderRole {
+    /// U-space service provider
+    Ussp,
+
+    /// Common information service provider
+    Cisp
+}
+
+/// The base URL at which a service provider offers a service
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct ProviderEndpoint {
+    /// The service offered at this URL
+    pub service: ProviderService,
+
+    /// Base URL of the service, without trailing slash
+    pub base_url: String
+}
+
+/// A public key used to verify the messages of a service provider
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct ProviderPublicKey {
+    /// Identifier of the key, as found in the `kid` header of signed messages
+    pub key_id: String,
+
+    /// The PEM encoded public key
+    pub pem: String
+}
+
+/// A known U-space service provider
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct ServiceProvider {
+    /// Unique identifier of the provider
+    pub id: String,
+
+    /// Human readable name of the provider
+    pub name: String,
+
+    /// Role of the provider
+    pub role: ProviderRole,
+
+    /// Base URLs of the services offered by the provider
+    pub endpoints: Vec<ProviderEndpoint>,
+
+    /// Regions served by the provider
+    pub regions: Vec<Polygon>,
+
+    /// Public keys of the provider
+    pub public_keys: Vec<ProviderPublicKey>
+}
+
+/// Response to a list or lookup of service providers
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct ListServiceProvidersResponse {
+    /// The matching providers, ordered by ID
+    pub providers: Vec<ServiceProvider>
+}
+
+/// Request to look up the providers of a service in an area
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct LookupServiceProvidersRequest {
+    /// The area of interest, a string of format 'lat1,lon1,lat2,lon2'
+    pub view: String,
+
+    /// The requested service
+    pub service: ProviderService
+}
+
+/// The status of a vertiport
+#[derive(Debug, Display, Copy, Clone, EnumString, EnumIter, Serialize, Deserialize, PartialEq)]
+#[derive(ToSchema)]
+#[schema(default="Open")]
+pub enum VertiportStatus {
+    /// The vertiport accepts arrivals and departures
+    Open,
+
+    /// The vertiport accepts emergency landings only
+    Restricted,
+
+    /// The vertiport is closed
+    Closed
+}
+
+/// A day of the week
+#[derive(Debug, Display, Copy, Clone, EnumString, EnumIter, Serialize, Deserialize, PartialEq)]
+#[derive(ToSchema)]
+#[schema(default="Monday")]
+pub enum DayOfWeek {
+    /// Monday
+    Monday,
+
+    /// Tuesday
+    Tuesday,
+
+    /// Wednesday
+    Wednesday,
+
+    /// Thursday
+    Thursday,
+
+    /// Friday
+    Friday,
+
+    /// Saturday
+    Saturday,
+
+    /// Sunday
+    Sunday
+}
+
+/// The hours a vertiport is open on a day of the week
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct OperatingHours {
+    /// The day of the week
+    pub day: DayOfWeek,
+
+    /// Opening time in UTC, format 'HH:MM'
+    pub open: String,
+
+    /// Closing time in UTC, format 'HH:MM'
+    pub close: String
+}
+
+/// A vertiport in the Aetheric network
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct Vertiport {
+    /// Unique identifier of the vertiport
+    pub id: String,
+
+    /// Human readable name of the vertiport
+    pub name: String,
+
+    /// The center of the vertiport
+    pub location: LatLngPoint,
+
+    /// The outline of the vertiport
+    pub footprint: Polygon,
+
+    /// The number of landing pads
+    pub pad_count: u32,
+
+    /// The opening hours, a vertiport is closed on days without hours
+    pub operating_hours: Vec<OperatingHours>,
+
+    /// The current status of the vertiport
+    pub status: VertiportStatus
+}
+
+/// Request body for the vertiports endpoint
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetVertiportsRequest {
+    /// The area of interest, a string of format 'lat1,lon1,lat2,lon2'
+    pub view: String
+}
+
+/// Response to a request for the vertiports in an area
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetVertiportsResponse {
+    /// The vertiports in the area, ordered by ID
+    pub vertiports: Vec<Vertiport>
+}
+
+/// The kind of a meteorological report
+#[derive(Debug, Display, Copy, Clone, EnumString, EnumIter, Serialize, Deserialize, PartialEq)]
+#[derive(ToSchema)]
+#[schema(default="Metar")]
+pub enum WeatherReportType {
+    /// Observed weather (METAR or SPECI)
+    Metar,
+
+    /// Forecast weather (TAF)
+    Taf
+}
+
+/// Wind reported by a meteorological report
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, ToSchema, IntoParams, PartialEq)]
+pub struct Wind {
+    /// Direction the wind blows from in degrees true, none if variable
+    pub direction_degrees: Option<u16>,
+
+    /// Mean wind speed in knots
+    pub speed_knots: f32,
+
+    /// Gust speed in knots, if reported
+    pub gust_knots: Option<f32>
+}
+
+/// A parsed METAR or TAF
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct WeatherReport {
+    /// The kind of report
+    pub report_type: WeatherReportType,
+
+    /// ICAO identifier of the reporting station
+    pub station: String,
+
+    /// Time of the observation (METAR) or of issue (TAF)
+    pub issued: Time,
+
+    /// Start of the validity of a forecast
+    pub valid_from: Option<Time>,
+
+    /// End of the validity of a forecast
+    pub valid_to: Option<Time>,
+
+    /// The (prevailing) wind
+    pub wind: Option<Wind>,
+
+    /// Prevailing visibility in meters, 10000 or more is reported as 10000
+    pub visibility_meters: Option<f32>,
+
+    /// Height of the lowest broken or overcast layer, or vertical visibility, in feet
+    pub ceiling_feet: Option<u32>,
+
+    /// Altimeter setting in hectopascal
+    pub qnh_hpa: Option<f32>,
+
+    /// Air temperature in degrees Celsius
+    pub temperature_celsius: Option<i32>,
+
+    /// Dew point in degrees Celsius
+    pub dew_point_celsius: Option<i32>,
+
+    /// The report as received
+    pub raw: String
+}
+
+/// The latest reports of a station
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct StationWeather {
+    /// ICAO identifier of the station
+    pub station: String,
+
+    /// Location of the station, if known
+    pub location: Option<LatLngPoint>,
+
+    /// The latest observation
+    pub metar: Option<WeatherReport>,
+
+    /// The latest forecast
+    pub taf: Option<WeatherReport>
+}
+
+/// Request body for the weather endpoint
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetWeatherRequest {
+    /// The area of interest, a string of format 'lat1,lon1,lat2,lon2'
+    pub view: String
+}
+
+/// Response to a request for the weather in an area
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetWeatherResponse {
+    /// The stations in the area, ordered by station
+    pub stations: Vec<StationWeather>
+}
+
+/// Response to pushed meteorological reports
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct PostWeatherResponse {
+    /// The reports that were parsed and stored
+    pub accepted: Vec<WeatherReport>,
+
+    /// The number of reports that could not be parsed
+    pub rejected: usize
+}
+
+/// A planned departure or arrival at a vertiport
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct ScheduleEntry {
+    /// The flight plan
+    pub flight_id: String,
+
+    /// Vertiport the flight departs from, if any
+    pub origin_vertiport_id: Option<String>,
+
+    /// Vertiport the flight arrives at, if any
+    pub target_vertiport_id: Option<String>,
+
+    /// Planned departure time
+    pub departure: Time,
+
+    /// Planned arrival time
+    pub arrival: Time,
+
+    /// State of the flight plan
+    pub state: OperationalIntentState
+}
+
+/// Parameters to query the schedule of a vertiport
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetVertiportScheduleRequest {
+    /// Start of the time range, RFC3339
+    pub time_start: String,
+
+    /// End of the time range, RFC3339
+    pub time_end: String
+}
+
+/// The planned departures and arrivals of a vertiport in a time range
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetVertiportScheduleResponse {
+    /// The vertiport
+    pub vertiport_id: String,
+
+    /// Flights departing in the time range, ordered by departure time
+    pub departures: Vec<ScheduleEntry>,
+
+    /// Flights arriving in the time range, ordered by arrival time
+    pub arrivals: Vec<ScheduleEntry>
+}
+
+/// The planned route of a flight
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetFlightRouteResponse {
+    /// The flight plan
+    pub flight_id: String,
+
+    /// The planned route as 4D volumes, in flight order
+    pub volumes: Vec<Volume4D>
+}
+
+/// Parameters of Open Drone ID frames pushed by a receiver
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct PostOdidRequest {
+    /// Address of the transmitter, such as its Bluetooth or Wi-Fi MAC address
+    pub transmitter: String
+}
+
+/// Response to Open Drone ID frames pushed by a receiver
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct PostOdidResponse {
+    /// The ID of the flight of the transmitter
+    pub flight_id: String,
+
+    /// The number of decoded messages
+    pub messages: usize
+}
+
+/// A batch of states of a flight pushed by a partner
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct PostTelemetryRequest {
+    /// The ID of the flight, unique to the provider pushing it
+    pub flight_id: String,
+
+    /// The type of aircraft
+    pub aircraft_type: UAType,
+
+    /// If this is a simulated flight, this will be true
+    pub simulated: bool,
+
+    /// The states of the aircraft, oldest first
+    pub states: Vec<RIDAircraftState>
+}
+
+/// Response to a batch of pushed states
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct PostTelemetryResponse {
+    /// The number of new states
+    pub accepted: usize,
+
+    /// The number of states that were already received
+    pub duplicates: usize
+}
+
+/// Statistics of the cache of svc-gis flights
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct FlightsCacheStats {
+    /// The number of queries served by a cached query of the same area
+    ///  and duration
+    pub hits: u64,
+
+    /// The number of queries served by a cached query of a larger area or
+    ///  duration
+    pub superset_hits: u64,
+
+    /// The number of queries not served from the cache
+    pub misses: u64,
+
+    /// The number of missed queries sharing a call to svc-gis in flight
+    ///  for the same area and duration
+    pub coalesced: u64,
+
+    /// The number of queries currently cached
+    pub cached_queries: usize,
+
+    /// Time a query is cached, in milliseconds
+    pub ttl_ms: u64,
+
+    /// Cached flights older than this are never served, in milliseconds
+    pub max_staleness_ms: u64
+}
+
+/// State and consistency of the live traffic index
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct LiveTrafficStats {
+    /// If a service region is indexed
+    pub enabled: bool,
+
+    /// The number of indexed flights
+    pub flights: usize,
+
+    /// Age of the index in milliseconds, None until svc-gis was polled
+    pub age_ms: Option<i64>,
+
+    /// The number of views answered from the index
+    pub served: u64,
+
+    /// The number of views answered from the index also queried from
+    ///  svc-gis
+    pub checks: u64,
+
+    /// The number of checked views for which svc-gis reported other
+    ///  flights
+    pub mismatches: u64
+}
+
+/// Parameters of a map tile of flights
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetFlightTileRequest {
+    /// The number of seconds of recent positions of zoomed in tiles,
+    ///  5 seconds if not provided
+    pub recent_positions_duration: Option<f32>,
+
+    /// The format of the tile, `json` or `mvt`, takes precedence over the
+    ///  Accept header
+    pub format: Option<String>
+}
+
+/// The flights with a current position in a cell of a map tile
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct FlightCluster {
+    /// The mean latitude of the flights
+    pub lat: f64,
+
+    /// The mean longitude of the flights
+    pub lng: f64,
+
+    /// The number of flights
+    pub count: usize
+}
+
+/// The flights of a map tile, either individual flights when zoomed in
+///  or aggregated counts when zoomed out
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetFlightTileResponse {
+    /// The zoom level of the tile
+    pub z: u8,
+
+    /// The column of the tile
+    pub x: u32,
+
+    /// The row of the tile
+    pub y: u32,
+
+    /// The time of the response
+    pub timestamp: Time,
+
+    /// The flights of the tile, empty when zoomed out
+    pub flights: Vec<RIDFlight>,
+
+    /// The flights of the tile aggregated by cell, empty when zoomed in
+    pub clusters: Vec<FlightCluster>
+}
+
+/// Parameters of a query of traffic statistics
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetTrafficStatsRequest {
+    /// The area as a string of format "lat1,lon1,lat2,lon2", with a
+    ///  diagonal of at most 20 km
+    pub view: String,
+
+    /// Start of the time range, RFC3339
+    pub time_start: String,
+
+    /// End of the time range, RFC3339
+    pub time_end: String,
+
+    /// The size of the cells of the density grid in degrees
+    pub resolution: f64
+}
+
+/// The traffic of a cell of a density grid
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct DensityCell {
+    /// The row of the cell, from the south edge of the grid
+    pub row: u32,
+
+    /// The column of the cell, from the west edge of the grid
+    pub col: u32,
+
+    /// The latitude of the center of the cell
+    pub lat: f64,
+
+    /// The longitude of the center of the cell
+    pub lng: f64,
+
+    /// The number of positions reported in the cell
+    pub positions: u64,
+
+    /// The number of flights with a position in the cell
+    pub flights: u64
+}
+
+/// The traffic of an aircraft type
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct AircraftTypeStats {
+    /// The type of aircraft
+    pub aircraft_type: UAType,
+
+    /// The number of positions reported by aircraft of the type
+    pub positions: u64,
+
+    /// The number of flights of aircraft of the type
+    pub flights: u64
+}
+
+/// The traffic of an operational status
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct OperationalStatusStats {
+    /// The operational status of the aircraft
+    pub operational_status: RIDOperationalStatus,
+
+    /// The number of positions reported with the status
+    pub positions: u64,
+
+    /// The number of flights reporting the status
+    pub flights: u64
+}
+
+/// The density and breakdowns of the traffic of an area over a time range
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct GetTrafficStatsResponse {
+    /// Start of the time range
+    pub time_start: Time,
+
+    /// End of the time range
+    pub time_end: Time,
+
+    /// The latitude of the south edge of the grid
+    pub min_lat: f64,
+
+    /// The longitude of the west edge of the grid
+    pub min_lng: f64,
+
+    /// The size of the cells in degrees
+    pub resolution: f64,
+
+    /// The number of rows of the grid
+    pub rows: u32,
+
+    /// The number of columns of the grid
+    pub cols: u32,
+
+    /// The number of positions reported in the area
+    pub positions: u64,
+
+    /// The number of flights with a position in the area
+    pub flights: u64,
+
+    /// The cells with traffic, by row then column
+    pub cells: Vec<DensityCell>,
+
+    /// The traffic by aircraft type, for types with traffic
+    pub aircraft_types: Vec<AircraftTypeStats>,
+
+    /// The traffic by operational status, for statuses with traffic
+    pub operational_statuses: Vec<OperationalStatusStats>
+}
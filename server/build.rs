@@ -7,7 +7,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let server_config = tonic_build::configure()
         .type_attribute("ReadyRequest", "#[derive(Eq, Copy)]")
-        .type_attribute("ReadyResponse", "#[derive(Eq, Copy)]");
+        .type_attribute("ReadyResponse", "#[derive(Eq, Copy)]")
+        .type_attribute("PathPoint", "#[derive(Copy)]")
+        .type_attribute("PutFlightPlanResponse", "#[derive(Eq, Copy)]")
+        .type_attribute("DeleteFlightPlanResponse", "#[derive(Eq, Copy)]")
+        .type_attribute("Coordinates", "#[derive(Copy)]")
+        .type_attribute("PutConstraintResponse", "#[derive(Eq, Copy)]")
+        .type_attribute("DeleteConstraintResponse", "#[derive(Eq, Copy)]")
+        .type_attribute("GetPartnerVertiportsRequest", "#[derive(Copy)]")
+        .type_attribute("Wind", "#[derive(Copy)]")
+        .type_attribute("GetBroadcastFramesRequest", "#[derive(Copy)]")
+        .type_attribute("PushTelemetryResponse", "#[derive(Eq, Copy)]")
+        .type_attribute("RidAircraftPosition", "#[derive(Copy)]");
     let client_config = server_config.clone();
 
     client_config
@@ -21,7 +32,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .build_client(false)
         .compile(&[proto_file], &[proto_dir])?;
 
+    // Build the Mapbox Vector Tile messages
+    let vector_tile_file = &format!("{}/vector_tile.proto", proto_dir);
+    tonic_build::configure()
+        .build_client(false)
+        .build_server(false)
+        .compile(&[vector_tile_file], &[proto_dir])?;
+
     println!("cargo:rerun-if-changed={}", proto_file);
+    println!("cargo:rerun-if-changed={}", vector_tile_file);
 
     Ok(())
 }
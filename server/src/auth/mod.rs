@@ -0,0 +1,305 @@
+//! Authentication of service providers
+//! Providers authenticate with a bearer token issued to them out of band.
+//! Only the SHA-256 digest of each token is configured, and a presented
+//! token is compared with every digest in constant time.
+//! Admin providers are additionally allowed to operate this service.
+
+use axum::http::HeaderMap;
+use hyper::header::AUTHORIZATION;
+use hyper::StatusCode;
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Scheme of the `Authorization` header
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// Errors authenticating a provider
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuthError {
+    /// No bearer token was presented
+    Missing,
+
+    /// The token was not issued to any provider
+    Invalid,
+}
+
+impl std::error::Error for AuthError {}
+
+impl Display for AuthError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            AuthError::Missing => write!(f, "No bearer token was presented."),
+            AuthError::Invalid => write!(f, "The bearer token is invalid."),
+        }
+    }
+}
+
+/// Parse the hex SHA-256 digest of a token
+fn parse_digest(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+
+    let mut digest = [0; 32];
+    for (byte, pair) in digest.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair).ok()?;
+        *byte = u8::from_str_radix(pair, 16).ok()?;
+    }
+
+    Some(digest)
+}
+
+/// The digests of the bearer tokens of the providers
+#[derive(Debug, Clone, Default)]
+pub struct ProviderCredentials {
+    /// Provider ID and token digest
+    digests: Vec<(String, [u8; 32])>,
+
+    /// IDs of the providers allowed to operate this service
+    admins: Vec<String>,
+}
+
+impl ProviderCredentials {
+    /// Parse the credentials from a comma separated list of 'id=sha256',
+    ///  with the hex SHA-256 digest of the token of the provider.
+    /// Invalid entries are skipped.
+    pub fn parse(value: &str) -> Self {
+        let digests = value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let Some((id, digest)) = entry.split_once('=') else {
+                    rest_warn!("provider credentials must be of format 'id=sha256'.");
+                    return None;
+                };
+
+                let id = id.trim();
+                match parse_digest(digest.trim()) {
+                    Some(digest) if !id.is_empty() => Some((id.to_string(), digest)),
+                    _ => {
+                        rest_warn!("invalid credentials of provider '{}', skipped.", id);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        ProviderCredentials {
+            digests,
+            admins: vec![],
+        }
+    }
+
+    /// The credentials with the admin providers of a comma separated list
+    ///  of provider IDs
+    pub fn with_admins(self, value: &str) -> Self {
+        let admins = value
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        ProviderCredentials { admins, ..self }
+    }
+
+    /// The provider a bearer token was issued to
+    pub fn authenticate(&self, token: &str) -> Result<String, AuthError> {
+        let digest = openssl::sha::sha256(token.as_bytes());
+
+        // every digest is compared, so the time taken doesn't tell which
+        //  providers come first
+        let mut provider = None;
+        for (id, expected) in &self.digests {
+            if openssl::memcmp::eq(expected, &digest) && provider.is_none() {
+                provider = Some(id);
+            }
+        }
+
+        provider.cloned().ok_or(AuthError::Invalid)
+    }
+
+    /// The provider of an `Authorization: Bearer <token>` value
+    pub fn authenticate_bearer(&self, authorization: Option<&str>) -> Result<String, AuthError> {
+        let token = authorization
+            .and_then(|value| value.strip_prefix(BEARER_PREFIX))
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .ok_or(AuthError::Missing)?;
+
+        self.authenticate(token)
+    }
+
+    /// The provider authenticated by the `Authorization` header of a REST
+    ///  request.
+    /// Returns 401 if the token is missing or invalid.
+    pub fn authenticated_provider(&self, headers: &HeaderMap) -> Result<String, StatusCode> {
+        let authorization = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok());
+
+        self.authenticate_bearer(authorization).map_err(|e| {
+            rest_error!("could not authenticate the provider: {}", e);
+            StatusCode::UNAUTHORIZED
+        })
+    }
+
+    /// The admin provider authenticated by the `Authorization` header of a
+    ///  REST request.
+    /// Returns 401 if the token is missing or invalid, 403 if the provider
+    ///  is not an admin.
+    pub fn authenticated_admin(&self, headers: &HeaderMap) -> Result<String, StatusCode> {
+        let provider = self.authenticated_provider(headers)?;
+        if !self.admins.contains(&provider) {
+            rest_error!("provider {} is not an admin.", provider);
+            return Err(StatusCode::FORBIDDEN);
+        }
+
+        Ok(provider)
+    }
+}
+
+pub(crate) static PROVIDER_CREDENTIALS: OnceCell<Arc<ProviderCredentials>> = OnceCell::const_new();
+
+/// Returns the credentials of the providers, parsed from the provider
+///  tokens and admin providers of a Config object generated from
+///  environment variables.
+/// Initializes the credentials if they haven't been initialized yet.
+pub async fn get_provider_credentials() -> Arc<ProviderCredentials> {
+    PROVIDER_CREDENTIALS
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            Arc::new(
+                ProviderCredentials::parse(&config.provider_tokens)
+                    .with_admins(&config.admin_providers),
+            )
+        })
+        .await
+        .clone()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// The configured credential of a provider with the given token
+    pub(crate) fn credential(id: &str, token: &str) -> String {
+        let digest = openssl::sha::sha256(token.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        format!("{}={}", id, digest)
+    }
+
+    /// Headers with the bearer token
+    pub(crate) fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            format!("{}{}", BEARER_PREFIX, token).parse().unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_parse() {
+        let value = format!(
+            "{}, {} ,b=1234,=00,c,d=é{}",
+            credential("a", "token-a"),
+            credential("b", "token-b"),
+            "0".repeat(62)
+        );
+        let credentials = ProviderCredentials::parse(&value);
+        assert_eq!(credentials.digests.len(), 2);
+        assert!(ProviderCredentials::parse("").digests.is_empty());
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let value = format!(
+            "{},{}",
+            credential("a", "token-a"),
+            credential("b", "token-b")
+        );
+        let credentials = ProviderCredentials::parse(&value);
+        assert_eq!(credentials.authenticate("token-b").unwrap(), "b");
+        assert_eq!(
+            credentials.authenticate("token-c").unwrap_err(),
+            AuthError::Invalid
+        );
+
+        assert_eq!(
+            credentials
+                .authenticate_bearer(Some("Bearer token-a"))
+                .unwrap(),
+            "a"
+        );
+        assert_eq!(
+            credentials
+                .authenticate_bearer(Some("token-a"))
+                .unwrap_err(),
+            AuthError::Missing
+        );
+        assert_eq!(
+            credentials.authenticate_bearer(None).unwrap_err(),
+            AuthError::Missing
+        );
+
+        assert_eq!(
+            credentials
+                .authenticated_provider(&bearer("token-a"))
+                .unwrap(),
+            "a"
+        );
+        assert_eq!(
+            credentials
+                .authenticated_provider(&bearer("token-c"))
+                .unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            credentials
+                .authenticated_provider(&HeaderMap::new())
+                .unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[test]
+    fn test_authenticated_admin() {
+        let value = format!(
+            "{},{}",
+            credential("ops", "token-ops"),
+            credential("b", "token-b")
+        );
+        let credentials = ProviderCredentials::parse(&value).with_admins(" ops, ,unknown");
+        assert_eq!(credentials.admins, vec!["ops", "unknown"]);
+        assert_eq!(
+            credentials
+                .authenticated_admin(&bearer("token-ops"))
+                .unwrap(),
+            "ops"
+        );
+        assert_eq!(
+            credentials
+                .authenticated_admin(&bearer("token-b"))
+                .unwrap_err(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            credentials
+                .authenticated_admin(&HeaderMap::new())
+                .unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_provider_credentials() {
+        let a = get_provider_credentials().await;
+        let b = get_provider_credentials().await;
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+}
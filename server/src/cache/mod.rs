@@ -0,0 +1,227 @@
+//! Cache of the recent flights of svc-gis
+//! Display clients often look at the same busy areas. Queries are made for
+//! the window snapped outward to a grid and the duration rounded up to whole
+//! seconds, so nearby views share a query, and smaller views are served from
+//! any cached query covering them. Cached flights are never served older
+//! than the configured staleness bound.
+
+pub mod store;
+
+use crate::rest::api::rest_types::*;
+use crate::rest::api::uss::Window;
+use lib_common::time::{DateTime, Utc};
+use std::sync::Arc;
+use store::FlightsCache;
+use tokio::sync::OnceCell;
+
+/// Size of the grid windows are snapped to, in degrees
+pub const GRID_DEGREES: f64 = 0.01;
+
+/// Time a query is cached, in milliseconds
+pub const CACHE_TTL_MILLISECONDS: i64 = 500;
+
+/// Maximum number of cached queries
+pub const MAX_CACHED_QUERIES: usize = 256;
+
+pub(crate) static FLIGHTS_CACHE: OnceCell<Arc<FlightsCache>> = OnceCell::const_new();
+
+/// Returns the cache of svc-gis flights, shared by the REST and gRPC
+///  servers.
+/// Initializes the cache with the staleness bound of the configuration if
+///  it hasn't been initialized yet.
+pub async fn get_flights_cache() -> Arc<FlightsCache> {
+    FLIGHTS_CACHE
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            Arc::new(FlightsCache::new(config.gis_cache_max_staleness_ms))
+        })
+        .await
+        .clone()
+}
+
+/// A query of recent flights snapped to the cache grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// Grid cell of the minimum longitude
+    min_lon: i32,
+
+    /// Grid cell of the minimum latitude
+    min_lat: i32,
+
+    /// Grid cell of the maximum longitude
+    max_lon: i32,
+
+    /// Grid cell of the maximum latitude
+    max_lat: i32,
+
+    /// Duration of the recent positions in whole seconds
+    pub duration_s: u32,
+}
+
+impl CacheKey {
+    /// The smallest query on the grid covering a window and duration
+    pub fn new(window: &Window, duration_s: f32) -> Self {
+        let (min_lon, min_lat, max_lon, max_lat) = window.bounds();
+        CacheKey {
+            min_lon: (min_lon / GRID_DEGREES).floor() as i32,
+            min_lat: (min_lat / GRID_DEGREES).floor() as i32,
+            max_lon: (max_lon / GRID_DEGREES).ceil() as i32,
+            max_lat: (max_lat / GRID_DEGREES).ceil() as i32,
+            duration_s: duration_s.max(0.0).ceil() as u32,
+        }
+    }
+
+    /// The window of the query, within valid coordinates
+    pub fn window(&self) -> Window {
+        let degrees = |cell: i32, limit: f64| (cell as f64 * GRID_DEGREES).clamp(-limit, limit);
+        Window {
+            lon1: degrees(self.min_lon, 180.0),
+            lat1: degrees(self.min_lat, 90.0),
+            lon2: degrees(self.max_lon, 180.0),
+            lat2: degrees(self.max_lat, 90.0),
+        }
+    }
+
+    /// If the results of this query include the results of another query
+    pub fn covers(&self, other: &CacheKey) -> bool {
+        self.min_lon <= other.min_lon
+            && self.min_lat <= other.min_lat
+            && self.max_lon >= other.max_lon
+            && self.max_lat >= other.max_lat
+            && self.duration_s >= other.duration_s
+    }
+}
+
+/// If a position lies within the bounds of a window
+fn in_window(position: &RIDAircraftPosition, window: &Window) -> bool {
+    let (min_lon, min_lat, max_lon, max_lat) = window.bounds();
+    (min_lon..=max_lon).contains(&position.lng) && (min_lat..=max_lat).contains(&position.lat)
+}
+
+/// Narrow the flights of a larger query down to a window, keeping the
+///  positions since the given time.
+/// A flight is kept if its current state or one of the kept positions
+///  lies within the window.
+pub fn narrow_flights(
+    flights: &[RIDFlight],
+    window: &Window,
+    since: DateTime<Utc>,
+) -> Vec<RIDFlight> {
+    let recent = |time: &Time| time.to_datetime().is_some_and(|time| time >= since);
+
+    flights
+        .iter()
+        .filter_map(|flight| {
+            let recent_positions = flight
+                .recent_positions
+                .iter()
+                .filter(|position| recent(&position.time))
+                .cloned()
+                .collect::<Vec<RIDRecentAircraftPosition>>();
+
+            let state = &flight.current_state;
+            let visible = (recent(&state.timestamp) && in_window(&state.position, window))
+                || recent_positions
+                    .iter()
+                    .any(|position| in_window(&position.position, window));
+            if !visible {
+                return None;
+            }
+
+            Some(RIDFlight {
+                recent_positions,
+                ..flight.clone()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::tests::flight;
+    use lib_common::time::Duration;
+
+    #[tokio::test]
+    async fn test_get_flights_cache() {
+        let a = get_flights_cache().await;
+        let b = get_flights_cache().await;
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let window = Window {
+            lon1: 4.9153,
+            lat1: 52.3751,
+            lon2: 4.8867,
+            lat2: 52.3682,
+        };
+        let key = CacheKey::new(&window, 9.2);
+        assert_eq!(key.duration_s, 10);
+
+        let snapped = key.window();
+        assert!((snapped.lon1 - 4.88).abs() < 1e-9);
+        assert!((snapped.lat1 - 52.36).abs() < 1e-9);
+        assert!((snapped.lon2 - 4.92).abs() < 1e-9);
+        assert!((snapped.lat2 - 52.38).abs() < 1e-9);
+
+        // nearby views share a key
+        let nearby = Window {
+            lon1: 4.8851,
+            lat1: 52.3611,
+            lon2: 4.9199,
+            lat2: 52.3799,
+        };
+        assert_eq!(CacheKey::new(&nearby, 10.0), key);
+
+        let smaller = Window {
+            lon1: 4.90,
+            lat1: 52.37,
+            lon2: 4.91,
+            lat2: 52.375,
+        };
+        assert!(key.covers(&CacheKey::new(&smaller, 5.0)));
+        assert!(!key.covers(&CacheKey::new(&smaller, 11.0)));
+        assert!(!CacheKey::new(&smaller, 5.0).covers(&key));
+
+        // snapped windows stay within valid coordinates
+        let edge = Window {
+            lon1: -180.0,
+            lat1: -90.0,
+            lon2: 179.999,
+            lat2: 89.999,
+        };
+        let snapped = CacheKey::new(&edge, 1.0).window();
+        assert!(snapped.lon1 >= -180.0 && snapped.lon2 <= 180.0);
+        assert!(snapped.lat1 >= -90.0 && snapped.lat2 <= 90.0);
+    }
+
+    #[test]
+    fn test_narrow_flights() {
+        let now = Utc::now();
+        let flights = vec![
+            flight("AC-1", now, 52.37, 4.90),
+            flight("AC-2", now, 52.40, 4.90),
+        ];
+        let window = Window {
+            lon1: 4.89,
+            lat1: 52.36,
+            lon2: 4.91,
+            lat2: 52.38,
+        };
+
+        let narrowed = narrow_flights(&flights, &window, now - Duration::milliseconds(5_500));
+        assert_eq!(narrowed.len(), 1);
+        assert_eq!(narrowed[0].id, "AC-1");
+        assert_eq!(narrowed[0].recent_positions.len(), 5);
+
+        // flights that left the window are kept while recently within it
+        let mut moved = flight("AC-3", now, 52.40, 4.90);
+        moved.recent_positions[9].position.lat = 52.37;
+        let narrowed = narrow_flights(&[moved.clone()], &window, now - Duration::seconds(2));
+        assert_eq!(narrowed.len(), 1);
+        moved.recent_positions[9].time = (now - Duration::seconds(3)).into();
+        assert!(narrow_flights(&[moved], &window, now - Duration::seconds(2)).is_empty());
+    }
+}
@@ -0,0 +1,295 @@
+//! Cached queries of recent flights
+//! A query is served while it is younger than both the TTL and the staleness
+//! bound, counted from the end of its time range rather than from when
+//! svc-gis answered, so the age of served flights is never underestimated.
+//! Lookups missing the cache at the same time share the call in flight for
+//! their key, and its result or error.
+
+use super::{CacheKey, CACHE_TTL_MILLISECONDS, MAX_CACHED_QUERIES};
+use crate::rest::api::rest_types::*;
+use hyper::StatusCode;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::OnceCell;
+
+/// The flights of a query of svc-gis
+#[derive(Debug, Clone)]
+pub struct CachedQuery {
+    /// End of the time range of the query
+    pub time: DateTime<Utc>,
+
+    /// The flights returned by svc-gis
+    pub flights: Arc<Vec<RIDFlight>>,
+}
+
+/// A call to svc-gis, set once it completes
+pub type FlightsCall = Arc<OnceCell<Result<CachedQuery, StatusCode>>>;
+
+/// Recent queries of svc-gis by cache key
+#[derive(Debug)]
+pub struct FlightsCache {
+    /// Cached flights are never served older than this
+    max_staleness: Duration,
+
+    /// Cached queries
+    queries: Mutex<HashMap<CacheKey, CachedQuery>>,
+
+    /// Number of lookups served by a query with the same key
+    hits: AtomicU64,
+
+    /// Number of lookups served by a query covering the key
+    superset_hits: AtomicU64,
+
+    /// Calls to svc-gis in flight by cache key
+    calls: Mutex<HashMap<CacheKey, FlightsCall>>,
+
+    /// Number of lookups not served from the cache
+    misses: AtomicU64,
+
+    /// Number of lookups sharing a call in flight
+    coalesced: AtomicU64,
+}
+
+impl FlightsCache {
+    /// A cache never serving flights older than the given milliseconds,
+    ///  disabled if 0
+    pub fn new(max_staleness_ms: u32) -> Self {
+        FlightsCache {
+            max_staleness: Duration::milliseconds(max_staleness_ms as i64),
+            queries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            superset_hits: AtomicU64::new(0),
+            calls: Mutex::new(HashMap::new()),
+            misses: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+        }
+    }
+
+    /// Lock the queries, recovering from a poisoned lock as every
+    ///  modification leaves the map in a consistent state
+    fn lock(&self) -> MutexGuard<'_, HashMap<CacheKey, CachedQuery>> {
+        self.queries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The call to svc-gis in flight for a key, or a new call if none is.
+    /// Calls that completed or were abandoned by every lookup are dropped,
+    ///  a cancelled call is made by the next lookup awaiting it.
+    pub fn in_flight(&self, key: &CacheKey) -> FlightsCall {
+        let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+        calls.retain(|_, call| !call.initialized() && Arc::strong_count(call) > 1);
+
+        match calls.get(key) {
+            Some(call) => {
+                self.coalesced.fetch_add(1, Ordering::Relaxed);
+                call.clone()
+            }
+            None => {
+                let call = FlightsCall::default();
+                calls.insert(*key, call.clone());
+                call
+            }
+        }
+    }
+
+    /// Queries are served while younger than this
+    fn max_age(&self) -> Duration {
+        self.max_staleness
+            .min(Duration::milliseconds(CACHE_TTL_MILLISECONDS))
+    }
+
+    /// Look up the freshest query covering a key, preferring a query with
+    ///  the same key
+    pub fn get(&self, key: &CacheKey, now: DateTime<Utc>) -> Option<CachedQuery> {
+        let oldest = now - self.max_age();
+        let queries = self.lock();
+
+        if let Some(query) = queries.get(key).filter(|query| query.time > oldest) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Some(query.clone());
+        }
+
+        let superset = queries
+            .iter()
+            .filter(|(cached, query)| query.time > oldest && cached.covers(key))
+            .map(|(_, query)| query)
+            .max_by_key(|query| query.time);
+        match superset {
+            Some(query) => {
+                self.superset_hits.fetch_add(1, Ordering::Relaxed);
+                Some(query.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Store the flights of a query, dropping the expired queries and the
+    ///  oldest query if the cache is full
+    pub fn insert(&self, key: CacheKey, query: CachedQuery, now: DateTime<Utc>) {
+        let oldest = now - self.max_age();
+        if query.time <= oldest {
+            return;
+        }
+
+        let mut queries = self.lock();
+        queries.retain(|_, query| query.time > oldest);
+        if queries.len() >= MAX_CACHED_QUERIES && !queries.contains_key(&key) {
+            let expiring = queries
+                .iter()
+                .min_by_key(|(_, query)| query.time)
+                .map(|(key, _)| *key);
+            if let Some(expiring) = expiring {
+                queries.remove(&expiring);
+            }
+        }
+
+        if queries
+            .get(&key)
+            .is_none_or(|cached| cached.time < query.time)
+        {
+            queries.insert(key, query);
+        }
+    }
+
+    /// The hit and miss counts of the cache
+    pub fn stats(&self) -> FlightsCacheStats {
+        FlightsCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            superset_hits: self.superset_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            cached_queries: self.lock().len(),
+            ttl_ms: CACHE_TTL_MILLISECONDS as u64,
+            max_staleness_ms: self.max_staleness.num_milliseconds() as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::uss::Window;
+    use crate::telemetry::tests::flight;
+
+    fn key(lon1: f64, lat1: f64, lon2: f64, lat2: f64, duration_s: f32) -> CacheKey {
+        let window = Window {
+            lon1,
+            lat1,
+            lon2,
+            lat2,
+        };
+        CacheKey::new(&window, duration_s)
+    }
+
+    fn query(time: DateTime<Utc>) -> CachedQuery {
+        CachedQuery {
+            time,
+            flights: Arc::new(vec![flight("AC-1", time, 52.37, 4.90)]),
+        }
+    }
+
+    #[test]
+    fn test_hits_and_misses() {
+        let cache = FlightsCache::new(1_000);
+        let now = Utc::now();
+        let large = key(4.80, 52.30, 5.00, 52.40, 10.0);
+        let small = key(4.85, 52.35, 4.90, 52.38, 5.0);
+        let longer = key(4.85, 52.35, 4.90, 52.38, 20.0);
+
+        assert!(cache.get(&large, now).is_none());
+        cache.insert(large, query(now), now);
+
+        let later = now + Duration::milliseconds(100);
+        assert_eq!(cache.get(&large, later).unwrap().time, now);
+        assert_eq!(cache.get(&small, later).unwrap().time, now);
+        assert!(cache.get(&longer, later).is_none());
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.superset_hits, 1);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.cached_queries, 1);
+        assert_eq!(stats.max_staleness_ms, 1_000);
+    }
+
+    #[test]
+    fn test_staleness() {
+        // the TTL bounds the age of served flights
+        let cache = FlightsCache::new(1_000);
+        let now = Utc::now();
+        let key = key(4.80, 52.30, 5.00, 52.40, 10.0);
+        cache.insert(key, query(now), now);
+        let expired = now + Duration::milliseconds(CACHE_TTL_MILLISECONDS);
+        assert!(cache.get(&key, expired).is_none());
+
+        // and so does a tighter staleness bound
+        let cache = FlightsCache::new(100);
+        cache.insert(key, query(now), now);
+        assert!(cache.get(&key, now + Duration::milliseconds(99)).is_some());
+        assert!(cache.get(&key, now + Duration::milliseconds(100)).is_none());
+
+        // a query that is already stale is not stored
+        let cache = FlightsCache::new(100);
+        cache.insert(key, query(now - Duration::seconds(1)), now);
+        assert!(cache.get(&key, now).is_none());
+
+        let cache = FlightsCache::new(0);
+        cache.insert(key, query(now), now);
+        assert!(cache.get(&key, now).is_none());
+        assert_eq!(cache.stats().cached_queries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_in_flight() {
+        let cache = FlightsCache::new(1_000);
+        let key = key(4.80, 52.30, 5.00, 52.40, 10.0);
+        let first = cache.in_flight(&key);
+        let second = cache.in_flight(&key);
+        assert!(Arc::ptr_eq(&first, &second));
+
+        // every lookup gets the result of the single call
+        let calls = AtomicU64::new(0);
+        let call = || async {
+            calls.fetch_add(1, Ordering::Relaxed);
+            tokio::task::yield_now().await;
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        };
+        let (a, b) = tokio::join!(first.get_or_init(call), second.get_or_init(call));
+        assert_eq!(a.clone().unwrap_err(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(b.clone().unwrap_err(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert_eq!(cache.stats().coalesced, 1);
+
+        // a completed call is not shared
+        assert!(!Arc::ptr_eq(&cache.in_flight(&key), &first));
+
+        // nor is a call abandoned by every lookup
+        drop(first);
+        drop(second);
+        let abandoned = Arc::downgrade(&cache.in_flight(&key));
+        let call = cache.in_flight(&key);
+        assert!(abandoned.upgrade().is_none());
+        assert!(!call.initialized());
+        assert_eq!(cache.stats().coalesced, 1);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let cache = FlightsCache::new(1_000);
+        let now = Utc::now();
+        for i in 0..=MAX_CACHED_QUERIES {
+            let lon = i as f64 * 0.1 - 90.0;
+            let time = now + Duration::microseconds(i as i64);
+            cache.insert(key(lon, 0.0, lon + 0.01, 0.01, 1.0), query(time), now);
+        }
+
+        assert_eq!(cache.stats().cached_queries, MAX_CACHED_QUERIES);
+        assert!(cache
+            .get(&key(-90.0, 0.0, -89.99, 0.01, 1.0), now)
+            .is_none());
+    }
+}
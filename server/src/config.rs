@@ -27,6 +27,60 @@ pub struct Config {
     /// Full url (including port number) to be allowed as request origin for
     /// REST requests
     pub rest_cors_allowed_origin: String,
+    /// port to be used for the embedded DSS REST server
+    ///  (only used with the `embedded_dss` feature)
+    pub dss_port_rest: u16,
+    /// identifier of this USS, reported as the manager of our
+    ///  operational intents
+    pub uss_manager: String,
+    /// base URL at which other USSs reach this service
+    pub uss_base_url: String,
+    /// path to the JSON Lines file storing error reports of other USSs
+    pub reports_file: String,
+    /// path to the JSON file storing the service provider registry
+    pub registry_file: String,
+    /// path to the JSON file listing the vertiports
+    pub vertiports_file: String,
+    /// comma separated list of partner vertiport providers,
+    ///  each of format 'id=base_url'
+    pub partner_vertiport_providers: String,
+    /// directory scanned for METAR and TAF files, disabled if empty
+    pub weather_drop_dir: String,
+    /// path to the JSON file with the locations of weather stations
+    pub weather_stations_file: String,
+    /// comma separated list of providers allowed to read our schedules,
+    ///  each of format 'id=vertiports' with vertiports either '*' or
+    ///  separated by '|'
+    pub schedule_access: String,
+    /// UDP port receiving Open Drone ID frames, disabled if 0
+    pub odid_udp_port: u16,
+    /// comma separated list of providers allowed to push telemetry
+    pub telemetry_providers: String,
+    /// cached svc-gis flights older than this are never served, in
+    ///  milliseconds, cache disabled if 0
+    pub gis_cache_max_staleness_ms: u32,
+    /// service region indexed from svc-gis, of format
+    ///  'lat1,lon1,lat2,lon2', disabled if empty
+    pub live_traffic_region: String,
+    /// interval between polls of the service region, in milliseconds
+    pub live_traffic_poll_ms: u32,
+    /// comma separated list of provider credentials, each of format
+    ///  'id=sha256' with the hex SHA-256 digest of the bearer token
+    ///  issued to the provider
+    pub provider_tokens: String,
+    /// comma separated list of the providers allowed to operate this
+    ///  service, authenticated with their provider token
+    pub admin_providers: String,
+    /// Redis ingesting the aircraft updates of svc-gis, pushed telemetry is
+    ///  not forwarded to svc-gis if None
+    pub redis: Option<RedisConfig>,
+}
+
+/// struct holding the Redis connection options
+#[derive(Debug, Deserialize, Clone)]
+pub struct RedisConfig {
+    /// url of the Redis server, of format 'redis://host:port'
+    pub url: String,
 }
 
 impl Default for Config {
@@ -47,6 +101,24 @@ impl Config {
             rest_request_limit_per_second: 2,
             rest_concurrency_limit_per_service: 5,
             rest_cors_allowed_origin: String::from("http://localhost:3000"),
+            dss_port_rest: 8001,
+            uss_manager: String::from("svc-discovery"),
+            uss_base_url: String::from("http://localhost:8000"),
+            reports_file: String::from("error_reports.jsonl"),
+            registry_file: String::from("service_providers.json"),
+            vertiports_file: String::from("vertiports.json"),
+            partner_vertiport_providers: String::new(),
+            weather_drop_dir: String::new(),
+            weather_stations_file: String::from("weather_stations.json"),
+            schedule_access: String::new(),
+            odid_udp_port: 0,
+            telemetry_providers: String::new(),
+            gis_cache_max_staleness_ms: 500,
+            live_traffic_region: String::new(),
+            live_traffic_poll_ms: 1000,
+            provider_tokens: String::new(),
+            admin_providers: String::new(),
+            redis: None,
         }
     }
 
@@ -74,6 +146,32 @@ impl Config {
                 "rest_cors_allowed_origin",
                 default_config.rest_cors_allowed_origin,
             )?
+            .set_default("dss_port_rest", default_config.dss_port_rest)?
+            .set_default("uss_manager", default_config.uss_manager)?
+            .set_default("uss_base_url", default_config.uss_base_url)?
+            .set_default("reports_file", default_config.reports_file)?
+            .set_default("registry_file", default_config.registry_file)?
+            .set_default("vertiports_file", default_config.vertiports_file)?
+            .set_default(
+                "partner_vertiport_providers",
+                default_config.partner_vertiport_providers,
+            )?
+            .set_default("weather_drop_dir", default_config.weather_drop_dir)?
+            .set_default(
+                "weather_stations_file",
+                default_config.weather_stations_file,
+            )?
+            .set_default("schedule_access", default_config.schedule_access)?
+            .set_default("odid_udp_port", default_config.odid_udp_port)?
+            .set_default("telemetry_providers", default_config.telemetry_providers)?
+            .set_default(
+                "gis_cache_max_staleness_ms",
+                default_config.gis_cache_max_staleness_ms,
+            )?
+            .set_default("live_traffic_region", default_config.live_traffic_region)?
+            .set_default("live_traffic_poll_ms", default_config.live_traffic_poll_ms)?
+            .set_default("provider_tokens", default_config.provider_tokens)?
+            .set_default("admin_providers", default_config.admin_providers)?
             .add_source(Environment::default().separator("__"))
             .build()?
             .try_deserialize()
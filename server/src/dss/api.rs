@@ -0,0 +1,569 @@
+//! REST API of the embedded DSS
+//! Implements the ASTM F3411 DSS endpoints at <https://github.com/uastech/standards/blob/astm_rid_api_2.1/remoteid/canonical.yaml>
+
+use super::store::{polygon_window, Dss, DssError};
+use crate::rest::api::rest_types::*;
+use crate::rest::api::uss::{parse_coordinate, Window};
+use axum::extract::{Path, Query};
+use axum::{Extension, Json};
+use hyper::header::CONTENT_TYPE;
+use hyper::{Body, Client, Method, Request, StatusCode};
+use lib_common::time::{DateTime, Utc};
+use std::sync::Arc;
+
+impl From<DssError> for StatusCode {
+    fn from(e: DssError) -> Self {
+        match e {
+            DssError::NotFound => StatusCode::NOT_FOUND,
+            DssError::AlreadyExists => StatusCode::CONFLICT,
+            DssError::VersionMismatch => StatusCode::CONFLICT,
+            DssError::InvalidExtents => StatusCode::BAD_REQUEST,
+            DssError::AreaTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            DssError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Parse an area of format "lat1,lng1,lat2,lng2,lat3,lng3,..." into its bounding window
+fn parse_area(area: &str) -> Result<Window, StatusCode> {
+    let values = area.split(',').collect::<Vec<&str>>();
+    if values.len() < 6 || values.len() % 2 != 0 {
+        rest_error!("area must be a string of format 'lat1,lng1,lat2,lng2,lat3,lng3,...'.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let vertices = values
+        .chunks(2)
+        .map(|pair| {
+            Ok(LatLngPoint {
+                lat: parse_coordinate(pair[0], true)?,
+                lng: parse_coordinate(pair[1], false)?,
+            })
+        })
+        .collect::<Result<Vec<_>, StatusCode>>()?;
+
+    polygon_window(&vertices).map_err(StatusCode::from)
+}
+
+/// Parse an optional RFC3339 time
+fn parse_time(time: &Option<String>) -> Result<Option<DateTime<Utc>>, StatusCode> {
+    match time {
+        None => Ok(None),
+        Some(value) => DateTime::parse_from_rfc3339(value)
+            .map(|dt| Some(dt.with_timezone(&Utc)))
+            .map_err(|e| {
+                rest_error!("invalid time '{}': {}", value, e);
+                StatusCode::BAD_REQUEST
+            }),
+    }
+}
+
+/// Notify subscribers of a change to an ISA
+/// Notifications are sent in the background, failures are only logged
+fn notify_subscribers(
+    isa_id: &str,
+    subscribers: &[SubscriberToNotify],
+    service_area: Option<IdentificationServiceArea>,
+    extents: Option<Volume4D>,
+) {
+    for subscriber in subscribers {
+        let uri = format!(
+            "{}/uss/identification_service_areas/{}",
+            subscriber.url.trim_end_matches('/'),
+            isa_id
+        );
+
+        let body = PutIdentificationServiceAreaNotificationParameters {
+            subscriptions: subscriber.subscriptions.clone(),
+            service_area: service_area.clone(),
+            extents: extents.clone(),
+        };
+
+        tokio::spawn(async move {
+            let body = match serde_json::to_string(&body) {
+                Ok(body) => body,
+                Err(e) => {
+                    rest_error!("could not serialize notification: {}", e);
+                    return;
+                }
+            };
+
+            let request = match Request::builder()
+                .method(Method::POST)
+                .uri(&uri)
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(body))
+            {
+                Ok(request) => request,
+                Err(e) => {
+                    rest_error!("could not build notification for {}: {}", uri, e);
+                    return;
+                }
+            };
+
+            match Client::new().request(request).await {
+                Ok(response) if response.status().is_success() => {
+                    rest_debug!("notified subscriber at {}.", uri);
+                }
+                Ok(response) => {
+                    rest_warn!("subscriber at {} responded {}.", uri, response.status());
+                }
+                Err(e) => {
+                    rest_warn!("could not notify subscriber at {}: {}", uri, e);
+                }
+            }
+        });
+    }
+}
+
+/// Search for ISAs in an area
+#[utoipa::path(
+    get,
+    path = "/rid/v2/dss/identification_service_areas",
+    tag = "svc-discovery",
+    params(SearchAreaRequest),
+    responses(
+        (status = 200, description = "ISAs in the area were successfully retrieved.", body = SearchIdentificationServiceAreasResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 413, description = "The requested area was too large.")
+    )
+)]
+pub async fn search_isas(
+    Extension(dss): Extension<Arc<Dss>>,
+    Query(query): Query<SearchAreaRequest>,
+) -> Result<Json<SearchIdentificationServiceAreasResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let window = parse_area(&query.area)?;
+    let service_areas = dss.search_isas(
+        &window,
+        parse_time(&query.earliest_time)?,
+        parse_time(&query.latest_time)?,
+        Utc::now(),
+    )?;
+
+    Ok(Json(SearchIdentificationServiceAreasResponse {
+        service_areas,
+    }))
+}
+
+/// Get a single ISA
+#[utoipa::path(
+    get,
+    path = "/rid/v2/dss/identification_service_areas/{id}",
+    tag = "svc-discovery",
+    params(("id" = String, Path, description = "ISA identifier")),
+    responses(
+        (status = 200, description = "The ISA was successfully retrieved.", body = GetIdentificationServiceAreaResponse),
+        (status = 404, description = "The requested ISA was not found.")
+    )
+)]
+pub async fn get_isa(
+    Extension(dss): Extension<Arc<Dss>>,
+    Path(id): Path<String>,
+) -> Result<Json<GetIdentificationServiceAreaResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let (service_area, _) = dss.get_isa(&id, Utc::now())?;
+    Ok(Json(GetIdentificationServiceAreaResponse { service_area }))
+}
+
+/// Create a new ISA
+#[utoipa::path(
+    put,
+    path = "/rid/v2/dss/identification_service_areas/{id}",
+    tag = "svc-discovery",
+    params(("id" = String, Path, description = "ISA identifier")),
+    request_body = CreateIdentificationServiceAreaParameters,
+    responses(
+        (status = 200, description = "The ISA was successfully created.", body = PutIdentificationServiceAreaResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 409, description = "An ISA with this identifier already exists."),
+        (status = 413, description = "The ISA area was too large.")
+    )
+)]
+pub async fn create_isa(
+    Extension(dss): Extension<Arc<Dss>>,
+    Path(id): Path<String>,
+    Json(params): Json<CreateIdentificationServiceAreaParameters>,
+) -> Result<Json<PutIdentificationServiceAreaResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let extents = params.extents.clone();
+    let response = dss.put_isa(&id, None, params, Utc::now())?;
+    notify_subscribers(
+        &id,
+        &response.subscribers,
+        Some(response.service_area.clone()),
+        Some(extents),
+    );
+
+    Ok(Json(response))
+}
+
+/// Update an existing ISA
+#[utoipa::path(
+    put,
+    path = "/rid/v2/dss/identification_service_areas/{id}/{version}",
+    tag = "svc-discovery",
+    params(
+        ("id" = String, Path, description = "ISA identifier"),
+        ("version" = String, Path, description = "Current version of the ISA")
+    ),
+    request_body = CreateIdentificationServiceAreaParameters,
+    responses(
+        (status = 200, description = "The ISA was successfully updated.", body = PutIdentificationServiceAreaResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 404, description = "The requested ISA was not found."),
+        (status = 409, description = "The provided version does not match the current version."),
+        (status = 413, description = "The ISA area was too large.")
+    )
+)]
+pub async fn update_isa(
+    Extension(dss): Extension<Arc<Dss>>,
+    Path((id, version)): Path<(String, String)>,
+    Json(params): Json<CreateIdentificationServiceAreaParameters>,
+) -> Result<Json<PutIdentificationServiceAreaResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let extents = params.extents.clone();
+    let response = dss.put_isa(&id, Some(&version), params, Utc::now())?;
+    notify_subscribers(
+        &id,
+        &response.subscribers,
+        Some(response.service_area.clone()),
+        Some(extents),
+    );
+
+    Ok(Json(response))
+}
+
+/// Delete an ISA
+#[utoipa::path(
+    delete,
+    path = "/rid/v2/dss/identification_service_areas/{id}/{version}",
+    tag = "svc-discovery",
+    params(
+        ("id" = String, Path, description = "ISA identifier"),
+        ("version" = String, Path, description = "Current version of the ISA")
+    ),
+    responses(
+        (status = 200, description = "The ISA was successfully deleted.", body = PutIdentificationServiceAreaResponse),
+        (status = 404, description = "The requested ISA was not found."),
+        (status = 409, description = "The provided version does not match the current version.")
+    )
+)]
+pub async fn delete_isa(
+    Extension(dss): Extension<Arc<Dss>>,
+    Path((id, version)): Path<(String, String)>,
+) -> Result<Json<PutIdentificationServiceAreaResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let response = dss.delete_isa(&id, &version, Utc::now())?;
+    notify_subscribers(&id, &response.subscribers, None, None);
+
+    Ok(Json(response))
+}
+
+/// Search for subscriptions in an area
+#[utoipa::path(
+    get,
+    path = "/rid/v2/dss/subscriptions",
+    tag = "svc-discovery",
+    params(SearchAreaRequest),
+    responses(
+        (status = 200, description = "Subscriptions in the area were successfully retrieved.", body = SearchSubscriptionsResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 413, description = "The requested area was too large.")
+    )
+)]
+pub async fn search_subscriptions(
+    Extension(dss): Extension<Arc<Dss>>,
+    Query(query): Query<SearchAreaRequest>,
+) -> Result<Json<SearchSubscriptionsResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let window = parse_area(&query.area)?;
+    let subscriptions = dss.search_subscriptions(&window, Utc::now())?;
+
+    Ok(Json(SearchSubscriptionsResponse { subscriptions }))
+}
+
+/// Get a single subscription
+#[utoipa::path(
+    get,
+    path = "/rid/v2/dss/subscriptions/{id}",
+    tag = "svc-discovery",
+    params(("id" = String, Path, description = "Subscription identifier")),
+    responses(
+        (status = 200, description = "The subscription was successfully retrieved.", body = GetSubscriptionResponse),
+        (status = 404, description = "The requested subscription was not found.")
+    )
+)]
+pub async fn get_subscription(
+    Extension(dss): Extension<Arc<Dss>>,
+    Path(id): Path<String>,
+) -> Result<Json<GetSubscriptionResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let subscription = dss.get_subscription(&id, Utc::now())?;
+    Ok(Json(GetSubscriptionResponse { subscription }))
+}
+
+/// Create a new subscription
+#[utoipa::path(
+    put,
+    path = "/rid/v2/dss/subscriptions/{id}",
+    tag = "svc-discovery",
+    params(("id" = String, Path, description = "Subscription identifier")),
+    request_body = CreateSubscriptionParameters,
+    responses(
+        (status = 200, description = "The subscription was successfully created.", body = PutSubscriptionResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 409, description = "A subscription with this identifier already exists."),
+        (status = 413, description = "The subscription area was too large.")
+    )
+)]
+pub async fn create_subscription(
+    Extension(dss): Extension<Arc<Dss>>,
+    Path(id): Path<String>,
+    Json(params): Json<CreateSubscriptionParameters>,
+) -> Result<Json<PutSubscriptionResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let response = dss.put_subscription(&id, None, params, Utc::now())?;
+    Ok(Json(response))
+}
+
+/// Update an existing subscription
+#[utoipa::path(
+    put,
+    path = "/rid/v2/dss/subscriptions/{id}/{version}",
+    tag = "svc-discovery",
+    params(
+        ("id" = String, Path, description = "Subscription identifier"),
+        ("version" = String, Path, description = "Current version of the subscription")
+    ),
+    request_body = CreateSubscriptionParameters,
+    responses(
+        (status = 200, description = "The subscription was successfully updated.", body = PutSubscriptionResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 404, description = "The requested subscription was not found."),
+        (status = 409, description = "The provided version does not match the current version."),
+        (status = 413, description = "The subscription area was too large.")
+    )
+)]
+pub async fn update_subscription(
+    Extension(dss): Extension<Arc<Dss>>,
+    Path((id, version)): Path<(String, String)>,
+    Json(params): Json<CreateSubscriptionParameters>,
+) -> Result<Json<PutSubscriptionResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let response = dss.put_subscription(&id, Some(&version), params, Utc::now())?;
+    Ok(Json(response))
+}
+
+/// Delete a subscription
+#[utoipa::path(
+    delete,
+    path = "/rid/v2/dss/subscriptions/{id}/{version}",
+    tag = "svc-discovery",
+    params(
+        ("id" = String, Path, description = "Subscription identifier"),
+        ("version" = String, Path, description = "Current version of the subscription")
+    ),
+    responses(
+        (status = 200, description = "The subscription was successfully deleted.", body = GetSubscriptionResponse),
+        (status = 404, description = "The requested subscription was not found."),
+        (status = 409, description = "The provided version does not match the current version.")
+    )
+)]
+pub async fn delete_subscription(
+    Extension(dss): Extension<Arc<Dss>>,
+    Path((id, version)): Path<(String, String)>,
+) -> Result<Json<GetSubscriptionResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let subscription = dss.delete_subscription(&id, &version, Utc::now())?;
+    Ok(Json(GetSubscriptionResponse { subscription }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::Duration;
+
+    fn isa_params() -> CreateIdentificationServiceAreaParameters {
+        let now = Utc::now();
+        CreateIdentificationServiceAreaParameters {
+            extents: Volume4D {
+                volume: Volume3D {
+                    outline_circle: Some(Circle {
+                        center: LatLngPoint {
+                            lat: 52.37,
+                            lng: 4.89,
+                        },
+                        radius: Radius {
+                            value: 500.0,
+                            units: "M".to_string(),
+                        },
+                    }),
+                    outline_polygon: None,
+                    altitude_lower: None,
+                    altitude_upper: None,
+                },
+                time_start: now.into(),
+                time_end: (now + Duration::minutes(5)).into(),
+            },
+            uss_base_url: "http://localhost:1/rid/v2".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_area() {
+        let window = parse_area("52.0,4.0,52.1,4.0,52.1,4.1").unwrap();
+        assert_eq!(window.bounds(), (4.0, 52.0, 4.1, 52.1));
+
+        // too few points
+        let e = parse_area("52.0,4.0,52.1,4.0").unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        // odd number of values
+        let e = parse_area("52.0,4.0,52.1,4.0,52.1,4.1,52.0").unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        // invalid latitude
+        let e = parse_area("92.0,4.0,52.1,4.0,52.1,4.1").unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert!(parse_time(&None).unwrap().is_none());
+        assert!(parse_time(&Some("2024-01-01T00:00:00Z".to_string()))
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            parse_time(&Some("tomorrow".to_string())).unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    async fn test_isa_endpoints() {
+        let dss = Extension(Arc::new(Dss::default()));
+        let id = "ffffffff-0000-4000-8000-000000000001".to_string();
+
+        let created = create_isa(dss.clone(), Path(id.clone()), Json(isa_params()))
+            .await
+            .unwrap();
+
+        let e = create_isa(dss.clone(), Path(id.clone()), Json(isa_params()))
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::CONFLICT);
+
+        let found = get_isa(dss.clone(), Path(id.clone())).await.unwrap();
+        assert_eq!(found.service_area.id, id);
+
+        let query = SearchAreaRequest {
+            area: "52.36,4.88,52.38,4.88,52.38,4.90".to_string(),
+            earliest_time: None,
+            latest_time: None,
+        };
+        let result = search_isas(dss.clone(), Query(query)).await.unwrap();
+        assert_eq!(result.service_areas.len(), 1);
+
+        let version = created.service_area.version.clone();
+        let updated = update_isa(
+            dss.clone(),
+            Path((id.clone(), version.clone())),
+            Json(isa_params()),
+        )
+        .await
+        .unwrap();
+
+        let e = delete_isa(dss.clone(), Path((id.clone(), version)))
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::CONFLICT);
+
+        let _ = delete_isa(
+            dss.clone(),
+            Path((id.clone(), updated.service_area.version.clone())),
+        )
+        .await
+        .unwrap();
+
+        let e = get_isa(dss.clone(), Path(id)).await.unwrap_err();
+        assert_eq!(e, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_subscription_endpoints() {
+        let dss = Extension(Arc::new(Dss::default()));
+        let id = "ffffffff-0000-4000-8000-000000000002".to_string();
+        let params = CreateSubscriptionParameters {
+            extents: isa_params().extents,
+            uss_base_url: "http://localhost:1/rid/v2".to_string(),
+        };
+
+        let created = create_subscription(dss.clone(), Path(id.clone()), Json(params.clone()))
+            .await
+            .unwrap();
+
+        // an ISA in the subscription area triggers a notification
+        let isa = create_isa(
+            dss.clone(),
+            Path("ffffffff-0000-4000-8000-000000000003".to_string()),
+            Json(isa_params()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(isa.subscribers.len(), 1);
+
+        let query = SearchAreaRequest {
+            area: "52.36,4.88,52.38,4.88,52.38,4.90".to_string(),
+            earliest_time: None,
+            latest_time: None,
+        };
+        let result = search_subscriptions(dss.clone(), Query(query))
+            .await
+            .unwrap();
+        assert_eq!(result.subscriptions.len(), 1);
+
+        let found = get_subscription(dss.clone(), Path(id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(found.subscription.notification_index, 1);
+
+        let e = update_subscription(
+            dss.clone(),
+            Path((id.clone(), "wrong".to_string())),
+            Json(params.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::CONFLICT);
+
+        let updated = update_subscription(
+            dss.clone(),
+            Path((id.clone(), created.subscription.version.clone())),
+            Json(params),
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.service_areas.len(), 1);
+
+        let _ = delete_subscription(
+            dss.clone(),
+            Path((id.clone(), updated.subscription.version.clone())),
+        )
+        .await
+        .unwrap();
+
+        let e = get_subscription(dss.clone(), Path(id)).await.unwrap_err();
+        assert_eq!(e, StatusCode::NOT_FOUND);
+    }
+}
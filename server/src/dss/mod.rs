@@ -0,0 +1,86 @@
+//! Embedded DSS
+//! An in-memory ASTM F3411 Discovery and Synchronization Service for
+//! deployments without an external DSS, such as test regions and
+//! integration tests. ISAs and subscriptions are kept in a grid cell index
+//! and expire at the end of their time range.
+
+pub mod api;
+pub mod server;
+pub mod store;
+
+use crate::rest::api::rest_types;
+use std::sync::Arc;
+use store::Dss;
+use tokio::sync::OnceCell;
+use utoipa::OpenApi;
+
+/// OpenAPI 3.0 specification for the embedded DSS
+#[derive(OpenApi, Copy, Clone, Debug)]
+#[openapi(
+    paths(
+        api::search_isas,
+        api::get_isa,
+        api::create_isa,
+        api::update_isa,
+        api::delete_isa,
+        api::search_subscriptions,
+        api::get_subscription,
+        api::create_subscription,
+        api::update_subscription,
+        api::delete_subscription,
+    ),
+    components(
+        schemas(
+            rest_types::IdentificationServiceArea,
+            rest_types::Subscription,
+            rest_types::SubscriptionState,
+            rest_types::SubscriberToNotify,
+            rest_types::CreateIdentificationServiceAreaParameters,
+            rest_types::PutIdentificationServiceAreaResponse,
+            rest_types::GetIdentificationServiceAreaResponse,
+            rest_types::SearchIdentificationServiceAreasResponse,
+            rest_types::CreateSubscriptionParameters,
+            rest_types::PutSubscriptionResponse,
+            rest_types::GetSubscriptionResponse,
+            rest_types::SearchSubscriptionsResponse,
+            rest_types::PutIdentificationServiceAreaNotificationParameters,
+            rest_types::Volume4D,
+            rest_types::Volume3D,
+            rest_types::Circle,
+            rest_types::Polygon,
+            rest_types::Radius,
+            rest_types::LatLngPoint,
+            rest_types::Altitude,
+            rest_types::Time,
+        )
+    ),
+    tags(
+        (name = "svc-discovery", description = "svc-discovery embedded DSS API")
+    )
+)]
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) OpenAPI 3.0 specification, integration tested
+pub struct DssApiDoc;
+
+pub(crate) static DSS: OnceCell<Arc<Dss>> = OnceCell::const_new();
+
+/// Returns the embedded DSS storage, shared by the DSS server
+///  and the `/uss/flights` ISA check.
+/// Initializes the storage if it hasn't been initialized yet.
+pub async fn get_dss() -> Arc<Dss> {
+    DSS.get_or_init(|| async move { Arc::new(Dss::default()) })
+        .await
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_dss() {
+        let a = get_dss().await;
+        let b = get_dss().await;
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+}
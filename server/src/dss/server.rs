@@ -0,0 +1,117 @@
+//! Embedded DSS server implementation
+
+use super::{api, get_dss};
+use crate::config::Config;
+use crate::shutdown_signal;
+use axum::{extract::Extension, routing, Router};
+use lib_common::time::Utc;
+use std::net::SocketAddr;
+use tower::ServiceBuilder;
+use tower_http::trace::TraceLayer;
+
+/// Interval at which expired ISAs and subscriptions are removed
+const PURGE_INTERVAL_SECONDS: u64 = 10;
+
+/// Starts the embedded DSS server
+///
+/// # Example:
+/// ```
+/// use svc_discovery::dss::server::dss_server;
+/// use svc_discovery::Config;
+/// async fn example() -> Result<(), tokio::task::JoinError> {
+///     let config = Config::default();
+///     tokio::spawn(dss_server(config, None)).await;
+///     Ok(())
+/// }
+/// ```
+pub async fn dss_server(
+    config: Config,
+    shutdown_rx: Option<tokio::sync::oneshot::Receiver<()>>,
+) -> Result<(), ()> {
+    rest_info!("entry.");
+    let dss_port = config.dss_port_rest;
+    let full_dss_addr: SocketAddr = format!("[::]:{}", dss_port).parse().map_err(|e| {
+        rest_error!("invalid address: {:?}, exiting.", e);
+    })?;
+
+    let dss = get_dss().await;
+
+    // Expired entities are also purged on access, this keeps memory bounded
+    //  when nobody queries the DSS
+    let purge_dss = dss.clone();
+    let purge_task = tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(PURGE_INTERVAL_SECONDS));
+        loop {
+            interval.tick().await;
+            purge_dss.purge_expired(Utc::now());
+        }
+    });
+
+    let app = Router::new()
+        .route(
+            "/rid/v2/dss/identification_service_areas",
+            routing::get(api::search_isas),
+        )
+        .route(
+            "/rid/v2/dss/identification_service_areas/:id",
+            routing::get(api::get_isa).put(api::create_isa),
+        )
+        .route(
+            "/rid/v2/dss/identification_service_areas/:id/:version",
+            routing::put(api::update_isa).delete(api::delete_isa),
+        )
+        .route(
+            "/rid/v2/dss/subscriptions",
+            routing::get(api::search_subscriptions),
+        )
+        .route(
+            "/rid/v2/dss/subscriptions/:id",
+            routing::get(api::get_subscription).put(api::create_subscription),
+        )
+        .route(
+            "/rid/v2/dss/subscriptions/:id/:version",
+            routing::put(api::update_subscription).delete(api::delete_subscription),
+        )
+        .layer(ServiceBuilder::new().layer(TraceLayer::new_for_http()))
+        .layer(Extension(dss)); // Extension layer must be last
+
+    rest_info!("embedded DSS hosted at {:?}", full_dss_addr);
+    let result = axum::Server::bind(&full_dss_addr)
+        .serve(app.into_make_service())
+        .with_graceful_shutdown(shutdown_signal("dss", shutdown_rx))
+        .await
+        .map_err(|e| {
+            rest_error!("could not start DSS server: {}", e);
+        });
+
+    purge_task.abort();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dss_server_start_and_shutdown() {
+        use tokio::time::{sleep, Duration};
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = Config::default();
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        // Start the DSS server
+        tokio::spawn(dss_server(config, Some(shutdown_rx)));
+
+        // Give the server time to get through the startup sequence (and thus code)
+        sleep(Duration::from_secs(1)).await;
+
+        // Shut down server
+        assert!(shutdown_tx.send(()).is_ok());
+
+        ut_info!("success");
+    }
+}
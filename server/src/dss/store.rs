@@ -0,0 +1,958 @@
+//! In-memory storage of ISAs and subscriptions, indexed by grid cells
+
+use crate::rest::api::rest_types::*;
+use crate::rest::api::uss::Window;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use std::sync::{Mutex, MutexGuard};
+
+/// Size of a cell of the spatial index, in degrees
+pub const CELL_SIZE_DEGREES: f64 = 0.01;
+
+/// Maximum number of cells a single ISA, subscription or search may cover
+pub const MAX_CELLS: usize = 10_000;
+
+/// Maximum lifetime of a subscription, in hours
+pub const MAX_SUBSCRIPTION_DURATION_HOURS: i64 = 24;
+
+/// A cell of the spatial index
+type Cell = (i32, i32);
+
+/// Errors returned by the DSS store
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DssError {
+    /// No entity exists with the provided ID
+    NotFound,
+
+    /// An entity with the provided ID already exists
+    AlreadyExists,
+
+    /// The provided version does not match the current version
+    VersionMismatch,
+
+    /// The provided extents or times are invalid
+    InvalidExtents,
+
+    /// The provided area covers too many cells
+    AreaTooLarge,
+
+    /// The DSS state could not be accessed
+    Internal,
+}
+
+impl std::error::Error for DssError {}
+
+impl Display for DssError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DssError::NotFound => write!(f, "Entity not found"),
+            DssError::AlreadyExists => write!(f, "Entity already exists"),
+            DssError::VersionMismatch => write!(f, "Version does not match current version"),
+            DssError::InvalidExtents => write!(f, "Invalid extents"),
+            DssError::AreaTooLarge => write!(f, "Area is too large"),
+            DssError::Internal => write!(f, "Internal error"),
+        }
+    }
+}
+
+/// Validated extents of an ISA or subscription
+#[derive(Debug, Clone)]
+struct Extents {
+    window: Window,
+    cells: Vec<Cell>,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+}
+
+impl Extents {
+    /// Check if two extents overlap in space and time
+    fn intersects(
+        &self,
+        window: &Window,
+        time_start: DateTime<Utc>,
+        time_end: DateTime<Utc>,
+    ) -> bool {
+        self.window.intersects(window)
+            && times_overlap(self.time_start, self.time_end, time_start, time_end)
+    }
+}
+
+/// An ISA with its indexing information
+#[derive(Debug, Clone)]
+struct IsaRecord {
+    isa: IdentificationServiceArea,
+    volume: Volume4D,
+    extents: Extents,
+}
+
+/// A subscription with its indexing information
+#[derive(Debug, Clone)]
+struct SubscriptionRecord {
+    subscription: Subscription,
+    extents: Extents,
+}
+
+#[derive(Debug, Default)]
+struct DssState {
+    isas: HashMap<String, IsaRecord>,
+    subscriptions: HashMap<String, SubscriptionRecord>,
+    isa_cells: HashMap<Cell, HashSet<String>>,
+    subscription_cells: HashMap<Cell, HashSet<String>>,
+    version_counter: u64,
+}
+
+/// In-memory DSS storage
+#[derive(Debug, Default)]
+pub struct Dss {
+    state: Mutex<DssState>,
+}
+
+/// Get the bounding window of a 3D volume
+fn volume_window(volume: &Volume3D) -> Result<Window, DssError> {
+    match (&volume.outline_polygon, &volume.outline_circle) {
+        (Some(polygon), None) => {
+            if polygon.vertices.len() < 3 {
+                rest_error!("polygon must have at least 3 vertices.");
+                return Err(DssError::InvalidExtents);
+            }
+
+            polygon_window(&polygon.vertices)
+        }
+        (None, Some(circle)) => {
+            let center = circle.center;
+            if circle.radius.units != "M" || circle.radius.value <= 0.0 {
+                rest_error!("circle radius must be a positive value in meters.");
+                return Err(DssError::InvalidExtents);
+            }
+
+            let radius = circle.radius.value as f64;
+            let d_lat = radius / METERS_PER_DEGREE;
+            let d_lon = radius / (METERS_PER_DEGREE * center.lat.to_radians().cos().max(0.01));
+            polygon_window(&[
+                LatLngPoint {
+                    lat: (center.lat - d_lat).max(-90.0),
+                    lng: (center.lng - d_lon).max(-180.0),
+                },
+                LatLngPoint {
+                    lat: (center.lat + d_lat).min(90.0),
+                    lng: (center.lng + d_lon).min(180.0),
+                },
+            ])
+        }
+        _ => {
+            rest_error!("exactly one of outline_polygon or outline_circle must be provided.");
+            Err(DssError::InvalidExtents)
+        }
+    }
+}
+
+/// Get the bounding window of a list of points
+pub(crate) fn polygon_window(vertices: &[LatLngPoint]) -> Result<Window, DssError> {
+    if vertices
+        .iter()
+        .any(|v| !(-90.0..=90.0).contains(&v.lat) || !(-180.0..=180.0).contains(&v.lng))
+    {
+        rest_error!("vertex coordinates out of range.");
+        return Err(DssError::InvalidExtents);
+    }
+
+    let first = vertices.first().ok_or(DssError::InvalidExtents)?;
+    let init = Window {
+        lon1: first.lng,
+        lat1: first.lat,
+        lon2: first.lng,
+        lat2: first.lat,
+    };
+
+    Ok(vertices.iter().fold(init, |w, v| Window {
+        lon1: w.lon1.min(v.lng),
+        lat1: w.lat1.min(v.lat),
+        lon2: w.lon2.max(v.lng),
+        lat2: w.lat2.max(v.lat),
+    }))
+}
+
+/// Get the cells of the spatial index covered by a window
+pub(crate) fn window_cells(window: &Window) -> Result<Vec<Cell>, DssError> {
+    let (min_lon, min_lat, max_lon, max_lat) = window.bounds();
+    let x_min = (min_lon / CELL_SIZE_DEGREES).floor() as i32;
+    let x_max = (max_lon / CELL_SIZE_DEGREES).floor() as i32;
+    let y_min = (min_lat / CELL_SIZE_DEGREES).floor() as i32;
+    let y_max = (max_lat / CELL_SIZE_DEGREES).floor() as i32;
+
+    let count = (x_max - x_min + 1) as usize * (y_max - y_min + 1) as usize;
+    if count > MAX_CELLS {
+        rest_error!("area covers {} cells, maximum is {}.", count, MAX_CELLS);
+        return Err(DssError::AreaTooLarge);
+    }
+
+    Ok((x_min..=x_max)
+        .flat_map(|x| (y_min..=y_max).map(move |y| (x, y)))
+        .collect())
+}
+
+/// Validate a 4D volume and compute its index cells
+fn validate_extents(extents: &Volume4D, now: DateTime<Utc>) -> Result<Extents, DssError> {
+    let time_start = extents.time_start.to_datetime().ok_or_else(|| {
+        rest_error!("invalid time_start.");
+        DssError::InvalidExtents
+    })?;
+
+    let time_end = extents.time_end.to_datetime().ok_or_else(|| {
+        rest_error!("invalid time_end.");
+        DssError::InvalidExtents
+    })?;
+
+    if time_end <= time_start || time_end <= now {
+        rest_error!("time_end must be after time_start and in the future.");
+        return Err(DssError::InvalidExtents);
+    }
+
+    let window = volume_window(&extents.volume)?;
+    let cells = window_cells(&window)?;
+
+    Ok(Extents {
+        window,
+        cells,
+        time_start,
+        time_end,
+    })
+}
+
+/// Check if two time ranges overlap
+fn times_overlap(
+    start_a: DateTime<Utc>,
+    end_a: DateTime<Utc>,
+    start_b: DateTime<Utc>,
+    end_b: DateTime<Utc>,
+) -> bool {
+    start_a <= end_b && start_b <= end_a
+}
+
+/// The owner of an entity, assigned from the host of its USS base URL
+///  since requests are not authenticated
+fn owner_from_url(url: &str) -> String {
+    url.split("://")
+        .last()
+        .unwrap_or(url)
+        .split('/')
+        .next()
+        .unwrap_or(url)
+        .to_string()
+}
+
+impl DssState {
+    fn next_version(&mut self) -> String {
+        self.version_counter += 1;
+        format!("{:016x}", self.version_counter)
+    }
+
+    /// Remove all ISAs and subscriptions that ended before `now`
+    fn purge_expired(&mut self, now: DateTime<Utc>) {
+        let expired_isas: Vec<String> = self
+            .isas
+            .iter()
+            .filter(|(_, r)| r.extents.time_end < now)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for id in expired_isas {
+            rest_debug!("ISA {} expired.", id);
+            self.remove_isa(&id);
+        }
+
+        let expired_subscriptions: Vec<String> = self
+            .subscriptions
+            .iter()
+            .filter(|(_, r)| r.extents.time_end < now)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for id in expired_subscriptions {
+            rest_debug!("subscription {} expired.", id);
+            self.remove_subscription(&id);
+        }
+    }
+
+    fn remove_isa(&mut self, id: &str) -> Option<IsaRecord> {
+        let record = self.isas.remove(id)?;
+        for cell in &record.extents.cells {
+            if let Some(ids) = self.isa_cells.get_mut(cell) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.isa_cells.remove(cell);
+                }
+            }
+        }
+
+        Some(record)
+    }
+
+    fn insert_isa(&mut self, record: IsaRecord) {
+        for cell in &record.extents.cells {
+            self.isa_cells
+                .entry(*cell)
+                .or_default()
+                .insert(record.isa.id.clone());
+        }
+
+        self.isas.insert(record.isa.id.clone(), record);
+    }
+
+    fn remove_subscription(&mut self, id: &str) -> Option<SubscriptionRecord> {
+        let record = self.subscriptions.remove(id)?;
+        for cell in &record.extents.cells {
+            if let Some(ids) = self.subscription_cells.get_mut(cell) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.subscription_cells.remove(cell);
+                }
+            }
+        }
+
+        Some(record)
+    }
+
+    fn insert_subscription(&mut self, record: SubscriptionRecord) {
+        for cell in &record.extents.cells {
+            self.subscription_cells
+                .entry(*cell)
+                .or_default()
+                .insert(record.subscription.id.clone());
+        }
+
+        self.subscriptions
+            .insert(record.subscription.id.clone(), record);
+    }
+
+    /// IDs of the ISAs indexed in any of the given cells
+    fn isa_candidates(&self, cells: &[Cell]) -> HashSet<String> {
+        cells
+            .iter()
+            .filter_map(|cell| self.isa_cells.get(cell))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// IDs of the subscriptions indexed in any of the given cells
+    fn subscription_candidates(&self, cells: &[Cell]) -> HashSet<String> {
+        cells
+            .iter()
+            .filter_map(|cell| self.subscription_cells.get(cell))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// ISAs intersecting the given extents
+    fn find_isas(&self, extents: &Extents) -> Vec<IdentificationServiceArea> {
+        let mut isas: Vec<IdentificationServiceArea> = self
+            .isa_candidates(&extents.cells)
+            .iter()
+            .filter_map(|id| self.isas.get(id))
+            .filter(|r| {
+                r.extents
+                    .intersects(&extents.window, extents.time_start, extents.time_end)
+            })
+            .map(|r| r.isa.clone())
+            .collect();
+
+        isas.sort_by(|a, b| a.id.cmp(&b.id));
+        isas
+    }
+
+    /// Increment the notification index of all subscriptions affected by
+    ///  a change in the given areas, and group them per USS
+    fn notify_subscriptions(&mut self, areas: &[&Extents]) -> Vec<SubscriberToNotify> {
+        let mut ids: Vec<String> = areas
+            .iter()
+            .flat_map(|extents| {
+                self.subscription_candidates(&extents.cells)
+                    .into_iter()
+                    .filter(|id| {
+                        self.subscriptions.get(id).is_some_and(|r| {
+                            r.extents.intersects(
+                                &extents.window,
+                                extents.time_start,
+                                extents.time_end,
+                            )
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        ids.sort();
+        ids.dedup();
+
+        let mut subscribers: Vec<SubscriberToNotify> = vec![];
+        for id in ids {
+            let Some(record) = self.subscriptions.get_mut(&id) else {
+                continue;
+            };
+
+            record.subscription.notification_index += 1;
+            let state = SubscriptionState {
+                subscription_id: id.clone(),
+                notification_index: record.subscription.notification_index,
+            };
+
+            let url = record.subscription.uss_base_url.clone();
+            match subscribers.iter_mut().find(|s| s.url == url) {
+                Some(subscriber) => subscriber.subscriptions.push(state),
+                None => subscribers.push(SubscriberToNotify {
+                    subscriptions: vec![state],
+                    url,
+                }),
+            }
+        }
+
+        subscribers
+    }
+}
+
+impl Dss {
+    fn lock(&self) -> Result<MutexGuard<'_, DssState>, DssError> {
+        self.state.lock().map_err(|_| {
+            rest_error!("DSS state lock poisoned.");
+            DssError::Internal
+        })
+    }
+
+    /// Create or update an ISA
+    /// Updates require the current version of the ISA
+    pub fn put_isa(
+        &self,
+        id: &str,
+        version: Option<&str>,
+        params: CreateIdentificationServiceAreaParameters,
+        now: DateTime<Utc>,
+    ) -> Result<PutIdentificationServiceAreaResponse, DssError> {
+        let extents = validate_extents(&params.extents, now)?;
+        let mut state = self.lock()?;
+
+        state.purge_expired(now);
+        let previous = match (state.isas.get(id), version) {
+            (Some(_), None) => return Err(DssError::AlreadyExists),
+            (None, Some(_)) => return Err(DssError::NotFound),
+            (Some(r), Some(v)) if r.isa.version != v => return Err(DssError::VersionMismatch),
+            _ => state.remove_isa(id),
+        };
+
+        let isa = IdentificationServiceArea {
+            id: id.to_string(),
+            owner: owner_from_url(&params.uss_base_url),
+            uss_base_url: params.uss_base_url,
+            time_start: extents.time_start.into(),
+            time_end: extents.time_end.into(),
+            version: state.next_version(),
+        };
+
+        let mut areas = vec![&extents];
+        if let Some(previous) = &previous {
+            areas.push(&previous.extents);
+        }
+
+        let subscribers = state.notify_subscriptions(&areas);
+        state.insert_isa(IsaRecord {
+            isa: isa.clone(),
+            volume: params.extents,
+            extents,
+        });
+
+        Ok(PutIdentificationServiceAreaResponse {
+            subscribers,
+            service_area: isa,
+        })
+    }
+
+    /// Get a single ISA and its extents
+    pub fn get_isa(
+        &self,
+        id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(IdentificationServiceArea, Volume4D), DssError> {
+        let mut state = self.lock()?;
+
+        state.purge_expired(now);
+        state
+            .isas
+            .get(id)
+            .map(|r| (r.isa.clone(), r.volume.clone()))
+            .ok_or(DssError::NotFound)
+    }
+
+    /// Delete an ISA with the given version
+    pub fn delete_isa(
+        &self,
+        id: &str,
+        version: &str,
+        now: DateTime<Utc>,
+    ) -> Result<PutIdentificationServiceAreaResponse, DssError> {
+        let mut state = self.lock()?;
+
+        state.purge_expired(now);
+        match state.isas.get(id) {
+            None => return Err(DssError::NotFound),
+            Some(r) if r.isa.version != version => return Err(DssError::VersionMismatch),
+            _ => (),
+        }
+
+        let record = state.remove_isa(id).ok_or(DssError::NotFound)?;
+        let subscribers = state.notify_subscriptions(&[&record.extents]);
+
+        Ok(PutIdentificationServiceAreaResponse {
+            subscribers,
+            service_area: record.isa,
+        })
+    }
+
+    /// Find all ISAs intersecting an area and an optional time range
+    pub fn search_isas(
+        &self,
+        window: &Window,
+        earliest_time: Option<DateTime<Utc>>,
+        latest_time: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<IdentificationServiceArea>, DssError> {
+        let extents = Extents {
+            window: *window,
+            cells: window_cells(window)?,
+            time_start: earliest_time.unwrap_or(now),
+            time_end: latest_time.unwrap_or(DateTime::<Utc>::MAX_UTC),
+        };
+
+        let mut state = self.lock()?;
+        state.purge_expired(now);
+        Ok(state.find_isas(&extents))
+    }
+
+    /// Check if any ISA is currently active in the given window
+    pub fn has_isas(&self, window: &Window, now: DateTime<Utc>) -> bool {
+        match self.search_isas(window, Some(now), Some(now), now) {
+            Ok(isas) => !isas.is_empty(),
+            Err(e) => {
+                rest_warn!("could not search ISAs: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Create or update a subscription
+    /// Updates require the current version of the subscription
+    pub fn put_subscription(
+        &self,
+        id: &str,
+        version: Option<&str>,
+        params: CreateSubscriptionParameters,
+        now: DateTime<Utc>,
+    ) -> Result<PutSubscriptionResponse, DssError> {
+        let extents = validate_extents(&params.extents, now)?;
+        if extents.time_end - extents.time_start > Duration::hours(MAX_SUBSCRIPTION_DURATION_HOURS)
+        {
+            rest_error!(
+                "subscriptions may last at most {} hours.",
+                MAX_SUBSCRIPTION_DURATION_HOURS
+            );
+            return Err(DssError::InvalidExtents);
+        }
+
+        let mut state = self.lock()?;
+
+        state.purge_expired(now);
+        let notification_index = match (state.subscriptions.get(id), version) {
+            (Some(_), None) => return Err(DssError::AlreadyExists),
+            (None, Some(_)) => return Err(DssError::NotFound),
+            (Some(r), Some(v)) if r.subscription.version != v => {
+                return Err(DssError::VersionMismatch)
+            }
+            _ => state
+                .remove_subscription(id)
+                .map(|r| r.subscription.notification_index)
+                .unwrap_or(0),
+        };
+
+        let subscription = Subscription {
+            id: id.to_string(),
+            owner: owner_from_url(&params.uss_base_url),
+            uss_base_url: params.uss_base_url,
+            notification_index,
+            time_start: extents.time_start.into(),
+            time_end: extents.time_end.into(),
+            version: state.next_version(),
+        };
+
+        let service_areas = state.find_isas(&extents);
+        state.insert_subscription(SubscriptionRecord {
+            subscription: subscription.clone(),
+            extents,
+        });
+
+        Ok(PutSubscriptionResponse {
+            service_areas,
+            subscription,
+        })
+    }
+
+    /// Get a single subscription
+    pub fn get_subscription(&self, id: &str, now: DateTime<Utc>) -> Result<Subscription, DssError> {
+        let mut state = self.lock()?;
+
+        state.purge_expired(now);
+        state
+            .subscriptions
+            .get(id)
+            .map(|r| r.subscription.clone())
+            .ok_or(DssError::NotFound)
+    }
+
+    /// Delete a subscription with the given version
+    pub fn delete_subscription(
+        &self,
+        id: &str,
+        version: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Subscription, DssError> {
+        let mut state = self.lock()?;
+
+        state.purge_expired(now);
+        match state.subscriptions.get(id) {
+            None => return Err(DssError::NotFound),
+            Some(r) if r.subscription.version != version => return Err(DssError::VersionMismatch),
+            _ => (),
+        }
+
+        state
+            .remove_subscription(id)
+            .map(|r| r.subscription)
+            .ok_or(DssError::NotFound)
+    }
+
+    /// Find all subscriptions intersecting an area
+    pub fn search_subscriptions(
+        &self,
+        window: &Window,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Subscription>, DssError> {
+        let cells = window_cells(window)?;
+        let mut state = self.lock()?;
+
+        state.purge_expired(now);
+        let mut subscriptions: Vec<Subscription> = state
+            .subscription_candidates(&cells)
+            .iter()
+            .filter_map(|id| state.subscriptions.get(id))
+            .filter(|r| r.extents.window.intersects(window))
+            .map(|r| r.subscription.clone())
+            .collect();
+
+        subscriptions.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(subscriptions)
+    }
+
+    /// Remove all expired ISAs and subscriptions
+    pub fn purge_expired(&self, now: DateTime<Utc>) {
+        if let Ok(mut state) = self.lock() {
+            state.purge_expired(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square_volume(lat: f64, lng: f64, size: f64) -> Volume3D {
+        Volume3D {
+            outline_circle: None,
+            outline_polygon: Some(Polygon {
+                vertices: vec![
+                    LatLngPoint { lat, lng },
+                    LatLngPoint {
+                        lat: lat + size,
+                        lng,
+                    },
+                    LatLngPoint {
+                        lat: lat + size,
+                        lng: lng + size,
+                    },
+                ],
+            }),
+            altitude_lower: None,
+            altitude_upper: None,
+        }
+    }
+
+    fn extents(volume: Volume3D, now: DateTime<Utc>, minutes: i64) -> Volume4D {
+        Volume4D {
+            volume,
+            time_start: now.into(),
+            time_end: (now + Duration::minutes(minutes)).into(),
+        }
+    }
+
+    fn isa_params(
+        lat: f64,
+        lng: f64,
+        now: DateTime<Utc>,
+    ) -> CreateIdentificationServiceAreaParameters {
+        CreateIdentificationServiceAreaParameters {
+            extents: extents(square_volume(lat, lng, 0.01), now, 10),
+            uss_base_url: "https://uss.example.com/rid/v2".to_string(),
+        }
+    }
+
+    fn subscription_params(lat: f64, lng: f64, now: DateTime<Utc>) -> CreateSubscriptionParameters {
+        CreateSubscriptionParameters {
+            extents: extents(square_volume(lat, lng, 0.05), now, 10),
+            uss_base_url: "https://subscriber.example.com/rid/v2".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_owner_from_url() {
+        assert_eq!(
+            owner_from_url("https://uss.example.com/rid/v2"),
+            "uss.example.com"
+        );
+        assert_eq!(owner_from_url("uss.example.com"), "uss.example.com");
+    }
+
+    #[test]
+    fn test_window_cells() {
+        let window = Window {
+            lon1: 4.0,
+            lat1: 52.0,
+            lon2: 4.015,
+            lat2: 52.005,
+        };
+        assert_eq!(window_cells(&window).unwrap().len(), 2);
+
+        let window = Window {
+            lon1: 0.0,
+            lat1: 0.0,
+            lon2: 10.0,
+            lat2: 10.0,
+        };
+        assert_eq!(window_cells(&window).unwrap_err(), DssError::AreaTooLarge);
+    }
+
+    #[test]
+    fn test_volume_window() {
+        let mut volume = square_volume(52.0, 4.0, 0.01);
+        let window = volume_window(&volume).unwrap();
+        assert_eq!(window.bounds(), (4.0, 52.0, 4.01, 52.01));
+
+        // both outlines
+        volume.outline_circle = Some(Circle {
+            center: LatLngPoint {
+                lat: 52.0,
+                lng: 4.0,
+            },
+            radius: Radius {
+                value: 1000.0,
+                units: "M".to_string(),
+            },
+        });
+        assert_eq!(
+            volume_window(&volume).unwrap_err(),
+            DssError::InvalidExtents
+        );
+
+        // circle only
+        volume.outline_polygon = None;
+        let window = volume_window(&volume).unwrap();
+        let (min_lon, min_lat, max_lon, max_lat) = window.bounds();
+        assert!(min_lat < 52.0 && max_lat > 52.0);
+        assert!(min_lon < 4.0 && max_lon > 4.0);
+
+        // too few vertices
+        let volume = Volume3D {
+            outline_circle: None,
+            outline_polygon: Some(Polygon {
+                vertices: vec![LatLngPoint { lat: 0.0, lng: 0.0 }],
+            }),
+            altitude_lower: None,
+            altitude_upper: None,
+        };
+        assert_eq!(
+            volume_window(&volume).unwrap_err(),
+            DssError::InvalidExtents
+        );
+    }
+
+    #[test]
+    fn test_isa_versions() {
+        let dss = Dss::default();
+        let now = Utc::now();
+
+        let created = dss
+            .put_isa("isa-1", None, isa_params(52.0, 4.0, now), now)
+            .unwrap();
+        let version = created.service_area.version.clone();
+        assert_eq!(created.service_area.owner, "uss.example.com");
+
+        // create twice
+        let e = dss
+            .put_isa("isa-1", None, isa_params(52.0, 4.0, now), now)
+            .unwrap_err();
+        assert_eq!(e, DssError::AlreadyExists);
+
+        // update with wrong version
+        let e = dss
+            .put_isa("isa-1", Some("wrong"), isa_params(52.0, 4.0, now), now)
+            .unwrap_err();
+        assert_eq!(e, DssError::VersionMismatch);
+
+        // update non-existent
+        let e = dss
+            .put_isa("isa-2", Some(&version), isa_params(52.0, 4.0, now), now)
+            .unwrap_err();
+        assert_eq!(e, DssError::NotFound);
+
+        let updated = dss
+            .put_isa("isa-1", Some(&version), isa_params(52.1, 4.1, now), now)
+            .unwrap();
+        assert_ne!(updated.service_area.version, version);
+
+        let e = dss.delete_isa("isa-1", &version, now).unwrap_err();
+        assert_eq!(e, DssError::VersionMismatch);
+
+        dss.delete_isa("isa-1", &updated.service_area.version, now)
+            .unwrap();
+        assert_eq!(dss.get_isa("isa-1", now).unwrap_err(), DssError::NotFound);
+    }
+
+    #[test]
+    fn test_isa_search_and_expiry() {
+        let dss = Dss::default();
+        let now = Utc::now();
+        dss.put_isa("isa-1", None, isa_params(52.0, 4.0, now), now)
+            .unwrap();
+
+        let inside = Window {
+            lon1: 4.005,
+            lat1: 52.005,
+            lon2: 4.006,
+            lat2: 52.006,
+        };
+        let outside = Window {
+            lon1: 5.0,
+            lat1: 53.0,
+            lon2: 5.01,
+            lat2: 53.01,
+        };
+
+        assert_eq!(dss.search_isas(&inside, None, None, now).unwrap().len(), 1);
+        assert!(dss
+            .search_isas(&outside, None, None, now)
+            .unwrap()
+            .is_empty());
+        assert!(dss.has_isas(&inside, now));
+        assert!(!dss.has_isas(&outside, now));
+
+        // after the end time the ISA is gone
+        let later = now + Duration::minutes(11);
+        assert!(dss
+            .search_isas(&inside, None, None, later)
+            .unwrap()
+            .is_empty());
+        assert_eq!(dss.get_isa("isa-1", later).unwrap_err(), DssError::NotFound);
+    }
+
+    #[test]
+    fn test_invalid_extents() {
+        let dss = Dss::default();
+        let now = Utc::now();
+
+        // already ended
+        let mut params = isa_params(52.0, 4.0, now);
+        params.extents.time_end = (now - Duration::minutes(1)).into();
+        let e = dss.put_isa("isa-1", None, params, now).unwrap_err();
+        assert_eq!(e, DssError::InvalidExtents);
+
+        // invalid time string
+        let mut params = isa_params(52.0, 4.0, now);
+        params.extents.time_start.value = "yesterday".to_string();
+        let e = dss.put_isa("isa-1", None, params, now).unwrap_err();
+        assert_eq!(e, DssError::InvalidExtents);
+
+        // subscription too long
+        let mut params = subscription_params(52.0, 4.0, now);
+        params.extents.time_end = (now + Duration::hours(25)).into();
+        let e = dss
+            .put_subscription("sub-1", None, params, now)
+            .unwrap_err();
+        assert_eq!(e, DssError::InvalidExtents);
+    }
+
+    #[test]
+    fn test_subscription_notifications() {
+        let dss = Dss::default();
+        let now = Utc::now();
+
+        let isa = dss
+            .put_isa("isa-1", None, isa_params(52.0, 4.0, now), now)
+            .unwrap();
+        assert!(isa.subscribers.is_empty());
+
+        let subscription = dss
+            .put_subscription("sub-1", None, subscription_params(51.99, 3.99, now), now)
+            .unwrap();
+        assert_eq!(subscription.service_areas.len(), 1);
+        assert_eq!(subscription.subscription.notification_index, 0);
+
+        // an update of the ISA notifies the subscriber
+        let updated = dss
+            .put_isa(
+                "isa-1",
+                Some(&isa.service_area.version),
+                isa_params(52.01, 4.01, now),
+                now,
+            )
+            .unwrap();
+        assert_eq!(updated.subscribers.len(), 1);
+        assert_eq!(
+            updated.subscribers[0].url,
+            "https://subscriber.example.com/rid/v2"
+        );
+        assert_eq!(
+            updated.subscribers[0].subscriptions[0].notification_index,
+            1
+        );
+
+        // deletion also notifies
+        let deleted = dss
+            .delete_isa("isa-1", &updated.service_area.version, now)
+            .unwrap();
+        assert_eq!(
+            deleted.subscribers[0].subscriptions[0].notification_index,
+            2
+        );
+
+        // an ISA outside of the subscription does not notify
+        let outside = dss
+            .put_isa("isa-2", None, isa_params(10.0, 10.0, now), now)
+            .unwrap();
+        assert!(outside.subscribers.is_empty());
+
+        let window = Window {
+            lon1: 4.0,
+            lat1: 52.0,
+            lon2: 4.001,
+            lat2: 52.001,
+        };
+        assert_eq!(dss.search_subscriptions(&window, now).unwrap().len(), 1);
+
+        let subscription = dss.get_subscription("sub-1", now).unwrap();
+        assert_eq!(subscription.notification_index, 2);
+
+        let e = dss.delete_subscription("sub-1", "wrong", now).unwrap_err();
+        assert_eq!(e, DssError::VersionMismatch);
+        dss.delete_subscription("sub-1", &subscription.version, now)
+            .unwrap();
+        assert!(dss.search_subscriptions(&window, now).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,151 @@
+//! Availability of this and other USSs
+//!
+//! The availability of this USS can be set to `Down` by the ops team, for
+//! example during maintenance, which is reflected in the health check.
+
+use super::F3548Error;
+use crate::rest::api::rest_types::*;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// Availability states by USS, with the version of each state
+#[derive(Debug)]
+pub struct UssAvailability {
+    /// Identifier of this USS
+    own_uss: String,
+
+    /// Availability and version by USS
+    states: Mutex<HashMap<String, (UssAvailabilityState, u64)>>,
+}
+
+impl UssAvailability {
+    /// Create an empty storage, the availability of every USS is unknown
+    pub fn new(own_uss: &str) -> Self {
+        UssAvailability {
+            own_uss: own_uss.to_string(),
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lock the states, recovering from a poisoned lock as every
+    ///  modification leaves the map in a consistent state
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, (UssAvailabilityState, u64)>> {
+        self.states.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Build the response for a USS from its stored state
+    fn response(
+        uss: &str,
+        state: Option<&(UssAvailabilityState, u64)>,
+    ) -> UssAvailabilityStatusResponse {
+        let (availability, version) = match state {
+            Some((availability, version)) => (*availability, version.to_string()),
+            None => (UssAvailabilityState::Unknown, String::new()),
+        };
+
+        UssAvailabilityStatusResponse {
+            status: UssAvailabilityStatus {
+                uss: uss.to_string(),
+                availability,
+            },
+            version,
+        }
+    }
+
+    /// Get the availability of a USS
+    pub fn get(&self, uss: &str) -> UssAvailabilityStatusResponse {
+        Self::response(uss, self.lock().get(uss))
+    }
+
+    /// Change the availability of a USS, the old version must match
+    ///  the current version
+    pub fn set(
+        &self,
+        uss: &str,
+        params: SetUssAvailabilityStatusParameters,
+    ) -> Result<UssAvailabilityStatusResponse, F3548Error> {
+        let mut states = self.lock();
+        let current = states
+            .get(uss)
+            .map_or(String::new(), |(_, version)| version.to_string());
+        if current != params.old_version {
+            return Err(F3548Error::VersionMismatch);
+        }
+
+        let version = states.get(uss).map_or(1, |(_, version)| version + 1);
+        states.insert(uss.to_string(), (params.availability, version));
+        Ok(Self::response(uss, states.get(uss)))
+    }
+
+    /// Whether the USS is this USS
+    pub fn is_own(&self, uss: &str) -> bool {
+        uss == self.own_uss
+    }
+
+    /// The availability of this USS, `Normal` until set otherwise
+    pub fn own_state(&self) -> UssAvailabilityState {
+        self.lock()
+            .get(&self.own_uss)
+            .map_or(UssAvailabilityState::Normal, |(availability, _)| {
+                *availability
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_set() {
+        let availability = UssAvailability::new("own");
+
+        let response = availability.get("peer");
+        assert_eq!(response.status.availability, UssAvailabilityState::Unknown);
+        assert_eq!(response.version, "");
+
+        let params = SetUssAvailabilityStatusParameters {
+            old_version: "".to_string(),
+            availability: UssAvailabilityState::Down,
+        };
+        let response = availability.set("peer", params.clone()).unwrap();
+        assert_eq!(response.status.uss, "peer");
+        assert_eq!(response.status.availability, UssAvailabilityState::Down);
+        assert_eq!(response.version, "1");
+
+        // stale version
+        assert_eq!(
+            availability.set("peer", params).unwrap_err(),
+            F3548Error::VersionMismatch
+        );
+
+        let params = SetUssAvailabilityStatusParameters {
+            old_version: "1".to_string(),
+            availability: UssAvailabilityState::Normal,
+        };
+        let response = availability.set("peer", params).unwrap();
+        assert_eq!(response.version, "2");
+        assert_eq!(
+            availability.get("peer").status.availability,
+            UssAvailabilityState::Normal
+        );
+    }
+
+    #[test]
+    fn test_own_state() {
+        let availability = UssAvailability::new("own");
+        assert!(availability.is_own("own"));
+        assert!(!availability.is_own("peer"));
+        assert_eq!(availability.own_state(), UssAvailabilityState::Normal);
+
+        let params = SetUssAvailabilityStatusParameters {
+            old_version: "".to_string(),
+            availability: UssAvailabilityState::Down,
+        };
+        availability.set("peer", params.clone()).unwrap();
+        assert_eq!(availability.own_state(), UssAvailabilityState::Normal);
+
+        availability.set("own", params).unwrap();
+        assert_eq!(availability.own_state(), UssAvailabilityState::Down);
+    }
+}
@@ -0,0 +1,385 @@
+//! Constraints published by us and shared with us by other USSs
+//!
+//! Our own constraints are published internally, for example for the
+//! no-fly zones known to svc-gis or for vertiport closures.
+
+use super::F3548Error;
+use crate::rest::api::rest_types::*;
+use lib_common::time::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// Check that every volume has a single valid outline and time range,
+///  returns the time range covered by all volumes
+pub fn volumes_time_range(
+    volumes: &[Volume4D],
+) -> Result<(DateTime<Utc>, DateTime<Utc>), F3548Error> {
+    let mut range: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+    for volume in volumes {
+        let valid_outline = match (
+            &volume.volume.outline_circle,
+            &volume.volume.outline_polygon,
+        ) {
+            (Some(circle), None) => circle.radius.value > 0.0 && circle.center.is_valid(),
+            (None, Some(polygon)) => {
+                polygon.vertices.len() >= 3 && polygon.vertices.iter().all(LatLngPoint::is_valid)
+            }
+            _ => false,
+        };
+
+        if !valid_outline {
+            return Err(F3548Error::InvalidVolume);
+        }
+
+        let (Some(start), Some(end)) = (
+            volume.time_start.to_datetime(),
+            volume.time_end.to_datetime(),
+        ) else {
+            return Err(F3548Error::InvalidTimeRange);
+        };
+
+        if end < start {
+            return Err(F3548Error::InvalidTimeRange);
+        }
+
+        range = Some(match range {
+            Some((s, e)) => (s.min(start), e.max(end)),
+            None => (start, end),
+        });
+    }
+
+    range.ok_or(F3548Error::InvalidVolume)
+}
+
+/// A constraint published by us
+#[derive(Debug, Clone)]
+struct Entry {
+    volumes: Vec<Volume4D>,
+    constraint_type: Option<String>,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+    version: i32,
+}
+
+/// Constraints published by this USS
+#[derive(Debug)]
+pub struct Constraints {
+    /// Identifier of this USS
+    manager: String,
+
+    /// Base URL of this USS
+    uss_base_url: String,
+
+    /// Constraints by ID
+    constraints: Mutex<HashMap<String, Entry>>,
+}
+
+impl Constraints {
+    /// Create an empty storage for the given USS
+    pub fn new(manager: &str, uss_base_url: &str) -> Self {
+        Constraints {
+            manager: manager.to_string(),
+            uss_base_url: uss_base_url.to_string(),
+            constraints: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lock the constraints, recovering from a poisoned lock as every
+    ///  modification leaves the map in a consistent state
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Entry>> {
+        self.constraints.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Publish or replace a constraint, returns its new version
+    pub fn put(
+        &self,
+        id: &str,
+        constraint_type: Option<String>,
+        volumes: Vec<Volume4D>,
+        now: DateTime<Utc>,
+    ) -> Result<i32, F3548Error> {
+        let (time_start, time_end) = volumes_time_range(&volumes)?;
+        if time_end < now {
+            return Err(F3548Error::InvalidTimeRange);
+        }
+
+        let mut constraints = self.lock();
+        constraints.retain(|_, entry| entry.time_end >= now);
+
+        let version = constraints.get(id).map_or(1, |entry| entry.version + 1);
+        constraints.insert(
+            id.to_string(),
+            Entry {
+                volumes,
+                constraint_type,
+                time_start,
+                time_end,
+                version,
+            },
+        );
+
+        Ok(version)
+    }
+
+    /// Withdraw a constraint
+    pub fn delete(&self, id: &str) -> Result<(), F3548Error> {
+        self.lock()
+            .remove(id)
+            .map(|_| ())
+            .ok_or(F3548Error::NotFound)
+    }
+
+    /// Get a constraint that has not ended yet
+    pub fn get(&self, id: &str, now: DateTime<Utc>) -> Result<Constraint, F3548Error> {
+        let entry = self
+            .lock()
+            .get(id)
+            .filter(|entry| entry.time_end >= now)
+            .cloned()
+            .ok_or(F3548Error::NotFound)?;
+
+        Ok(Constraint {
+            reference: ConstraintReference {
+                id: id.to_string(),
+                manager: self.manager.clone(),
+                uss_availability: UssAvailabilityState::Normal,
+                version: entry.version,
+                ovn: None,
+                time_start: entry.time_start.into(),
+                time_end: entry.time_end.into(),
+                uss_base_url: self.uss_base_url.clone(),
+            },
+            details: ConstraintDetails {
+                volumes: entry.volumes,
+                constraint_type: entry.constraint_type,
+            },
+        })
+    }
+}
+
+/// Constraints received through notifications of other USSs
+#[derive(Debug, Default)]
+pub struct PeerConstraints {
+    /// Constraints and their end time by ID
+    constraints: Mutex<HashMap<String, (Constraint, DateTime<Utc>)>>,
+}
+
+impl PeerConstraints {
+    /// Lock the constraints, recovering from a poisoned lock as every
+    ///  modification leaves the map in a consistent state
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, (Constraint, DateTime<Utc>)>> {
+        self.constraints.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply a notification: stores the new state of the constraint,
+    ///  or removes it if it is no longer shared
+    pub fn update(
+        &self,
+        params: PutConstraintDetailsParameters,
+        now: DateTime<Utc>,
+    ) -> Result<(), F3548Error> {
+        let mut constraints = self.lock();
+        constraints.retain(|_, (_, end)| *end >= now);
+
+        let Some(constraint) = params.constraint else {
+            constraints.remove(&params.constraint_id);
+            return Ok(());
+        };
+
+        if constraint.reference.id != params.constraint_id {
+            return Err(F3548Error::IdMismatch);
+        }
+
+        let (_, end) = volumes_time_range(&constraint.details.volumes)?;
+        constraints.insert(params.constraint_id, (constraint, end));
+        Ok(())
+    }
+
+    /// Get a constraint that has not ended yet
+    pub fn get(&self, id: &str, now: DateTime<Utc>) -> Result<Constraint, F3548Error> {
+        self.lock()
+            .get(id)
+            .filter(|(_, end)| *end >= now)
+            .map(|(constraint, _)| constraint.clone())
+            .ok_or(F3548Error::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::Duration;
+
+    fn volume(start: DateTime<Utc>, end: DateTime<Utc>) -> Volume4D {
+        Volume4D {
+            volume: Volume3D {
+                outline_circle: None,
+                outline_polygon: Some(Polygon {
+                    vertices: vec![
+                        LatLngPoint {
+                            lat: 52.37,
+                            lng: 4.89,
+                        },
+                        LatLngPoint {
+                            lat: 52.38,
+                            lng: 4.89,
+                        },
+                        LatLngPoint {
+                            lat: 52.38,
+                            lng: 4.90,
+                        },
+                    ],
+                }),
+                altitude_lower: None,
+                altitude_upper: None,
+            },
+            time_start: start.into(),
+            time_end: end.into(),
+        }
+    }
+
+    #[test]
+    fn test_volumes_time_range() {
+        let now = Utc::now();
+        let a = volume(now, now + Duration::minutes(10));
+        let b = volume(now - Duration::minutes(5), now + Duration::minutes(5));
+
+        let (start, end) = volumes_time_range(&[a.clone(), b.clone()]).unwrap();
+        assert_eq!(start, b.time_start.to_datetime().unwrap());
+        assert_eq!(end, a.time_end.to_datetime().unwrap());
+
+        assert_eq!(
+            volumes_time_range(&[]).unwrap_err(),
+            F3548Error::InvalidVolume
+        );
+
+        // two outlines
+        let mut invalid = a.clone();
+        invalid.volume.outline_circle = Some(Circle {
+            center: LatLngPoint {
+                lat: 52.37,
+                lng: 4.89,
+            },
+            radius: Radius::default(),
+        });
+        assert_eq!(
+            volumes_time_range(&[invalid.clone()]).unwrap_err(),
+            F3548Error::InvalidVolume
+        );
+
+        // circle only
+        invalid.volume.outline_polygon = None;
+        assert!(volumes_time_range(&[invalid]).is_ok());
+
+        // too few vertices
+        let mut invalid = a.clone();
+        if let Some(polygon) = invalid.volume.outline_polygon.as_mut() {
+            polygon.vertices.pop();
+        }
+        assert_eq!(
+            volumes_time_range(&[invalid]).unwrap_err(),
+            F3548Error::InvalidVolume
+        );
+
+        // reversed time range
+        let invalid = volume(now, now - Duration::minutes(1));
+        assert_eq!(
+            volumes_time_range(&[invalid]).unwrap_err(),
+            F3548Error::InvalidTimeRange
+        );
+    }
+
+    #[test]
+    fn test_put_get_delete() {
+        let now = Utc::now();
+        let constraints = Constraints::new("uss", "https://uss.example.com");
+        let volumes = vec![volume(now, now + Duration::minutes(10))];
+
+        let version = constraints
+            .put("zone", Some("NoFlyZone".to_string()), volumes.clone(), now)
+            .unwrap();
+        assert_eq!(version, 1);
+        let version = constraints
+            .put("zone", Some("NoFlyZone".to_string()), volumes.clone(), now)
+            .unwrap();
+        assert_eq!(version, 2);
+
+        let constraint = constraints.get("zone", now).unwrap();
+        assert_eq!(constraint.reference.version, 2);
+        assert_eq!(constraint.reference.manager, "uss");
+        assert_eq!(
+            constraint.details.constraint_type,
+            Some("NoFlyZone".to_string())
+        );
+        assert_eq!(constraint.details.volumes.len(), 1);
+
+        // ended
+        let later = now + Duration::minutes(11);
+        assert_eq!(
+            constraints.get("zone", later).unwrap_err(),
+            F3548Error::NotFound
+        );
+        assert_eq!(
+            constraints.put("zone", None, volumes, later).unwrap_err(),
+            F3548Error::InvalidTimeRange
+        );
+
+        assert!(constraints.delete("zone").is_ok());
+        assert_eq!(
+            constraints.delete("zone").unwrap_err(),
+            F3548Error::NotFound
+        );
+    }
+
+    #[test]
+    fn test_peer_update() {
+        let now = Utc::now();
+        let peers = PeerConstraints::default();
+        let constraint = Constraint {
+            reference: ConstraintReference {
+                id: "zone".to_string(),
+                manager: "peer".to_string(),
+                uss_availability: UssAvailabilityState::Normal,
+                version: 1,
+                ovn: None,
+                time_start: now.into(),
+                time_end: (now + Duration::minutes(10)).into(),
+                uss_base_url: "https://peer.example.com".to_string(),
+            },
+            details: ConstraintDetails {
+                volumes: vec![volume(now, now + Duration::minutes(10))],
+                constraint_type: None,
+            },
+        };
+
+        let params = PutConstraintDetailsParameters {
+            constraint_id: "zone".to_string(),
+            constraint: Some(constraint.clone()),
+            subscriptions: vec![],
+        };
+        peers.update(params, now).unwrap();
+        assert_eq!(peers.get("zone", now).unwrap().reference.manager, "peer");
+        assert_eq!(
+            peers.get("zone", now + Duration::minutes(11)).unwrap_err(),
+            F3548Error::NotFound
+        );
+
+        let params = PutConstraintDetailsParameters {
+            constraint_id: "other".to_string(),
+            constraint: Some(constraint),
+            subscriptions: vec![],
+        };
+        assert_eq!(
+            peers.update(params, now).unwrap_err(),
+            F3548Error::IdMismatch
+        );
+
+        let params = PutConstraintDetailsParameters {
+            constraint_id: "zone".to_string(),
+            constraint: None,
+            subscriptions: vec![],
+        };
+        peers.update(params, now).unwrap();
+        assert_eq!(peers.get("zone", now).unwrap_err(), F3548Error::NotFound);
+    }
+}
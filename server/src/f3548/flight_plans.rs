@@ -0,0 +1,601 @@
+//! Flight plans shared as operational intents
+//!
+//! Each segment of a planned path becomes one 4D volume: the segment is
+//! buffered laterally and vertically, and the volume is active during the
+//! part of the flight in which the segment is flown (assuming constant
+//! ground speed) plus a time buffer.
+
+use super::F3548Error;
+use crate::rest::api::rest_types::*;
+use crate::rest::api::uss::Window;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// Lateral buffer around the planned path in meters
+const PATH_BUFFER_METERS: f64 = 50.0;
+
+/// Vertical buffer around the planned path in meters
+const ALTITUDE_BUFFER_METERS: f64 = 30.0;
+
+/// Time buffer before and after each volume in seconds
+const TIME_BUFFER_SECONDS: i64 = 60;
+
+/// Segments shorter than this are treated as a hover
+const MIN_SEGMENT_METERS: f64 = 0.01;
+
+/// Reported as the subscription of our operational intents until
+///  subscriptions are managed with the DSS
+const NULL_SUBSCRIPTION_ID: &str = "00000000-0000-4000-8000-000000000000";
+
+/// A point of a planned flight path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathPoint {
+    /// Degrees of latitude
+    pub latitude: f64,
+
+    /// Degrees of longitude
+    pub longitude: f64,
+
+    /// Altitude in meters (WGS84)
+    pub altitude_meters: f32,
+}
+
+/// A flight plan as provided by the scheduler
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlightPlan {
+    /// Unique identifier, used as the operational intent ID
+    pub id: String,
+
+    /// Identifier of the flight reporting telemetry for this plan
+    pub session_id: Option<String>,
+
+    /// Priority of the flight, higher values take precedence
+    pub priority: i32,
+
+    /// State of the flight plan
+    pub state: OperationalIntentState,
+
+    /// Planned departure time
+    pub time_start: DateTime<Utc>,
+
+    /// Planned arrival time
+    pub time_end: DateTime<Utc>,
+
+    /// Planned path, from departure to arrival
+    pub path: Vec<PathPoint>,
+
+    /// Vertiport the flight departs from, if any
+    pub origin_vertiport_id: Option<String>,
+
+    /// Vertiport the flight arrives at, if any
+    pub target_vertiport_id: Option<String>,
+}
+
+impl FlightPlan {
+    /// Check that the path and time range can be turned into volumes
+    pub fn validate(&self) -> Result<(), F3548Error> {
+        if self.path.is_empty()
+            || self.path.iter().any(|p| {
+                !(-90.0..=90.0).contains(&p.latitude) || !(-180.0..=180.0).contains(&p.longitude)
+            })
+        {
+            return Err(F3548Error::InvalidPath);
+        }
+
+        if self.time_end <= self.time_start {
+            return Err(F3548Error::InvalidTimeRange);
+        }
+
+        Ok(())
+    }
+
+    /// The ID of the flight reporting telemetry for this plan
+    pub fn session_id(&self) -> &str {
+        self.session_id.as_deref().unwrap_or(&self.id)
+    }
+
+    /// The end of the last volume of this plan
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.time_end + Duration::seconds(TIME_BUFFER_SECONDS)
+    }
+
+    /// Smallest window containing every volume of this plan
+    pub fn window(&self) -> Window {
+        let mut min_lon = f64::MAX;
+        let mut min_lat = f64::MAX;
+        let mut max_lon = f64::MIN;
+        let mut max_lat = f64::MIN;
+        for p in &self.path {
+            min_lon = min_lon.min(p.longitude);
+            min_lat = min_lat.min(p.latitude);
+            max_lon = max_lon.max(p.longitude);
+            max_lat = max_lat.max(p.latitude);
+        }
+
+        let d_lat = PATH_BUFFER_METERS / METERS_PER_DEGREE;
+        let d_lon = d_lat
+            / max_lat
+                .abs()
+                .max(min_lat.abs())
+                .to_radians()
+                .cos()
+                .max(0.01);
+        Window {
+            lon1: (min_lon - d_lon).max(-180.0),
+            lat1: (min_lat - d_lat).max(-90.0),
+            lon2: (max_lon + d_lon).min(180.0),
+            lat2: (max_lat + d_lat).min(90.0),
+        }
+    }
+
+    /// Converts the planned path into 4D volumes, one per segment
+    pub fn volumes(&self) -> Vec<Volume4D> {
+        let lengths = self
+            .path
+            .windows(2)
+            .map(|s| {
+                let (dx, dy) = offset_meters(&s[0], &s[1]);
+                dx.hypot(dy)
+            })
+            .collect::<Vec<f64>>();
+        let total: f64 = lengths.iter().sum();
+        let duration_ms = (self.time_end - self.time_start).num_milliseconds() as f64;
+
+        if lengths.is_empty() || total < MIN_SEGMENT_METERS {
+            // Hovering over a single location for the whole flight
+            return vec![self.volume(
+                hover_outline(&self.path[0]),
+                &self.path,
+                self.time_start,
+                self.time_end,
+            )];
+        }
+
+        let mut flown = 0.0;
+        lengths
+            .iter()
+            .zip(self.path.windows(2))
+            .map(|(length, segment)| {
+                let start =
+                    self.time_start + Duration::milliseconds((duration_ms * flown / total) as i64);
+                flown += length;
+                let end =
+                    self.time_start + Duration::milliseconds((duration_ms * flown / total) as i64);
+
+                let outline = match *length < MIN_SEGMENT_METERS {
+                    true => hover_outline(&segment[0]),
+                    false => segment_outline(&segment[0], &segment[1]),
+                };
+
+                self.volume(outline, segment, start, end)
+            })
+            .collect()
+    }
+
+    /// Builds a single buffered volume around the given points
+    fn volume(
+        &self,
+        outline: (Option<Circle>, Option<Polygon>),
+        points: &[PathPoint],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Volume4D {
+        let lower = points
+            .iter()
+            .map(|p| p.altitude_meters as f64)
+            .fold(f64::MAX, f64::min);
+        let upper = points
+            .iter()
+            .map(|p| p.altitude_meters as f64)
+            .fold(f64::MIN, f64::max);
+
+        Volume4D {
+            volume: Volume3D {
+                outline_circle: outline.0,
+                outline_polygon: outline.1,
+                altitude_lower: Some(Altitude {
+                    value: lower - ALTITUDE_BUFFER_METERS,
+                    ..Default::default()
+                }),
+                altitude_upper: Some(Altitude {
+                    value: upper + ALTITUDE_BUFFER_METERS,
+                    ..Default::default()
+                }),
+            },
+            time_start: (start - Duration::seconds(TIME_BUFFER_SECONDS)).into(),
+            time_end: (end + Duration::seconds(TIME_BUFFER_SECONDS)).into(),
+        }
+    }
+}
+
+/// Offset from `a` to `b` in meters (east, north), using a local flat
+///  earth approximation
+fn offset_meters(a: &PathPoint, b: &PathPoint) -> (f64, f64) {
+    let lat = ((a.latitude + b.latitude) / 2.0).to_radians();
+    (
+        (b.longitude - a.longitude) * METERS_PER_DEGREE * lat.cos(),
+        (b.latitude - a.latitude) * METERS_PER_DEGREE,
+    )
+}
+
+/// A circle around a point where the aircraft hovers
+fn hover_outline(p: &PathPoint) -> (Option<Circle>, Option<Polygon>) {
+    let circle = Circle {
+        center: LatLngPoint {
+            lng: p.longitude,
+            lat: p.latitude,
+        },
+        radius: Radius {
+            value: PATH_BUFFER_METERS as f32,
+            ..Default::default()
+        },
+    };
+
+    (Some(circle), None)
+}
+
+/// A rectangle around a segment, extended by the buffer in every direction
+fn segment_outline(a: &PathPoint, b: &PathPoint) -> (Option<Circle>, Option<Polygon>) {
+    let (dx, dy) = offset_meters(a, b);
+    let length = dx.hypot(dy);
+
+    // unit vectors along and across the segment, scaled by the buffer
+    let (ax, ay) = (
+        dx / length * PATH_BUFFER_METERS,
+        dy / length * PATH_BUFFER_METERS,
+    );
+    let (cx, cy) = (-ay, ax);
+
+    let meters_per_degree_lon =
+        METERS_PER_DEGREE * ((a.latitude + b.latitude) / 2.0).to_radians().cos();
+    let vertex = |origin: &PathPoint, x: f64, y: f64| LatLngPoint {
+        lng: origin.longitude + x / meters_per_degree_lon,
+        lat: origin.latitude + y / METERS_PER_DEGREE,
+    };
+
+    let polygon = Polygon {
+        vertices: vec![
+            vertex(a, -ax + cx, -ay + cy),
+            vertex(b, ax + cx, ay + cy),
+            vertex(b, ax - cx, ay - cy),
+            vertex(a, -ax - cx, -ay - cy),
+        ],
+    };
+
+    (None, Some(polygon))
+}
+
+/// A stored flight plan with the version of its operational intent
+#[derive(Debug, Clone)]
+struct Entry {
+    plan: FlightPlan,
+    version: i32,
+}
+
+/// Flight plans shared as operational intents
+#[derive(Debug)]
+pub struct FlightPlans {
+    /// Identifier of this USS
+    manager: String,
+
+    /// Base URL of this USS
+    uss_base_url: String,
+
+    /// Flight plans by ID
+    plans: Mutex<HashMap<String, Entry>>,
+}
+
+impl FlightPlans {
+    /// Create an empty storage for the given USS
+    pub fn new(manager: &str, uss_base_url: &str) -> Self {
+        FlightPlans {
+            manager: manager.to_string(),
+            uss_base_url: uss_base_url.to_string(),
+            plans: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lock the plans, recovering from a poisoned lock as every
+    ///  modification leaves the map in a consistent state
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Entry>> {
+        self.plans.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Insert or replace a flight plan, returns the new version of its
+    ///  operational intent
+    pub fn put(&self, plan: FlightPlan, now: DateTime<Utc>) -> Result<i32, F3548Error> {
+        plan.validate()?;
+        if plan.expires_at() < now {
+            return Err(F3548Error::InvalidTimeRange);
+        }
+
+        let mut plans = self.lock();
+        plans.retain(|_, entry| entry.plan.expires_at() >= now);
+
+        let version = plans.get(&plan.id).map_or(1, |entry| entry.version + 1);
+        plans.insert(plan.id.clone(), Entry { plan, version });
+
+        Ok(version)
+    }
+
+    /// Remove a flight plan
+    pub fn delete(&self, id: &str) -> Result<(), F3548Error> {
+        self.lock()
+            .remove(id)
+            .map(|_| ())
+            .ok_or(F3548Error::NotFound)
+    }
+
+    /// Get a flight plan that has not expired yet
+    pub fn get(&self, id: &str, now: DateTime<Utc>) -> Result<FlightPlan, F3548Error> {
+        self.lock()
+            .get(id)
+            .filter(|entry| entry.plan.expires_at() >= now)
+            .map(|entry| entry.plan.clone())
+            .ok_or(F3548Error::NotFound)
+    }
+
+    /// List the flight plans that have not expired yet, ordered by
+    ///  departure time
+    pub fn list(&self, now: DateTime<Utc>) -> Vec<FlightPlan> {
+        let mut plans = self
+            .lock()
+            .values()
+            .filter(|entry| entry.plan.expires_at() >= now)
+            .map(|entry| entry.plan.clone())
+            .collect::<Vec<FlightPlan>>();
+        plans.sort_by(|a, b| a.time_start.cmp(&b.time_start).then(a.id.cmp(&b.id)));
+        plans
+    }
+
+    /// Get the operational intent of a flight plan that has not expired yet
+    pub fn get_operational_intent(
+        &self,
+        id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<OperationalIntent, F3548Error> {
+        let entry = self
+            .lock()
+            .get(id)
+            .filter(|entry| entry.plan.expires_at() >= now)
+            .cloned()
+            .ok_or(F3548Error::NotFound)?;
+
+        let volumes = entry.plan.volumes();
+        let reference = OperationalIntentReference {
+            id: entry.plan.id.clone(),
+            manager: self.manager.clone(),
+            uss_availability: UssAvailabilityState::Normal,
+            version: entry.version,
+            state: entry.plan.state,
+            ovn: None,
+            time_start: volumes
+                .first()
+                .map_or_else(|| entry.plan.time_start.into(), |v| v.time_start.clone()),
+            time_end: volumes
+                .last()
+                .map_or_else(|| entry.plan.time_end.into(), |v| v.time_end.clone()),
+            uss_base_url: self.uss_base_url.clone(),
+            subscription_id: NULL_SUBSCRIPTION_ID.to_string(),
+        };
+
+        // Off-nominal volumes are only provided once the flight left its plan
+        let off_nominal_volumes = match entry.plan.state {
+            OperationalIntentState::Nonconforming | OperationalIntentState::Contingent => {
+                volumes.clone()
+            }
+            _ => vec![],
+        };
+
+        Ok(OperationalIntent {
+            reference,
+            details: OperationalIntentDetails {
+                volumes,
+                off_nominal_volumes,
+                priority: entry.plan.priority,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn plan(id: &str, path: Vec<(f64, f64, f32)>) -> FlightPlan {
+        let now = Utc::now();
+        FlightPlan {
+            id: id.to_string(),
+            session_id: None,
+            priority: 0,
+            state: OperationalIntentState::Accepted,
+            time_start: now,
+            time_end: now + Duration::minutes(10),
+            path: path
+                .into_iter()
+                .map(|(latitude, longitude, altitude_meters)| PathPoint {
+                    latitude,
+                    longitude,
+                    altitude_meters,
+                })
+                .collect(),
+            origin_vertiport_id: None,
+            target_vertiport_id: None,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut p = plan("a", vec![(52.37, 4.89, 100.0)]);
+        assert!(p.validate().is_ok());
+
+        p.path[0].latitude = 90.1;
+        assert_eq!(p.validate().unwrap_err(), F3548Error::InvalidPath);
+
+        p.path.clear();
+        assert_eq!(p.validate().unwrap_err(), F3548Error::InvalidPath);
+
+        let mut p = plan("a", vec![(52.37, 4.89, 100.0)]);
+        p.time_end = p.time_start;
+        assert_eq!(p.validate().unwrap_err(), F3548Error::InvalidTimeRange);
+    }
+
+    #[test]
+    fn test_session_id() {
+        let mut p = plan("a", vec![(52.37, 4.89, 100.0)]);
+        assert_eq!(p.session_id(), "a");
+
+        p.session_id = Some("b".to_string());
+        assert_eq!(p.session_id(), "b");
+    }
+
+    #[test]
+    fn test_volumes_hover() {
+        let p = plan("a", vec![(52.37, 4.89, 100.0)]);
+        let volumes = p.volumes();
+        assert_eq!(volumes.len(), 1);
+
+        let volume = &volumes[0].volume;
+        assert!(volume.outline_polygon.is_none());
+        let circle = volume.outline_circle.as_ref().unwrap();
+        assert_eq!(circle.radius.value, PATH_BUFFER_METERS as f32);
+        assert_eq!(circle.center.lat, 52.37);
+        assert_eq!(
+            volume.altitude_lower.as_ref().unwrap().value,
+            100.0 - ALTITUDE_BUFFER_METERS
+        );
+        assert_eq!(
+            volume.altitude_upper.as_ref().unwrap().value,
+            100.0 + ALTITUDE_BUFFER_METERS
+        );
+
+        let start = volumes[0].time_start.to_datetime().unwrap();
+        let end = volumes[0].time_end.to_datetime().unwrap();
+        assert_eq!((end - start).num_seconds(), 600 + 2 * TIME_BUFFER_SECONDS);
+    }
+
+    #[test]
+    fn test_volumes_segments() {
+        let p = plan(
+            "a",
+            vec![
+                (52.37, 4.89, 100.0),
+                (52.38, 4.89, 120.0),
+                (52.38, 4.89, 150.0), // vertical climb
+                (52.40, 4.89, 150.0),
+            ],
+        );
+
+        let volumes = p.volumes();
+        assert_eq!(volumes.len(), 3);
+
+        // First segment is flown in the first third of the flight
+        let polygon = volumes[0].volume.outline_polygon.as_ref().unwrap();
+        assert_eq!(polygon.vertices.len(), 4);
+        let start = volumes[0].time_start.to_datetime().unwrap();
+        let end = volumes[0].time_end.to_datetime().unwrap();
+        // times are reported with millisecond precision
+        let expected = p.time_start - Duration::seconds(TIME_BUFFER_SECONDS);
+        assert!((start - expected).num_milliseconds().abs() <= 1);
+        let expected = p.time_start + Duration::seconds(200 + TIME_BUFFER_SECONDS);
+        assert!((end - expected).num_milliseconds().abs() <= 1);
+
+        // Polygon encloses the segment with the buffer
+        let lats: Vec<f64> = polygon.vertices.iter().map(|v| v.lat).collect();
+        let lngs: Vec<f64> = polygon.vertices.iter().map(|v| v.lng).collect();
+        assert!(lats.iter().cloned().fold(f64::MAX, f64::min) < 52.37);
+        assert!(lats.iter().cloned().fold(f64::MIN, f64::max) > 52.38);
+        assert!(lngs.iter().cloned().fold(f64::MAX, f64::min) < 4.89);
+        assert!(lngs.iter().cloned().fold(f64::MIN, f64::max) > 4.89);
+
+        // The climb has no length, so is a hover
+        assert!(volumes[1].volume.outline_circle.is_some());
+        assert_eq!(
+            volumes[1].volume.altitude_upper.as_ref().unwrap().value,
+            150.0 + ALTITUDE_BUFFER_METERS
+        );
+    }
+
+    #[test]
+    fn test_window() {
+        let p = plan("a", vec![(52.37, 4.89, 100.0), (52.38, 4.90, 100.0)]);
+        let (min_lon, min_lat, max_lon, max_lat) = p.window().bounds();
+        assert!(min_lon < 4.89 && max_lon > 4.90);
+        assert!(min_lat < 52.37 && max_lat > 52.38);
+    }
+
+    #[test]
+    fn test_put_get_delete() {
+        let now = Utc::now();
+        let plans = FlightPlans::new("uss", "https://uss.example.com");
+
+        let p = plan("a", vec![(52.37, 4.89, 100.0), (52.38, 4.90, 100.0)]);
+        assert_eq!(plans.put(p.clone(), now).unwrap(), 1);
+        assert_eq!(plans.put(p.clone(), now).unwrap(), 2);
+        assert_eq!(plans.get("a", now).unwrap(), p);
+
+        let intent = plans.get_operational_intent("a", now).unwrap();
+        assert_eq!(intent.reference.id, "a");
+        assert_eq!(intent.reference.version, 2);
+        assert_eq!(intent.reference.manager, "uss");
+        assert_eq!(intent.reference.uss_base_url, "https://uss.example.com");
+        assert!(intent.reference.ovn.is_none());
+        assert_eq!(intent.details.volumes.len(), 1);
+        assert!(intent.details.off_nominal_volumes.is_empty());
+
+        // expired
+        let later = p.time_end + Duration::hours(1);
+        assert_eq!(plans.get("a", later).unwrap_err(), F3548Error::NotFound);
+        assert_eq!(
+            plans.get_operational_intent("a", later).unwrap_err(),
+            F3548Error::NotFound
+        );
+        assert_eq!(
+            plans.put(p.clone(), later).unwrap_err(),
+            F3548Error::InvalidTimeRange
+        );
+
+        assert!(plans.delete("a").is_ok());
+        assert_eq!(plans.delete("a").unwrap_err(), F3548Error::NotFound);
+        assert_eq!(plans.get("a", now).unwrap_err(), F3548Error::NotFound);
+    }
+
+    #[test]
+    fn test_list() {
+        let now = Utc::now();
+        let plans = FlightPlans::new("uss", "https://uss.example.com");
+
+        let mut a = plan("a", vec![(52.37, 4.89, 100.0)]);
+        a.time_start = now + Duration::minutes(5);
+        a.time_end = now + Duration::minutes(15);
+        let b = plan("b", vec![(52.37, 4.89, 100.0)]);
+        plans.put(a, now).unwrap();
+        plans.put(b.clone(), now).unwrap();
+
+        let ids = plans
+            .list(now)
+            .into_iter()
+            .map(|p| p.id)
+            .collect::<Vec<String>>();
+        assert_eq!(ids, vec!["b", "a"]);
+
+        // b has expired
+        let later = b.time_end + Duration::minutes(2);
+        assert_eq!(plans.list(later).len(), 1);
+    }
+
+    #[test]
+    fn test_off_nominal_volumes() {
+        let now = Utc::now();
+        let plans = FlightPlans::new("uss", "https://uss.example.com");
+
+        let mut p = plan("a", vec![(52.37, 4.89, 100.0), (52.38, 4.90, 100.0)]);
+        p.state = OperationalIntentState::Contingent;
+        plans.put(p, now).unwrap();
+
+        let intent = plans.get_operational_intent("a", now).unwrap();
+        assert_eq!(intent.reference.state, OperationalIntentState::Contingent);
+        assert_eq!(intent.details.off_nominal_volumes.len(), 1);
+    }
+}
@@ -0,0 +1,153 @@
+//! Operational intents shared with us by other USSs
+
+use super::F3548Error;
+use crate::rest::api::rest_types::*;
+use lib_common::time::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// Operational intents received through notifications of other USSs
+#[derive(Debug, Default)]
+pub struct PeerIntents {
+    /// Operational intents by ID
+    intents: Mutex<HashMap<String, OperationalIntent>>,
+}
+
+impl PeerIntents {
+    /// Lock the intents, recovering from a poisoned lock as every
+    ///  modification leaves the map in a consistent state
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, OperationalIntent>> {
+        self.intents.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply a notification: stores the new state of the operational
+    ///  intent, or removes it if it is no longer shared
+    pub fn update(
+        &self,
+        params: PutOperationalIntentDetailsParameters,
+        now: DateTime<Utc>,
+    ) -> Result<(), F3548Error> {
+        let mut intents = self.lock();
+        intents.retain(|_, intent| !is_expired(intent, now));
+
+        let Some(intent) = params.operational_intent else {
+            intents.remove(&params.operational_intent_id);
+            return Ok(());
+        };
+
+        if intent.reference.id != params.operational_intent_id {
+            return Err(F3548Error::IdMismatch);
+        }
+
+        let (Some(start), Some(end)) = (
+            intent.reference.time_start.to_datetime(),
+            intent.reference.time_end.to_datetime(),
+        ) else {
+            return Err(F3548Error::InvalidTimeRange);
+        };
+
+        if end < start {
+            return Err(F3548Error::InvalidTimeRange);
+        }
+
+        intents.insert(params.operational_intent_id, intent);
+        Ok(())
+    }
+
+    /// Get an operational intent that has not ended yet
+    pub fn get(&self, id: &str, now: DateTime<Utc>) -> Result<OperationalIntent, F3548Error> {
+        self.lock()
+            .get(id)
+            .filter(|intent| !is_expired(intent, now))
+            .cloned()
+            .ok_or(F3548Error::NotFound)
+    }
+}
+
+/// An operational intent expires at its end time
+fn is_expired(intent: &OperationalIntent, now: DateTime<Utc>) -> bool {
+    match intent.reference.time_end.to_datetime() {
+        Some(end) => end < now,
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::Duration;
+
+    fn notification(id: &str, end: DateTime<Utc>) -> PutOperationalIntentDetailsParameters {
+        PutOperationalIntentDetailsParameters {
+            operational_intent_id: id.to_string(),
+            operational_intent: Some(OperationalIntent {
+                reference: OperationalIntentReference {
+                    id: id.to_string(),
+                    manager: "peer".to_string(),
+                    uss_availability: UssAvailabilityState::Normal,
+                    version: 1,
+                    state: OperationalIntentState::Accepted,
+                    ovn: None,
+                    time_start: Utc::now().into(),
+                    time_end: end.into(),
+                    uss_base_url: "https://peer.example.com".to_string(),
+                    subscription_id: "sub".to_string(),
+                },
+                details: OperationalIntentDetails {
+                    volumes: vec![],
+                    off_nominal_volumes: vec![],
+                    priority: 0,
+                },
+            }),
+            subscriptions: vec![],
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let now = Utc::now();
+        let intents = PeerIntents::default();
+
+        let end = now + Duration::minutes(10);
+        intents.update(notification("a", end), now).unwrap();
+        assert_eq!(intents.get("a", now).unwrap().reference.manager, "peer");
+
+        // expired
+        let later = end + Duration::seconds(1);
+        assert_eq!(intents.get("a", later).unwrap_err(), F3548Error::NotFound);
+
+        // removed
+        let mut params = notification("a", end);
+        params.operational_intent = None;
+        intents.update(params, now).unwrap();
+        assert_eq!(intents.get("a", now).unwrap_err(), F3548Error::NotFound);
+    }
+
+    #[test]
+    fn test_update_invalid() {
+        let now = Utc::now();
+        let intents = PeerIntents::default();
+
+        let mut params = notification("a", now + Duration::minutes(10));
+        params.operational_intent_id = "b".to_string();
+        assert_eq!(
+            intents.update(params, now).unwrap_err(),
+            F3548Error::IdMismatch
+        );
+
+        let mut params = notification("a", now + Duration::minutes(10));
+        if let Some(intent) = params.operational_intent.as_mut() {
+            intent.reference.time_end.value = "invalid".to_string();
+        }
+        assert_eq!(
+            intents.update(params, now).unwrap_err(),
+            F3548Error::InvalidTimeRange
+        );
+
+        let params = notification("a", now - Duration::minutes(10));
+        assert_eq!(
+            intents.update(params, now).unwrap_err(),
+            F3548Error::InvalidTimeRange
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! ASTM F3548 Strategic Coordination
+//! Shares our flight plans and constraints with other USSs and keeps track
+//! of the operational intents and constraints other USSs share with us.
+
+pub mod availability;
+pub mod constraints;
+pub mod flight_plans;
+pub mod intents;
+pub mod reports;
+
+use availability::UssAvailability;
+use constraints::{Constraints, PeerConstraints};
+use flight_plans::FlightPlans;
+use intents::PeerIntents;
+use reports::ErrorReports;
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Errors with F3548 entities
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum F3548Error {
+    /// The entity does not exist
+    NotFound,
+
+    /// The path must contain at least one valid point
+    InvalidPath,
+
+    /// The time range is invalid or has already ended
+    InvalidTimeRange,
+
+    /// A volume must have exactly one valid outline
+    InvalidVolume,
+
+    /// The entity ID does not match the ID of the request
+    IdMismatch,
+
+    /// The provided version does not match the current version
+    VersionMismatch,
+
+    /// The entity could not be persisted
+    Storage,
+
+    /// The entity exceeds the maximum size
+    TooLarge,
+}
+
+impl std::error::Error for F3548Error {}
+
+impl Display for F3548Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            F3548Error::NotFound => write!(f, "The entity does not exist"),
+            F3548Error::InvalidPath => write!(f, "The path contains no valid points"),
+            F3548Error::InvalidTimeRange => write!(f, "The time range is invalid"),
+            F3548Error::InvalidVolume => write!(f, "The volume outline is invalid"),
+            F3548Error::IdMismatch => write!(f, "The entity ID does not match the request"),
+            F3548Error::VersionMismatch => write!(f, "The version does not match"),
+            F3548Error::Storage => write!(f, "The entity could not be persisted"),
+            F3548Error::TooLarge => write!(f, "The entity is too large"),
+        }
+    }
+}
+
+pub(crate) static FLIGHT_PLANS: OnceCell<Arc<FlightPlans>> = OnceCell::const_new();
+pub(crate) static PEER_INTENTS: OnceCell<Arc<PeerIntents>> = OnceCell::const_new();
+pub(crate) static CONSTRAINTS: OnceCell<Arc<Constraints>> = OnceCell::const_new();
+pub(crate) static PEER_CONSTRAINTS: OnceCell<Arc<PeerConstraints>> = OnceCell::const_new();
+pub(crate) static ERROR_REPORTS: OnceCell<Arc<ErrorReports>> = OnceCell::const_new();
+pub(crate) static USS_AVAILABILITY: OnceCell<Arc<UssAvailability>> = OnceCell::const_new();
+
+/// Returns the flight plans shared as operational intents, filled by the
+///  gRPC server and read by the REST server.
+/// Uses the USS identity from a Config object generated from
+///  environment variables.
+/// Initializes the storage if it hasn't been initialized yet.
+pub async fn get_flight_plans() -> Arc<FlightPlans> {
+    FLIGHT_PLANS
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            Arc::new(FlightPlans::new(&config.uss_manager, &config.uss_base_url))
+        })
+        .await
+        .clone()
+}
+
+/// Returns the operational intents received from other USSs.
+/// Initializes the storage if it hasn't been initialized yet.
+pub async fn get_peer_intents() -> Arc<PeerIntents> {
+    PEER_INTENTS
+        .get_or_init(|| async move { Arc::new(PeerIntents::default()) })
+        .await
+        .clone()
+}
+
+/// Returns the constraints published by this USS, filled by the gRPC
+///  server and read by the REST server.
+/// Uses the USS identity from a Config object generated from
+///  environment variables.
+/// Initializes the storage if it hasn't been initialized yet.
+pub async fn get_constraints() -> Arc<Constraints> {
+    CONSTRAINTS
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            Arc::new(Constraints::new(&config.uss_manager, &config.uss_base_url))
+        })
+        .await
+        .clone()
+}
+
+/// Returns the constraints received from other USSs.
+/// Initializes the storage if it hasn't been initialized yet.
+pub async fn get_peer_constraints() -> Arc<PeerConstraints> {
+    PEER_CONSTRAINTS
+        .get_or_init(|| async move { Arc::new(PeerConstraints::default()) })
+        .await
+        .clone()
+}
+
+/// Returns the error reports received from other USSs.
+/// Uses the reports file from a Config object generated from
+///  environment variables.
+/// Initializes the storage, loading the stored reports, if it hasn't
+///  been initialized yet.
+pub async fn get_error_reports() -> Arc<ErrorReports> {
+    ERROR_REPORTS
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            Arc::new(ErrorReports::load(&config.reports_file))
+        })
+        .await
+        .clone()
+}
+
+/// Returns the availability of this and other USSs, shared by the
+///  availability endpoints and the health check.
+/// Uses the USS identity from a Config object generated from
+///  environment variables.
+/// Initializes the storage if it hasn't been initialized yet.
+pub async fn get_uss_availability() -> Arc<UssAvailability> {
+    USS_AVAILABILITY
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            Arc::new(UssAvailability::new(&config.uss_manager))
+        })
+        .await
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_storage() {
+        let a = get_flight_plans().await;
+        let b = get_flight_plans().await;
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let a = get_peer_intents().await;
+        let b = get_peer_intents().await;
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let a = get_constraints().await;
+        let b = get_constraints().await;
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let a = get_peer_constraints().await;
+        let b = get_peer_constraints().await;
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let a = get_error_reports().await;
+        let b = get_error_reports().await;
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let a = get_uss_availability().await;
+        let b = get_uss_availability().await;
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            F3548Error::NotFound.to_string(),
+            "The entity does not exist"
+        );
+        assert_eq!(
+            F3548Error::IdMismatch.to_string(),
+            "The entity ID does not match the request"
+        );
+    }
+}
@@ -0,0 +1,336 @@
+//! Error reports received from other USSs
+//!
+//! Reports are appended to a JSON Lines file, so they survive restarts and
+//! can be inspected by the ops team without this service running.
+//! Reports are limited in size and only the most recent reports are kept,
+//! the file is rewritten with them once it holds twice as many.
+
+use super::F3548Error;
+use crate::rest::api::rest_types::*;
+use lib_common::time::{DateTime, Utc};
+use rand::Rng;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use tokio::io::AsyncWriteExt;
+
+/// Maximum size of a stored report in bytes
+pub const MAX_REPORT_BYTES: usize = 64 * 1024;
+
+/// Maximum number of stored reports, older reports are dropped
+pub const MAX_STORED_REPORTS: usize = 10_000;
+
+/// Generates a random (version 4) UUID for a new report
+fn new_report_id() -> String {
+    let mut bytes: [u8; 16] = rand::thread_rng().gen();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Error reports, kept in memory and in a JSON Lines file
+#[derive(Debug)]
+pub struct ErrorReports {
+    /// File the reports are appended to
+    path: PathBuf,
+
+    /// Reports in the order they were received
+    reports: Mutex<Vec<StoredErrorReport>>,
+
+    /// Number of reports in the file, held while writing so the file has
+    ///  the same order
+    lines: tokio::sync::Mutex<usize>,
+}
+
+impl ErrorReports {
+    /// Load the reports stored in the given file.
+    /// A missing file results in an empty storage, invalid lines are skipped.
+    pub fn load(path: &str) -> Self {
+        let mut reports = match std::fs::read_to_string(path) {
+            Ok(content) => content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .filter_map(|line| {
+                    serde_json::from_str::<StoredErrorReport>(line)
+                        .map_err(|e| rest_warn!("skipping invalid report in {}: {}", path, e))
+                        .ok()
+                })
+                .collect::<Vec<StoredErrorReport>>(),
+            Err(e) => {
+                rest_info!("no reports loaded from {}: {}", path, e);
+                vec![]
+            }
+        };
+
+        let lines = reports.len();
+        reports.drain(..lines.saturating_sub(MAX_STORED_REPORTS));
+        rest_info!("loaded {} reports from {}.", reports.len(), path);
+        ErrorReports {
+            path: PathBuf::from(path),
+            reports: Mutex::new(reports),
+            lines: tokio::sync::Mutex::new(lines),
+        }
+    }
+
+    /// Lock the reports, recovering from a poisoned lock as every
+    ///  modification leaves the list in a consistent state
+    fn lock(&self) -> MutexGuard<'_, Vec<StoredErrorReport>> {
+        self.reports.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Append a line to the file
+    async fn append(&self, line: &str) -> std::io::Result<()> {
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(format!("{}\n", line).as_bytes()).await?;
+        file.flush().await
+    }
+
+    /// Rewrite the file with the kept reports, through a temporary file so
+    ///  a failed write never loses the file
+    async fn compact(&self) -> Result<usize, F3548Error> {
+        let reports = self.lock().clone();
+        let mut content = String::new();
+        for stored in &reports {
+            let line = serde_json::to_string(stored).map_err(|e| {
+                rest_error!("could not serialize report: {}", e);
+                F3548Error::Storage
+            })?;
+            content.push_str(&line);
+            content.push('\n');
+        }
+
+        let temp = self.path.with_extension("tmp");
+        tokio::fs::write(&temp, content)
+            .await
+            .and(tokio::fs::rename(&temp, &self.path).await)
+            .map_err(|e| {
+                rest_error!("could not rewrite reports {:?}: {}", self.path, e);
+                F3548Error::Storage
+            })?;
+
+        Ok(reports.len())
+    }
+
+    /// Store a new report, returns the report with its assigned ID.
+    /// Returns TooLarge if the report exceeds the maximum size.
+    pub async fn add(
+        &self,
+        report: ErrorReport,
+        now: DateTime<Utc>,
+    ) -> Result<ErrorReport, F3548Error> {
+        let report = ErrorReport {
+            report_id: Some(new_report_id()),
+            ..report
+        };
+        let stored = StoredErrorReport {
+            received: now.into(),
+            report: report.clone(),
+        };
+
+        let line = serde_json::to_string(&stored).map_err(|e| {
+            rest_error!("could not serialize report: {}", e);
+            F3548Error::Storage
+        })?;
+        if line.len() > MAX_REPORT_BYTES {
+            rest_error!("report exceeds the maximum of {} bytes.", MAX_REPORT_BYTES);
+            return Err(F3548Error::TooLarge);
+        }
+
+        let mut lines = self.lines.lock().await;
+        self.append(&line).await.map_err(|e| {
+            rest_error!("could not write report to {:?}: {}", self.path, e);
+            F3548Error::Storage
+        })?;
+        *lines += 1;
+
+        {
+            let mut reports = self.lock();
+            reports.push(stored);
+            let count = reports.len();
+            reports.drain(..count.saturating_sub(MAX_STORED_REPORTS));
+        }
+
+        if *lines >= 2 * MAX_STORED_REPORTS {
+            // the report is stored either way, the file is compacted later
+            if let Ok(count) = self.compact().await {
+                *lines = count;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Reports received in the given time range, most recent first
+    pub fn query(
+        &self,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Vec<StoredErrorReport> {
+        self.lock()
+            .iter()
+            .rev()
+            .filter(|stored| {
+                let Some(received) = stored.received.to_datetime() else {
+                    return false;
+                };
+
+                since.iter().all(|since| received >= *since)
+                    && until.iter().all(|until| received <= *until)
+            })
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::Duration;
+    use std::io::Write;
+
+    fn report() -> ErrorReport {
+        ErrorReport {
+            report_id: None,
+            exchange: ExchangeRecord {
+                url: "https://peer.example.com/uss/v1/operational_intents/abc".to_string(),
+                method: "GET".to_string(),
+                headers: vec!["accept: application/json".to_string()],
+                recorder_role: RecorderRole::Client,
+                request_time: Time::default(),
+                request_body: None,
+                response_time: None,
+                response_body: None,
+                response_code: Some(500),
+                problem: Some("internal server error".to_string()),
+            },
+        }
+    }
+
+    fn temp_path(name: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("svc-discovery-{}-{}.jsonl", name, new_report_id()));
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_new_report_id() {
+        let id = new_report_id();
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "4");
+        assert_ne!(id, new_report_id());
+    }
+
+    #[tokio::test]
+    async fn test_add_query_reload() {
+        let path = temp_path("reports");
+        let reports = ErrorReports::load(&path);
+        assert!(reports.query(None, None, 10).is_empty());
+
+        let now = Utc::now();
+        let first = reports
+            .add(report(), now - Duration::hours(1))
+            .await
+            .unwrap();
+        let second = reports.add(report(), now).await.unwrap();
+        assert!(first.report_id.is_some());
+        assert_ne!(first.report_id, second.report_id);
+
+        // most recent first
+        let all = reports.query(None, None, 10);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].report.report_id, second.report_id);
+
+        let recent = reports.query(Some(now - Duration::minutes(1)), None, 10);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].report.report_id, second.report_id);
+
+        let old = reports.query(None, Some(now - Duration::minutes(1)), 10);
+        assert_eq!(old.len(), 1);
+        assert_eq!(old[0].report.report_id, first.report_id);
+
+        assert_eq!(reports.query(None, None, 1).len(), 1);
+
+        // invalid lines are skipped when loading
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        writeln!(file, "not a report").unwrap();
+
+        let reloaded = ErrorReports::load(&path);
+        let all = reloaded.query(None, None, 10);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].report.report_id, first.report_id);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_add_write_failure() {
+        let reports = ErrorReports::load("/nonsense/reports.jsonl");
+        let e = reports.add(report(), Utc::now()).await.unwrap_err();
+        assert_eq!(e, F3548Error::Storage);
+        assert!(reports.query(None, None, 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_too_large() {
+        let path = temp_path("reports-large");
+        let reports = ErrorReports::load(&path);
+        let mut large = report();
+        large.exchange.response_body = Some("x".repeat(MAX_REPORT_BYTES));
+        let e = reports.add(large, Utc::now()).await.unwrap_err();
+        assert_eq!(e, F3548Error::TooLarge);
+        assert!(reports.query(None, None, 10).is_empty());
+        assert!(!std::path::Path::new(&path).exists());
+    }
+
+    #[tokio::test]
+    async fn test_max_stored_reports() {
+        let path = temp_path("reports-max");
+        let now = Utc::now();
+        let line = serde_json::to_string(&StoredErrorReport {
+            received: (now - Duration::hours(1)).into(),
+            report: report(),
+        })
+        .unwrap();
+        let content = format!("{}\n", line).repeat(2 * MAX_STORED_REPORTS - 1);
+        std::fs::write(&path, content).unwrap();
+
+        // only the most recent reports are kept
+        let reports = ErrorReports::load(&path);
+        assert_eq!(reports.lock().len(), MAX_STORED_REPORTS);
+
+        // the file is compacted once it holds twice as many
+        let added = reports.add(report(), now).await.unwrap();
+        assert_eq!(reports.lock().len(), MAX_STORED_REPORTS);
+        assert_eq!(*reports.lines.lock().await, MAX_STORED_REPORTS);
+        let reloaded = ErrorReports::load(&path);
+        let all = reloaded.query(None, None, 1);
+        assert_eq!(all[0].report.report_id, added.report_id);
+        assert_eq!(reloaded.lock().len(), MAX_STORED_REPORTS);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap().lines().count(),
+            MAX_STORED_REPORTS
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -6,16 +6,342 @@ pub mod grpc_server {
     tonic::include_proto!("grpc");
 }
 use grpc_server::rpc_service_server::{RpcService, RpcServiceServer};
-use grpc_server::{ReadyRequest, ReadyResponse};
+use grpc_server::{
+    Coordinates, DeleteConstraintRequest, DeleteConstraintResponse, DeleteFlightPlanRequest,
+    DeleteFlightPlanResponse, FlightPlanState, GetPartnerVertiportsRequest,
+    GetPartnerVertiportsResponse, PutConstraintResponse, PutFlightPlanResponse, ReadyRequest,
+    ReadyResponse,
+};
 
+use crate::auth::get_provider_credentials;
 use crate::config::Config;
+use crate::f3548::flight_plans::{FlightPlan, PathPoint};
+use crate::f3548::{get_constraints, get_flight_plans, F3548Error};
+use crate::grpc::client::get_clients;
+use crate::odid::encoder::{encode_flight, BroadcastDetails};
+use crate::registry::get_registry;
+use crate::rest::api::rest_types::{
+    Altitude, HorizontalAccuracy, LatLngPoint, OperationalIntentState, Polygon,
+    PostTelemetryRequest, RIDAircraftPosition, RIDAircraftState, RIDHeight, RIDHeightReference,
+    RIDOperationalStatus, SpeedAccuracy, StationWeather, Time, UAType, VerticalAccuracy,
+    VertiportStatus, Volume3D, Volume4D, WeatherReport, WeatherReportType,
+};
+use crate::rest::api::uss::{get_recent_flights, Window, MAX_DISPLAY_AREA_DIAGONAL_METERS};
 use crate::shutdown_signal;
+use crate::telemetry::forward::GisBatch;
+use crate::telemetry::{get_gis_forwarder, get_pushed_telemetry, TelemetryError};
+use crate::vertiports::get_partner_vertiports;
+use crate::vertiports::partners::{registered_partners, PartnerVertiport};
+use crate::weather::{get_weather, WeatherError};
+use lib_common::time::{DateTime, Utc};
 
 use std::fmt::Debug;
 use std::net::SocketAddr;
+use strum::IntoEnumIterator;
 use tonic::transport::Server;
 use tonic::{Request, Response, Status};
 
+/// Flights reported within this many seconds are re-broadcast
+const BROADCAST_FRAMES_DURATION_SECONDS: f32 = 5.0;
+
+/// Convert an optional protobuf timestamp, fails if missing or out of range
+fn to_datetime(ts: Option<prost_types::Timestamp>) -> Option<DateTime<Utc>> {
+    ts.and_then(|ts| DateTime::<Utc>::from_timestamp(ts.seconds, ts.nanos as u32))
+}
+
+/// Convert a REST time to a protobuf timestamp, fails if the time is invalid
+fn to_timestamp(time: &Time) -> Option<prost_types::Timestamp> {
+    time.to_datetime().map(|dt| prost_types::Timestamp {
+        seconds: dt.timestamp(),
+        nanos: dt.timestamp_subsec_nanos() as i32,
+    })
+}
+
+/// Build a window from two opposite corners, fails if a corner is missing
+///  or out of range
+fn to_window(corner_1: Option<Coordinates>, corner_2: Option<Coordinates>) -> Option<Window> {
+    let (corner_1, corner_2) = (corner_1?, corner_2?);
+    let valid = |c: &Coordinates| {
+        (-90.0..=90.0).contains(&c.latitude) && (-180.0..=180.0).contains(&c.longitude)
+    };
+    if !valid(&corner_1) || !valid(&corner_2) {
+        return None;
+    }
+
+    Some(Window {
+        lat1: corner_1.latitude,
+        lon1: corner_1.longitude,
+        lat2: corner_2.latitude,
+        lon2: corner_2.longitude,
+    })
+}
+
+impl From<FlightPlanState> for OperationalIntentState {
+    fn from(state: FlightPlanState) -> Self {
+        match state {
+            FlightPlanState::Accepted => OperationalIntentState::Accepted,
+            FlightPlanState::Activated => OperationalIntentState::Activated,
+            FlightPlanState::Nonconforming => OperationalIntentState::Nonconforming,
+            FlightPlanState::Contingent => OperationalIntentState::Contingent,
+        }
+    }
+}
+
+impl TryFrom<grpc_server::FlightPlan> for FlightPlan {
+    type Error = Status;
+
+    fn try_from(plan: grpc_server::FlightPlan) -> Result<Self, Self::Error> {
+        let state = FlightPlanState::from_i32(plan.state).ok_or_else(|| {
+            grpc_error!("invalid flight plan state: {}", plan.state);
+            Status::invalid_argument("invalid flight plan state")
+        })?;
+
+        let (Some(time_start), Some(time_end)) =
+            (to_datetime(plan.time_start), to_datetime(plan.time_end))
+        else {
+            grpc_error!("missing or invalid flight plan time.");
+            return Err(Status::invalid_argument(
+                "missing or invalid flight plan time",
+            ));
+        };
+
+        Ok(FlightPlan {
+            state: state.into(),
+            time_start,
+            time_end,
+            path: plan
+                .path
+                .into_iter()
+                .map(|p| PathPoint {
+                    latitude: p.latitude,
+                    longitude: p.longitude,
+                    altitude_meters: p.altitude_meters,
+                })
+                .collect(),
+            id: plan.id,
+            session_id: plan.session_id,
+            priority: plan.priority,
+            origin_vertiport_id: plan.origin_vertiport_id,
+            target_vertiport_id: plan.target_vertiport_id,
+        })
+    }
+}
+
+impl TryFrom<grpc_server::Constraint> for Volume4D {
+    type Error = Status;
+
+    fn try_from(constraint: grpc_server::Constraint) -> Result<Self, Self::Error> {
+        let (Some(time_start), Some(time_end)) = (
+            to_datetime(constraint.time_start),
+            to_datetime(constraint.time_end),
+        ) else {
+            grpc_error!("missing or invalid constraint time.");
+            return Err(Status::invalid_argument(
+                "missing or invalid constraint time",
+            ));
+        };
+
+        Ok(Volume4D {
+            volume: Volume3D {
+                outline_circle: None,
+                outline_polygon: Some(Polygon {
+                    vertices: constraint
+                        .vertices
+                        .into_iter()
+                        .map(|v| LatLngPoint {
+                            lat: v.latitude,
+                            lng: v.longitude,
+                        })
+                        .collect(),
+                }),
+                altitude_lower: Some(Altitude {
+                    value: constraint.altitude_meters_min as f64,
+                    ..Default::default()
+                }),
+                altitude_upper: Some(Altitude {
+                    value: constraint.altitude_meters_max as f64,
+                    ..Default::default()
+                }),
+            },
+            time_start: time_start.into(),
+            time_end: time_end.into(),
+        })
+    }
+}
+
+impl TryFrom<grpc_server::AircraftState> for RIDAircraftState {
+    type Error = Status;
+
+    fn try_from(state: grpc_server::AircraftState) -> Result<Self, Self::Error> {
+        let (Some(timestamp), Some(position)) = (to_datetime(state.timestamp), state.position)
+        else {
+            grpc_error!("missing or invalid aircraft state time or position.");
+            return Err(Status::invalid_argument(
+                "missing or invalid aircraft state time or position",
+            ));
+        };
+
+        // the proto enum mirrors the order of the REST enum
+        let operational_status = grpc_server::OperationalStatus::from_i32(state.status)
+            .and_then(|status| RIDOperationalStatus::iter().nth(status as usize))
+            .ok_or_else(|| {
+                grpc_error!("invalid operational status: {}", state.status);
+                Status::invalid_argument("invalid operational status")
+            })?;
+
+        Ok(RIDAircraftState {
+            timestamp: timestamp.into(),
+            timestamp_accuracy: 0.0,
+            operational_status,
+            position: RIDAircraftPosition {
+                lat: position.latitude,
+                lng: position.longitude,
+                alt: state.altitude_meters,
+                accuracy_h: HorizontalAccuracy::HAUnknown,
+                accuracy_v: VerticalAccuracy::VAUnknown,
+                extrapolated: false,
+                pressure_alt: crate::odid::decoder::UNKNOWN_ALTITUDE,
+                height: RIDHeight {
+                    distance: crate::odid::decoder::UNKNOWN_ALTITUDE,
+                    reference: RIDHeightReference::TakeoffLocation,
+                },
+            },
+            track: state.track_degrees,
+            speed: state.ground_speed_mps,
+            speed_accuracy: SpeedAccuracy::SAUnknown,
+            vertical_speed: state.vertical_speed_mps,
+        })
+    }
+}
+
+impl From<LatLngPoint> for Coordinates {
+    fn from(point: LatLngPoint) -> Self {
+        Coordinates {
+            latitude: point.lat,
+            longitude: point.lng,
+        }
+    }
+}
+
+impl From<VertiportStatus> for grpc_server::VertiportStatus {
+    fn from(status: VertiportStatus) -> Self {
+        match status {
+            VertiportStatus::Open => grpc_server::VertiportStatus::Open,
+            VertiportStatus::Restricted => grpc_server::VertiportStatus::Restricted,
+            VertiportStatus::Closed => grpc_server::VertiportStatus::Closed,
+        }
+    }
+}
+
+impl From<PartnerVertiport> for grpc_server::Vertiport {
+    fn from(partner: PartnerVertiport) -> Self {
+        let vertiport = partner.vertiport;
+        grpc_server::Vertiport {
+            id: vertiport.id,
+            provider: partner.provider,
+            name: vertiport.name,
+            location: Some(vertiport.location.into()),
+            footprint: vertiport
+                .footprint
+                .vertices
+                .into_iter()
+                .map(Coordinates::from)
+                .collect(),
+            pad_count: vertiport.pad_count,
+            operating_hours: vertiport
+                .operating_hours
+                .into_iter()
+                .map(|hours| grpc_server::OperatingHours {
+                    day: hours.day.to_string(),
+                    open: hours.open,
+                    close: hours.close,
+                })
+                .collect(),
+            status: grpc_server::VertiportStatus::from(vertiport.status) as i32,
+        }
+    }
+}
+
+impl From<WeatherReport> for grpc_server::WeatherReport {
+    fn from(report: WeatherReport) -> Self {
+        let report_type = match report.report_type {
+            WeatherReportType::Metar => grpc_server::WeatherReportType::Metar,
+            WeatherReportType::Taf => grpc_server::WeatherReportType::Taf,
+        };
+
+        grpc_server::WeatherReport {
+            report_type: report_type as i32,
+            issued: to_timestamp(&report.issued),
+            valid_from: report.valid_from.as_ref().and_then(to_timestamp),
+            valid_to: report.valid_to.as_ref().and_then(to_timestamp),
+            wind: report.wind.map(|wind| grpc_server::Wind {
+                direction_degrees: wind.direction_degrees.map(u32::from),
+                speed_knots: wind.speed_knots,
+                gust_knots: wind.gust_knots,
+            }),
+            visibility_meters: report.visibility_meters,
+            ceiling_feet: report.ceiling_feet,
+            qnh_hpa: report.qnh_hpa,
+            temperature_celsius: report.temperature_celsius,
+            dew_point_celsius: report.dew_point_celsius,
+            station: report.station,
+            raw: report.raw,
+        }
+    }
+}
+
+impl From<StationWeather> for grpc_server::StationWeather {
+    fn from(station: StationWeather) -> Self {
+        grpc_server::StationWeather {
+            station: station.station,
+            location: station.location.map(Coordinates::from),
+            metar: station.metar.map(grpc_server::WeatherReport::from),
+            taf: station.taf.map(grpc_server::WeatherReport::from),
+        }
+    }
+}
+
+impl TryFrom<grpc_server::PushTelemetryRequest> for PostTelemetryRequest {
+    type Error = Status;
+
+    fn try_from(request: grpc_server::PushTelemetryRequest) -> Result<Self, Self::Error> {
+        let flight_id = request.flight_id.trim();
+        if flight_id.is_empty() {
+            grpc_error!("flight_id must not be empty.");
+            return Err(Status::invalid_argument("empty flight_id"));
+        }
+
+        // the proto enum mirrors the order of the REST enum
+        let aircraft_type = grpc_server::AircraftType::from_i32(request.aircraft_type)
+            .and_then(|aircraft_type| UAType::iter().nth(aircraft_type as usize))
+            .ok_or_else(|| {
+                grpc_error!("invalid aircraft type: {}", request.aircraft_type);
+                Status::invalid_argument("invalid aircraft type")
+            })?;
+
+        Ok(PostTelemetryRequest {
+            flight_id: flight_id.to_string(),
+            aircraft_type,
+            simulated: request.simulated,
+            states: request
+                .states
+                .into_iter()
+                .map(RIDAircraftState::try_from)
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
+impl From<TelemetryError> for Status {
+    fn from(e: TelemetryError) -> Self {
+        match e {
+            TelemetryError::InvalidState => Status::invalid_argument(e.to_string()),
+            TelemetryError::OutOfOrder => Status::failed_precondition(e.to_string()),
+            TelemetryError::NotOwner => Status::permission_denied(e.to_string()),
+        }
+    }
+}
+
 /// struct to implement the gRPC server functions
 #[derive(Debug, Default, Copy, Clone)]
 pub struct GRPCServerImpl {}
@@ -31,6 +357,256 @@ impl RpcService for GRPCServerImpl {
         let response = ReadyResponse { ready: true };
         Ok(Response::new(response))
     }
+
+    /// Shares a flight plan as an operational intent
+    async fn put_flight_plan(
+        &self,
+        request: Request<grpc_server::FlightPlan>,
+    ) -> Result<Response<PutFlightPlanResponse>, Status> {
+        grpc_debug!("(grpc put_flight_plan) entry.");
+        let plan: FlightPlan = request.into_inner().try_into()?;
+        let id = plan.id.clone();
+        let version = get_flight_plans()
+            .await
+            .put(plan, Utc::now())
+            .map_err(|e| {
+                grpc_error!("could not store flight plan {}: {}", id, e);
+                Status::invalid_argument(e.to_string())
+            })?;
+
+        grpc_info!("flight plan {} stored with version {}.", id, version);
+        Ok(Response::new(PutFlightPlanResponse { version }))
+    }
+
+    /// Stops sharing a flight plan
+    async fn delete_flight_plan(
+        &self,
+        request: Request<DeleteFlightPlanRequest>,
+    ) -> Result<Response<DeleteFlightPlanResponse>, Status> {
+        grpc_debug!("(grpc delete_flight_plan) entry.");
+        let id = request.into_inner().id;
+        let deleted = match get_flight_plans().await.delete(&id) {
+            Ok(()) => true,
+            Err(F3548Error::NotFound) => false,
+            Err(e) => {
+                grpc_error!("could not delete flight plan {}: {}", id, e);
+                return Err(Status::internal(e.to_string()));
+            }
+        };
+
+        Ok(Response::new(DeleteFlightPlanResponse { deleted }))
+    }
+
+    /// Publishes a constraint
+    async fn put_constraint(
+        &self,
+        request: Request<grpc_server::Constraint>,
+    ) -> Result<Response<PutConstraintResponse>, Status> {
+        grpc_debug!("(grpc put_constraint) entry.");
+        let constraint = request.into_inner();
+        let id = constraint.id.clone();
+        let constraint_type = constraint.constraint_type.clone();
+        let volume: Volume4D = constraint.try_into()?;
+        let version = get_constraints()
+            .await
+            .put(&id, constraint_type, vec![volume], Utc::now())
+            .map_err(|e| {
+                grpc_error!("could not store constraint {}: {}", id, e);
+                Status::invalid_argument(e.to_string())
+            })?;
+
+        grpc_info!("constraint {} stored with version {}.", id, version);
+        Ok(Response::new(PutConstraintResponse { version }))
+    }
+
+    /// Finds the vertiports of partner networks in an area
+    async fn get_partner_vertiports(
+        &self,
+        request: Request<GetPartnerVertiportsRequest>,
+    ) -> Result<Response<GetPartnerVertiportsResponse>, Status> {
+        grpc_debug!("(grpc get_partner_vertiports) entry.");
+        let request = request.into_inner();
+        let Some(window) = to_window(request.corner_1, request.corner_2) else {
+            grpc_error!("two valid corners of the area are required.");
+            return Err(Status::invalid_argument("invalid corners"));
+        };
+
+        let registered = registered_partners(&*get_registry().await, &window);
+        let vertiports = get_partner_vertiports()
+            .await
+            .vertiports_in(&window, &registered)
+            .await
+            .into_iter()
+            .map(grpc_server::Vertiport::from)
+            .collect();
+
+        Ok(Response::new(GetPartnerVertiportsResponse { vertiports }))
+    }
+
+    /// Gets the latest meteorological reports of a station or of an area
+    async fn get_weather(
+        &self,
+        request: Request<grpc_server::GetWeatherRequest>,
+    ) -> Result<Response<grpc_server::GetWeatherResponse>, Status> {
+        grpc_debug!("(grpc get_weather) entry.");
+        let request = request.into_inner();
+        let weather = get_weather().await;
+        let stations = match request.station {
+            Some(station) => match weather.station(&station) {
+                Ok(station) => vec![station],
+                Err(WeatherError::NotFound) => vec![],
+                Err(e) => {
+                    grpc_error!("could not get weather of {}: {}", station, e);
+                    return Err(Status::internal(e.to_string()));
+                }
+            },
+            None => {
+                let Some(window) = to_window(request.corner_1, request.corner_2) else {
+                    grpc_error!("a station or two valid corners of an area are required.");
+                    return Err(Status::invalid_argument("invalid corners"));
+                };
+
+                weather.stations_in(&window)
+            }
+        };
+
+        let stations = stations
+            .into_iter()
+            .map(grpc_server::StationWeather::from)
+            .collect();
+        Ok(Response::new(grpc_server::GetWeatherResponse { stations }))
+    }
+
+    /// Gets the flights in an area encoded as Open Drone ID message packs
+    async fn get_broadcast_frames(
+        &self,
+        request: Request<grpc_server::GetBroadcastFramesRequest>,
+    ) -> Result<Response<grpc_server::GetBroadcastFramesResponse>, Status> {
+        grpc_debug!("(grpc get_broadcast_frames) entry.");
+        let request = request.into_inner();
+        let Some(window) = to_window(request.corner_1, request.corner_2) else {
+            grpc_error!("two valid corners of an area are required.");
+            return Err(Status::invalid_argument("invalid corners"));
+        };
+
+        if window.diagonal() > MAX_DISPLAY_AREA_DIAGONAL_METERS {
+            grpc_error!("the requested area was too large.");
+            return Err(Status::invalid_argument("area too large"));
+        }
+
+        let mut flights = get_recent_flights(
+            &mut get_clients().await.clone(),
+            &window,
+            BROADCAST_FRAMES_DURATION_SECONDS,
+        )
+        .await
+        .map_err(|e| {
+            grpc_error!("could not get flights: {}", e);
+            Status::internal(e.to_string())
+        })?;
+        flights.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let details = BroadcastDetails::default();
+        let frames = flights
+            .iter()
+            .filter_map(|flight| match encode_flight(flight, &details) {
+                Ok(message_pack) => Some(grpc_server::BroadcastFrame {
+                    flight_id: flight.id.clone(),
+                    message_pack,
+                }),
+                Err(e) => {
+                    grpc_warn!("could not encode flight {}: {}", flight.id, e);
+                    None
+                }
+            })
+            .collect();
+
+        Ok(Response::new(grpc_server::GetBroadcastFramesResponse {
+            frames,
+        }))
+    }
+
+    /// Pushes a batch of states of a partner flight
+    async fn push_telemetry(
+        &self,
+        request: Request<grpc_server::PushTelemetryRequest>,
+    ) -> Result<Response<grpc_server::PushTelemetryResponse>, Status> {
+        grpc_debug!("(grpc push_telemetry) entry.");
+        let authorization = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok());
+        let provider = get_provider_credentials()
+            .await
+            .authenticate_bearer(authorization)
+            .map_err(|e| {
+                grpc_error!("could not authenticate the provider: {}", e);
+                Status::unauthenticated(e.to_string())
+            })?;
+
+        let request = request.into_inner();
+        let claimed = request.provider_id.trim();
+        if !claimed.is_empty() && claimed != provider {
+            grpc_error!("provider {} may not push as {}.", provider, claimed);
+            return Err(Status::permission_denied(
+                "provider_id does not match the token",
+            ));
+        }
+
+        let telemetry = get_pushed_telemetry().await;
+        if !telemetry.is_provider(&provider) {
+            grpc_error!("provider {} may not push telemetry.", provider);
+            return Err(Status::permission_denied("unknown provider"));
+        }
+
+        let batch = PostTelemetryRequest::try_from(request)?;
+        let now = Utc::now();
+        let result = telemetry
+            .push(
+                &provider,
+                &batch.flight_id,
+                batch.aircraft_type,
+                batch.simulated,
+                batch.states,
+                now,
+            )
+            .map_err(|e| {
+                grpc_error!("could not push telemetry of {}: {}", batch.flight_id, e);
+                Status::from(e)
+            })?;
+
+        get_gis_forwarder().await.forward(GisBatch::new(
+            &batch.flight_id,
+            batch.aircraft_type,
+            batch.simulated,
+            &result.states,
+            now,
+        ));
+
+        Ok(Response::new(grpc_server::PushTelemetryResponse {
+            accepted: result.accepted as u32,
+            duplicates: result.duplicates as u32,
+        }))
+    }
+
+    /// Withdraws a constraint
+    async fn delete_constraint(
+        &self,
+        request: Request<DeleteConstraintRequest>,
+    ) -> Result<Response<DeleteConstraintResponse>, Status> {
+        grpc_debug!("(grpc delete_constraint) entry.");
+        let id = request.into_inner().id;
+        let deleted = match get_constraints().await.delete(&id) {
+            Ok(()) => true,
+            Err(F3548Error::NotFound) => false,
+            Err(e) => {
+                grpc_error!("could not delete constraint {}: {}", id, e);
+                return Err(Status::internal(e.to_string()));
+            }
+        };
+
+        Ok(Response::new(DeleteConstraintResponse { deleted }))
+    }
 }
 
 /// Starts the grpc servers for this microservice using the provided configuration
@@ -103,4 +679,482 @@ mod tests {
 
         ut_info!("success");
     }
+
+    fn flight_plan(id: &str) -> grpc_server::FlightPlan {
+        let now = Utc::now();
+        grpc_server::FlightPlan {
+            id: id.to_string(),
+            session_id: None,
+            priority: 1,
+            state: FlightPlanState::Activated as i32,
+            time_start: Some(prost_types::Timestamp {
+                seconds: now.timestamp(),
+                nanos: 0,
+            }),
+            time_end: Some(prost_types::Timestamp {
+                seconds: now.timestamp() + 600,
+                nanos: 0,
+            }),
+            path: vec![grpc_server::PathPoint {
+                latitude: 52.37,
+                longitude: 4.89,
+                altitude_meters: 100.0,
+            }],
+            origin_vertiport_id: Some("vertiport-a".to_string()),
+            target_vertiport_id: None,
+        }
+    }
+
+    #[test]
+    fn test_flight_plan_try_from() {
+        let plan: FlightPlan = flight_plan("a").try_into().unwrap();
+        assert_eq!(plan.id, "a");
+        assert_eq!(plan.state, OperationalIntentState::Activated);
+        assert_eq!(plan.path.len(), 1);
+        assert_eq!((plan.time_end - plan.time_start).num_seconds(), 600);
+        assert_eq!(plan.origin_vertiport_id.as_deref(), Some("vertiport-a"));
+        assert!(plan.target_vertiport_id.is_none());
+
+        let mut invalid = flight_plan("a");
+        invalid.state = 100;
+        let e = FlightPlan::try_from(invalid).unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+
+        let mut invalid = flight_plan("a");
+        invalid.time_end = None;
+        let e = FlightPlan::try_from(invalid).unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_put_delete_flight_plan() {
+        let imp = GRPCServerImpl::default();
+
+        let response = imp
+            .put_flight_plan(Request::new(flight_plan("grpc-plan")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.version, 1);
+
+        let mut invalid = flight_plan("grpc-plan");
+        invalid.path.clear();
+        let e = imp
+            .put_flight_plan(Request::new(invalid))
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+
+        let request = DeleteFlightPlanRequest {
+            id: "grpc-plan".to_string(),
+        };
+        let response = imp
+            .delete_flight_plan(Request::new(request.clone()))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.deleted);
+
+        let response = imp
+            .delete_flight_plan(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!response.deleted);
+    }
+
+    fn constraint(id: &str) -> grpc_server::Constraint {
+        let now = Utc::now();
+        grpc_server::Constraint {
+            id: id.to_string(),
+            constraint_type: Some("NoFlyZone".to_string()),
+            vertices: vec![
+                grpc_server::Coordinates {
+                    latitude: 52.37,
+                    longitude: 4.89,
+                },
+                grpc_server::Coordinates {
+                    latitude: 52.38,
+                    longitude: 4.89,
+                },
+                grpc_server::Coordinates {
+                    latitude: 52.38,
+                    longitude: 4.90,
+                },
+            ],
+            altitude_meters_min: 0.0,
+            altitude_meters_max: 500.0,
+            time_start: Some(prost_types::Timestamp {
+                seconds: now.timestamp(),
+                nanos: 0,
+            }),
+            time_end: Some(prost_types::Timestamp {
+                seconds: now.timestamp() + 600,
+                nanos: 0,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_volume_try_from_constraint() {
+        let volume: Volume4D = constraint("a").try_into().unwrap();
+        let polygon = volume.volume.outline_polygon.unwrap();
+        assert_eq!(polygon.vertices.len(), 3);
+        assert_eq!(polygon.vertices[2].lng, 4.90);
+        assert_eq!(volume.volume.altitude_upper.unwrap().value, 500.0);
+
+        let mut invalid = constraint("a");
+        invalid.time_start = None;
+        let e = Volume4D::try_from(invalid).unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_put_delete_constraint() {
+        let imp = GRPCServerImpl::default();
+
+        let response = imp
+            .put_constraint(Request::new(constraint("grpc-zone")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.version, 1);
+
+        let mut invalid = constraint("grpc-zone");
+        invalid.vertices.truncate(2);
+        let e = imp.put_constraint(Request::new(invalid)).await.unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+
+        let request = DeleteConstraintRequest {
+            id: "grpc-zone".to_string(),
+        };
+        let response = imp
+            .delete_constraint(Request::new(request.clone()))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.deleted);
+
+        let response = imp
+            .delete_constraint(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!response.deleted);
+    }
+
+    #[test]
+    fn test_vertiport_from_partner_vertiport() {
+        let partner = PartnerVertiport {
+            provider: "partner".to_string(),
+            vertiport: crate::vertiports::file::tests::vertiport("partner:vp"),
+        };
+        let vertiport = grpc_server::Vertiport::from(partner);
+        assert_eq!(vertiport.id, "partner:vp");
+        assert_eq!(vertiport.provider, "partner");
+        assert_eq!(vertiport.footprint.len(), 4);
+        assert_eq!(vertiport.location.unwrap().latitude, 52.3785);
+        assert_eq!(vertiport.operating_hours[0].day, "Monday");
+        assert_eq!(vertiport.status, grpc_server::VertiportStatus::Open as i32);
+
+        assert_eq!(
+            grpc_server::VertiportStatus::from(VertiportStatus::Closed),
+            grpc_server::VertiportStatus::Closed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_grpc_get_partner_vertiports() {
+        let imp = GRPCServerImpl::default();
+        let corner = |latitude, longitude| Coordinates {
+            latitude,
+            longitude,
+        };
+
+        let request = GetPartnerVertiportsRequest {
+            corner_1: Some(corner(52.37, 4.89)),
+            corner_2: None,
+        };
+        let e = imp
+            .get_partner_vertiports(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+
+        let request = GetPartnerVertiportsRequest {
+            corner_1: Some(corner(52.37, 4.89)),
+            corner_2: Some(corner(90.1, 4.91)),
+        };
+        let e = imp
+            .get_partner_vertiports(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+
+        // no partners are configured
+        let request = GetPartnerVertiportsRequest {
+            corner_1: Some(corner(52.37, 4.89)),
+            corner_2: Some(corner(52.38, 4.91)),
+        };
+        let response = imp
+            .get_partner_vertiports(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.vertiports.is_empty());
+    }
+
+    #[test]
+    fn test_to_window() {
+        let corner = |latitude, longitude| {
+            Some(Coordinates {
+                latitude,
+                longitude,
+            })
+        };
+
+        let window = to_window(corner(52.37, 4.89), corner(52.38, 4.91)).unwrap();
+        assert_eq!(window.lat2, 52.38);
+        assert_eq!(window.lon1, 4.89);
+
+        assert!(to_window(corner(52.37, 4.89), None).is_none());
+        assert!(to_window(corner(52.37, 180.1), corner(52.38, 4.91)).is_none());
+    }
+
+    #[test]
+    fn test_station_weather_from() {
+        let store = crate::weather::store::tests::store();
+        store.ingest(crate::weather::store::tests::BULLETIN, Utc::now());
+        let station = grpc_server::StationWeather::from(store.station("EHAM").unwrap());
+        assert_eq!(station.station, "EHAM");
+        assert_eq!(station.location.unwrap().latitude, 52.31);
+
+        let metar = station.metar.unwrap();
+        assert_eq!(
+            metar.report_type,
+            grpc_server::WeatherReportType::Metar as i32
+        );
+        assert!(metar.issued.is_some());
+        assert!(metar.valid_from.is_none());
+        assert_eq!(metar.wind.unwrap().direction_degrees, Some(240));
+        assert_eq!(metar.dew_point_celsius, Some(-3));
+
+        let taf = station.taf.unwrap();
+        assert_eq!(taf.report_type, grpc_server::WeatherReportType::Taf as i32);
+        assert!(taf.valid_to.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_grpc_get_weather() {
+        let imp = GRPCServerImpl::default();
+        get_weather()
+            .await
+            .ingest("METAR ZZGR 010925Z 24015KT 9999 Q1013", Utc::now());
+
+        let request = grpc_server::GetWeatherRequest {
+            station: Some("ZZGR".to_string()),
+            corner_1: None,
+            corner_2: None,
+        };
+        let response = imp
+            .get_weather(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.stations.len(), 1);
+        assert!(response.stations[0].metar.is_some());
+
+        let request = grpc_server::GetWeatherRequest {
+            station: Some("ZZNO".to_string()),
+            corner_1: None,
+            corner_2: None,
+        };
+        let response = imp
+            .get_weather(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.stations.is_empty());
+
+        let request = grpc_server::GetWeatherRequest {
+            station: None,
+            corner_1: None,
+            corner_2: None,
+        };
+        let e = imp.get_weather(Request::new(request)).await.unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+
+        // the station has no known location
+        let request = grpc_server::GetWeatherRequest {
+            station: None,
+            corner_1: Some(Coordinates {
+                latitude: -90.0,
+                longitude: -180.0,
+            }),
+            corner_2: Some(Coordinates {
+                latitude: 90.0,
+                longitude: 180.0,
+            }),
+        };
+        let response = imp
+            .get_weather(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.stations.iter().all(|s| s.station != "ZZGR"));
+    }
+
+    #[tokio::test]
+    async fn test_grpc_get_broadcast_frames() {
+        let imp = GRPCServerImpl::default();
+        let request = grpc_server::GetBroadcastFramesRequest {
+            corner_1: Some(Coordinates {
+                latitude: 52.36,
+                longitude: 4.9,
+            }),
+            corner_2: None,
+        };
+        let e = imp
+            .get_broadcast_frames(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+
+        // larger than a display area
+        let request = grpc_server::GetBroadcastFramesRequest {
+            corner_1: Some(Coordinates {
+                latitude: -80.0,
+                longitude: -170.0,
+            }),
+            corner_2: Some(Coordinates {
+                latitude: 80.0,
+                longitude: 170.0,
+            }),
+        };
+        let e = imp
+            .get_broadcast_frames(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+
+        let request = grpc_server::GetBroadcastFramesRequest {
+            corner_1: Some(Coordinates {
+                latitude: 52.36,
+                longitude: 4.9,
+            }),
+            corner_2: Some(Coordinates {
+                latitude: 52.37,
+                longitude: 4.91,
+            }),
+        };
+        let Ok(response) = imp.get_broadcast_frames(Request::new(request)).await else {
+            // svc-gis is not reachable without the stub backends
+            return;
+        };
+
+        for frame in response.into_inner().frames {
+            let (messages, _) = crate::odid::decoder::decode_frame(&frame.message_pack).unwrap();
+            assert!(!messages.is_empty());
+        }
+    }
+
+    /// A state of a partner aircraft at an offset from now
+    fn aircraft_state(seconds: i64) -> grpc_server::AircraftState {
+        grpc_server::AircraftState {
+            timestamp: to_timestamp(&Time::from(
+                Utc::now() + lib_common::time::Duration::seconds(seconds),
+            )),
+            status: grpc_server::OperationalStatus::Airborne as i32,
+            position: Some(Coordinates {
+                latitude: 52.37,
+                longitude: 4.9,
+            }),
+            altitude_meters: 120.0,
+            track_degrees: 90.0,
+            ground_speed_mps: 20.0,
+            vertical_speed_mps: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_push_telemetry_request_conversion() {
+        let request = grpc_server::PushTelemetryRequest {
+            provider_id: "acme".to_string(),
+            flight_id: " AC-1 ".to_string(),
+            aircraft_type: grpc_server::AircraftType::Helicopter as i32,
+            simulated: true,
+            states: vec![aircraft_state(-1), aircraft_state(0)],
+        };
+        let batch = PostTelemetryRequest::try_from(request.clone()).unwrap();
+        assert_eq!(batch.flight_id, "AC-1");
+        assert_eq!(batch.aircraft_type, UAType::Helicopter);
+        assert!(batch.simulated);
+        assert_eq!(batch.states.len(), 2);
+
+        let state = &batch.states[1];
+        assert_eq!(state.operational_status, RIDOperationalStatus::Airborne);
+        assert_eq!(state.position.lat, 52.37);
+        assert_eq!(state.position.alt, 120.0);
+        assert_eq!(state.track, 90.0);
+        assert_eq!(state.speed, 20.0);
+        assert_eq!(state.speed_accuracy, SpeedAccuracy::SAUnknown);
+        assert!(crate::telemetry::validate_state(state, Utc::now()).is_ok());
+
+        let mut invalid = request.clone();
+        invalid.flight_id = " ".to_string();
+        assert!(PostTelemetryRequest::try_from(invalid).is_err());
+
+        let mut invalid = request.clone();
+        invalid.aircraft_type = 42;
+        assert!(PostTelemetryRequest::try_from(invalid).is_err());
+
+        let mut invalid = request.clone();
+        invalid.states[0].status = 42;
+        assert!(PostTelemetryRequest::try_from(invalid).is_err());
+
+        let mut invalid = request;
+        invalid.states[0].position = None;
+        let e = PostTelemetryRequest::try_from(invalid).unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_telemetry_error_status() {
+        assert_eq!(
+            Status::from(TelemetryError::InvalidState).code(),
+            tonic::Code::InvalidArgument
+        );
+        assert_eq!(
+            Status::from(TelemetryError::OutOfOrder).code(),
+            tonic::Code::FailedPrecondition
+        );
+        assert_eq!(
+            Status::from(TelemetryError::NotOwner).code(),
+            tonic::Code::PermissionDenied
+        );
+    }
+
+    #[tokio::test]
+    async fn test_grpc_push_telemetry() {
+        let imp = GRPCServerImpl::default();
+        let request = grpc_server::PushTelemetryRequest {
+            provider_id: " ".to_string(),
+            flight_id: "AC-1".to_string(),
+            aircraft_type: grpc_server::AircraftType::Helicopter as i32,
+            simulated: false,
+            states: vec![aircraft_state(0)],
+        };
+        let e = imp
+            .push_telemetry(Request::new(request.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), tonic::Code::Unauthenticated);
+
+        let mut forged = Request::new(request);
+        forged
+            .metadata_mut()
+            .insert("authorization", "Bearer forged".parse().unwrap());
+        let e = imp.push_telemetry(forged).await.unwrap_err();
+        assert_eq!(e.code(), tonic::Code::Unauthenticated);
+    }
 }
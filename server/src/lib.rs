@@ -10,7 +10,45 @@ pub mod config;
 pub mod grpc;
 
 /// rest implementation module
+#[macro_use]
 pub mod rest;
+
+/// provider authentication module
+pub mod auth;
+
+/// svc-gis flights cache module
+pub mod cache;
+
+/// ASTM F3548 USS-USS storage module
+pub mod f3548;
+
+/// Open Drone ID broadcast module
+pub mod odid;
+
+/// service provider registry module
+pub mod registry;
+
+/// schedule sharing module
+pub mod schedules;
+
+/// traffic statistics module
+pub mod stats;
+
+/// pushed telemetry module
+pub mod telemetry;
+
+/// live traffic index module
+pub mod traffic;
+
+/// vertiport data source module
+pub mod vertiports;
+
+/// meteorological reports module
+pub mod weather;
+
+/// embedded DSS implementation module
+#[cfg(feature = "embedded_dss")]
+pub mod dss;
 pub use crate::config::Config;
 
 /// struct holding cli configuration options
@@ -36,6 +36,42 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Start REST server
     tokio::spawn(rest_server(config.clone(), None));
 
+    // Start scanning the weather drop directory
+    if !config.weather_drop_dir.is_empty() {
+        tokio::spawn(svc_discovery::weather::watch_drop_dir(
+            svc_discovery::weather::get_weather().await,
+            config.weather_drop_dir.clone(),
+        ));
+    }
+
+    // Start receiving Open Drone ID frames
+    if config.odid_udp_port != 0 {
+        tokio::spawn(svc_discovery::odid::listen_udp(
+            svc_discovery::odid::get_broadcast_flights().await,
+            config.odid_udp_port,
+        ));
+    }
+
+    // Start indexing the live traffic of the service region
+    if !config.live_traffic_region.is_empty() {
+        tokio::spawn(svc_discovery::traffic::poll_gis(
+            svc_discovery::traffic::get_live_traffic().await,
+            svc_discovery::grpc::client::GrpcClients::default(config.clone()),
+        ));
+    }
+
+    // Start forwarding pushed telemetry to svc-gis
+    if let Some(redis) = config.redis.clone() {
+        tokio::spawn(svc_discovery::telemetry::forward::forward_to_gis(
+            svc_discovery::telemetry::get_gis_forwarder().await,
+            redis,
+        ));
+    }
+
+    // Start embedded DSS server
+    #[cfg(feature = "embedded_dss")]
+    tokio::spawn(svc_discovery::dss::server::dss_server(config.clone(), None));
+
     // Start gRPC server
     let _ = tokio::spawn(grpc_server(config, None)).await;
 
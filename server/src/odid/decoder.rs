@@ -0,0 +1,868 @@
+//! Decoder of Open Drone ID broadcast messages
+//! Implements the 25 byte messages of ASTM F3411 Broadcast Remote ID, as
+//! sent over Bluetooth and Wi-Fi beacons.
+
+use super::OdidError;
+use crate::rest::api::rest_types::*;
+use lib_common::time::{DateTime, Utc};
+use strum::IntoEnumIterator;
+
+/// Size of a single message in bytes
+pub const MESSAGE_SIZE: usize = 25;
+
+/// Size of the header of a message pack in bytes
+pub const PACK_HEADER_SIZE: usize = 3;
+
+/// Maximum number of messages in a message pack
+pub const MAX_PACK_MESSAGES: usize = 9;
+
+/// Latest supported protocol version (ASTM F3411-22a)
+pub const MAX_PROTOCOL_VERSION: u8 = 2;
+
+/// Length of the UAS and operator IDs in bytes
+pub const ID_SIZE: usize = 20;
+
+/// Length of the self-ID description in bytes
+pub const DESCRIPTION_SIZE: usize = 23;
+
+/// Length of the authentication data of the first page in bytes
+pub const AUTH_FIRST_PAGE_DATA_SIZE: usize = 17;
+
+/// Length of the authentication data of the following pages in bytes
+pub const AUTH_PAGE_DATA_SIZE: usize = 23;
+
+/// Seconds between the Unix epoch and the Open Drone ID epoch
+///  (2019-01-01T00:00:00Z)
+pub const ODID_EPOCH_SECONDS: i64 = 1_546_300_800;
+
+/// Reported direction when the direction is unknown
+pub const UNKNOWN_DIRECTION: f32 = 361.0;
+
+/// Reported speed when the speed is unknown
+pub const UNKNOWN_SPEED: f32 = 255.0;
+
+/// Reported vertical speed when the vertical speed is unknown
+pub const UNKNOWN_VERTICAL_SPEED: f32 = 63.0;
+
+/// Reported altitude when the altitude is unknown
+pub use crate::rest::api::rest_types::UNKNOWN_ALTITUDE;
+
+/// Encoded timestamp when the timestamp is unknown
+pub const UNKNOWN_TIMESTAMP: u16 = 0xFFFF;
+
+/// Message types, the upper nibble of the message header
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageType {
+    /// Identification of the aircraft
+    BasicId = 0x0,
+
+    /// Position and vector of the aircraft
+    Location = 0x1,
+
+    /// Page of authentication data
+    Authentication = 0x2,
+
+    /// Free text description of the flight
+    SelfId = 0x3,
+
+    /// Location of the operator and area of operation
+    System = 0x4,
+
+    /// Identification of the operator
+    OperatorId = 0x5,
+
+    /// Several messages of the other types
+    MessagePack = 0xF,
+}
+
+impl MessageType {
+    /// The message type with the given value
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0x0 => Some(MessageType::BasicId),
+            0x1 => Some(MessageType::Location),
+            0x2 => Some(MessageType::Authentication),
+            0x3 => Some(MessageType::SelfId),
+            0x4 => Some(MessageType::System),
+            0x5 => Some(MessageType::OperatorId),
+            0xF => Some(MessageType::MessagePack),
+            _ => None,
+        }
+    }
+}
+
+/// Types of UAS IDs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IdType {
+    /// No ID
+    None = 0,
+
+    /// Manufacturer serial number (ANSI/CTA-2063-A)
+    SerialNumber = 1,
+
+    /// Registration ID assigned by a civil aviation authority
+    CaaRegistration = 2,
+
+    /// UUID assigned by a UTM service provider
+    UtmAssigned = 3,
+
+    /// Session ID assigned for the flight
+    SpecificSession = 4,
+}
+
+impl IdType {
+    /// The ID type with the given value, reserved values are reported
+    ///  as no ID
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => IdType::SerialNumber,
+            2 => IdType::CaaRegistration,
+            3 => IdType::UtmAssigned,
+            4 => IdType::SpecificSession,
+            _ => IdType::None,
+        }
+    }
+}
+
+/// Identification of the aircraft
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicId {
+    /// The type of the UAS ID
+    pub id_type: IdType,
+
+    /// The type of aircraft
+    pub ua_type: UAType,
+
+    /// The UAS ID
+    pub uas_id: String,
+}
+
+/// Position and vector of the aircraft
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    /// The operational status of the aircraft
+    pub status: RIDOperationalStatus,
+
+    /// The reference of the height
+    pub height_reference: RIDHeightReference,
+
+    /// Track in degrees clockwise from true north, 361 if unknown
+    pub direction: f32,
+
+    /// Ground speed in meters per second, 255 if unknown
+    pub speed: f32,
+
+    /// Vertical speed in meters per second, up is positive, 63 if unknown
+    pub vertical_speed: f32,
+
+    /// Degrees of latitude
+    pub latitude: f64,
+
+    /// Degrees of longitude
+    pub longitude: f64,
+
+    /// Pressure altitude in meters, -1000 if unknown
+    pub pressure_altitude: f32,
+
+    /// Geodetic altitude in meters (WGS84), -1000 if unknown
+    pub geodetic_altitude: f32,
+
+    /// Height above the reference in meters, -1000 if unknown
+    pub height: f32,
+
+    /// Horizontal accuracy of the position
+    pub accuracy_h: HorizontalAccuracy,
+
+    /// Vertical accuracy of the geodetic altitude
+    pub accuracy_v: VerticalAccuracy,
+
+    /// Vertical accuracy of the pressure altitude
+    pub accuracy_baro: VerticalAccuracy,
+
+    /// Accuracy of the speed
+    pub accuracy_speed: SpeedAccuracy,
+
+    /// Tenths of seconds since the full hour, if known
+    pub timestamp: Option<u16>,
+
+    /// Accuracy of the timestamp in seconds, 0 if unknown
+    pub timestamp_accuracy: f32,
+}
+
+/// A page of authentication data
+#[derive(Debug, Clone, PartialEq)]
+pub struct Authentication {
+    /// The type of authentication
+    pub auth_type: u8,
+
+    /// The number of this page
+    pub page: u8,
+
+    /// The number of the last page, only provided on the first page
+    pub last_page: Option<u8>,
+
+    /// The length of the authentication data over all pages in bytes,
+    ///  only provided on the first page
+    pub length: Option<u8>,
+
+    /// Time of the authentication, only provided on the first page
+    pub timestamp: Option<DateTime<Utc>>,
+
+    /// The authentication data of this page
+    pub data: Vec<u8>,
+}
+
+/// Free text description of the flight
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfId {
+    /// The type of description: 0 for text, 1 for an emergency,
+    ///  2 for an extended status
+    pub description_type: u8,
+
+    /// The description
+    pub description: String,
+}
+
+/// Location of the operator and area of operation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct System {
+    /// Source of the operator location: 0 for the takeoff location,
+    ///  1 for a live GNSS location, 2 for a fixed location
+    pub operator_location_type: u8,
+
+    /// Classification scheme of the aircraft: 0 for undeclared, 1 for EU
+    pub classification_type: u8,
+
+    /// Degrees of latitude of the operator
+    pub operator_latitude: f64,
+
+    /// Degrees of longitude of the operator
+    pub operator_longitude: f64,
+
+    /// Number of aircraft in the area of operation
+    pub area_count: u16,
+
+    /// Radius of the area of operation around the operator in meters
+    pub area_radius: u16,
+
+    /// Ceiling of the area of operation in meters, -1000 if unknown
+    pub area_ceiling: f32,
+
+    /// Floor of the area of operation in meters, -1000 if unknown
+    pub area_floor: f32,
+
+    /// Category of the aircraft in the classification scheme
+    pub category: u8,
+
+    /// Class of the aircraft in the classification scheme
+    pub class: u8,
+
+    /// Geodetic altitude of the operator in meters, -1000 if unknown
+    pub operator_altitude: f32,
+
+    /// Time of the message
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Identification of the operator
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperatorId {
+    /// The type of operator ID, 0 for an operator ID
+    pub id_type: u8,
+
+    /// The operator ID
+    pub operator_id: String,
+}
+
+/// A decoded broadcast message
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// Identification of the aircraft
+    BasicId(BasicId),
+
+    /// Position and vector of the aircraft
+    Location(Location),
+
+    /// Page of authentication data
+    Authentication(Authentication),
+
+    /// Free text description of the flight
+    SelfId(SelfId),
+
+    /// Location of the operator and area of operation
+    System(System),
+
+    /// Identification of the operator
+    OperatorId(OperatorId),
+}
+
+/// The variant of an enum with the given index, or the first (unknown)
+///  variant if the index is out of range. The variants of the REST
+///  enums are declared in the order of their encoding in ASTM F3411.
+fn nth_variant<T: IntoEnumIterator>(index: u8) -> T {
+    T::iter()
+        .nth(index as usize)
+        .or_else(|| T::iter().next())
+        .expect("enums have at least one variant")
+}
+
+/// Read a little endian u16 at the offset of a message
+fn le_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+/// Read a little endian u32 at the offset of a message
+fn le_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// Read a coordinate, encoded as a little endian i32 in 1e-7 degrees
+fn coordinate(bytes: &[u8], offset: usize) -> f64 {
+    le_u32(bytes, offset) as i32 as f64 * 1e-7
+}
+
+/// Read an altitude, encoded as a little endian u16 in half meters
+///  offset by -1000 meters
+fn altitude(bytes: &[u8], offset: usize) -> f32 {
+    le_u16(bytes, offset) as f32 * 0.5 + UNKNOWN_ALTITUDE
+}
+
+/// Read a timestamp, encoded as a little endian u32 in seconds since
+///  the Open Drone ID epoch
+fn timestamp(bytes: &[u8], offset: usize) -> Option<DateTime<Utc>> {
+    DateTime::<Utc>::from_timestamp(ODID_EPOCH_SECONDS + le_u32(bytes, offset) as i64, 0)
+}
+
+/// Read a null padded ASCII string
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches('\0')
+        .trim()
+        .to_string()
+}
+
+/// Check that the coordinates are within range
+fn valid_coordinates(latitude: f64, longitude: f64) -> Result<(), OdidError> {
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return Err(OdidError::InvalidMessage);
+    }
+
+    Ok(())
+}
+
+/// Decode a Basic ID message
+fn decode_basic_id(bytes: &[u8]) -> BasicId {
+    BasicId {
+        id_type: IdType::from_u8(bytes[1] >> 4),
+        ua_type: nth_variant(bytes[1] & 0x0F),
+        uas_id: text(&bytes[2..2 + ID_SIZE]),
+    }
+}
+
+/// Decode a Location/Vector message
+fn decode_location(bytes: &[u8]) -> Result<Location, OdidError> {
+    let flags = bytes[1];
+    let status = flags >> 4;
+    let height_reference = match flags & 0x04 {
+        0 => RIDHeightReference::TakeoffLocation,
+        _ => RIDHeightReference::GroundLevel,
+    };
+
+    let direction = match flags & 0x02 {
+        0 => bytes[2] as f32,
+        _ => bytes[2] as f32 + 180.0,
+    };
+
+    let speed = match (flags & 0x01, bytes[3]) {
+        (0, value) => value as f32 * 0.25,
+        (_, 255) => UNKNOWN_SPEED,
+        (_, value) => value as f32 * 0.75 + 255.0 * 0.25,
+    };
+
+    let latitude = coordinate(bytes, 5);
+    let longitude = coordinate(bytes, 9);
+    valid_coordinates(latitude, longitude)?;
+
+    let timestamp = match le_u16(bytes, 21) {
+        value if value <= 36_000 => Some(value),
+        _ => None,
+    };
+
+    Ok(Location {
+        // reserved values are reported as undeclared
+        status: match status {
+            0..=4 => nth_variant(status),
+            _ => RIDOperationalStatus::Undeclared,
+        },
+        height_reference,
+        direction: match direction {
+            d if d > 360.0 => UNKNOWN_DIRECTION,
+            d => d,
+        },
+        speed,
+        vertical_speed: bytes[4] as i8 as f32 * 0.5,
+        latitude,
+        longitude,
+        pressure_altitude: altitude(bytes, 13),
+        geodetic_altitude: altitude(bytes, 15),
+        height: altitude(bytes, 17),
+        accuracy_h: nth_variant(bytes[19] & 0x0F),
+        accuracy_v: nth_variant(bytes[19] >> 4),
+        accuracy_baro: nth_variant(bytes[20] >> 4),
+        accuracy_speed: nth_variant(bytes[20] & 0x0F),
+        timestamp,
+        timestamp_accuracy: (bytes[23] & 0x0F) as f32 * 0.1,
+    })
+}
+
+/// Decode an Authentication message
+fn decode_authentication(bytes: &[u8]) -> Authentication {
+    let auth_type = bytes[1] >> 4;
+    let page = bytes[1] & 0x0F;
+    if page > 0 {
+        return Authentication {
+            auth_type,
+            page,
+            last_page: None,
+            length: None,
+            timestamp: None,
+            data: bytes[2..2 + AUTH_PAGE_DATA_SIZE].to_vec(),
+        };
+    }
+
+    Authentication {
+        auth_type,
+        page,
+        last_page: Some(bytes[2] & 0x0F),
+        length: Some(bytes[3]),
+        timestamp: timestamp(bytes, 4),
+        data: bytes[8..8 + AUTH_FIRST_PAGE_DATA_SIZE].to_vec(),
+    }
+}
+
+/// Decode a Self-ID message
+fn decode_self_id(bytes: &[u8]) -> SelfId {
+    SelfId {
+        description_type: bytes[1],
+        description: text(&bytes[2..2 + DESCRIPTION_SIZE]),
+    }
+}
+
+/// Decode a System message
+fn decode_system(bytes: &[u8]) -> Result<System, OdidError> {
+    let operator_latitude = coordinate(bytes, 2);
+    let operator_longitude = coordinate(bytes, 6);
+    valid_coordinates(operator_latitude, operator_longitude)?;
+
+    let Some(timestamp) = timestamp(bytes, 20) else {
+        return Err(OdidError::InvalidMessage);
+    };
+
+    Ok(System {
+        operator_location_type: bytes[1] & 0x03,
+        classification_type: (bytes[1] >> 2) & 0x07,
+        operator_latitude,
+        operator_longitude,
+        area_count: le_u16(bytes, 10),
+        area_radius: bytes[12] as u16 * 10,
+        area_ceiling: altitude(bytes, 13),
+        area_floor: altitude(bytes, 15),
+        category: bytes[17] >> 4,
+        class: bytes[17] & 0x0F,
+        operator_altitude: altitude(bytes, 18),
+        timestamp,
+    })
+}
+
+/// Decode an Operator ID message
+fn decode_operator_id(bytes: &[u8]) -> OperatorId {
+    OperatorId {
+        id_type: bytes[1],
+        operator_id: text(&bytes[2..2 + ID_SIZE]),
+    }
+}
+
+/// The message type and protocol version of a message header
+fn header(byte: u8) -> Result<(MessageType, u8), OdidError> {
+    let version = byte & 0x0F;
+    if version > MAX_PROTOCOL_VERSION {
+        return Err(OdidError::UnsupportedVersion);
+    }
+
+    let message_type = MessageType::from_u8(byte >> 4).ok_or(OdidError::UnknownMessageType)?;
+    Ok((message_type, version))
+}
+
+/// Decode a single message of 25 bytes, message packs are not accepted
+pub fn decode_message(bytes: &[u8]) -> Result<Message, OdidError> {
+    if bytes.len() != MESSAGE_SIZE {
+        return Err(OdidError::InvalidLength);
+    }
+
+    let message = match header(bytes[0])?.0 {
+        MessageType::BasicId => Message::BasicId(decode_basic_id(bytes)),
+        MessageType::Location => Message::Location(decode_location(bytes)?),
+        MessageType::Authentication => Message::Authentication(decode_authentication(bytes)),
+        MessageType::SelfId => Message::SelfId(decode_self_id(bytes)),
+        MessageType::System => Message::System(decode_system(bytes)?),
+        MessageType::OperatorId => Message::OperatorId(decode_operator_id(bytes)),
+        MessageType::MessagePack => return Err(OdidError::InvalidMessage),
+    };
+
+    Ok(message)
+}
+
+/// Decode a frame, either a single message or a message pack.
+/// Returns the decoded messages and the size of the frame in bytes.
+pub fn decode_frame(bytes: &[u8]) -> Result<(Vec<Message>, usize), OdidError> {
+    let Some(first) = bytes.first() else {
+        return Err(OdidError::InvalidLength);
+    };
+
+    if header(*first)?.0 != MessageType::MessagePack {
+        let frame = bytes.get(..MESSAGE_SIZE).ok_or(OdidError::InvalidLength)?;
+        return Ok((vec![decode_message(frame)?], MESSAGE_SIZE));
+    }
+
+    let Some(pack) = bytes.get(..PACK_HEADER_SIZE) else {
+        return Err(OdidError::InvalidLength);
+    };
+
+    let (size, count) = (pack[1] as usize, pack[2] as usize);
+    if size != MESSAGE_SIZE || count > MAX_PACK_MESSAGES {
+        return Err(OdidError::InvalidMessage);
+    }
+
+    let length = PACK_HEADER_SIZE + count * MESSAGE_SIZE;
+    let frame = bytes
+        .get(PACK_HEADER_SIZE..length)
+        .ok_or(OdidError::InvalidLength)?;
+    let messages = frame
+        .chunks(MESSAGE_SIZE)
+        .map(decode_message)
+        .collect::<Result<Vec<Message>, OdidError>>()?;
+
+    Ok((messages, length))
+}
+
+/// Decode a sequence of frames
+pub fn decode_frames(mut bytes: &[u8]) -> Result<Vec<Message>, OdidError> {
+    let mut messages = vec![];
+    while !bytes.is_empty() {
+        let (decoded, length) = decode_frame(bytes)?;
+        messages.extend(decoded);
+        bytes = &bytes[length..];
+    }
+
+    Ok(messages)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Basic ID: serial number of a helicopter/multirotor
+    pub(crate) const BASIC_ID: [u8; MESSAGE_SIZE] = [
+        0x02, 0x12, b'1', b'5', b'9', b'6', b'F', b'3', b'5', b'4', b'6', b'S', b'N', b'0', b'0',
+        b'1', 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    /// Location: airborne at 52.3676 N, 4.9041 E, heading 270, 12.5 m/s
+    ///  climbing 1.5 m/s at 120 m, 30 m above takeoff, 12:28.5 past the
+    ///  hour
+    pub(crate) const LOCATION: [u8; MESSAGE_SIZE] = [
+        0x12, // Location, version 2
+        0x22, // airborne, above takeoff, direction >= 180, speed x0.25
+        90,   // 270 - 180
+        50,   // 12.5 / 0.25
+        3,    // 1.5 / 0.5
+        0x60, 0xA9, 0x36, 0x1F, // 523_676_000
+        0x68, 0x4E, 0xEC, 0x02, // 49_041_000
+        0xBC, 0x08, // (118 + 1000) / 0.5 = 2236
+        0xC0, 0x08, // (120 + 1000) / 0.5 = 2240
+        0x0C, 0x08, // (30 + 1000) / 0.5 = 2060
+        0x4B, // vertical < 25m, horizontal < 10m
+        0x34, // baro < 45m, speed < 1m/s
+        0x3D, 0x1D, // 7485 tenths of seconds
+        0x02, // timestamp accuracy 0.2s
+        0x00,
+    ];
+
+    /// Self-ID: text description
+    pub(crate) const SELF_ID: [u8; MESSAGE_SIZE] = [
+        0x32, 0x00, b'P', b'a', b'r', b'c', b'e', b'l', b' ', b'd', b'e', b'l', b'i', b'v', b'e',
+        b'r', b'y', 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    /// System: operator at 52.3670 N, 4.9000 E, one aircraft within 500 m,
+    ///  EU class 2 of the open category
+    pub(crate) const SYSTEM: [u8; MESSAGE_SIZE] = [
+        0x42, // System, version 2
+        0x05, // EU classification, live GNSS operator location
+        0xF0, 0x91, 0x36, 0x1F, // 523_670_000
+        0x40, 0xAE, 0xEB, 0x02, // 49_000_000
+        0x01, 0x00, // 1 aircraft
+        50,   // 500 m
+        0x10, 0x09, // (160 + 1000) / 0.5 = 2320
+        0xD0, 0x07, // (0 + 1000) / 0.5 = 2000
+        0x12, // open category, class 2
+        0xE4, 0x07, // (10 + 1000) / 0.5 = 2020
+        0x00, 0x00, 0x00, 0x10, // 268_435_456 s after 2019-01-01
+        0x00,
+    ];
+
+    /// Operator ID
+    pub(crate) const OPERATOR_ID: [u8; MESSAGE_SIZE] = [
+        0x52, 0x00, b'N', b'L', b'D', b'8', b'7', b'a', b's', b'd', b'f', b'7', b'6', b'5', b'4',
+        b'3', 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    /// Authentication: first page of a UAS ID signature over 2 pages
+    pub(crate) const AUTHENTICATION: [u8; MESSAGE_SIZE] = [
+        0x22, // Authentication, version 2
+        0x10, // UAS ID signature, page 0
+        0x01, // last page 1
+        40,   // length
+        0x80, 0x51, 0x01, 0x00, // 86_400 s after 2019-01-01
+        1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17,
+    ];
+
+    /// The location message, timestamped at the given time
+    pub(crate) fn location_at(time: DateTime<Utc>) -> [u8; MESSAGE_SIZE] {
+        let tenths =
+            time.timestamp().rem_euclid(3600) * 10 + time.timestamp_subsec_millis() as i64 / 100;
+        let mut location = LOCATION;
+        location[21..23].copy_from_slice(&(tenths as u16).to_le_bytes());
+        location
+    }
+
+    /// A message pack of the given messages
+    pub(crate) fn pack(messages: &[[u8; MESSAGE_SIZE]]) -> Vec<u8> {
+        let mut bytes = vec![0xF2, MESSAGE_SIZE as u8, messages.len() as u8];
+        for message in messages {
+            bytes.extend_from_slice(message);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_decode_basic_id() {
+        let Message::BasicId(basic_id) = decode_message(&BASIC_ID).unwrap() else {
+            panic!("expected a basic ID");
+        };
+        assert_eq!(basic_id.id_type, IdType::SerialNumber);
+        assert_eq!(basic_id.ua_type, UAType::Helicopter);
+        assert_eq!(basic_id.uas_id, "1596F3546SN001");
+
+        let mut bytes = BASIC_ID;
+        bytes[1] = 0x4F;
+        let Message::BasicId(basic_id) = decode_message(&bytes).unwrap() else {
+            panic!("expected a basic ID");
+        };
+        assert_eq!(basic_id.id_type, IdType::SpecificSession);
+        assert_eq!(basic_id.ua_type, UAType::Other);
+    }
+
+    #[test]
+    fn test_decode_location() {
+        let Message::Location(location) = decode_message(&LOCATION).unwrap() else {
+            panic!("expected a location");
+        };
+        assert_eq!(location.status, RIDOperationalStatus::Airborne);
+        assert_eq!(
+            location.height_reference,
+            RIDHeightReference::TakeoffLocation
+        );
+        assert_eq!(location.direction, 270.0);
+        assert_eq!(location.speed, 12.5);
+        assert_eq!(location.vertical_speed, 1.5);
+        assert!((location.latitude - 52.3676).abs() < 1e-9);
+        assert!((location.longitude - 4.9041).abs() < 1e-9);
+        assert_eq!(location.pressure_altitude, 118.0);
+        assert_eq!(location.geodetic_altitude, 120.0);
+        assert_eq!(location.height, 30.0);
+        assert_eq!(location.accuracy_h, HorizontalAccuracy::HA10m);
+        assert_eq!(location.accuracy_v, VerticalAccuracy::VA25m);
+        assert_eq!(location.accuracy_baro, VerticalAccuracy::VA45m);
+        assert_eq!(location.accuracy_speed, SpeedAccuracy::SA1mps);
+        assert_eq!(location.timestamp, Some(7485));
+        assert!((location.timestamp_accuracy - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_decode_location_encodings() {
+        // high speed, unknown direction, descending, above ground level
+        let mut bytes = LOCATION;
+        bytes[1] = 0x37;
+        bytes[2] = 181;
+        bytes[3] = 100;
+        bytes[4] = (-4i8) as u8;
+        bytes[21] = 0xFF;
+        bytes[22] = 0xFF;
+        let Message::Location(location) = decode_message(&bytes).unwrap() else {
+            panic!("expected a location");
+        };
+        assert_eq!(location.status, RIDOperationalStatus::Emergency);
+        assert_eq!(location.height_reference, RIDHeightReference::GroundLevel);
+        assert_eq!(location.direction, UNKNOWN_DIRECTION);
+        assert_eq!(location.speed, 100.0 * 0.75 + 63.75);
+        assert_eq!(location.vertical_speed, -2.0);
+        assert!(location.timestamp.is_none());
+
+        // unknown speed and altitudes, reserved status and accuracies
+        bytes[1] = 0xF1;
+        bytes[3] = 255;
+        bytes[13..19].fill(0);
+        bytes[19] = 0xFF;
+        bytes[20] = 0xFF;
+        let Message::Location(location) = decode_message(&bytes).unwrap() else {
+            panic!("expected a location");
+        };
+        assert_eq!(location.status, RIDOperationalStatus::Undeclared);
+        assert_eq!(location.speed, UNKNOWN_SPEED);
+        assert_eq!(location.geodetic_altitude, UNKNOWN_ALTITUDE);
+        assert_eq!(location.accuracy_h, HorizontalAccuracy::HAUnknown);
+        assert_eq!(location.accuracy_v, VerticalAccuracy::VAUnknown);
+        assert_eq!(location.accuracy_speed, SpeedAccuracy::SAUnknown);
+
+        // latitude out of range
+        let mut bytes = LOCATION;
+        bytes[5..9].copy_from_slice(&950_000_000i32.to_le_bytes());
+        assert_eq!(
+            decode_message(&bytes).unwrap_err(),
+            OdidError::InvalidMessage
+        );
+    }
+
+    #[test]
+    fn test_decode_authentication() {
+        let Message::Authentication(auth) = decode_message(&AUTHENTICATION).unwrap() else {
+            panic!("expected an authentication");
+        };
+        assert_eq!(auth.auth_type, 1);
+        assert_eq!(auth.page, 0);
+        assert_eq!(auth.last_page, Some(1));
+        assert_eq!(auth.length, Some(40));
+        assert_eq!(
+            auth.timestamp.unwrap().to_rfc3339(),
+            "2019-01-02T00:00:00+00:00"
+        );
+        assert_eq!(auth.data.len(), AUTH_FIRST_PAGE_DATA_SIZE);
+        assert_eq!(auth.data[16], 17);
+
+        let mut bytes = AUTHENTICATION;
+        bytes[1] = 0x11;
+        let Message::Authentication(auth) = decode_message(&bytes).unwrap() else {
+            panic!("expected an authentication");
+        };
+        assert_eq!(auth.page, 1);
+        assert!(auth.last_page.is_none() && auth.timestamp.is_none());
+        assert_eq!(auth.data.len(), AUTH_PAGE_DATA_SIZE);
+        assert_eq!(auth.data[0], 0x01);
+    }
+
+    #[test]
+    fn test_decode_self_id_operator_id() {
+        let Message::SelfId(self_id) = decode_message(&SELF_ID).unwrap() else {
+            panic!("expected a self ID");
+        };
+        assert_eq!(self_id.description_type, 0);
+        assert_eq!(self_id.description, "Parcel delivery");
+
+        let Message::OperatorId(operator_id) = decode_message(&OPERATOR_ID).unwrap() else {
+            panic!("expected an operator ID");
+        };
+        assert_eq!(operator_id.id_type, 0);
+        assert_eq!(operator_id.operator_id, "NLD87asdf76543");
+    }
+
+    #[test]
+    fn test_decode_system() {
+        let Message::System(system) = decode_message(&SYSTEM).unwrap() else {
+            panic!("expected a system message");
+        };
+        assert_eq!(system.operator_location_type, 1);
+        assert_eq!(system.classification_type, 1);
+        assert!((system.operator_latitude - 52.367).abs() < 1e-9);
+        assert!((system.operator_longitude - 4.9).abs() < 1e-9);
+        assert_eq!(system.area_count, 1);
+        assert_eq!(system.area_radius, 500);
+        assert_eq!(system.area_ceiling, 160.0);
+        assert_eq!(system.area_floor, 0.0);
+        assert_eq!((system.category, system.class), (1, 2));
+        assert_eq!(system.operator_altitude, 10.0);
+        assert_eq!(
+            system.timestamp.timestamp(),
+            ODID_EPOCH_SECONDS + 268_435_456
+        );
+    }
+
+    #[test]
+    fn test_decode_frames() {
+        let bytes = pack(&[BASIC_ID, LOCATION, SYSTEM]);
+        let (messages, length) = decode_frame(&bytes).unwrap();
+        assert_eq!(length, PACK_HEADER_SIZE + 3 * MESSAGE_SIZE);
+        assert_eq!(messages.len(), 3);
+        assert!(matches!(messages[1], Message::Location(_)));
+
+        let mut bytes = pack(&[BASIC_ID]);
+        bytes.extend_from_slice(&OPERATOR_ID);
+        bytes.extend(pack(&[SELF_ID, AUTHENTICATION]));
+        let messages = decode_frames(&bytes).unwrap();
+        assert_eq!(messages.len(), 4);
+        assert!(matches!(messages[1], Message::OperatorId(_)));
+        assert!(matches!(messages[3], Message::Authentication(_)));
+
+        assert!(decode_frames(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            decode_message(&BASIC_ID[..24]).unwrap_err(),
+            OdidError::InvalidLength
+        );
+        assert_eq!(
+            decode_frames(&LOCATION[..20]).unwrap_err(),
+            OdidError::InvalidLength
+        );
+
+        let mut bytes = BASIC_ID;
+        bytes[0] = 0x62;
+        assert_eq!(
+            decode_message(&bytes).unwrap_err(),
+            OdidError::UnknownMessageType
+        );
+
+        bytes[0] = 0x03;
+        assert_eq!(
+            decode_message(&bytes).unwrap_err(),
+            OdidError::UnsupportedVersion
+        );
+
+        // nested message pack
+        let mut nested = [0u8; MESSAGE_SIZE];
+        nested[0] = 0xF2;
+        assert_eq!(
+            decode_frame(&pack(&[nested])).unwrap_err(),
+            OdidError::InvalidMessage
+        );
+
+        // truncated or oversized message pack
+        let bytes = pack(&[BASIC_ID, LOCATION]);
+        assert_eq!(
+            decode_frame(&bytes[..40]).unwrap_err(),
+            OdidError::InvalidLength
+        );
+        let mut bytes = pack(&[BASIC_ID; 10]);
+        assert_eq!(decode_frame(&bytes).unwrap_err(), OdidError::InvalidMessage);
+        bytes[1] = 24;
+        bytes[2] = 1;
+        assert_eq!(decode_frame(&bytes).unwrap_err(), OdidError::InvalidMessage);
+    }
+}
@@ -0,0 +1,594 @@
+//! Encoder of Open Drone ID broadcast messages
+//! Turns our flights into the 25 byte messages of ASTM F3411 Broadcast
+//! Remote ID, so ground stations can re-broadcast them.
+
+use super::decoder::*;
+use super::OdidError;
+use crate::rest::api::rest_types::*;
+use lib_common::time::{DateTime, Utc};
+use strum::IntoEnumIterator;
+
+/// Protocol version of the encoded messages (ASTM F3411-22a)
+pub const PROTOCOL_VERSION: u8 = MAX_PROTOCOL_VERSION;
+
+/// Highest speed encoded with the fine resolution, in meters per second
+const FINE_SPEED_LIMIT: f32 = 255.0 * 0.25;
+
+/// Highest encodable speed, in meters per second
+const MAX_SPEED: f32 = 254.0 * 0.75 + FINE_SPEED_LIMIT;
+
+/// Highest encodable vertical speed, in meters per second
+const MAX_VERTICAL_SPEED: f32 = 62.0;
+
+/// Highest encodable altitude, in meters
+const MAX_ALTITUDE: f32 = u16::MAX as f32 * 0.5 + UNKNOWN_ALTITUDE;
+
+/// Details of a flight that are broadcast but not part of a [`RIDFlight`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BroadcastDetails {
+    /// The type of the flight ID
+    pub id_type: IdType,
+
+    /// Free text description of the flight
+    pub description: Option<String>,
+
+    /// Identification of the operator
+    pub operator_id: Option<String>,
+
+    /// Location of the operator and area of operation
+    pub system: Option<System>,
+}
+
+impl Default for BroadcastDetails {
+    fn default() -> Self {
+        BroadcastDetails {
+            id_type: IdType::SpecificSession,
+            description: None,
+            operator_id: None,
+            system: None,
+        }
+    }
+}
+
+/// The index of an enum variant, the variants of the REST enums are
+///  declared in the order of their encoding in ASTM F3411
+fn variant_index<T: IntoEnumIterator + PartialEq>(value: T) -> u8 {
+    T::iter().position(|v| v == value).unwrap_or(0) as u8
+}
+
+/// A message with its header
+fn message(message_type: MessageType) -> [u8; MESSAGE_SIZE] {
+    let mut bytes = [0u8; MESSAGE_SIZE];
+    bytes[0] = (message_type as u8) << 4 | PROTOCOL_VERSION;
+    bytes
+}
+
+/// Write a null padded ASCII string, strings longer than the field are
+///  rejected rather than truncated into another identity
+fn write_text(field: &mut [u8], value: &str) -> Result<(), OdidError> {
+    if !value.is_ascii() || value.len() > field.len() {
+        return Err(OdidError::InvalidMessage);
+    }
+
+    field[..value.len()].copy_from_slice(value.as_bytes());
+    Ok(())
+}
+
+/// Write a coordinate as a little endian i32 in 1e-7 degrees
+fn write_coordinate(field: &mut [u8], degrees: f64) {
+    field.copy_from_slice(&((degrees * 1e7).round() as i32).to_le_bytes());
+}
+
+/// Write an altitude as a little endian u16 in half meters offset by
+///  -1000 meters, out of range altitudes are clamped
+fn write_altitude(field: &mut [u8], meters: f32) {
+    let meters = meters.clamp(UNKNOWN_ALTITUDE, MAX_ALTITUDE);
+    field.copy_from_slice(&(((meters - UNKNOWN_ALTITUDE) / 0.5).round() as u16).to_le_bytes());
+}
+
+/// Write a time as a little endian u32 in seconds since the Open Drone
+///  ID epoch
+fn write_timestamp(field: &mut [u8], time: DateTime<Utc>) {
+    let seconds = (time.timestamp() - ODID_EPOCH_SECONDS).clamp(0, u32::MAX as i64);
+    field.copy_from_slice(&(seconds as u32).to_le_bytes());
+}
+
+/// Check that the coordinates are within range
+fn check_coordinates(latitude: f64, longitude: f64) -> Result<(), OdidError> {
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return Err(OdidError::InvalidMessage);
+    }
+
+    Ok(())
+}
+
+/// Encode a Basic ID message
+pub fn encode_basic_id(basic_id: &BasicId) -> Result<[u8; MESSAGE_SIZE], OdidError> {
+    let mut bytes = message(MessageType::BasicId);
+    bytes[1] = (basic_id.id_type as u8) << 4 | variant_index(basic_id.ua_type);
+    write_text(&mut bytes[2..2 + ID_SIZE], &basic_id.uas_id)?;
+    Ok(bytes)
+}
+
+/// Encode a Location/Vector message
+pub fn encode_location(location: &Location) -> Result<[u8; MESSAGE_SIZE], OdidError> {
+    check_coordinates(location.latitude, location.longitude)?;
+
+    let mut bytes = message(MessageType::Location);
+    let mut flags = variant_index(location.status) << 4;
+    if location.height_reference == RIDHeightReference::GroundLevel {
+        flags |= 0x04;
+    }
+
+    let direction = match location.direction {
+        d if (0.0..=360.0).contains(&d) => (d.round() as u16) % 360,
+        _ => UNKNOWN_DIRECTION as u16,
+    };
+    bytes[2] = match direction {
+        d if d >= 180 => {
+            flags |= 0x02;
+            (d - 180) as u8
+        }
+        d => d as u8,
+    };
+
+    bytes[3] = match location.speed {
+        s if !(0.0..UNKNOWN_SPEED).contains(&s) => {
+            flags |= 0x01;
+            255
+        }
+        s if s <= FINE_SPEED_LIMIT => (s / 0.25).round() as u8,
+        s => {
+            flags |= 0x01;
+            ((s.min(MAX_SPEED) - FINE_SPEED_LIMIT) / 0.75).round() as u8
+        }
+    };
+    bytes[1] = flags;
+
+    let vertical_speed = match location.vertical_speed {
+        v if v.is_nan() || v >= UNKNOWN_VERTICAL_SPEED => UNKNOWN_VERTICAL_SPEED,
+        v => v.clamp(-MAX_VERTICAL_SPEED, MAX_VERTICAL_SPEED),
+    };
+    bytes[4] = ((vertical_speed / 0.5).round() as i8) as u8;
+
+    write_coordinate(&mut bytes[5..9], location.latitude);
+    write_coordinate(&mut bytes[9..13], location.longitude);
+    write_altitude(&mut bytes[13..15], location.pressure_altitude);
+    write_altitude(&mut bytes[15..17], location.geodetic_altitude);
+    write_altitude(&mut bytes[17..19], location.height);
+    bytes[19] = variant_index(location.accuracy_v) << 4 | variant_index(location.accuracy_h);
+    bytes[20] = variant_index(location.accuracy_baro) << 4 | variant_index(location.accuracy_speed);
+
+    let timestamp = location
+        .timestamp
+        .filter(|tenths| *tenths <= 36_000)
+        .unwrap_or(UNKNOWN_TIMESTAMP);
+    bytes[21..23].copy_from_slice(&timestamp.to_le_bytes());
+    bytes[23] = (location.timestamp_accuracy / 0.1).round().clamp(0.0, 15.0) as u8;
+    Ok(bytes)
+}
+
+/// Encode an Authentication message
+pub fn encode_authentication(
+    authentication: &Authentication,
+) -> Result<[u8; MESSAGE_SIZE], OdidError> {
+    let mut bytes = message(MessageType::Authentication);
+    bytes[1] = (authentication.auth_type & 0x0F) << 4 | (authentication.page & 0x0F);
+    let data = match authentication.page {
+        0 => {
+            bytes[2] = authentication.last_page.unwrap_or(0) & 0x0F;
+            bytes[3] = authentication.length.unwrap_or(0);
+            if let Some(timestamp) = authentication.timestamp {
+                write_timestamp(&mut bytes[4..8], timestamp);
+            }
+            &mut bytes[8..8 + AUTH_FIRST_PAGE_DATA_SIZE]
+        }
+        _ => &mut bytes[2..2 + AUTH_PAGE_DATA_SIZE],
+    };
+
+    if authentication.data.len() > data.len() {
+        return Err(OdidError::InvalidLength);
+    }
+    data[..authentication.data.len()].copy_from_slice(&authentication.data);
+    Ok(bytes)
+}
+
+/// Encode a Self-ID message
+pub fn encode_self_id(self_id: &SelfId) -> Result<[u8; MESSAGE_SIZE], OdidError> {
+    let mut bytes = message(MessageType::SelfId);
+    bytes[1] = self_id.description_type;
+    write_text(&mut bytes[2..2 + DESCRIPTION_SIZE], &self_id.description)?;
+    Ok(bytes)
+}
+
+/// Encode a System message
+pub fn encode_system(system: &System) -> Result<[u8; MESSAGE_SIZE], OdidError> {
+    check_coordinates(system.operator_latitude, system.operator_longitude)?;
+
+    let mut bytes = message(MessageType::System);
+    bytes[1] = (system.classification_type & 0x07) << 2 | (system.operator_location_type & 0x03);
+    write_coordinate(&mut bytes[2..6], system.operator_latitude);
+    write_coordinate(&mut bytes[6..10], system.operator_longitude);
+    bytes[10..12].copy_from_slice(&system.area_count.to_le_bytes());
+    bytes[12] = (system.area_radius / 10).min(u8::MAX as u16) as u8;
+    write_altitude(&mut bytes[13..15], system.area_ceiling);
+    write_altitude(&mut bytes[15..17], system.area_floor);
+    bytes[17] = (system.category & 0x0F) << 4 | (system.class & 0x0F);
+    write_altitude(&mut bytes[18..20], system.operator_altitude);
+    write_timestamp(&mut bytes[20..24], system.timestamp);
+    Ok(bytes)
+}
+
+/// Encode an Operator ID message
+pub fn encode_operator_id(operator_id: &OperatorId) -> Result<[u8; MESSAGE_SIZE], OdidError> {
+    let mut bytes = message(MessageType::OperatorId);
+    bytes[1] = operator_id.id_type;
+    write_text(&mut bytes[2..2 + ID_SIZE], &operator_id.operator_id)?;
+    Ok(bytes)
+}
+
+/// Encode a single message
+pub fn encode_message(message: &Message) -> Result<[u8; MESSAGE_SIZE], OdidError> {
+    match message {
+        Message::BasicId(basic_id) => encode_basic_id(basic_id),
+        Message::Location(location) => encode_location(location),
+        Message::Authentication(authentication) => encode_authentication(authentication),
+        Message::SelfId(self_id) => encode_self_id(self_id),
+        Message::System(system) => encode_system(system),
+        Message::OperatorId(operator_id) => encode_operator_id(operator_id),
+    }
+}
+
+/// Encode messages into a message pack
+pub fn encode_pack(messages: &[Message]) -> Result<Vec<u8>, OdidError> {
+    if messages.len() > MAX_PACK_MESSAGES {
+        return Err(OdidError::InvalidLength);
+    }
+
+    let mut bytes = Vec::with_capacity(PACK_HEADER_SIZE + messages.len() * MESSAGE_SIZE);
+    bytes.push((MessageType::MessagePack as u8) << 4 | PROTOCOL_VERSION);
+    bytes.push(MESSAGE_SIZE as u8);
+    bytes.push(messages.len() as u8);
+    for message in messages {
+        bytes.extend_from_slice(&encode_message(message)?);
+    }
+
+    Ok(bytes)
+}
+
+/// The location message of the current state of a flight
+pub fn location(state: &RIDAircraftState) -> Location {
+    // tenths of seconds since the full hour
+    let timestamp = state.timestamp.to_datetime().map(|time| {
+        (time.timestamp().rem_euclid(3600) * 10) as u16
+            + (time.timestamp_subsec_millis() / 100) as u16
+    });
+
+    Location {
+        status: state.operational_status,
+        height_reference: state.position.height.reference,
+        direction: state.track,
+        speed: state.speed,
+        vertical_speed: state.vertical_speed,
+        latitude: state.position.lat,
+        longitude: state.position.lng,
+        pressure_altitude: state.position.pressure_alt,
+        geodetic_altitude: state.position.alt,
+        height: state.position.height.distance,
+        accuracy_h: state.position.accuracy_h,
+        accuracy_v: state.position.accuracy_v,
+        accuracy_baro: state.position.accuracy_v,
+        accuracy_speed: state.speed_accuracy,
+        timestamp,
+        timestamp_accuracy: state.timestamp_accuracy,
+    }
+}
+
+/// The messages broadcast for a flight
+pub fn flight_messages(flight: &RIDFlight, details: &BroadcastDetails) -> Vec<Message> {
+    let mut messages = vec![
+        Message::BasicId(BasicId {
+            id_type: details.id_type,
+            ua_type: flight.aircraft_type,
+            uas_id: flight.id.clone(),
+        }),
+        Message::Location(location(&flight.current_state)),
+    ];
+
+    if let Some(description) = &details.description {
+        messages.push(Message::SelfId(SelfId {
+            description_type: 0,
+            description: description.clone(),
+        }));
+    }
+
+    if let Some(system) = details.system {
+        messages.push(Message::System(system));
+    }
+
+    if let Some(operator_id) = &details.operator_id {
+        messages.push(Message::OperatorId(OperatorId {
+            id_type: 0,
+            operator_id: operator_id.clone(),
+        }));
+    }
+
+    messages
+}
+
+/// Encode a flight into a message pack
+pub fn encode_flight(flight: &RIDFlight, details: &BroadcastDetails) -> Result<Vec<u8>, OdidError> {
+    encode_pack(&flight_messages(flight, details))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::decoder::tests::*;
+    use super::*;
+
+    /// Decode a single message, checking the header
+    fn decode(bytes: [u8; MESSAGE_SIZE]) -> Message {
+        assert_eq!(bytes[0] & 0x0F, PROTOCOL_VERSION);
+        decode_message(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_encode_known_vectors() {
+        for vector in [
+            BASIC_ID,
+            LOCATION,
+            AUTHENTICATION,
+            SELF_ID,
+            SYSTEM,
+            OPERATOR_ID,
+        ] {
+            let message = decode_message(&vector).unwrap();
+            assert_eq!(encode_message(&message).unwrap(), vector);
+        }
+
+        let bytes = pack(&[BASIC_ID, LOCATION, SYSTEM]);
+        let (messages, _) = decode_frame(&bytes).unwrap();
+        assert_eq!(encode_pack(&messages).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_encode_ua_types() {
+        for ua_type in UAType::iter() {
+            let basic_id = BasicId {
+                id_type: IdType::UtmAssigned,
+                ua_type,
+                uas_id: "flight".to_string(),
+            };
+            let message = decode(encode_basic_id(&basic_id).unwrap());
+            assert_eq!(message, Message::BasicId(basic_id));
+        }
+    }
+
+    #[test]
+    fn test_encode_accuracies() {
+        let Message::Location(base) = decode_message(&LOCATION).unwrap() else {
+            panic!("expected a location");
+        };
+
+        for accuracy_h in HorizontalAccuracy::iter() {
+            let location = Location { accuracy_h, ..base };
+            let message = decode(encode_location(&location).unwrap());
+            assert_eq!(message, Message::Location(location));
+        }
+
+        for accuracy_v in VerticalAccuracy::iter() {
+            let location = Location {
+                accuracy_v,
+                accuracy_baro: accuracy_v,
+                ..base
+            };
+            let message = decode(encode_location(&location).unwrap());
+            assert_eq!(message, Message::Location(location));
+        }
+
+        for accuracy_speed in SpeedAccuracy::iter() {
+            let location = Location {
+                accuracy_speed,
+                ..base
+            };
+            let message = decode(encode_location(&location).unwrap());
+            assert_eq!(message, Message::Location(location));
+        }
+
+        for status in RIDOperationalStatus::iter() {
+            let location = Location { status, ..base };
+            let message = decode(encode_location(&location).unwrap());
+            assert_eq!(message, Message::Location(location));
+        }
+    }
+
+    #[test]
+    fn test_encode_location_scaling() {
+        let Message::Location(base) = decode_message(&LOCATION).unwrap() else {
+            panic!("expected a location");
+        };
+
+        let round_trip = |location: Location| {
+            let Message::Location(decoded) = decode(encode_location(&location).unwrap()) else {
+                panic!("expected a location");
+            };
+            decoded
+        };
+
+        // speed resolution of 0.25 m/s up to 63.75 m/s, 0.75 m/s above
+        for (speed, expected) in [
+            (0.0, 0.0),
+            (12.6, 12.5),
+            (63.75, 63.75),
+            (100.0, 99.75),
+            (254.9, MAX_SPEED),
+            (300.0, UNKNOWN_SPEED),
+            (-1.0, UNKNOWN_SPEED),
+            (UNKNOWN_SPEED, UNKNOWN_SPEED),
+        ] {
+            assert_eq!(round_trip(Location { speed, ..base }).speed, expected);
+        }
+
+        for (direction, expected) in [
+            (0.0, 0.0),
+            (179.6, 180.0),
+            (359.7, 0.0),
+            (360.0, 0.0),
+            (UNKNOWN_DIRECTION, UNKNOWN_DIRECTION),
+            (-5.0, UNKNOWN_DIRECTION),
+        ] {
+            let location = Location { direction, ..base };
+            assert_eq!(round_trip(location).direction, expected);
+        }
+
+        for (vertical_speed, expected) in [
+            (-3.3, -3.5),
+            (70.0, UNKNOWN_VERTICAL_SPEED),
+            (62.5, 62.0),
+            (-70.0, -62.0),
+        ] {
+            let location = Location {
+                vertical_speed,
+                ..base
+            };
+            assert_eq!(round_trip(location).vertical_speed, expected);
+        }
+
+        let location = Location {
+            latitude: -33.8688197,
+            longitude: 151.2092955,
+            geodetic_altitude: -2000.0,
+            pressure_altitude: 40_000.0,
+            height: 12.3,
+            height_reference: RIDHeightReference::GroundLevel,
+            timestamp: None,
+            timestamp_accuracy: 1.5,
+            ..base
+        };
+        let decoded = round_trip(location);
+        assert!((decoded.latitude - location.latitude).abs() < 1e-7);
+        assert!((decoded.longitude - location.longitude).abs() < 1e-7);
+        assert_eq!(decoded.geodetic_altitude, UNKNOWN_ALTITUDE);
+        assert_eq!(decoded.pressure_altitude, MAX_ALTITUDE);
+        assert_eq!(decoded.height, 12.5);
+        assert_eq!(decoded.height_reference, RIDHeightReference::GroundLevel);
+        assert!(decoded.timestamp.is_none());
+        assert_eq!(decoded.timestamp_accuracy, 1.5);
+
+        let location = Location {
+            latitude: 91.0,
+            ..base
+        };
+        assert_eq!(
+            encode_location(&location).unwrap_err(),
+            OdidError::InvalidMessage
+        );
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        let basic_id = BasicId {
+            id_type: IdType::SerialNumber,
+            ua_type: UAType::Aeroplane,
+            uas_id: "vliegtuig-ë".to_string(),
+        };
+        assert_eq!(
+            encode_basic_id(&basic_id).unwrap_err(),
+            OdidError::InvalidMessage
+        );
+
+        let basic_id = BasicId {
+            uas_id: "a".repeat(ID_SIZE + 1),
+            ..basic_id
+        };
+        assert_eq!(
+            encode_basic_id(&basic_id).unwrap_err(),
+            OdidError::InvalidMessage
+        );
+        let basic_id = BasicId {
+            uas_id: "a".repeat(ID_SIZE),
+            ..basic_id
+        };
+        assert!(encode_basic_id(&basic_id).is_ok());
+
+        let operator_id = OperatorId {
+            id_type: 0,
+            operator_id: "NLD".repeat(ID_SIZE),
+        };
+        assert_eq!(
+            encode_operator_id(&operator_id).unwrap_err(),
+            OdidError::InvalidMessage
+        );
+
+        let Message::Authentication(mut auth) = decode_message(&AUTHENTICATION).unwrap() else {
+            panic!("expected an authentication");
+        };
+        auth.data = vec![0; AUTH_FIRST_PAGE_DATA_SIZE + 1];
+        assert_eq!(
+            encode_authentication(&auth).unwrap_err(),
+            OdidError::InvalidLength
+        );
+
+        let messages = vec![decode_message(&BASIC_ID).unwrap(); MAX_PACK_MESSAGES + 1];
+        assert_eq!(
+            encode_pack(&messages).unwrap_err(),
+            OdidError::InvalidLength
+        );
+    }
+
+    #[test]
+    fn test_encode_flight() {
+        use crate::odid::store::BroadcastFlights;
+        use crate::rest::api::uss::Window;
+
+        let now = Utc::now();
+        let store = BroadcastFlights::default();
+        let messages = decode_frames(&pack(&[BASIC_ID, location_at(now)])).unwrap();
+        store.ingest("aa:bb", messages, now);
+        let window = Window {
+            lon1: 4.9,
+            lat1: 52.36,
+            lon2: 4.91,
+            lat2: 52.37,
+        };
+        let flight = store.flights_in(&window, 0.0, now).remove(0);
+
+        let Message::System(system) = decode_message(&SYSTEM).unwrap() else {
+            panic!("expected a system message");
+        };
+        let details = BroadcastDetails {
+            id_type: IdType::SerialNumber,
+            description: Some("Parcel delivery".to_string()),
+            operator_id: Some("NLD87asdf76543".to_string()),
+            system: Some(system),
+        };
+        let bytes = encode_flight(&flight, &details).unwrap();
+        assert_eq!(bytes.len(), PACK_HEADER_SIZE + 5 * MESSAGE_SIZE);
+
+        // the frames of a flight decode back into the same flight
+        let store = BroadcastFlights::default();
+        let id = store.ingest("cc:dd", decode_frames(&bytes).unwrap(), now);
+        assert_eq!(id, flight.id);
+        let decoded = store.flights_in(&window, 0.0, now).remove(0);
+        assert_eq!(decoded.aircraft_type, flight.aircraft_type);
+        assert_eq!(
+            decoded.current_state.timestamp.value,
+            flight.current_state.timestamp.value
+        );
+        assert_eq!(decoded.current_state.track, flight.current_state.track);
+        assert_eq!(decoded.operating_area.aircraft_count, 1);
+
+        let aircraft = store.aircraft("cc:dd").unwrap();
+        assert_eq!(aircraft.self_id.unwrap().description, "Parcel delivery");
+        assert_eq!(aircraft.system, Some(system));
+
+        // without details only the basic ID and location are broadcast
+        let bytes = encode_flight(&flight, &BroadcastDetails::default()).unwrap();
+        let messages = decode_frames(&bytes).unwrap();
+        assert_eq!(messages.len(), 2);
+        let Message::BasicId(basic_id) = &messages[0] else {
+            panic!("expected a basic ID");
+        };
+        assert_eq!(basic_id.id_type, IdType::SpecificSession);
+    }
+}
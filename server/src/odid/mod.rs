@@ -0,0 +1,178 @@
+//! Open Drone ID
+//! Decodes ASTM F3411 Broadcast Remote ID messages picked up by receivers,
+//! so broadcasting aircraft are reported with the network flights.
+//!
+//! Receivers push frames to the REST API, or send them as UDP datagrams
+//! made of the 6 byte address of the transmitter followed by one or more
+//! frames, a frame being a single message or a message pack.
+//!
+//! The encoder turns reported flights back into message packs, for
+//! ground stations re-broadcasting network flights.
+
+pub mod decoder;
+pub mod encoder;
+pub mod store;
+
+use decoder::decode_frames;
+use lib_common::time::{DateTime, Utc};
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use store::BroadcastFlights;
+use tokio::sync::OnceCell;
+
+/// Size of the transmitter address at the start of a UDP datagram
+pub const TRANSMITTER_ADDRESS_SIZE: usize = 6;
+
+/// Maximum size of a UDP datagram
+const MAX_DATAGRAM_SIZE: usize = 1500;
+
+/// Errors with Open Drone ID messages
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OdidError {
+    /// The message or message pack is truncated or too long
+    InvalidLength,
+
+    /// The message type is not defined
+    UnknownMessageType,
+
+    /// The protocol version is not supported
+    UnsupportedVersion,
+
+    /// The message contains invalid values
+    InvalidMessage,
+}
+
+impl std::error::Error for OdidError {}
+
+impl Display for OdidError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            OdidError::InvalidLength => write!(f, "Invalid message length"),
+            OdidError::UnknownMessageType => write!(f, "Unknown message type"),
+            OdidError::UnsupportedVersion => write!(f, "Unsupported protocol version"),
+            OdidError::InvalidMessage => write!(f, "Invalid message"),
+        }
+    }
+}
+
+pub(crate) static BROADCAST_FLIGHTS: OnceCell<Arc<BroadcastFlights>> = OnceCell::const_new();
+
+/// Returns the aircraft received over Broadcast Remote ID, shared by the
+///  REST server and the UDP listener.
+/// Initializes the storage if it hasn't been initialized yet.
+pub async fn get_broadcast_flights() -> Arc<BroadcastFlights> {
+    BROADCAST_FLIGHTS
+        .get_or_init(|| async move { Arc::new(BroadcastFlights::default()) })
+        .await
+        .clone()
+}
+
+/// Ingest a UDP datagram of a receiver.
+/// Returns the ID of the flight of the transmitter.
+pub fn ingest_datagram(
+    store: &BroadcastFlights,
+    datagram: &[u8],
+    now: DateTime<Utc>,
+) -> Result<String, OdidError> {
+    if datagram.len() <= TRANSMITTER_ADDRESS_SIZE {
+        return Err(OdidError::InvalidLength);
+    }
+
+    let (address, frames) = datagram.split_at(TRANSMITTER_ADDRESS_SIZE);
+    let transmitter = address
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<String>>()
+        .join(":");
+
+    let messages = decode_frames(frames)?;
+    Ok(store.ingest(&transmitter, messages, now))
+}
+
+/// Receive datagrams on the UDP port, forever
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) endless loop, ingest_datagram is unit tested
+pub async fn listen_udp(store: Arc<BroadcastFlights>, port: u16) {
+    let socket = match tokio::net::UdpSocket::bind(("0.0.0.0", port)).await {
+        Ok(socket) => socket,
+        Err(e) => {
+            rest_error!("could not bind Open Drone ID UDP port {}: {}", port, e);
+            return;
+        }
+    };
+
+    rest_info!("listening for Open Drone ID frames on UDP port {}.", port);
+    let mut buffer = [0u8; MAX_DATAGRAM_SIZE];
+    loop {
+        let (length, receiver) = match socket.recv_from(&mut buffer).await {
+            Ok(received) => received,
+            Err(e) => {
+                rest_warn!("could not receive Open Drone ID datagram: {}", e);
+                continue;
+            }
+        };
+
+        if let Err(e) = ingest_datagram(&store, &buffer[..length], Utc::now()) {
+            rest_warn!("invalid Open Drone ID datagram from {}: {}", receiver, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::uss::Window;
+    use decoder::tests::{location_at, pack, BASIC_ID};
+
+    #[tokio::test]
+    async fn test_get_broadcast_flights() {
+        let a = get_broadcast_flights().await;
+        let b = get_broadcast_flights().await;
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            OdidError::UnknownMessageType.to_string(),
+            "Unknown message type"
+        );
+        assert_eq!(OdidError::InvalidMessage.to_string(), "Invalid message");
+    }
+
+    #[test]
+    fn test_ingest_datagram() {
+        let now = Utc::now();
+        let store = BroadcastFlights::default();
+
+        let mut datagram = vec![0x02, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E];
+        datagram.extend(pack(&[location_at(now)]));
+        assert_eq!(
+            ingest_datagram(&store, &datagram, now).unwrap(),
+            "02:1a:2b:3c:4d:5e"
+        );
+        assert!(store.aircraft("02:1a:2b:3c:4d:5e").is_some());
+
+        datagram.extend_from_slice(&BASIC_ID);
+        assert_eq!(
+            ingest_datagram(&store, &datagram, now).unwrap(),
+            "1596F3546SN001"
+        );
+        let window = Window {
+            lon1: 4.9,
+            lat1: 52.36,
+            lon2: 4.91,
+            lat2: 52.37,
+        };
+        assert_eq!(store.flights_in(&window, 0.0, now).len(), 1);
+
+        assert_eq!(
+            ingest_datagram(&store, &datagram[..6], now).unwrap_err(),
+            OdidError::InvalidLength
+        );
+        assert_eq!(
+            ingest_datagram(&store, &datagram[..20], now).unwrap_err(),
+            OdidError::InvalidLength
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! Aircraft received over Broadcast Remote ID
+//! Messages are collected per transmitter, as a single message only
+//! describes part of an aircraft, and turned into flights once a location
+//! was received.
+
+use super::decoder::{
+    Authentication, BasicId, IdType, Location, Message, OperatorId, SelfId, System,
+};
+use crate::rest::api::rest_types::*;
+use crate::rest::api::uss::Window;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+
+/// Aircraft that were not received for this long are no longer reported
+///  as flights, in seconds
+pub const BROADCAST_TIMEOUT_SECONDS: i64 = 10;
+
+/// Positions older than this are dropped, in seconds
+pub const MAX_POSITION_AGE_SECONDS: i64 = 60;
+
+/// Maximum number of positions kept per aircraft
+pub const MAX_POSITIONS: usize = 600;
+
+/// Preferred ID types to identify a flight, most preferred first
+const FLIGHT_ID_TYPES: [IdType; 4] = [
+    IdType::SpecificSession,
+    IdType::UtmAssigned,
+    IdType::SerialNumber,
+    IdType::CaaRegistration,
+];
+
+/// A location with the time it was received
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ReceivedLocation {
+    /// Time of the location, from the message timestamp if known
+    time: DateTime<Utc>,
+
+    /// The decoded location
+    location: Location,
+}
+
+/// The latest messages received from a transmitter
+#[derive(Debug, Clone, Default)]
+pub struct Aircraft {
+    /// Basic IDs by ID type, an aircraft may broadcast several IDs
+    pub basic_ids: BTreeMap<IdType, BasicId>,
+
+    /// Recent locations, oldest first
+    locations: VecDeque<ReceivedLocation>,
+
+    /// Authentication pages by page number
+    pub authentication: BTreeMap<u8, Authentication>,
+
+    /// The latest self-ID
+    pub self_id: Option<SelfId>,
+
+    /// The latest system message
+    pub system: Option<System>,
+
+    /// The latest operator ID
+    pub operator_id: Option<OperatorId>,
+
+    /// Time the latest message was received
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// Resolve a timestamp in tenths of seconds since the full hour to the
+///  time closest to the time it was received
+fn resolve_timestamp(tenths: u16, received: DateTime<Utc>) -> DateTime<Utc> {
+    let hour = received.timestamp() - received.timestamp().rem_euclid(3600);
+    let millis = tenths as i64 * 100;
+    [hour - 3600, hour, hour + 3600]
+        .into_iter()
+        .filter_map(|start| DateTime::<Utc>::from_timestamp(start, 0))
+        .map(|start| start + Duration::milliseconds(millis))
+        .min_by_key(|time| (*time - received).num_milliseconds().abs())
+        .unwrap_or(received)
+}
+
+/// The REST position of a location
+fn position(location: &Location) -> RIDAircraftPosition {
+    RIDAircraftPosition {
+        lat: location.latitude,
+        lng: location.longitude,
+        alt: location.geodetic_altitude,
+        accuracy_h: location.accuracy_h,
+        accuracy_v: location.accuracy_v,
+        extrapolated: false,
+        pressure_alt: location.pressure_altitude,
+        height: RIDHeight {
+            distance: location.height,
+            reference: location.height_reference,
+        },
+    }
+}
+
+impl Aircraft {
+    /// Apply a decoded message
+    pub fn apply(&mut self, message: Message, received: DateTime<Utc>) {
+        self.last_seen = Some(received);
+        match message {
+            Message::BasicId(basic_id) => {
+                self.basic_ids.insert(basic_id.id_type, basic_id);
+            }
+            Message::Location(location) => {
+                let time = location
+                    .timestamp
+                    .map_or(received, |tenths| resolve_timestamp(tenths, received));
+                self.locations
+                    .push_back(ReceivedLocation { time, location });
+                let oldest = received - Duration::seconds(MAX_POSITION_AGE_SECONDS);
+                while self.locations.len() > MAX_POSITIONS
+                    || self.locations.front().is_some_and(|l| l.time < oldest)
+                {
+                    self.locations.pop_front();
+                }
+            }
+            Message::Authentication(page) => {
+                // a new first page starts a new authentication
+                if page.page == 0 {
+                    self.authentication.clear();
+                }
+                self.authentication.insert(page.page, page);
+            }
+            Message::SelfId(self_id) => self.self_id = Some(self_id),
+            Message::System(system) => self.system = Some(system),
+            Message::OperatorId(operator_id) => self.operator_id = Some(operator_id),
+        }
+    }
+
+    /// The ID of the flight, the preferred broadcast UAS ID if any
+    pub fn flight_id(&self) -> Option<&str> {
+        FLIGHT_ID_TYPES
+            .iter()
+            .filter_map(|id_type| self.basic_ids.get(id_type))
+            .map(|basic_id| basic_id.uas_id.as_str())
+            .find(|uas_id| !uas_id.is_empty())
+    }
+
+    /// The flight of this aircraft, if a location was received
+    pub fn flight(&self, transmitter: &str, recent_duration_s: f32) -> Option<RIDFlight> {
+        let latest = self.locations.back()?;
+        let aircraft_type = FLIGHT_ID_TYPES
+            .iter()
+            .chain([IdType::None].iter())
+            .find_map(|id_type| self.basic_ids.get(id_type))
+            .map_or(UAType::NotDeclared, |basic_id| basic_id.ua_type);
+
+        let since = latest.time - Duration::milliseconds((recent_duration_s * 1000.0) as i64);
+        let recent_positions = self
+            .locations
+            .iter()
+            .filter(|l| l.time >= since)
+            .map(|l| RIDRecentAircraftPosition {
+                time: l.time.into(),
+                position: position(&l.location),
+            })
+            .collect();
+
+        Some(RIDFlight {
+            id: self.flight_id().unwrap_or(transmitter).to_string(),
+            aircraft_type,
+            current_state: RIDAircraftState {
+                timestamp: latest.time.into(),
+                timestamp_accuracy: latest.location.timestamp_accuracy,
+                operational_status: latest.location.status,
+                position: position(&latest.location),
+                track: latest.location.direction,
+                speed: latest.location.speed,
+                speed_accuracy: latest.location.accuracy_speed,
+                vertical_speed: latest.location.vertical_speed,
+            },
+            operating_area: OperatingArea {
+                aircraft_count: self.system.map_or(0, |s| s.area_count as i32),
+                volumes: vec![],
+            },
+            simulated: false,
+            recent_positions,
+        })
+    }
+}
+
+/// Aircraft received over Broadcast Remote ID, by transmitter
+#[derive(Debug, Default)]
+pub struct BroadcastFlights {
+    /// Aircraft by transmitter address
+    aircraft: Mutex<HashMap<String, Aircraft>>,
+}
+
+impl BroadcastFlights {
+    /// Lock the aircraft, recovering from a poisoned lock as every
+    ///  modification leaves the map in a consistent state
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Aircraft>> {
+        self.aircraft.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply the messages received from a transmitter, dropping the
+    ///  aircraft that timed out.
+    /// Returns the ID of the flight of the transmitter.
+    pub fn ingest(&self, transmitter: &str, messages: Vec<Message>, now: DateTime<Utc>) -> String {
+        let mut aircraft = self.lock();
+        let timeout = now - Duration::seconds(MAX_POSITION_AGE_SECONDS);
+        aircraft.retain(|_, a| a.last_seen.is_some_and(|last_seen| last_seen >= timeout));
+
+        let entry = aircraft.entry(transmitter.to_string()).or_default();
+        for message in messages {
+            entry.apply(message, now);
+        }
+
+        entry.flight_id().unwrap_or(transmitter).to_string()
+    }
+
+    /// The aircraft received from a transmitter
+    pub fn aircraft(&self, transmitter: &str) -> Option<Aircraft> {
+        self.lock().get(transmitter).cloned()
+    }
+
+    /// The flights located in the window that reported a location
+    ///  recently, ordered by ID
+    pub fn flights_in(
+        &self,
+        window: &Window,
+        recent_duration_s: f32,
+        now: DateTime<Utc>,
+    ) -> Vec<RIDFlight> {
+        let (min_lon, min_lat, max_lon, max_lat) = window.bounds();
+        let timeout = now - Duration::seconds(BROADCAST_TIMEOUT_SECONDS);
+        let mut flights = self
+            .lock()
+            .iter()
+            .filter(|(_, a)| a.last_seen.is_some_and(|last_seen| last_seen >= timeout))
+            .filter_map(|(transmitter, a)| a.flight(transmitter, recent_duration_s))
+            .filter(|f| {
+                let p = &f.current_state.position;
+                (min_lat..=max_lat).contains(&p.lat) && (min_lon..=max_lon).contains(&p.lng)
+            })
+            .collect::<Vec<RIDFlight>>();
+        flights.sort_by(|a, b| a.id.cmp(&b.id));
+        flights
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::decoder::decode_frames;
+    use super::super::decoder::tests::*;
+    use super::*;
+
+    fn window() -> Window {
+        Window {
+            lon1: 4.89,
+            lat1: 52.36,
+            lon2: 4.92,
+            lat2: 52.37,
+        }
+    }
+
+    #[test]
+    fn test_resolve_timestamp() {
+        let received = DateTime::parse_from_rfc3339("2024-05-01T10:12:30Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            resolve_timestamp(7485, received).to_rfc3339(),
+            "2024-05-01T10:12:28.500+00:00"
+        );
+
+        // sent just before the hour, received just after
+        let received = DateTime::parse_from_rfc3339("2024-05-01T11:00:01Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            resolve_timestamp(35_990, received).to_rfc3339(),
+            "2024-05-01T10:59:59+00:00"
+        );
+    }
+
+    #[test]
+    fn test_flight() {
+        let now = Utc::now();
+        let flights = BroadcastFlights::default();
+        let messages = decode_frames(&pack(&[BASIC_ID, location_at(now), SYSTEM])).unwrap();
+        assert_eq!(flights.ingest("aa:bb", messages, now), "1596F3546SN001");
+
+        let result = flights.flights_in(&window(), 10.0, now);
+        assert_eq!(result.len(), 1);
+        let flight = &result[0];
+        assert_eq!(flight.id, "1596F3546SN001");
+        assert_eq!(flight.aircraft_type, UAType::Helicopter);
+        assert_eq!(flight.operating_area.aircraft_count, 1);
+        assert!(!flight.simulated);
+        assert_eq!(flight.recent_positions.len(), 1);
+
+        let state = &flight.current_state;
+        assert_eq!(state.operational_status, RIDOperationalStatus::Airborne);
+        assert_eq!(state.track, 270.0);
+        assert_eq!(state.speed, 12.5);
+        assert_eq!(state.speed_accuracy, SpeedAccuracy::SA1mps);
+        assert_eq!(state.position.accuracy_h, HorizontalAccuracy::HA10m);
+        assert_eq!(state.position.accuracy_v, VerticalAccuracy::VA25m);
+        assert_eq!(state.position.alt, 120.0);
+        assert_eq!(state.position.pressure_alt, 118.0);
+        assert_eq!(state.position.height.distance, 30.0);
+        assert_eq!(
+            state.position.height.reference,
+            RIDHeightReference::TakeoffLocation
+        );
+
+        // a session ID is preferred over the serial number
+        let mut session = BASIC_ID;
+        session[1] = 0x42;
+        session[2..22].fill(0);
+        session[2..6].copy_from_slice(b"SESS");
+        let messages = decode_frames(&session).unwrap();
+        assert_eq!(flights.ingest("aa:bb", messages, now), "SESS");
+
+        // outside the window or timed out
+        let far = Window {
+            lon1: 5.0,
+            lat1: 53.0,
+            lon2: 5.1,
+            lat2: 53.1,
+        };
+        assert!(flights.flights_in(&far, 10.0, now).is_empty());
+        let later = now + Duration::seconds(BROADCAST_TIMEOUT_SECONDS + 1);
+        assert!(flights.flights_in(&window(), 10.0, later).is_empty());
+    }
+
+    #[test]
+    fn test_flight_without_basic_id() {
+        let now = Utc::now();
+        let flights = BroadcastFlights::default();
+
+        // no location yet
+        let messages = decode_frames(&OPERATOR_ID).unwrap();
+        assert_eq!(flights.ingest("aa:bb", messages, now), "aa:bb");
+        assert!(flights.flights_in(&window(), 10.0, now).is_empty());
+
+        let messages = decode_frames(&location_at(now)).unwrap();
+        flights.ingest("aa:bb", messages, now);
+        let result = flights.flights_in(&window(), 10.0, now);
+        assert_eq!(result[0].id, "aa:bb");
+        assert_eq!(result[0].aircraft_type, UAType::NotDeclared);
+
+        let aircraft = flights.aircraft("aa:bb").unwrap();
+        assert_eq!(aircraft.operator_id.unwrap().operator_id, "NLD87asdf76543");
+        assert!(flights.aircraft("cc:dd").is_none());
+    }
+
+    #[test]
+    fn test_recent_positions() {
+        let now = Utc::now();
+        let flights = BroadcastFlights::default();
+        for seconds in 0..5 {
+            let mut location = LOCATION;
+            location[21..23].copy_from_slice(&[0xFF, 0xFF]);
+            let messages = decode_frames(&location).unwrap();
+            flights.ingest("aa:bb", messages, now + Duration::seconds(seconds));
+        }
+
+        let later = now + Duration::seconds(4);
+        let result = flights.flights_in(&window(), 2.0, later);
+        assert_eq!(result[0].recent_positions.len(), 3);
+        let result = flights.flights_in(&window(), 0.0, later);
+        assert_eq!(result[0].recent_positions.len(), 1);
+
+        // positions expire
+        let later = now + Duration::seconds(MAX_POSITION_AGE_SECONDS + 3);
+        let mut location = LOCATION;
+        location[21..23].copy_from_slice(&[0xFF, 0xFF]);
+        flights.ingest("aa:bb", decode_frames(&location).unwrap(), later);
+        let aircraft = flights.aircraft("aa:bb").unwrap();
+        assert_eq!(aircraft.locations.len(), 3);
+    }
+
+    #[test]
+    fn test_authentication_pages() {
+        let now = Utc::now();
+        let flights = BroadcastFlights::default();
+        let mut second = AUTHENTICATION;
+        second[1] = 0x11;
+        let messages = decode_frames(&pack(&[AUTHENTICATION, second])).unwrap();
+        flights.ingest("aa:bb", messages, now);
+        assert_eq!(flights.aircraft("aa:bb").unwrap().authentication.len(), 2);
+
+        // a new first page replaces the previous authentication
+        let messages = decode_frames(&AUTHENTICATION).unwrap();
+        flights.ingest("aa:bb", messages, now);
+        assert_eq!(flights.aircraft("aa:bb").unwrap().authentication.len(), 1);
+    }
+}
@@ -0,0 +1,37 @@
+//! U-space service provider registry
+//! Keeps track of the USSPs and CISPs we know of, the services they offer
+//! and the regions they serve, so clients can discover which providers to
+//! contact for an area.
+
+pub mod store;
+
+use std::sync::Arc;
+use store::ServiceProviders;
+use tokio::sync::OnceCell;
+
+pub(crate) static REGISTRY: OnceCell<Arc<ServiceProviders>> = OnceCell::const_new();
+
+/// Returns the service provider registry, loaded from the registry file
+///  of a Config object generated from environment variables.
+/// Initializes the registry if it hasn't been initialized yet.
+pub async fn get_registry() -> Arc<ServiceProviders> {
+    REGISTRY
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            Arc::new(ServiceProviders::load(&config.registry_file))
+        })
+        .await
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_registry() {
+        let a = get_registry().await;
+        let b = get_registry().await;
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+}
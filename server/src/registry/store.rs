@@ -0,0 +1,467 @@
+//! Storage of the known service providers, persisted to a JSON file
+
+use crate::rest::api::rest_types::*;
+use crate::rest::api::uss::Window;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+/// Errors returned by the registry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegistryError {
+    /// No provider exists with the provided ID
+    NotFound,
+
+    /// A provider with the provided ID already exists
+    AlreadyExists,
+
+    /// The provider ID does not match the ID of the request
+    IdMismatch,
+
+    /// The provided provider is invalid
+    InvalidProvider,
+
+    /// The registry could not be persisted
+    Storage,
+}
+
+impl std::error::Error for RegistryError {}
+
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::NotFound => write!(f, "Provider not found"),
+            RegistryError::AlreadyExists => write!(f, "Provider already exists"),
+            RegistryError::IdMismatch => write!(f, "Provider ID does not match request"),
+            RegistryError::InvalidProvider => write!(f, "Invalid provider"),
+            RegistryError::Storage => write!(f, "Registry could not be persisted"),
+        }
+    }
+}
+
+/// Validate the fields of a provider
+fn validate(provider: &ServiceProvider) -> Result<(), RegistryError> {
+    if provider.id.trim().is_empty() {
+        rest_error!("provider ID must not be empty.");
+        return Err(RegistryError::InvalidProvider);
+    }
+
+    if provider.endpoints.is_empty() {
+        rest_error!("provider {} must offer at least one service.", provider.id);
+        return Err(RegistryError::InvalidProvider);
+    }
+
+    for endpoint in &provider.endpoints {
+        if !endpoint.base_url.starts_with("http://") && !endpoint.base_url.starts_with("https://") {
+            rest_error!(
+                "invalid base URL for {} of provider {}: {}",
+                endpoint.service,
+                provider.id,
+                endpoint.base_url
+            );
+            return Err(RegistryError::InvalidProvider);
+        }
+    }
+
+    for region in &provider.regions {
+        if region.vertices.len() < 3 || !region.vertices.iter().all(LatLngPoint::is_valid) {
+            rest_error!(
+                "regions of provider {} must have at least 3 valid vertices.",
+                provider.id
+            );
+            return Err(RegistryError::InvalidProvider);
+        }
+    }
+
+    if provider
+        .public_keys
+        .iter()
+        .any(|key| key.key_id.is_empty() || key.pem.is_empty())
+    {
+        rest_error!(
+            "public keys of provider {} must have an ID and a key.",
+            provider.id
+        );
+        return Err(RegistryError::InvalidProvider);
+    }
+
+    Ok(())
+}
+
+/// Known service providers by ID, written to a file on every change
+#[derive(Debug)]
+pub struct ServiceProviders {
+    /// File the providers are persisted to
+    path: PathBuf,
+
+    /// Providers by ID
+    providers: Mutex<BTreeMap<String, ServiceProvider>>,
+}
+
+impl ServiceProviders {
+    /// Load the providers stored in the given file.
+    /// A missing or invalid file results in an empty registry.
+    pub fn load(path: &str) -> Self {
+        let providers = match std::fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str::<Vec<ServiceProvider>>(&content) {
+                Ok(providers) => providers,
+                Err(e) => {
+                    rest_error!("invalid registry file {}: {}", path, e);
+                    vec![]
+                }
+            },
+            Err(e) => {
+                rest_info!("no providers loaded from {}: {}", path, e);
+                vec![]
+            }
+        };
+
+        let providers = providers
+            .into_iter()
+            .filter(|provider| {
+                validate(provider)
+                    .map_err(|_| rest_warn!("skipping invalid provider {}.", provider.id))
+                    .is_ok()
+            })
+            .map(|provider| (provider.id.clone(), provider))
+            .collect::<BTreeMap<String, ServiceProvider>>();
+
+        rest_info!("loaded {} providers from {}.", providers.len(), path);
+        ServiceProviders {
+            path: PathBuf::from(path),
+            providers: Mutex::new(providers),
+        }
+    }
+
+    /// Lock the providers, recovering from a poisoned lock as every
+    ///  modification leaves the map in a consistent state
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, ServiceProvider>> {
+        self.providers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Write the providers to a temporary file and move it in place,
+    ///  so a failed write never leaves a truncated registry behind
+    fn save(&self, providers: &BTreeMap<String, ServiceProvider>) -> Result<(), RegistryError> {
+        let content = serde_json::to_string_pretty(&providers.values().collect::<Vec<_>>())
+            .map_err(|e| {
+                rest_error!("could not serialize providers: {}", e);
+                RegistryError::Storage
+            })?;
+
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, content)
+            .and_then(|_| std::fs::rename(&tmp, &self.path))
+            .map_err(|e| {
+                rest_error!("could not write providers to {:?}: {}", self.path, e);
+                RegistryError::Storage
+            })
+    }
+
+    /// Apply a change to a copy of the providers, which replaces the
+    ///  current providers once it was persisted
+    fn update(
+        &self,
+        change: impl FnOnce(&mut BTreeMap<String, ServiceProvider>) -> Result<(), RegistryError>,
+    ) -> Result<(), RegistryError> {
+        let mut providers = self.lock();
+        let mut updated = providers.clone();
+        change(&mut updated)?;
+        self.save(&updated)?;
+        *providers = updated;
+        Ok(())
+    }
+
+    /// Register a new provider
+    pub fn create(&self, provider: ServiceProvider) -> Result<ServiceProvider, RegistryError> {
+        validate(&provider)?;
+        self.update(|providers| {
+            if providers.contains_key(&provider.id) {
+                return Err(RegistryError::AlreadyExists);
+            }
+
+            providers.insert(provider.id.clone(), provider.clone());
+            Ok(())
+        })?;
+
+        Ok(provider)
+    }
+
+    /// Replace an existing provider
+    pub fn replace(
+        &self,
+        id: &str,
+        provider: ServiceProvider,
+    ) -> Result<ServiceProvider, RegistryError> {
+        if provider.id != id {
+            return Err(RegistryError::IdMismatch);
+        }
+
+        validate(&provider)?;
+        self.update(|providers| {
+            let Some(current) = providers.get_mut(id) else {
+                return Err(RegistryError::NotFound);
+            };
+
+            *current = provider.clone();
+            Ok(())
+        })?;
+
+        Ok(provider)
+    }
+
+    /// Remove a provider
+    pub fn delete(&self, id: &str) -> Result<ServiceProvider, RegistryError> {
+        let mut deleted = None;
+        self.update(|providers| {
+            deleted = providers.remove(id);
+            match deleted {
+                Some(_) => Ok(()),
+                None => Err(RegistryError::NotFound),
+            }
+        })?;
+
+        deleted.ok_or(RegistryError::NotFound)
+    }
+
+    /// Get a provider
+    pub fn get(&self, id: &str) -> Result<ServiceProvider, RegistryError> {
+        self.lock().get(id).cloned().ok_or(RegistryError::NotFound)
+    }
+
+    /// All providers, ordered by ID
+    pub fn list(&self) -> Vec<ServiceProvider> {
+        self.lock().values().cloned().collect()
+    }
+
+    /// Providers offering a service in a region overlapping the window
+    pub fn lookup(&self, window: &Window, service: ProviderService) -> Vec<ServiceProvider> {
+        self.lock()
+            .values()
+            .filter(|provider| {
+                provider
+                    .endpoints
+                    .iter()
+                    .any(|endpoint| endpoint.service == service)
+            })
+            .filter(|provider| {
+                provider
+                    .regions
+                    .iter()
+                    .any(|region| window.intersects_polygon(region))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A provider offering RID around Amsterdam
+    pub(crate) fn provider(id: &str) -> ServiceProvider {
+        ServiceProvider {
+            id: id.to_string(),
+            name: "Example USSP".to_string(),
+            role: ProviderRole::Ussp,
+            endpoints: vec![ProviderEndpoint {
+                service: ProviderService::Rid,
+                base_url: "https://ussp.example.com".to_string(),
+            }],
+            regions: vec![Polygon {
+                vertices: vec![
+                    LatLngPoint {
+                        lat: 52.3,
+                        lng: 4.8,
+                    },
+                    LatLngPoint {
+                        lat: 52.3,
+                        lng: 5.0,
+                    },
+                    LatLngPoint {
+                        lat: 52.4,
+                        lng: 5.0,
+                    },
+                    LatLngPoint {
+                        lat: 52.4,
+                        lng: 4.8,
+                    },
+                ],
+            }],
+            public_keys: vec![ProviderPublicKey {
+                key_id: "key-1".to_string(),
+                pem: "-----BEGIN PUBLIC KEY-----".to_string(),
+            }],
+        }
+    }
+
+    pub(crate) fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!(
+                "svc-discovery-{}-{}.json",
+                name,
+                rand::random::<u64>()
+            ))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&provider("ussp")).is_ok());
+
+        let mut invalid = provider("");
+        assert_eq!(validate(&invalid), Err(RegistryError::InvalidProvider));
+
+        invalid = provider("ussp");
+        invalid.endpoints.clear();
+        assert_eq!(validate(&invalid), Err(RegistryError::InvalidProvider));
+
+        invalid = provider("ussp");
+        invalid.endpoints[0].base_url = "ftp://ussp.example.com".to_string();
+        assert_eq!(validate(&invalid), Err(RegistryError::InvalidProvider));
+
+        invalid = provider("ussp");
+        invalid.regions[0].vertices.truncate(2);
+        assert_eq!(validate(&invalid), Err(RegistryError::InvalidProvider));
+
+        invalid = provider("ussp");
+        invalid.regions[0].vertices[0].lat = 91.0;
+        assert_eq!(validate(&invalid), Err(RegistryError::InvalidProvider));
+
+        invalid = provider("ussp");
+        invalid.public_keys[0].pem.clear();
+        assert_eq!(validate(&invalid), Err(RegistryError::InvalidProvider));
+    }
+
+    #[test]
+    fn test_crud_and_reload() {
+        let path = temp_path("registry");
+        let registry = ServiceProviders::load(&path);
+        assert!(registry.list().is_empty());
+
+        registry.create(provider("b")).unwrap();
+        registry.create(provider("a")).unwrap();
+        assert_eq!(
+            registry.create(provider("a")).unwrap_err(),
+            RegistryError::AlreadyExists
+        );
+
+        // ordered by ID
+        let ids = registry
+            .list()
+            .into_iter()
+            .map(|p| p.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        let mut updated = provider("a");
+        updated.name = "Renamed".to_string();
+        assert_eq!(
+            registry.replace("b", updated.clone()).unwrap_err(),
+            RegistryError::IdMismatch
+        );
+        assert_eq!(
+            registry.replace("c", provider("c")).unwrap_err(),
+            RegistryError::NotFound
+        );
+        registry.replace("a", updated).unwrap();
+        assert_eq!(registry.get("a").unwrap().name, "Renamed");
+
+        assert_eq!(registry.delete("b").unwrap().id, "b");
+        assert_eq!(registry.delete("b").unwrap_err(), RegistryError::NotFound);
+        assert_eq!(registry.get("b").unwrap_err(), RegistryError::NotFound);
+
+        // loaded on start
+        let reloaded = ServiceProviders::load(&path);
+        let providers = reloaded.list();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].name, "Renamed");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_invalid_file() {
+        let path = temp_path("registry-invalid");
+        std::fs::write(&path, "not json").unwrap();
+        assert!(ServiceProviders::load(&path).list().is_empty());
+
+        let mut invalid = provider("invalid");
+        invalid.endpoints.clear();
+        let content = serde_json::to_string(&vec![provider("valid"), invalid]).unwrap();
+        std::fs::write(&path, content).unwrap();
+        let providers = ServiceProviders::load(&path).list();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].id, "valid");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_write_failure() {
+        let registry = ServiceProviders::load("/nonsense/registry.json");
+        assert_eq!(
+            registry.create(provider("a")).unwrap_err(),
+            RegistryError::Storage
+        );
+        assert!(registry.list().is_empty());
+    }
+
+    #[test]
+    fn test_lookup() {
+        let path = temp_path("registry-lookup");
+        let registry = ServiceProviders::load(&path);
+        registry.create(provider("rid")).unwrap();
+
+        let mut weather = provider("weather");
+        weather.role = ProviderRole::Cisp;
+        weather.endpoints[0].service = ProviderService::Weather;
+        registry.create(weather).unwrap();
+
+        let inside = Window {
+            lat1: 52.35,
+            lon1: 4.85,
+            lat2: 52.36,
+            lon2: 4.86,
+        };
+        let found = registry.lookup(&inside, ProviderService::Rid);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "rid");
+
+        let found = registry.lookup(&inside, ProviderService::Weather);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "weather");
+
+        assert!(registry.lookup(&inside, ProviderService::F3548).is_empty());
+
+        // overlapping the edge of the region
+        let overlapping = Window {
+            lat1: 52.25,
+            lon1: 4.7,
+            lat2: 52.35,
+            lon2: 4.85,
+        };
+        assert_eq!(registry.lookup(&overlapping, ProviderService::Rid).len(), 1);
+
+        // a single point
+        let point = Window {
+            lat1: 52.35,
+            lon1: 4.85,
+            lat2: 52.35,
+            lon2: 4.85,
+        };
+        assert_eq!(registry.lookup(&point, ProviderService::Rid).len(), 1);
+
+        let outside = Window {
+            lat1: 51.0,
+            lon1: 4.0,
+            lat2: 51.1,
+            lon2: 4.1,
+        };
+        assert!(registry.lookup(&outside, ProviderService::Rid).is_empty());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
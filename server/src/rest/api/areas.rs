@@ -0,0 +1,540 @@
+//! REST API for flights in polygon and circle areas
+//! Operators think in corridors and vertiport radii rather than in
+//! rectangles, so flights can be queried in the outline of a 3D volume.
+
+use super::rest_types::*;
+use super::uss::{
+    check_isas, get_recent_flights, with_local_flights, Window, MAX_DISPLAY_AREA_DIAGONAL_METERS,
+};
+use crate::grpc::client::GrpcClients;
+use crate::odid::store::BroadcastFlights;
+use crate::telemetry::store::PushedTelemetry;
+use axum::{Extension, Json};
+use geo::algorithm::haversine_distance::HaversineDistance;
+use geo::line_intersection::{line_intersection, LineIntersection};
+use geo::{Area, ChamberlainDuquetteArea, Intersects};
+use hyper::StatusCode;
+use std::sync::Arc;
+
+/// Maximum surface of an area of interest in square meters, about the
+///  surface of the largest `/uss/flights` view
+const MAX_AREA_SQUARE_METERS: f64 = 25_000_000.0;
+
+/// Maximum number of vertices of a polygon
+const MAX_POLYGON_VERTICES: usize = 100;
+
+/// Minimum surface of a polygon in square meters, smaller polygons are
+///  degenerate
+const MIN_POLYGON_AREA_SQUARE_METERS: f64 = 1.0;
+
+/// If the edges of the polygon only meet their neighbours at their shared
+///  vertex, so the polygon does not cross or touch itself
+fn is_simple(polygon: &geo::Polygon<f64>) -> bool {
+    let edges = polygon.exterior().lines().collect::<Vec<geo::Line<f64>>>();
+    let count = edges.len();
+    for i in 0..count {
+        for j in (i + 1)..count {
+            let adjacent = j == i + 1 || (i == 0 && j == count - 1);
+            match line_intersection(edges[i], edges[j]) {
+                None => {}
+                Some(LineIntersection::SinglePoint {
+                    is_proper: false, ..
+                }) if adjacent => {}
+                Some(_) => return false,
+            }
+        }
+    }
+
+    true
+}
+
+/// The outline of an area of interest
+#[derive(Debug, Clone, PartialEq)]
+pub enum AreaOutline {
+    /// A polygon of (longitude, latitude) vertices
+    Polygon(geo::Polygon<f64>),
+
+    /// A circle around a (longitude, latitude) center
+    Circle {
+        /// The center of the circle
+        center: geo::Point<f64>,
+
+        /// The radius in meters
+        radius_meters: f64,
+    },
+}
+
+impl TryFrom<&Volume3D> for AreaOutline {
+    type Error = StatusCode;
+
+    fn try_from(volume: &Volume3D) -> Result<Self, Self::Error> {
+        match (&volume.outline_polygon, &volume.outline_circle) {
+            (Some(polygon), None) => {
+                let vertices = &polygon.vertices;
+                if vertices.len() < 3 || vertices.len() > MAX_POLYGON_VERTICES {
+                    rest_error!(
+                        "polygon must have between 3 and {} vertices.",
+                        MAX_POLYGON_VERTICES
+                    );
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+
+                if !vertices.iter().all(LatLngPoint::is_valid) {
+                    rest_error!("vertex coordinates out of range.");
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+
+                let exterior = vertices
+                    .iter()
+                    .map(|vertex| (vertex.lng, vertex.lat))
+                    .collect::<Vec<(f64, f64)>>();
+                let polygon = geo::Polygon::new(exterior.into(), vec![]);
+                if !is_simple(&polygon) {
+                    rest_error!("polygon edges must not cross or touch.");
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+
+                // planar, as vertices on a line of latitude and longitude
+                //  still enclose a surface on the sphere
+                let meters_per_degree_lon = METERS_PER_DEGREE * vertices[0].lat.to_radians().cos();
+                let planar_area =
+                    polygon.unsigned_area() * METERS_PER_DEGREE * meters_per_degree_lon;
+                if planar_area < MIN_POLYGON_AREA_SQUARE_METERS {
+                    rest_error!("polygon must have a surface.");
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+
+                Ok(AreaOutline::Polygon(polygon))
+            }
+            (None, Some(circle)) => {
+                if circle.radius.units != "M" || circle.radius.value <= 0.0 {
+                    rest_error!("circle radius must be a positive value in meters.");
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+
+                if !circle.center.is_valid() {
+                    rest_error!("circle center out of range.");
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+
+                Ok(AreaOutline::Circle {
+                    center: geo::Point::new(circle.center.lng, circle.center.lat),
+                    radius_meters: circle.radius.value as f64,
+                })
+            }
+            _ => {
+                rest_error!("exactly one of outline_polygon or outline_circle must be provided.");
+                Err(StatusCode::BAD_REQUEST)
+            }
+        }
+    }
+}
+
+impl AreaOutline {
+    /// The surface of the area in square meters
+    pub fn area_square_meters(&self) -> f64 {
+        match self {
+            AreaOutline::Polygon(polygon) => polygon.chamberlain_duquette_unsigned_area(),
+            AreaOutline::Circle { radius_meters, .. } => {
+                std::f64::consts::PI * radius_meters * radius_meters
+            }
+        }
+    }
+
+    /// The window bounding the area
+    pub fn window(&self) -> Window {
+        match self {
+            AreaOutline::Polygon(polygon) => {
+                let init = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+                let (min_lon, min_lat, max_lon, max_lat) = polygon.exterior().coords().fold(
+                    init,
+                    |(min_lon, min_lat, max_lon, max_lat), c| {
+                        (
+                            min_lon.min(c.x),
+                            min_lat.min(c.y),
+                            max_lon.max(c.x),
+                            max_lat.max(c.y),
+                        )
+                    },
+                );
+
+                Window {
+                    lon1: min_lon,
+                    lat1: min_lat,
+                    lon2: max_lon,
+                    lat2: max_lat,
+                }
+            }
+            AreaOutline::Circle {
+                center,
+                radius_meters,
+            } => {
+                let d_lat = radius_meters / METERS_PER_DEGREE;
+                let d_lon =
+                    radius_meters / (METERS_PER_DEGREE * center.y().to_radians().cos().max(0.01));
+                Window {
+                    lon1: (center.x() - d_lon).max(-180.0),
+                    lat1: (center.y() - d_lat).max(-90.0),
+                    lon2: (center.x() + d_lon).min(180.0),
+                    lat2: (center.y() + d_lat).min(90.0),
+                }
+            }
+        }
+    }
+
+    /// If the position is inside the area, boundaries included
+    pub fn contains(&self, lat: f64, lng: f64) -> bool {
+        let point = geo::Point::new(lng, lat);
+        match self {
+            AreaOutline::Polygon(polygon) => polygon.intersects(&point),
+            AreaOutline::Circle {
+                center,
+                radius_meters,
+            } => center.haversine_distance(&point) <= *radius_meters,
+        }
+    }
+}
+
+/// Get the altitude bounds of a volume in meters
+fn altitude_bounds(volume: &Volume3D) -> Result<(Option<f64>, Option<f64>), StatusCode> {
+    let bound = |altitude: &Option<Altitude>| match altitude {
+        None => Ok(None),
+        Some(altitude) if altitude.units == "M" && altitude.value.is_finite() => {
+            Ok(Some(altitude.value))
+        }
+        Some(_) => {
+            rest_error!("altitude bounds must be finite values in meters.");
+            Err(StatusCode::BAD_REQUEST)
+        }
+    };
+
+    let (lower, upper) = (
+        bound(&volume.altitude_lower)?,
+        bound(&volume.altitude_upper)?,
+    );
+    if let (Some(lower), Some(upper)) = (lower, upper) {
+        if lower > upper {
+            rest_error!("altitude_lower must not exceed altitude_upper.");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    Ok((lower, upper))
+}
+
+/// If the flight is located inside the area and its altitude bounds, a
+///  flight with an unknown altitude is outside of any altitude bounds
+fn in_volume(
+    flight: &RIDFlight,
+    outline: &AreaOutline,
+    (lower, upper): (Option<f64>, Option<f64>),
+) -> bool {
+    let position = &flight.current_state.position;
+    let altitude = position.alt as f64;
+    let in_bounds = match (lower, upper) {
+        (None, None) => true,
+        (lower, upper) => {
+            position.alt != UNKNOWN_ALTITUDE
+                && lower.is_none_or(|lower| altitude >= lower)
+                && upper.is_none_or(|upper| altitude <= upper)
+        }
+    };
+
+    in_bounds && outline.contains(position.lat, position.lng)
+}
+
+/// Get the flights in a polygon or circle area
+#[utoipa::path(
+    post,
+    path = "/uss/flights/area",
+    tag = "svc-discovery",
+    request_body = GetAreaFlightsRequest,
+    responses(
+        (status = 200, description = "Flight information was successfully retrieved.", body = GetFlightsResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 413, description = "The requested area was too large.")
+    )
+)]
+pub async fn get_area_flights(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(broadcast): Extension<Arc<BroadcastFlights>>,
+    Extension(telemetry): Extension<Arc<PushedTelemetry>>,
+    Json(request): Json<GetAreaFlightsRequest>,
+) -> Result<Json<GetFlightsResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let outline = AreaOutline::try_from(&request.area)?;
+    let bounds = altitude_bounds(&request.area)?;
+    let window = outline.window();
+    if outline.area_square_meters() > MAX_AREA_SQUARE_METERS
+        || window.diagonal() > MAX_DISPLAY_AREA_DIAGONAL_METERS
+    {
+        rest_error!("The requested area was too large.");
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    let flights = get_recent_flights(
+        &mut grpc_clients.clone(),
+        &window,
+        request.recent_positions_duration,
+    )
+    .await?;
+    let flights = with_local_flights(
+        flights,
+        &broadcast,
+        &telemetry,
+        &window,
+        request.recent_positions_duration,
+    )
+    .into_iter()
+    .filter(|flight| in_volume(flight, &outline, bounds))
+    .collect();
+
+    Ok(Json(GetFlightsResponse {
+        flights,
+        no_isas_present: !check_isas(&mut grpc_clients.clone(), &window).await?,
+        ..Default::default() // applies current timestamp
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::tests::state;
+    use lib_common::time::Utc;
+
+    fn polygon(vertices: &[(f64, f64)]) -> Volume3D {
+        Volume3D {
+            outline_circle: None,
+            outline_polygon: Some(Polygon {
+                vertices: vertices
+                    .iter()
+                    .map(|&(lat, lng)| LatLngPoint { lat, lng })
+                    .collect(),
+            }),
+            altitude_lower: None,
+            altitude_upper: None,
+        }
+    }
+
+    fn circle(lat: f64, lng: f64, radius: f32) -> Volume3D {
+        Volume3D {
+            outline_circle: Some(Circle {
+                center: LatLngPoint { lat, lng },
+                radius: Radius {
+                    value: radius,
+                    units: "M".to_string(),
+                },
+            }),
+            outline_polygon: None,
+            altitude_lower: None,
+            altitude_upper: None,
+        }
+    }
+
+    fn flight(lat: f64, lng: f64, alt: f32) -> RIDFlight {
+        let mut flight = crate::telemetry::tests::flight("AC-1", Utc::now(), lat, lng);
+        flight.current_state.position.alt = alt;
+        flight
+    }
+
+    /// A corridor from south west to north east
+    fn corridor() -> Volume3D {
+        polygon(&[(52.36, 4.90), (52.361, 4.90), (52.371, 4.92), (52.37, 4.92)])
+    }
+
+    #[test]
+    fn test_polygon_outline() {
+        let outline = AreaOutline::try_from(&corridor()).unwrap();
+        let window = outline.window();
+        assert_eq!(window.bounds(), (4.90, 52.36, 4.92, 52.371));
+
+        // inside the bounding window but outside the corridor
+        assert!(outline.contains(52.3655, 4.91));
+        assert!(!outline.contains(52.369, 4.902));
+        assert!(outline.contains(52.36, 4.90));
+
+        let area = outline.area_square_meters();
+        assert!(area > 100_000.0 && area < 200_000.0, "{}", area);
+    }
+
+    #[test]
+    fn test_circle_outline() {
+        let outline = AreaOutline::try_from(&circle(52.37, 4.9, 1000.0)).unwrap();
+        let (min_lon, min_lat, max_lon, max_lat) = outline.window().bounds();
+        assert!((max_lat - min_lat - 2000.0 / METERS_PER_DEGREE).abs() < 1e-9);
+        assert!(max_lon - min_lon > max_lat - min_lat);
+
+        assert!(outline.contains(52.375, 4.9));
+        assert!(!outline.contains(52.38, 4.9));
+
+        // corners of the bounding window are outside of the circle
+        assert!(!outline.contains(max_lat, max_lon));
+        assert!((outline.area_square_meters() - 3_141_592.65).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_invalid_outlines() {
+        let mut both = corridor();
+        both.outline_circle = circle(52.37, 4.9, 100.0).outline_circle;
+        let mut neither = corridor();
+        neither.outline_polygon = None;
+        let mut feet = circle(52.37, 4.9, 100.0);
+        feet.outline_circle.as_mut().unwrap().radius.units = "FT".to_string();
+        let too_many = (0..=MAX_POLYGON_VERTICES)
+            .map(|i| (52.0 + i as f64 * 0.001, 4.9))
+            .collect::<Vec<(f64, f64)>>();
+
+        for volume in [
+            both,
+            neither,
+            feet,
+            circle(52.37, 4.9, 0.0),
+            circle(92.37, 4.9, 100.0),
+            polygon(&[(52.36, 4.90), (52.37, 4.92)]),
+            polygon(&[(52.36, 4.90), (52.37, 4.92), (52.37, 184.92)]),
+            polygon(&too_many),
+            // crossing edges, a bow tie
+            polygon(&[(52.36, 4.90), (52.37, 4.92), (52.36, 4.92), (52.37, 4.90)]),
+            // an edge folding back over the previous one
+            polygon(&[(52.36, 4.90), (52.37, 4.90), (52.365, 4.90), (52.36, 4.92)]),
+            // all vertices on a line
+            polygon(&[(52.36, 4.90), (52.365, 4.91), (52.37, 4.92)]),
+            // a triangle without a surface to speak of
+            polygon(&[(52.36, 4.90), (52.36, 4.900001), (52.360001, 4.90)]),
+        ] {
+            let e = AreaOutline::try_from(&volume).unwrap_err();
+            assert_eq!(e, StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[test]
+    fn test_in_volume() {
+        let mut volume = circle(52.37, 4.9, 1000.0);
+        let outline = AreaOutline::try_from(&volume).unwrap();
+        let unbounded = altitude_bounds(&volume).unwrap();
+        assert!(in_volume(&flight(52.37, 4.9, 120.0), &outline, unbounded));
+        assert!(in_volume(
+            &flight(52.37, 4.9, UNKNOWN_ALTITUDE),
+            &outline,
+            unbounded
+        ));
+        assert!(!in_volume(&flight(52.39, 4.9, 120.0), &outline, unbounded));
+
+        volume.altitude_lower = Some(Altitude {
+            value: 100.0,
+            ..Default::default()
+        });
+        volume.altitude_upper = Some(Altitude {
+            value: 150.0,
+            ..Default::default()
+        });
+        let bounds = altitude_bounds(&volume).unwrap();
+        assert_eq!(bounds, (Some(100.0), Some(150.0)));
+        assert!(in_volume(&flight(52.37, 4.9, 120.0), &outline, bounds));
+        assert!(!in_volume(&flight(52.37, 4.9, 160.0), &outline, bounds));
+        assert!(!in_volume(
+            &flight(52.37, 4.9, UNKNOWN_ALTITUDE),
+            &outline,
+            bounds
+        ));
+
+        volume.altitude_lower.as_mut().unwrap().value = 200.0;
+        assert_eq!(
+            altitude_bounds(&volume).unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+        volume.altitude_lower.as_mut().unwrap().units = "FT".to_string();
+        assert_eq!(
+            altitude_bounds(&volume).unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_area_flights() {
+        let config = crate::config::Config::default();
+        let grpc_clients = Extension(GrpcClients::default(config));
+        let broadcast = Extension(Arc::new(BroadcastFlights::default()));
+        let telemetry = Arc::new(PushedTelemetry::new(["acme".to_string()].into()));
+        let now = Utc::now();
+        for (id, lat, lng) in [("IN", 52.3655, 4.91), ("OUT", 52.369, 4.902)] {
+            telemetry
+                .push(
+                    "acme",
+                    id,
+                    UAType::Helicopter,
+                    false,
+                    vec![state(now, lat, lng)],
+                    now,
+                )
+                .unwrap();
+        }
+
+        let request = |area: Volume3D| {
+            Json(GetAreaFlightsRequest {
+                area,
+                recent_positions_duration: 1.0,
+            })
+        };
+        let response = get_area_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            Extension(telemetry.clone()),
+            request(corridor()),
+        )
+        .await
+        .unwrap();
+        let ids = response
+            .flights
+            .iter()
+            .map(|flight| flight.id.as_str())
+            .collect::<Vec<&str>>();
+        assert!(ids.contains(&"IN"));
+        assert!(!ids.contains(&"OUT"));
+
+        let e = get_area_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            Extension(telemetry.clone()),
+            request(circle(52.37, 4.9, 3000.0)),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::PAYLOAD_TOO_LARGE);
+
+        // a narrow corridor, small in surface but longer than a display area
+        let long = polygon(&[
+            (52.30, 4.90),
+            (52.30, 4.9003),
+            (52.40, 4.9003),
+            (52.40, 4.90),
+        ]);
+        assert!(
+            AreaOutline::try_from(&long).unwrap().area_square_meters() < MAX_AREA_SQUARE_METERS
+        );
+        let e = get_area_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            Extension(telemetry.clone()),
+            request(long),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::PAYLOAD_TOO_LARGE);
+
+        let e = get_area_flights(
+            grpc_clients,
+            broadcast,
+            Extension(telemetry),
+            request(polygon(&[(52.36, 4.90)])),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,176 @@
+//! REST API for ASTM F3548 USS availability
+
+use super::rest_types::*;
+use crate::auth::ProviderCredentials;
+use crate::f3548::availability::UssAvailability;
+use axum::extract::Path;
+use axum::http::HeaderMap;
+use axum::{Extension, Json};
+use hyper::StatusCode;
+use std::sync::Arc;
+
+/// Get the availability of a USS
+#[utoipa::path(
+    get,
+    path = "/uss/v1/uss_availability/{uss_id}",
+    tag = "svc-discovery",
+    params(
+        ("uss_id" = String, Path, description = "Identifier of the USS")
+    ),
+    responses(
+        (status = 200, description = "The availability was retrieved successfully.", body = UssAvailabilityStatusResponse),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint.")
+    )
+)]
+pub async fn get_availability(
+    Extension(availability): Extension<Arc<UssAvailability>>,
+    Path(uss_id): Path<String>,
+) -> Result<Json<UssAvailabilityStatusResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    Ok(Json(availability.get(&uss_id)))
+}
+
+/// Change the availability of a USS
+#[utoipa::path(
+    put,
+    path = "/uss/v1/uss_availability/{uss_id}",
+    tag = "svc-discovery",
+    params(
+        ("uss_id" = String, Path, description = "Identifier of the USS")
+    ),
+    request_body = SetUssAvailabilityStatusParameters,
+    responses(
+        (status = 200, description = "The availability was changed successfully.", body = UssAvailabilityStatusResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint, or the availability of this USS was changed by another provider."),
+        (status = 409, description = "The old version does not match the current version.")
+    )
+)]
+pub async fn set_availability(
+    Extension(availability): Extension<Arc<UssAvailability>>,
+    Extension(credentials): Extension<Arc<ProviderCredentials>>,
+    headers: HeaderMap,
+    Path(uss_id): Path<String>,
+    Json(payload): Json<SetUssAvailabilityStatusParameters>,
+) -> Result<Json<UssAvailabilityStatusResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet for other USSs
+
+    // Taking this USS down fails /health, so only its own operators may
+    if availability.is_own(&uss_id) {
+        let provider = credentials.authenticated_provider(&headers)?;
+        if provider != uss_id {
+            rest_error!(
+                "provider {} may not change the availability of {}.",
+                provider,
+                uss_id
+            );
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+
+    let response = availability.set(&uss_id, payload).map_err(|e| {
+        rest_error!("could not set availability of {}: {}", uss_id, e);
+        StatusCode::from(e)
+    })?;
+
+    rest_info!(
+        "availability of {} is now {}.",
+        uss_id,
+        response.status.availability
+    );
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::tests::{bearer, credential};
+
+    fn credentials() -> Arc<ProviderCredentials> {
+        let value = format!(
+            "{},{}",
+            credential("own", "token-own"),
+            credential("peer", "token-peer")
+        );
+        Arc::new(ProviderCredentials::parse(&value))
+    }
+
+    #[tokio::test]
+    async fn test_get_set_availability() {
+        let availability = Arc::new(UssAvailability::new("own"));
+
+        let response = get_availability(Extension(availability.clone()), Path("peer".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.status.availability, UssAvailabilityState::Unknown);
+
+        let params = SetUssAvailabilityStatusParameters {
+            old_version: "".to_string(),
+            availability: UssAvailabilityState::Down,
+        };
+        let response = set_availability(
+            Extension(availability.clone()),
+            Extension(credentials()),
+            HeaderMap::new(),
+            Path("peer".to_string()),
+            Json(params.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status.availability, UssAvailabilityState::Down);
+
+        let e = set_availability(
+            Extension(availability.clone()),
+            Extension(credentials()),
+            HeaderMap::new(),
+            Path("peer".to_string()),
+            Json(params),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_set_own_availability() {
+        let availability = Arc::new(UssAvailability::new("own"));
+        let params = SetUssAvailabilityStatusParameters {
+            old_version: "".to_string(),
+            availability: UssAvailabilityState::Down,
+        };
+        let set = |headers: HeaderMap| {
+            set_availability(
+                Extension(availability.clone()),
+                Extension(credentials()),
+                headers,
+                Path("own".to_string()),
+                Json(params.clone()),
+            )
+        };
+
+        assert_eq!(
+            set(HeaderMap::new()).await.unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            set(bearer("forged")).await.unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            set(bearer("token-peer")).await.unwrap_err(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(availability.own_state(), UssAvailabilityState::Normal);
+
+        let response = set(bearer("token-own")).await.unwrap();
+        assert_eq!(response.status.availability, UssAvailabilityState::Down);
+        assert_eq!(availability.own_state(), UssAvailabilityState::Down);
+    }
+}
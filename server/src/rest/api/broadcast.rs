@@ -0,0 +1,168 @@
+//! REST API for Broadcast Remote ID receivers
+//! Receivers push the Open Drone ID frames they pick up, so broadcasting
+//! aircraft are reported by `/uss/flights`.
+
+use super::rest_types::*;
+use crate::auth::ProviderCredentials;
+use crate::odid::decoder::decode_frames;
+use crate::odid::store::BroadcastFlights;
+use crate::odid::OdidError;
+use axum::body::Bytes;
+use axum::extract::Query;
+use axum::{Extension, Json};
+use hyper::{HeaderMap, StatusCode};
+use lib_common::time::Utc;
+use std::sync::Arc;
+
+impl From<OdidError> for StatusCode {
+    fn from(_: OdidError) -> Self {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// Push the Open Drone ID frames received from a transmitter, each frame
+///  being a single message or a message pack.
+/// Transmitters are kept per authenticated receiver.
+#[utoipa::path(
+    post,
+    path = "/broadcast/odid",
+    tag = "svc-discovery",
+    params(PostOdidRequest),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "The frames were decoded successfully.", body = PostOdidResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid, or a frame could not be decoded."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+    )
+)]
+pub async fn post_odid(
+    Extension(broadcast): Extension<Arc<BroadcastFlights>>,
+    Extension(credentials): Extension<Arc<ProviderCredentials>>,
+    headers: HeaderMap,
+    Query(query): Query<PostOdidRequest>,
+    body: Bytes,
+) -> Result<Json<PostOdidResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let receiver = credentials.authenticated_provider(&headers)?;
+    let transmitter = query.transmitter.trim().to_lowercase();
+    if transmitter.is_empty() {
+        rest_error!("transmitter must not be empty.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let transmitter = format!("{}/{}", receiver, transmitter);
+
+    let messages = decode_frames(&body).map_err(|e| {
+        rest_error!("could not decode frames of {}: {}", transmitter, e);
+        StatusCode::from(e)
+    })?;
+
+    if messages.is_empty() {
+        rest_error!("no frames received from {}.", transmitter);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let count = messages.len();
+    let flight_id = broadcast.ingest(&transmitter, messages, Utc::now());
+    rest_debug!("decoded {} messages of flight {}.", count, flight_id);
+
+    Ok(Json(PostOdidResponse {
+        flight_id,
+        messages: count,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::tests::{bearer, credential};
+    use crate::odid::decoder::tests::{pack, BASIC_ID, LOCATION};
+
+    fn credentials() -> Extension<Arc<ProviderCredentials>> {
+        Extension(Arc::new(ProviderCredentials::parse(&credential(
+            "receiver", "secret",
+        ))))
+    }
+
+    fn query(transmitter: &str) -> Query<PostOdidRequest> {
+        Query(PostOdidRequest {
+            transmitter: transmitter.to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_post_odid() {
+        let broadcast = Arc::new(BroadcastFlights::default());
+
+        let body = Bytes::from(pack(&[BASIC_ID, LOCATION]));
+        let response = post_odid(
+            Extension(broadcast.clone()),
+            credentials(),
+            bearer("secret"),
+            query("AA:BB"),
+            body,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.flight_id, "1596F3546SN001");
+        assert_eq!(response.messages, 2);
+        assert!(broadcast.aircraft("receiver/aa:bb").is_some());
+        assert!(broadcast.aircraft("aa:bb").is_none());
+
+        let e = post_odid(
+            Extension(broadcast.clone()),
+            credentials(),
+            bearer("secret"),
+            query("aa:bb"),
+            Bytes::from_static(&LOCATION[..10]),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        let e = post_odid(
+            Extension(broadcast.clone()),
+            credentials(),
+            bearer("secret"),
+            query("aa:bb"),
+            Bytes::new(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        let body = Bytes::from_static(&LOCATION);
+        let e = post_odid(
+            Extension(broadcast),
+            credentials(),
+            bearer("secret"),
+            query(" "),
+            body,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_post_odid_unauthenticated() {
+        let broadcast = Arc::new(BroadcastFlights::default());
+
+        for headers in [HeaderMap::new(), bearer("wrong")] {
+            let body = Bytes::from(pack(&[BASIC_ID, LOCATION]));
+            let e = post_odid(
+                Extension(broadcast.clone()),
+                credentials(),
+                headers,
+                query("aa:bb"),
+                body,
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(e, StatusCode::UNAUTHORIZED);
+        }
+
+        assert!(broadcast.aircraft("receiver/aa:bb").is_none());
+    }
+}
@@ -0,0 +1,43 @@
+//! REST API for the cache of svc-gis flights
+//! The ops team checks how often display clients are served from the cache.
+
+use super::rest_types::*;
+use crate::cache::store::FlightsCache;
+use axum::{Extension, Json};
+use hyper::StatusCode;
+use std::sync::Arc;
+
+/// Get the hit and miss statistics of the cache of svc-gis flights
+#[utoipa::path(
+    get,
+    path = "/ops/cache/flights",
+    tag = "svc-discovery",
+    responses(
+        (status = 200, description = "Statistics were retrieved successfully.", body = FlightsCacheStats),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint.")
+    )
+)]
+pub async fn get_flights_cache_stats(
+    Extension(cache): Extension<Arc<FlightsCache>>,
+) -> Result<Json<FlightsCacheStats>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    Ok(Json(cache.stats()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_flights_cache_stats() {
+        let cache = Arc::new(FlightsCache::new(250));
+        let Json(stats) = get_flights_cache_stats(Extension(cache)).await.unwrap();
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.misses, 0);
+        assert_eq!(stats.max_staleness_ms, 250);
+    }
+}
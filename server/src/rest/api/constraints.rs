@@ -0,0 +1,193 @@
+//! REST API for ASTM F3548 constraints
+//! Implements the USS-USS interface at <https://github.com/astm-utm/Protocol/blob/master/utm.yaml>
+
+use super::rest_types::*;
+use crate::f3548::availability::UssAvailability;
+use crate::f3548::constraints::{Constraints, PeerConstraints};
+use axum::extract::Path;
+use axum::{Extension, Json};
+use hyper::StatusCode;
+use lib_common::time::Utc;
+use std::sync::Arc;
+
+/// Get the details of one of our constraints
+#[utoipa::path(
+    get,
+    path = "/uss/v1/constraints/{entityid}",
+    tag = "svc-discovery",
+    params(
+        ("entityid" = String, Path, description = "ID of the constraint")
+    ),
+    responses(
+        (status = 200, description = "Constraint details were retrieved successfully.", body = GetConstraintDetailsResponse),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 404, description = "The requested constraint was not found.")
+    )
+)]
+pub async fn get_constraint(
+    Extension(constraints): Extension<Arc<Constraints>>,
+    Extension(availability): Extension<Arc<UssAvailability>>,
+    Path(entityid): Path<String>,
+) -> Result<Json<GetConstraintDetailsResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let mut constraint = constraints.get(&entityid, Utc::now()).map_err(|e| {
+        rest_error!("could not get constraint {}: {}", entityid, e);
+        StatusCode::from(e)
+    })?;
+
+    constraint.reference.uss_availability = availability.own_state();
+    Ok(Json(GetConstraintDetailsResponse { constraint }))
+}
+
+/// Receive a notification of a changed constraint of another USS
+#[utoipa::path(
+    post,
+    path = "/uss/v1/constraints",
+    tag = "svc-discovery",
+    request_body = PutConstraintDetailsParameters,
+    responses(
+        (status = 204, description = "The notification was processed successfully."),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint.")
+    )
+)]
+pub async fn notify_constraint(
+    Extension(peer_constraints): Extension<Arc<PeerConstraints>>,
+    Json(payload): Json<PutConstraintDetailsParameters>,
+) -> Result<StatusCode, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let id = payload.constraint_id.clone();
+    peer_constraints.update(payload, Utc::now()).map_err(|e| {
+        rest_error!("invalid notification for constraint {}: {}", id, e);
+        StatusCode::from(e)
+    })?;
+
+    rest_info!("constraint {} updated.", id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::Duration;
+
+    fn volume() -> Volume4D {
+        let now = Utc::now();
+        Volume4D {
+            volume: Volume3D {
+                outline_circle: Some(Circle {
+                    center: LatLngPoint {
+                        lat: 52.37,
+                        lng: 4.89,
+                    },
+                    radius: Radius {
+                        value: 200.0,
+                        units: "M".to_string(),
+                    },
+                }),
+                outline_polygon: None,
+                altitude_lower: None,
+                altitude_upper: None,
+            },
+            time_start: now.into(),
+            time_end: (now + Duration::minutes(10)).into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_constraint() {
+        let constraints = Arc::new(Constraints::new("uss", "https://uss.example.com"));
+        let availability = Arc::new(UssAvailability::new("uss"));
+        let e = get_constraint(
+            Extension(constraints.clone()),
+            Extension(availability.clone()),
+            Path("zone".to_string()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::NOT_FOUND);
+
+        constraints
+            .put("zone", None, vec![volume()], Utc::now())
+            .unwrap();
+        let response = get_constraint(
+            Extension(constraints.clone()),
+            Extension(availability.clone()),
+            Path("zone".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.constraint.reference.id, "zone");
+        assert_eq!(response.constraint.details.volumes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_notify_constraint() {
+        let peer_constraints = Arc::new(PeerConstraints::default());
+
+        let payload = PutConstraintDetailsParameters {
+            constraint_id: "zone".to_string(),
+            constraint: None,
+            subscriptions: vec![],
+        };
+        let status = notify_constraint(Extension(peer_constraints.clone()), Json(payload))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        // A constraint without volumes is invalid
+        let now = Utc::now();
+        let payload = PutConstraintDetailsParameters {
+            constraint_id: "zone".to_string(),
+            constraint: Some(Constraint {
+                reference: ConstraintReference {
+                    id: "zone".to_string(),
+                    manager: "peer".to_string(),
+                    uss_availability: UssAvailabilityState::Normal,
+                    version: 1,
+                    ovn: None,
+                    time_start: now.into(),
+                    time_end: (now + Duration::minutes(10)).into(),
+                    uss_base_url: "https://peer.example.com".to_string(),
+                },
+                details: ConstraintDetails {
+                    volumes: vec![],
+                    constraint_type: None,
+                },
+            }),
+            subscriptions: vec![],
+        };
+        let e = notify_constraint(Extension(peer_constraints.clone()), Json(payload.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        let mut payload = payload;
+        if let Some(constraint) = payload.constraint.as_mut() {
+            constraint.details.volumes.push(volume());
+        }
+        let status = notify_constraint(Extension(peer_constraints.clone()), Json(payload))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(peer_constraints.get("zone", Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn test_constraint_type_serialization() {
+        let details = ConstraintDetails {
+            volumes: vec![],
+            constraint_type: Some("NoFlyZone".to_string()),
+        };
+        let json = serde_json::to_value(&details).unwrap();
+        assert_eq!(json["type"], "NoFlyZone");
+    }
+}
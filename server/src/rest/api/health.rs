@@ -1,13 +1,17 @@
 //! Rest API implementations
 
+use super::rest_types::UssAvailabilityState;
+use crate::f3548::availability::UssAvailability;
 use crate::grpc::client::GrpcClients;
 use axum::extract::Extension;
 use hyper::StatusCode;
+use std::sync::Arc;
 use svc_gis_client_grpc::client::ReadyRequest;
 use svc_gis_client_grpc::prelude::GisServiceClient;
 
 /// Provides a way to tell a caller if the service is healthy.
 /// Checks dependencies, making sure all connections can be made.
+/// Also reports unhealthy while this USS is marked as down.
 #[utoipa::path(
     get,
     path = "/health",
@@ -19,11 +23,17 @@ use svc_gis_client_grpc::prelude::GisServiceClient;
 )]
 pub async fn health_check(
     Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(availability): Extension<Arc<UssAvailability>>,
 ) -> Result<(), StatusCode> {
     rest_debug!("entry.");
 
     let mut ok = true;
 
+    if availability.own_state() == UssAvailabilityState::Down {
+        rest_error!("this USS is marked as down.");
+        ok = false;
+    };
+
     if grpc_clients.gis.is_ready(ReadyRequest {}).await.is_err() {
         rest_error!("svc-gis client unavailable.");
         ok = false;
@@ -49,7 +59,17 @@ mod tests {
     async fn test_health_check() {
         let config = crate::config::Config::default();
         let clients = GrpcClients::default(config);
-        let result = health_check(Extension(clients)).await;
+        let availability = Arc::new(UssAvailability::new("own"));
+        let result =
+            health_check(Extension(clients.clone()), Extension(availability.clone())).await;
         assert!(result.is_ok());
+
+        let params = super::super::rest_types::SetUssAvailabilityStatusParameters {
+            old_version: "".to_string(),
+            availability: UssAvailabilityState::Down,
+        };
+        availability.set("own", params).unwrap();
+        let result = health_check(Extension(clients), Extension(availability)).await;
+        assert_eq!(result.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
     }
 }
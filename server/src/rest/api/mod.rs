@@ -1,7 +1,23 @@
 //! REST API for the discovery service
 
+pub mod areas;
+pub mod availability;
+pub mod broadcast;
+pub mod cache;
+pub mod constraints;
 pub mod health;
+pub mod nearby;
+pub mod operational_intents;
+pub mod registry;
+pub mod reports;
+pub mod schedules;
+pub mod stats;
+pub mod telemetry;
+pub mod tiles;
+pub mod traffic;
 pub mod uss;
+pub mod vertiports;
+pub mod weather;
 
 /// openapi generated rest types
 pub mod rest_types {
@@ -0,0 +1,311 @@
+//! REST API for the flights nearest to a point
+//! Vertiport operators ask what is flying near their pad rather than in a
+//! rectangle, so flights are queried by radius around a point and reported
+//! nearest first, with their distance and bearing.
+
+use super::areas::AreaOutline;
+use super::rest_types::*;
+use super::uss::{
+    check_isas, get_live_flights, with_local_flights, Window, MAX_DISPLAY_AREA_DIAGONAL_METERS,
+};
+use crate::grpc::client::GrpcClients;
+use crate::odid::store::BroadcastFlights;
+use crate::telemetry::store::PushedTelemetry;
+use crate::traffic::index::LiveTraffic;
+use axum::extract::Query;
+use axum::{Extension, Json};
+use geo::algorithm::haversine_distance::HaversineDistance;
+use geo::HaversineBearing;
+use hyper::StatusCode;
+use std::sync::Arc;
+
+/// Number of reported flights if the request has no limit
+const DEFAULT_NEARBY_LIMIT: u32 = 10;
+
+/// Maximum number of reported flights
+const MAX_NEARBY_LIMIT: u32 = 100;
+
+/// Recent positions duration if the request has none, in seconds
+const DEFAULT_RECENT_POSITIONS_SECONDS: f32 = 5.0;
+
+/// The distance and bearing of a flight from a point, None if its current
+///  position is outside of the radius.
+/// The distance is 3D if the altitudes of the point and of the aircraft are
+///  known, horizontal otherwise.
+fn nearby_flight(
+    flight: RIDFlight,
+    center: geo::Point<f64>,
+    alt: Option<f32>,
+    radius_m: f64,
+) -> Option<NearbyFlight> {
+    let position = &flight.current_state.position;
+    let point = geo::Point::new(position.lng, position.lat);
+    let horizontal_distance_m = center.haversine_distance(&point);
+    if horizontal_distance_m > radius_m {
+        return None;
+    }
+
+    let distance_m = match alt {
+        Some(alt) if position.alt != UNKNOWN_ALTITUDE => {
+            horizontal_distance_m.hypot((position.alt - alt) as f64)
+        }
+        _ => horizontal_distance_m,
+    };
+
+    Some(NearbyFlight {
+        bearing_deg: center.haversine_bearing(point).rem_euclid(360.0),
+        distance_m,
+        horizontal_distance_m,
+        flight,
+    })
+}
+
+/// The flights within a radius of a point, nearest first
+fn nearest(
+    flights: Vec<RIDFlight>,
+    center: geo::Point<f64>,
+    alt: Option<f32>,
+    radius_m: f64,
+    limit: usize,
+) -> Vec<NearbyFlight> {
+    let mut nearby = flights
+        .into_iter()
+        .filter_map(|flight| nearby_flight(flight, center, alt, radius_m))
+        .collect::<Vec<NearbyFlight>>();
+    nearby.sort_by(|a, b| a.distance_m.total_cmp(&b.distance_m));
+    nearby.truncate(limit);
+    nearby
+}
+
+/// Validate the input for the get_nearby_flights endpoint.
+/// Returns the window bounding the radius, the recent positions duration
+///  and the maximum number of flights.
+fn validate_get_nearby_flights_request(
+    request: &GetNearbyFlightsRequest,
+) -> Result<(Window, f32, usize), StatusCode> {
+    if !(-90.0..=90.0).contains(&request.lat) || !(-180.0..=180.0).contains(&request.lng) {
+        rest_error!("point coordinates out of range.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if !request.radius_m.is_finite() || request.radius_m <= 0.0 {
+        rest_error!("radius_m must be a positive number of meters.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if request.alt.is_some_and(|alt| !alt.is_finite()) {
+        rest_error!("alt must be a finite value in meters.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let duration_s = request
+        .recent_positions_duration
+        .unwrap_or(DEFAULT_RECENT_POSITIONS_SECONDS);
+    if !(0.0..=60.0).contains(&duration_s) {
+        rest_error!("recent_positions_duration must be between 0.0 and 60.0.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let limit = request.limit.unwrap_or(DEFAULT_NEARBY_LIMIT);
+    if !(1..=MAX_NEARBY_LIMIT).contains(&limit) {
+        rest_error!("limit must be between 1 and {}.", MAX_NEARBY_LIMIT);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let window = AreaOutline::Circle {
+        center: geo::Point::new(request.lng, request.lat),
+        radius_meters: request.radius_m,
+    }
+    .window();
+    if window.diagonal() > MAX_DISPLAY_AREA_DIAGONAL_METERS {
+        rest_error!("The requested radius was too large.");
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    Ok((window, duration_s, limit as usize))
+}
+
+/// Get the flights nearest to a point
+#[utoipa::path(
+    get,
+    path = "/uss/flights/nearby",
+    tag = "svc-discovery",
+    params(GetNearbyFlightsRequest),
+    responses(
+        (status = 200, description = "Flight information was successfully retrieved.", body = GetNearbyFlightsResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 413, description = "The requested radius was too large.")
+    )
+)]
+pub async fn get_nearby_flights(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(broadcast): Extension<Arc<BroadcastFlights>>,
+    Extension(telemetry): Extension<Arc<PushedTelemetry>>,
+    Extension(traffic): Extension<Arc<LiveTraffic>>,
+    Query(query): Query<GetNearbyFlightsRequest>,
+) -> Result<Json<GetNearbyFlightsResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let (window, duration_s, limit) = validate_get_nearby_flights_request(&query)?;
+    let flights = get_live_flights(&grpc_clients, traffic, &window, duration_s).await?;
+    let flights = with_local_flights(flights, &broadcast, &telemetry, &window, duration_s);
+    let center = geo::Point::new(query.lng, query.lat);
+
+    Ok(Json(GetNearbyFlightsResponse {
+        timestamp: Time::default(),
+        flights: nearest(flights, center, query.alt, query.radius_m, limit),
+        no_isas_present: !check_isas(&mut grpc_clients.clone(), &window).await?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::tests::flight;
+    use lib_common::time::Utc;
+
+    /// Vertiport pad at Amsterdam Centraal
+    const PAD: (f64, f64) = (52.3791, 4.9003);
+
+    fn request(radius_m: f64) -> GetNearbyFlightsRequest {
+        GetNearbyFlightsRequest {
+            lat: PAD.0,
+            lng: PAD.1,
+            alt: None,
+            radius_m,
+            limit: None,
+            recent_positions_duration: None,
+        }
+    }
+
+    #[test]
+    fn test_nearest() {
+        let now = Utc::now();
+        let center = geo::Point::new(PAD.1, PAD.0);
+        let north = flight("NORTH", now, PAD.0 + 0.001, PAD.1);
+        let mut east = flight("EAST", now, PAD.0, PAD.1 + 0.002);
+        east.current_state.position.alt = 0.0;
+        let far = flight("FAR", now, PAD.0 + 0.1, PAD.1);
+        let flights = vec![far, east, north];
+
+        let nearby = nearest(flights.clone(), center, None, 1_000.0, 10);
+        let ids = nearby
+            .iter()
+            .map(|nearby| nearby.flight.id.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(ids, vec!["NORTH", "EAST"]);
+        assert!((nearby[0].horizontal_distance_m - 111.2).abs() < 1.0);
+        assert_eq!(nearby[0].distance_m, nearby[0].horizontal_distance_m);
+        assert!(nearby[0].bearing_deg < 0.1 || nearby[0].bearing_deg > 359.9);
+        assert!((nearby[1].bearing_deg - 90.0).abs() < 0.1);
+
+        // the altitude of the pad brings the low flight closer
+        let nearby = nearest(flights.clone(), center, Some(0.0), 1_000.0, 10);
+        assert_eq!(nearby[0].flight.id, "EAST");
+        assert!(nearby[1].distance_m > nearby[1].horizontal_distance_m);
+
+        // flights with an unknown altitude keep their horizontal distance
+        let mut unknown = flight("UNKNOWN", now, PAD.0 + 0.001, PAD.1);
+        unknown.current_state.position.alt = UNKNOWN_ALTITUDE;
+        let nearby = nearest(vec![unknown], center, Some(0.0), 1_000.0, 10);
+        assert_eq!(nearby[0].distance_m, nearby[0].horizontal_distance_m);
+
+        let nearby = nearest(flights, center, None, 1_000.0, 1);
+        assert_eq!(nearby.len(), 1);
+    }
+
+    #[test]
+    fn test_validate() {
+        let (window, duration_s, limit) =
+            validate_get_nearby_flights_request(&request(500.0)).unwrap();
+        assert!(window.lat1 < PAD.0 && PAD.0 < window.lat2);
+        assert_eq!(duration_s, DEFAULT_RECENT_POSITIONS_SECONDS);
+        assert_eq!(limit, DEFAULT_NEARBY_LIMIT as usize);
+
+        let mut invalid = vec![request(0.0), request(f64::NAN)];
+        invalid.push(GetNearbyFlightsRequest {
+            lat: 91.0,
+            ..request(500.0)
+        });
+        invalid.push(GetNearbyFlightsRequest {
+            limit: Some(0),
+            ..request(500.0)
+        });
+        invalid.push(GetNearbyFlightsRequest {
+            limit: Some(MAX_NEARBY_LIMIT + 1),
+            ..request(500.0)
+        });
+        invalid.push(GetNearbyFlightsRequest {
+            recent_positions_duration: Some(61.0),
+            ..request(500.0)
+        });
+        invalid.push(GetNearbyFlightsRequest {
+            alt: Some(f32::INFINITY),
+            ..request(500.0)
+        });
+        for request in invalid {
+            assert_eq!(
+                validate_get_nearby_flights_request(&request).unwrap_err(),
+                StatusCode::BAD_REQUEST,
+                "{:?}",
+                request
+            );
+        }
+
+        assert_eq!(
+            validate_get_nearby_flights_request(&request(5_000.0)).unwrap_err(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_nearby_flights() {
+        let config = crate::config::Config::default();
+        let grpc_clients = Extension(GrpcClients::default(config));
+        let broadcast = Extension(Arc::new(BroadcastFlights::default()));
+        let telemetry = Arc::new(PushedTelemetry::new(["acme".to_string()].into()));
+        let traffic = Extension(Arc::new(LiveTraffic::new(None, 1_000)));
+        let now = Utc::now();
+        for (id, lat, lng) in [
+            ("FAR", PAD.0 + 0.004, PAD.1),
+            ("NEAR", PAD.0 + 0.001, PAD.1),
+            ("OUT", PAD.0 + 0.01, PAD.1),
+        ] {
+            let state = crate::telemetry::tests::state(now, lat, lng);
+            telemetry
+                .push("acme", id, UAType::Helicopter, false, vec![state], now)
+                .unwrap();
+        }
+
+        let Json(response) = get_nearby_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            Extension(telemetry.clone()),
+            traffic.clone(),
+            Query(request(1_000.0)),
+        )
+        .await
+        .unwrap();
+        let ids = response
+            .flights
+            .iter()
+            .map(|nearby| nearby.flight.id.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(ids, vec!["NEAR", "FAR"]);
+
+        let e = get_nearby_flights(
+            grpc_clients,
+            broadcast,
+            Extension(telemetry),
+            traffic,
+            Query(request(5_000.0)),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
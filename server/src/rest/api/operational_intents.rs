@@ -0,0 +1,379 @@
+//! REST API for ASTM F3548 operational intents
+//! Implements the USS-USS interface at <https://github.com/astm-utm/Protocol/blob/master/utm.yaml>
+
+use super::rest_types::*;
+use super::uss::get_recent_flights;
+use crate::f3548::availability::UssAvailability;
+use crate::f3548::flight_plans::FlightPlans;
+use crate::f3548::intents::PeerIntents;
+use crate::f3548::F3548Error;
+use crate::grpc::client::GrpcClients;
+use axum::extract::Path;
+use axum::{Extension, Json};
+use hyper::StatusCode;
+use lib_common::time::{Duration, Utc};
+use std::sync::Arc;
+
+/// Telemetry older than this is not reported
+const TELEMETRY_MAX_AGE_SECONDS: f32 = 5.0;
+
+impl From<F3548Error> for StatusCode {
+    fn from(e: F3548Error) -> Self {
+        match e {
+            F3548Error::NotFound => StatusCode::NOT_FOUND,
+            F3548Error::InvalidPath
+            | F3548Error::InvalidTimeRange
+            | F3548Error::InvalidVolume
+            | F3548Error::IdMismatch => StatusCode::BAD_REQUEST,
+            F3548Error::VersionMismatch => StatusCode::CONFLICT,
+            F3548Error::Storage => StatusCode::INTERNAL_SERVER_ERROR,
+            F3548Error::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+        }
+    }
+}
+
+impl From<&RIDAircraftState> for VehicleTelemetry {
+    fn from(state: &RIDAircraftState) -> Self {
+        VehicleTelemetry {
+            time_measured: state.timestamp.clone(),
+            position: Position {
+                longitude: state.position.lng,
+                latitude: state.position.lat,
+                accuracy_h: state.position.accuracy_h,
+                accuracy_v: state.position.accuracy_v,
+                extrapolated: state.position.extrapolated,
+                altitude: Altitude {
+                    value: state.position.alt as f64,
+                    ..Default::default()
+                },
+            },
+            velocity: Velocity {
+                speed: state.speed,
+                units_speed: "MetersPerSecond".to_string(),
+                track: state.track,
+                speed_type: "Ground".to_string(),
+            },
+        }
+    }
+}
+
+/// Get the details of one of our operational intents
+#[utoipa::path(
+    get,
+    path = "/uss/v1/operational_intents/{entityid}",
+    tag = "svc-discovery",
+    params(
+        ("entityid" = String, Path, description = "ID of the operational intent")
+    ),
+    responses(
+        (status = 200, description = "Operational intent details were retrieved successfully.", body = GetOperationalIntentDetailsResponse),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 404, description = "The requested operational intent was not found.")
+    )
+)]
+pub async fn get_operational_intent(
+    Extension(flight_plans): Extension<Arc<FlightPlans>>,
+    Extension(availability): Extension<Arc<UssAvailability>>,
+    Path(entityid): Path<String>,
+) -> Result<Json<GetOperationalIntentDetailsResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let mut operational_intent = flight_plans
+        .get_operational_intent(&entityid, Utc::now())
+        .map_err(|e| {
+            rest_error!("could not get operational intent {}: {}", entityid, e);
+            StatusCode::from(e)
+        })?;
+
+    operational_intent.reference.uss_availability = availability.own_state();
+    Ok(Json(GetOperationalIntentDetailsResponse {
+        operational_intent,
+    }))
+}
+
+/// Get the most recent telemetry of the flight of one of our operational intents
+#[utoipa::path(
+    get,
+    path = "/uss/v1/operational_intents/{entityid}/telemetry",
+    tag = "svc-discovery",
+    params(
+        ("entityid" = String, Path, description = "ID of the operational intent")
+    ),
+    responses(
+        (status = 200, description = "Telemetry was retrieved successfully.", body = GetOperationalIntentTelemetryResponse),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 404, description = "The requested operational intent was not found."),
+        (status = 409, description = "The operational intent is not in a state that provides telemetry.")
+    )
+)]
+pub async fn get_operational_intent_telemetry(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(flight_plans): Extension<Arc<FlightPlans>>,
+    Path(entityid): Path<String>,
+) -> Result<Json<GetOperationalIntentTelemetryResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let now = Utc::now();
+    let plan = flight_plans.get(&entityid, now).map_err(|e| {
+        rest_error!("could not get operational intent {}: {}", entityid, e);
+        StatusCode::from(e)
+    })?;
+
+    if plan.state == OperationalIntentState::Accepted {
+        rest_info!("operational intent {} has not been activated.", entityid);
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let telemetry = get_recent_flights(
+        &mut grpc_clients.clone(),
+        &plan.window(),
+        TELEMETRY_MAX_AGE_SECONDS,
+    )
+    .await?
+    .iter()
+    .find(|flight| flight.id == plan.session_id())
+    .map(|flight| VehicleTelemetry::from(&flight.current_state));
+
+    // The flight keeps reporting until the end of the plan
+    let next = now + Duration::milliseconds((TELEMETRY_MAX_AGE_SECONDS * 1000.0) as i64);
+    let next_telemetry_opportunity = match next < plan.time_end {
+        true => Some(next.into()),
+        false => None,
+    };
+
+    Ok(Json(GetOperationalIntentTelemetryResponse {
+        operational_intent_id: entityid,
+        telemetry,
+        next_telemetry_opportunity,
+    }))
+}
+
+/// Receive a notification of a changed operational intent of another USS
+#[utoipa::path(
+    post,
+    path = "/uss/v1/operational_intents",
+    tag = "svc-discovery",
+    request_body = PutOperationalIntentDetailsParameters,
+    responses(
+        (status = 204, description = "The notification was processed successfully."),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint.")
+    )
+)]
+pub async fn notify_operational_intent(
+    Extension(peer_intents): Extension<Arc<PeerIntents>>,
+    Json(payload): Json<PutOperationalIntentDetailsParameters>,
+) -> Result<StatusCode, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let id = payload.operational_intent_id.clone();
+    peer_intents.update(payload, Utc::now()).map_err(|e| {
+        rest_error!("invalid notification for operational intent {}: {}", id, e);
+        StatusCode::from(e)
+    })?;
+
+    rest_info!("operational intent {} updated.", id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::f3548::flight_plans::{FlightPlan, PathPoint};
+
+    fn plan(state: OperationalIntentState) -> FlightPlan {
+        let now = Utc::now();
+        FlightPlan {
+            id: "intent".to_string(),
+            session_id: Some("session".to_string()),
+            priority: 0,
+            state,
+            time_start: now,
+            time_end: now + Duration::minutes(10),
+            path: vec![
+                PathPoint {
+                    latitude: 52.37,
+                    longitude: 4.89,
+                    altitude_meters: 100.0,
+                },
+                PathPoint {
+                    latitude: 52.38,
+                    longitude: 4.90,
+                    altitude_meters: 100.0,
+                },
+            ],
+            origin_vertiport_id: None,
+            target_vertiport_id: None,
+        }
+    }
+
+    #[test]
+    fn test_from_f3548_error() {
+        assert_eq!(
+            StatusCode::from(F3548Error::NotFound),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            StatusCode::from(F3548Error::IdMismatch),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_operational_intent() {
+        let flight_plans = Arc::new(FlightPlans::new("uss", "https://uss.example.com"));
+        let availability = Arc::new(UssAvailability::new("uss"));
+        let e = get_operational_intent(
+            Extension(flight_plans.clone()),
+            Extension(availability.clone()),
+            Path("intent".to_string()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::NOT_FOUND);
+
+        flight_plans
+            .put(plan(OperationalIntentState::Accepted), Utc::now())
+            .unwrap();
+        let response = get_operational_intent(
+            Extension(flight_plans.clone()),
+            Extension(availability.clone()),
+            Path("intent".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.operational_intent.reference.id, "intent");
+        assert_eq!(response.operational_intent.details.volumes.len(), 1);
+        assert_eq!(
+            response.operational_intent.reference.uss_availability,
+            UssAvailabilityState::Normal
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_operational_intent_telemetry() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let flight_plans = Arc::new(FlightPlans::new("uss", "https://uss.example.com"));
+
+        let e = get_operational_intent_telemetry(
+            Extension(grpc_clients.clone()),
+            Extension(flight_plans.clone()),
+            Path("intent".to_string()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::NOT_FOUND);
+
+        // No telemetry before activation
+        flight_plans
+            .put(plan(OperationalIntentState::Accepted), Utc::now())
+            .unwrap();
+        let e = get_operational_intent_telemetry(
+            Extension(grpc_clients.clone()),
+            Extension(flight_plans.clone()),
+            Path("intent".to_string()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::CONFLICT);
+
+        flight_plans
+            .put(plan(OperationalIntentState::Activated), Utc::now())
+            .unwrap();
+        let response = get_operational_intent_telemetry(
+            Extension(grpc_clients.clone()),
+            Extension(flight_plans.clone()),
+            Path("intent".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.operational_intent_id, "intent");
+        assert!(response.next_telemetry_opportunity.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_notify_operational_intent() {
+        let peer_intents = Arc::new(PeerIntents::default());
+
+        let payload = PutOperationalIntentDetailsParameters {
+            operational_intent_id: "peer".to_string(),
+            operational_intent: None,
+            subscriptions: vec![],
+        };
+        let status = notify_operational_intent(Extension(peer_intents.clone()), Json(payload))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let now = Utc::now();
+        let payload = PutOperationalIntentDetailsParameters {
+            operational_intent_id: "other".to_string(),
+            operational_intent: Some(OperationalIntent {
+                reference: OperationalIntentReference {
+                    id: "peer".to_string(),
+                    manager: "peer".to_string(),
+                    uss_availability: UssAvailabilityState::Normal,
+                    version: 1,
+                    state: OperationalIntentState::Accepted,
+                    ovn: None,
+                    time_start: now.into(),
+                    time_end: (now + Duration::minutes(1)).into(),
+                    uss_base_url: "https://peer.example.com".to_string(),
+                    subscription_id: "sub".to_string(),
+                },
+                details: OperationalIntentDetails {
+                    volumes: vec![],
+                    off_nominal_volumes: vec![],
+                    priority: 0,
+                },
+            }),
+            subscriptions: vec![],
+        };
+        let e = notify_operational_intent(Extension(peer_intents.clone()), Json(payload))
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_vehicle_telemetry_from_state() {
+        let state = RIDAircraftState {
+            timestamp: Time::default(),
+            timestamp_accuracy: 0.0,
+            operational_status: RIDOperationalStatus::Airborne,
+            position: RIDAircraftPosition {
+                lat: 52.37,
+                lng: 4.89,
+                alt: 120.0,
+                accuracy_h: HorizontalAccuracy::HAUnknown,
+                accuracy_v: VerticalAccuracy::VAUnknown,
+                extrapolated: false,
+                pressure_alt: -1000.0,
+                height: RIDHeight {
+                    distance: 0.0,
+                    reference: RIDHeightReference::TakeoffLocation,
+                },
+            },
+            track: 90.0,
+            speed: 12.0,
+            speed_accuracy: SpeedAccuracy::SAUnknown,
+            vertical_speed: 0.0,
+        };
+
+        let telemetry = VehicleTelemetry::from(&state);
+        assert_eq!(telemetry.position.latitude, 52.37);
+        assert_eq!(telemetry.position.altitude.value, 120.0);
+        assert_eq!(telemetry.velocity.speed, 12.0);
+        assert_eq!(telemetry.velocity.track, 90.0);
+    }
+}
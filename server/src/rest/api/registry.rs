@@ -0,0 +1,416 @@
+//! REST API for the service provider registry
+//! Admin endpoints to maintain the known providers and a lookup of the
+//! providers offering a service in an area.
+//! Partner base URLs are taken from the registry, so only admin providers
+//! may change it.
+
+use super::rest_types::*;
+use super::uss::parse_view;
+use crate::auth::ProviderCredentials;
+use crate::registry::store::{RegistryError, ServiceProviders};
+use axum::extract::{Path, Query};
+use axum::http::HeaderMap;
+use axum::{Extension, Json};
+use hyper::StatusCode;
+use std::sync::Arc;
+
+impl From<RegistryError> for StatusCode {
+    fn from(e: RegistryError) -> Self {
+        match e {
+            RegistryError::NotFound => StatusCode::NOT_FOUND,
+            RegistryError::AlreadyExists => StatusCode::CONFLICT,
+            RegistryError::IdMismatch | RegistryError::InvalidProvider => StatusCode::BAD_REQUEST,
+            RegistryError::Storage => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// List all known service providers
+#[utoipa::path(
+    get,
+    path = "/registry/providers",
+    tag = "svc-discovery",
+    responses(
+        (status = 200, description = "Providers were retrieved successfully.", body = ListServiceProvidersResponse),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint.")
+    )
+)]
+pub async fn list_providers(
+    Extension(registry): Extension<Arc<ServiceProviders>>,
+) -> Result<Json<ListServiceProvidersResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    Ok(Json(ListServiceProvidersResponse {
+        providers: registry.list(),
+    }))
+}
+
+/// Register a new service provider
+#[utoipa::path(
+    post,
+    path = "/registry/providers",
+    tag = "svc-discovery",
+    request_body = ServiceProvider,
+    responses(
+        (status = 201, description = "The provider was registered successfully.", body = ServiceProvider),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The provider of the access token is not an admin."),
+        (status = 409, description = "A provider with this ID already exists."),
+        (status = 500, description = "The registry could not be persisted.")
+    )
+)]
+pub async fn create_provider(
+    Extension(registry): Extension<Arc<ServiceProviders>>,
+    Extension(credentials): Extension<Arc<ProviderCredentials>>,
+    headers: HeaderMap,
+    Json(payload): Json<ServiceProvider>,
+) -> Result<(StatusCode, Json<ServiceProvider>), StatusCode> {
+    rest_debug!("entry.");
+
+    let admin = credentials.authenticated_admin(&headers)?;
+
+    let id = payload.id.clone();
+    let provider = registry.create(payload).map_err(|e| {
+        rest_error!("could not register provider {}: {}", id, e);
+        StatusCode::from(e)
+    })?;
+
+    rest_info!("provider {} registered by {}.", id, admin);
+    Ok((StatusCode::CREATED, Json(provider)))
+}
+
+/// Get a service provider
+#[utoipa::path(
+    get,
+    path = "/registry/providers/{id}",
+    tag = "svc-discovery",
+    params(
+        ("id" = String, Path, description = "ID of the provider")
+    ),
+    responses(
+        (status = 200, description = "The provider was retrieved successfully.", body = ServiceProvider),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 404, description = "The requested provider was not found.")
+    )
+)]
+pub async fn get_provider(
+    Extension(registry): Extension<Arc<ServiceProviders>>,
+    Path(id): Path<String>,
+) -> Result<Json<ServiceProvider>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let provider = registry.get(&id).map_err(|e| {
+        rest_error!("could not get provider {}: {}", id, e);
+        StatusCode::from(e)
+    })?;
+
+    Ok(Json(provider))
+}
+
+/// Replace a registered service provider
+#[utoipa::path(
+    put,
+    path = "/registry/providers/{id}",
+    tag = "svc-discovery",
+    params(
+        ("id" = String, Path, description = "ID of the provider")
+    ),
+    request_body = ServiceProvider,
+    responses(
+        (status = 200, description = "The provider was updated successfully.", body = ServiceProvider),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The provider of the access token is not an admin."),
+        (status = 404, description = "The requested provider was not found."),
+        (status = 500, description = "The registry could not be persisted.")
+    )
+)]
+pub async fn update_provider(
+    Extension(registry): Extension<Arc<ServiceProviders>>,
+    Extension(credentials): Extension<Arc<ProviderCredentials>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<ServiceProvider>,
+) -> Result<Json<ServiceProvider>, StatusCode> {
+    rest_debug!("entry.");
+
+    let admin = credentials.authenticated_admin(&headers)?;
+
+    let provider = registry.replace(&id, payload).map_err(|e| {
+        rest_error!("could not update provider {}: {}", id, e);
+        StatusCode::from(e)
+    })?;
+
+    rest_info!("provider {} updated by {}.", id, admin);
+    Ok(Json(provider))
+}
+
+/// Remove a registered service provider
+#[utoipa::path(
+    delete,
+    path = "/registry/providers/{id}",
+    tag = "svc-discovery",
+    params(
+        ("id" = String, Path, description = "ID of the provider")
+    ),
+    responses(
+        (status = 200, description = "The provider was removed successfully.", body = ServiceProvider),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The provider of the access token is not an admin."),
+        (status = 404, description = "The requested provider was not found."),
+        (status = 500, description = "The registry could not be persisted.")
+    )
+)]
+pub async fn delete_provider(
+    Extension(registry): Extension<Arc<ServiceProviders>>,
+    Extension(credentials): Extension<Arc<ProviderCredentials>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ServiceProvider>, StatusCode> {
+    rest_debug!("entry.");
+
+    let admin = credentials.authenticated_admin(&headers)?;
+
+    let provider = registry.delete(&id).map_err(|e| {
+        rest_error!("could not remove provider {}: {}", id, e);
+        StatusCode::from(e)
+    })?;
+
+    rest_info!("provider {} removed by {}.", id, admin);
+    Ok(Json(provider))
+}
+
+/// Find the providers offering a service in an area
+#[utoipa::path(
+    get,
+    path = "/registry/lookup",
+    tag = "svc-discovery",
+    params(LookupServiceProvidersRequest),
+    responses(
+        (status = 200, description = "Providers were retrieved successfully.", body = ListServiceProvidersResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint.")
+    )
+)]
+pub async fn lookup_providers(
+    Extension(registry): Extension<Arc<ServiceProviders>>,
+    Query(query): Query<LookupServiceProvidersRequest>,
+) -> Result<Json<ListServiceProvidersResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let window = parse_view(&query.view)?;
+    Ok(Json(ListServiceProvidersResponse {
+        providers: registry.lookup(&window, query.service),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::tests::{bearer, credential};
+    use crate::registry::store::tests::{provider, temp_path};
+
+    #[test]
+    fn test_from_registry_error() {
+        assert_eq!(
+            StatusCode::from(RegistryError::NotFound),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            StatusCode::from(RegistryError::AlreadyExists),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            StatusCode::from(RegistryError::InvalidProvider),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            StatusCode::from(RegistryError::Storage),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    fn credentials() -> Extension<Arc<ProviderCredentials>> {
+        let value = format!(
+            "{},{}",
+            credential("ops", "token-ops"),
+            credential("ussp", "token-ussp")
+        );
+        Extension(Arc::new(
+            ProviderCredentials::parse(&value).with_admins("ops"),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_provider_crud() {
+        let path = temp_path("rest-registry");
+        let registry = Arc::new(ServiceProviders::load(&path));
+        let admin = || bearer("token-ops");
+
+        let (status, _) = create_provider(
+            Extension(registry.clone()),
+            credentials(),
+            admin(),
+            Json(provider("ussp")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let e = create_provider(
+            Extension(registry.clone()),
+            credentials(),
+            admin(),
+            Json(provider("ussp")),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::CONFLICT);
+
+        let response = list_providers(Extension(registry.clone())).await.unwrap();
+        assert_eq!(response.providers.len(), 1);
+
+        let mut updated = provider("ussp");
+        updated.name = "Renamed".to_string();
+        let e = update_provider(
+            Extension(registry.clone()),
+            credentials(),
+            admin(),
+            Path("other".to_string()),
+            Json(updated.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        let response = update_provider(
+            Extension(registry.clone()),
+            credentials(),
+            admin(),
+            Path("ussp".to_string()),
+            Json(updated),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.name, "Renamed");
+        let response = get_provider(Extension(registry.clone()), Path("ussp".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.name, "Renamed");
+
+        let response = delete_provider(
+            Extension(registry.clone()),
+            credentials(),
+            admin(),
+            Path("ussp".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.id, "ussp");
+        let e = get_provider(Extension(registry.clone()), Path("ussp".to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::NOT_FOUND);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_provider_writes_require_admin() {
+        let path = temp_path("rest-registry-admin");
+        let registry = Arc::new(ServiceProviders::load(&path));
+        registry.create(provider("ussp")).unwrap();
+
+        // without a token, or with the token of the provider itself
+        for (headers, status) in [
+            (HeaderMap::new(), StatusCode::UNAUTHORIZED),
+            (bearer("token-other"), StatusCode::UNAUTHORIZED),
+            (bearer("token-ussp"), StatusCode::FORBIDDEN),
+        ] {
+            let e = create_provider(
+                Extension(registry.clone()),
+                credentials(),
+                headers.clone(),
+                Json(provider("internal")),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(e, status);
+
+            let mut updated = provider("ussp");
+            updated.name = "Renamed".to_string();
+            let e = update_provider(
+                Extension(registry.clone()),
+                credentials(),
+                headers.clone(),
+                Path("ussp".to_string()),
+                Json(updated),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(e, status);
+
+            let e = delete_provider(
+                Extension(registry.clone()),
+                credentials(),
+                headers,
+                Path("ussp".to_string()),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(e, status);
+        }
+
+        let providers = registry.list();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].name, provider("ussp").name);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_lookup_providers() {
+        let path = temp_path("rest-registry-lookup");
+        let registry = Arc::new(ServiceProviders::load(&path));
+        registry.create(provider("ussp")).unwrap();
+
+        let query = LookupServiceProvidersRequest {
+            view: "52.35,4.85,52.36,4.86".to_string(),
+            service: ProviderService::Rid,
+        };
+        let response = lookup_providers(Extension(registry.clone()), Query(query))
+            .await
+            .unwrap();
+        assert_eq!(response.providers.len(), 1);
+
+        let query = LookupServiceProvidersRequest {
+            view: "52.35,4.85,52.36,4.86".to_string(),
+            service: ProviderService::F3548,
+        };
+        let response = lookup_providers(Extension(registry.clone()), Query(query))
+            .await
+            .unwrap();
+        assert!(response.providers.is_empty());
+
+        let query = LookupServiceProvidersRequest {
+            view: "52.35,4.85,52.36".to_string(),
+            service: ProviderService::Rid,
+        };
+        let e = lookup_providers(Extension(registry.clone()), Query(query))
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
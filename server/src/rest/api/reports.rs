@@ -0,0 +1,262 @@
+//! REST API for ASTM F3548 error reports
+//! Peers submit reports of erroneous exchanges, the ops team queries them.
+
+use super::rest_types::*;
+use crate::auth::ProviderCredentials;
+use crate::f3548::reports::ErrorReports;
+use axum::extract::Query;
+use axum::http::HeaderMap;
+use axum::{Extension, Json};
+use hyper::StatusCode;
+use lib_common::time::{DateTime, Utc};
+use std::sync::Arc;
+
+/// Number of reports returned if no limit is provided
+const DEFAULT_REPORTS_LIMIT: usize = 100;
+
+/// Maximum number of reports returned by a single query
+const MAX_REPORTS_LIMIT: usize = 1_000;
+
+/// Parse an optional RFC3339 time from a query parameter
+fn parse_time(value: &Option<String>) -> Result<Option<DateTime<Utc>>, StatusCode> {
+    value
+        .as_ref()
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(|e| {
+                    rest_error!("invalid RFC3339 time {}: {}", value, e);
+                    StatusCode::BAD_REQUEST
+                })
+        })
+        .transpose()
+}
+
+/// Submit a report of an erroneous exchange with this USS
+#[utoipa::path(
+    post,
+    path = "/uss/v1/reports",
+    tag = "svc-discovery",
+    request_body = ErrorReport,
+    responses(
+        (status = 201, description = "The report was stored successfully.", body = ErrorReport),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 413, description = "The report is too large."),
+        (status = 500, description = "The report could not be stored.")
+    )
+)]
+pub async fn submit_report(
+    Extension(reports): Extension<Arc<ErrorReports>>,
+    Extension(credentials): Extension<Arc<ProviderCredentials>>,
+    headers: HeaderMap,
+    Json(payload): Json<ErrorReport>,
+) -> Result<(StatusCode, Json<ErrorReport>), StatusCode> {
+    rest_debug!("entry.");
+
+    let provider = credentials.authenticated_provider(&headers)?;
+    let report = reports.add(payload, Utc::now()).await.map_err(|e| {
+        rest_error!("could not store report of {}: {}", provider, e);
+        StatusCode::from(e)
+    })?;
+
+    rest_warn!(
+        "received error report {:?} from {} for {} {}: {:?}",
+        report.report_id,
+        provider,
+        report.exchange.method,
+        report.exchange.url,
+        report.exchange.problem
+    );
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+/// Query the received reports, most recent first
+#[utoipa::path(
+    get,
+    path = "/ops/reports",
+    tag = "svc-discovery",
+    params(QueryErrorReportsRequest),
+    responses(
+        (status = 200, description = "Reports were retrieved successfully.", body = QueryErrorReportsResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint.")
+    )
+)]
+pub async fn query_reports(
+    Extension(reports): Extension<Arc<ErrorReports>>,
+    Extension(credentials): Extension<Arc<ProviderCredentials>>,
+    headers: HeaderMap,
+    Query(query): Query<QueryErrorReportsRequest>,
+) -> Result<Json<QueryErrorReportsResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    credentials.authenticated_admin(&headers)?;
+
+    let since = parse_time(&query.since)?;
+    let until = parse_time(&query.until)?;
+    let limit = query.limit.unwrap_or(DEFAULT_REPORTS_LIMIT);
+    if limit > MAX_REPORTS_LIMIT {
+        rest_error!("limit must be <= {}.", MAX_REPORTS_LIMIT);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(Json(QueryErrorReportsResponse {
+        reports: reports.query(since, until, limit),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::tests::{bearer, credential};
+    use crate::f3548::reports::MAX_REPORT_BYTES;
+
+    fn credentials() -> Arc<ProviderCredentials> {
+        let value = format!(
+            "{},{}",
+            credential("ops", "token-ops"),
+            credential("peer", "token-peer")
+        );
+        Arc::new(ProviderCredentials::parse(&value).with_admins("ops"))
+    }
+
+    fn report() -> ErrorReport {
+        ErrorReport {
+            report_id: Some("ignored".to_string()),
+            exchange: ExchangeRecord {
+                url: "https://uss.example.com/uss/v1/operational_intents/abc".to_string(),
+                method: "GET".to_string(),
+                headers: vec![],
+                recorder_role: RecorderRole::Client,
+                request_time: Time::default(),
+                request_body: None,
+                response_time: None,
+                response_body: None,
+                response_code: Some(404),
+                problem: Some("operational intent not found".to_string()),
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert!(parse_time(&None).unwrap().is_none());
+        assert!(parse_time(&Some("2023-01-01T00:00:00Z".to_string()))
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            parse_time(&Some("yesterday".to_string())).unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_query_reports() {
+        let path = std::env::temp_dir().join(format!(
+            "svc-discovery-rest-reports-{}.jsonl",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let reports = Arc::new(ErrorReports::load(&path.to_string_lossy()));
+
+        let credentials = Extension(credentials());
+
+        let e = submit_report(
+            Extension(reports.clone()),
+            credentials.clone(),
+            HeaderMap::new(),
+            Json(report()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::UNAUTHORIZED);
+
+        let (status, Json(stored)) = submit_report(
+            Extension(reports.clone()),
+            credentials.clone(),
+            bearer("token-peer"),
+            Json(report()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_ne!(stored.report_id, Some("ignored".to_string()));
+
+        let mut large = report();
+        large.exchange.response_body = Some("x".repeat(MAX_REPORT_BYTES));
+        let e = submit_report(
+            Extension(reports.clone()),
+            credentials.clone(),
+            bearer("token-peer"),
+            Json(large),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::PAYLOAD_TOO_LARGE);
+
+        let query = || QueryErrorReportsRequest {
+            since: None,
+            until: None,
+            limit: None,
+        };
+        for (headers, status) in [
+            (HeaderMap::new(), StatusCode::UNAUTHORIZED),
+            (bearer("token-peer"), StatusCode::FORBIDDEN),
+        ] {
+            let e = query_reports(
+                Extension(reports.clone()),
+                credentials.clone(),
+                headers,
+                Query(query()),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(e, status);
+        }
+
+        let response = query_reports(
+            Extension(reports.clone()),
+            credentials.clone(),
+            bearer("token-ops"),
+            Query(query()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.reports.len(), 1);
+        assert_eq!(response.reports[0].report.report_id, stored.report_id);
+
+        let query = QueryErrorReportsRequest {
+            since: None,
+            until: None,
+            limit: Some(MAX_REPORTS_LIMIT + 1),
+        };
+        let e = query_reports(
+            Extension(reports.clone()),
+            credentials.clone(),
+            bearer("token-ops"),
+            Query(query),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        let query = QueryErrorReportsRequest {
+            since: Some("invalid".to_string()),
+            until: None,
+            limit: None,
+        };
+        let e = query_reports(
+            Extension(reports.clone()),
+            credentials.clone(),
+            bearer("token-ops"),
+            Query(query),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
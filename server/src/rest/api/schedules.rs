@@ -0,0 +1,329 @@
+//! REST API for the schedules of the Aetheric network
+//! Lets other service providers read the planned departures and arrivals
+//! of our vertiports and the planned routes of our flights.
+
+use super::rest_types::*;
+use crate::auth::ProviderCredentials;
+use crate::f3548::flight_plans::FlightPlan;
+use crate::schedules::access::ScheduleAccess;
+use crate::schedules::{ScheduleBackend, ScheduleError};
+use axum::extract::{Path, Query};
+use axum::http::HeaderMap;
+use axum::{Extension, Json};
+use hyper::StatusCode;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::sync::Arc;
+
+/// Maximum time range of a single schedule query in hours
+const MAX_SCHEDULE_RANGE_HOURS: i64 = 24;
+
+impl From<ScheduleError> for StatusCode {
+    fn from(e: ScheduleError) -> Self {
+        match e {
+            ScheduleError::NotFound => StatusCode::NOT_FOUND,
+            ScheduleError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+impl From<&FlightPlan> for ScheduleEntry {
+    fn from(plan: &FlightPlan) -> Self {
+        ScheduleEntry {
+            flight_id: plan.id.clone(),
+            origin_vertiport_id: plan.origin_vertiport_id.clone(),
+            target_vertiport_id: plan.target_vertiport_id.clone(),
+            departure: plan.time_start.into(),
+            arrival: plan.time_end.into(),
+            state: plan.state,
+        }
+    }
+}
+
+/// Get the requesting provider authenticated by its bearer token.
+/// Returns 401 if the provider could not be authenticated and 403 if it may
+///  not read schedules.
+fn requesting_provider(
+    headers: &HeaderMap,
+    credentials: &ProviderCredentials,
+    access: &ScheduleAccess,
+) -> Result<String, StatusCode> {
+    let provider = credentials.authenticated_provider(headers)?;
+    if !access.is_known(&provider) {
+        rest_error!("provider {} may not read schedules.", provider);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(provider)
+}
+
+/// Parse the RFC3339 time range of a schedule query
+fn parse_range(
+    query: &GetVertiportScheduleRequest,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), StatusCode> {
+    let parse = |value: &str| {
+        DateTime::parse_from_rfc3339(value)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| {
+                rest_error!("invalid RFC3339 time {}: {}", value, e);
+                StatusCode::BAD_REQUEST
+            })
+    };
+
+    let time_start = parse(&query.time_start)?;
+    let time_end = parse(&query.time_end)?;
+    if time_end <= time_start {
+        rest_error!("time range must end after it starts.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if time_end - time_start > Duration::hours(MAX_SCHEDULE_RANGE_HOURS) {
+        rest_error!(
+            "time range exceeds the maximum of {} hours.",
+            MAX_SCHEDULE_RANGE_HOURS
+        );
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    Ok((time_start, time_end))
+}
+
+/// Get the planned departures and arrivals of a vertiport
+#[utoipa::path(
+    get,
+    path = "/schedules/vertiports/{id}",
+    tag = "svc-discovery",
+    params(
+        ("id" = String, Path, description = "ID of the vertiport"),
+        GetVertiportScheduleRequest
+    ),
+    responses(
+        (status = 200, description = "The schedule was retrieved successfully.", body = GetVertiportScheduleResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 413, description = "The requested time range is too large."),
+        (status = 503, description = "Could not reach the schedule backend.")
+    )
+)]
+pub async fn get_vertiport_schedule(
+    Extension(backend): Extension<Arc<dyn ScheduleBackend>>,
+    Extension(credentials): Extension<Arc<ProviderCredentials>>,
+    Extension(access): Extension<Arc<ScheduleAccess>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(query): Query<GetVertiportScheduleRequest>,
+) -> Result<Json<GetVertiportScheduleResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let provider = requesting_provider(&headers, &credentials, &access)?;
+    if !access.allows(&provider, &id) {
+        rest_error!("provider {} may not read the schedule of {}.", provider, id);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let (time_start, time_end) = parse_range(&query)?;
+    let to_status = |e: ScheduleError| {
+        rest_error!("could not get the schedule of {}: {}", id, e);
+        StatusCode::from(e)
+    };
+    let departures = backend
+        .departures(&id, time_start, time_end)
+        .map_err(to_status)?;
+    let arrivals = backend
+        .arrivals(&id, time_start, time_end)
+        .map_err(to_status)?;
+
+    Ok(Json(GetVertiportScheduleResponse {
+        vertiport_id: id,
+        departures: departures.iter().map(ScheduleEntry::from).collect(),
+        arrivals: arrivals.iter().map(ScheduleEntry::from).collect(),
+    }))
+}
+
+/// Get the planned route of a flight as 4D volumes
+#[utoipa::path(
+    get,
+    path = "/schedules/flights/{id}/route",
+    tag = "svc-discovery",
+    params(
+        ("id" = String, Path, description = "ID of the flight plan")
+    ),
+    responses(
+        (status = 200, description = "The route was retrieved successfully.", body = GetFlightRouteResponse),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 404, description = "The requested flight was not found."),
+        (status = 503, description = "Could not reach the schedule backend.")
+    )
+)]
+pub async fn get_flight_route(
+    Extension(backend): Extension<Arc<dyn ScheduleBackend>>,
+    Extension(credentials): Extension<Arc<ProviderCredentials>>,
+    Extension(access): Extension<Arc<ScheduleAccess>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<GetFlightRouteResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let provider = requesting_provider(&headers, &credentials, &access)?;
+    let plan = backend.flight(&id).map_err(|e| {
+        rest_error!("could not get flight {}: {}", id, e);
+        StatusCode::from(e)
+    })?;
+
+    // Unknown and hidden flights are indistinguishable to the provider
+    if !access.allows_route(
+        &provider,
+        plan.origin_vertiport_id.as_deref(),
+        plan.target_vertiport_id.as_deref(),
+    ) {
+        rest_error!("provider {} may not read the route of {}.", provider, id);
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(Json(GetFlightRouteResponse {
+        flight_id: plan.id.clone(),
+        volumes: plan.volumes(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::tests::{bearer, credential};
+    use crate::schedules::tests::fixtures;
+
+    fn access() -> Arc<ScheduleAccess> {
+        Arc::new(ScheduleAccess::parse("all=*,a=VP-A,c=VP-C"))
+    }
+
+    /// Every provider is issued the token 'token-<id>'
+    fn credentials() -> Arc<ProviderCredentials> {
+        let value = ["all", "a", "c", "unknown"]
+            .iter()
+            .map(|id| credential(id, &format!("token-{}", id)))
+            .collect::<Vec<String>>()
+            .join(",");
+        Arc::new(ProviderCredentials::parse(&value))
+    }
+
+    fn headers(provider: &str) -> HeaderMap {
+        bearer(&format!("token-{}", provider))
+    }
+
+    fn query(time_start: DateTime<Utc>, time_end: DateTime<Utc>) -> GetVertiportScheduleRequest {
+        GetVertiportScheduleRequest {
+            time_start: Time::from(time_start).value,
+            time_end: Time::from(time_end).value,
+        }
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let now = Utc::now();
+        assert!(parse_range(&query(now, now + Duration::hours(24))).is_ok());
+        assert_eq!(
+            parse_range(&query(now, now)).unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            parse_range(&query(now, now + Duration::hours(25))).unwrap_err(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        let mut invalid = query(now, now + Duration::hours(1));
+        invalid.time_start = "today".to_string();
+        assert_eq!(parse_range(&invalid).unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_get_vertiport_schedule() {
+        let now = Utc::now();
+        let backend: Arc<dyn ScheduleBackend> = Arc::new(fixtures(now));
+        let schedule = |provider: &str, id: &str| {
+            get_vertiport_schedule(
+                Extension(backend.clone()),
+                Extension(credentials()),
+                Extension(access()),
+                headers(provider),
+                Path(id.to_string()),
+                Query(query(now, now + Duration::hours(1))),
+            )
+        };
+
+        let response = schedule("a", "VP-A").await.unwrap();
+        assert_eq!(response.vertiport_id, "VP-A");
+        let departures = response
+            .departures
+            .iter()
+            .map(|e| e.flight_id.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(departures, ["a", "b"]);
+        assert_eq!(response.arrivals.len(), 1);
+        assert_eq!(response.arrivals[0].flight_id, "c");
+        assert_eq!(
+            response.arrivals[0].origin_vertiport_id.as_deref(),
+            Some("VP-B")
+        );
+
+        let response = schedule("all", "VP-B").await.unwrap();
+        assert_eq!(response.departures.len(), 1);
+        assert_eq!(response.arrivals.len(), 1);
+
+        assert_eq!(
+            schedule("a", "VP-B").await.unwrap_err(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            schedule("unknown", "VP-A").await.unwrap_err(),
+            StatusCode::FORBIDDEN
+        );
+
+        for headers in [HeaderMap::new(), bearer("forged")] {
+            let e = get_vertiport_schedule(
+                Extension(backend.clone()),
+                Extension(credentials()),
+                Extension(access()),
+                headers,
+                Path("VP-A".to_string()),
+                Query(query(now, now + Duration::hours(1))),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(e, StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_flight_route() {
+        let now = Utc::now();
+        let backend: Arc<dyn ScheduleBackend> = Arc::new(fixtures(now));
+        let route = |provider: &str, id: &str| {
+            get_flight_route(
+                Extension(backend.clone()),
+                Extension(credentials()),
+                Extension(access()),
+                headers(provider),
+                Path(id.to_string()),
+            )
+        };
+
+        let response = route("a", "c").await.unwrap();
+        assert_eq!(response.flight_id, "c");
+        assert_eq!(response.volumes.len(), 1);
+
+        let response = route("c", "b").await.unwrap();
+        assert_eq!(response.flight_id, "b");
+
+        assert_eq!(route("c", "a").await.unwrap_err(), StatusCode::NOT_FOUND);
+        assert_eq!(route("all", "d").await.unwrap_err(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            route("unknown", "a").await.unwrap_err(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            route("forged", "a").await.unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! REST API for traffic statistics
+//! Airspace planners look at aggregate traffic rather than at individual
+//! aircraft: the density of an area and its breakdown by aircraft type and
+//! operational status over a time range.
+
+use super::rest_types::*;
+use super::uss::parse_view;
+use crate::auth::ProviderCredentials;
+use crate::grpc::client::GrpcClients;
+use crate::stats::{traffic_stats, Grid, QueryLimits, MAX_RANGE_HOURS};
+use axum::extract::Query;
+use axum::{Extension, Json};
+use hyper::{HeaderMap, StatusCode};
+use lib_common::time::{DateTime, Duration, Utc};
+use std::sync::Arc;
+
+/// Parse the RFC3339 time range of a statistics query
+fn parse_range(
+    query: &GetTrafficStatsRequest,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), StatusCode> {
+    let parse = |value: &str| {
+        DateTime::parse_from_rfc3339(value)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| {
+                rest_error!("invalid RFC3339 time {}: {}", value, e);
+                StatusCode::BAD_REQUEST
+            })
+    };
+
+    let time_start = parse(&query.time_start)?;
+    let time_end = parse(&query.time_end)?;
+    if time_end <= time_start {
+        rest_error!("time range must end after it starts.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if time_end - time_start > Duration::hours(MAX_RANGE_HOURS) {
+        rest_error!(
+            "time range exceeds the maximum of {} hours.",
+            MAX_RANGE_HOURS
+        );
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    Ok((time_start, time_end))
+}
+
+/// Get the traffic density of an area and its breakdowns by aircraft type
+///  and operational status over a time range
+#[utoipa::path(
+    get,
+    path = "/stats/traffic",
+    tag = "svc-discovery",
+    params(GetTrafficStatsRequest),
+    responses(
+        (status = 200, description = "The statistics were computed successfully.", body = GetTrafficStatsResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 413, description = "The view, the time range, the density grid or the number of flights is too large."),
+        (status = 500, description = "Could not query svc-gis."),
+        (status = 503, description = "Too many statistics are being computed, try again later."),
+        (status = 504, description = "The statistics could not be computed in time.")
+    )
+)]
+pub async fn get_traffic_stats(
+    Extension(mut grpc_clients): Extension<GrpcClients>,
+    Extension(limits): Extension<Arc<QueryLimits>>,
+    Extension(credentials): Extension<Arc<ProviderCredentials>>,
+    headers: HeaderMap,
+    Query(query): Query<GetTrafficStatsRequest>,
+) -> Result<Json<GetTrafficStatsResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let provider = credentials.authenticated_provider(&headers)?;
+    let window = parse_view(&query.view)?;
+    let (time_start, time_end) = parse_range(&query)?;
+    let grid = Grid::new(&window, query.resolution)?;
+
+    let response = traffic_stats(&mut grpc_clients, &limits, grid, time_start, time_end).await?;
+    rest_debug!(
+        "counted {} positions of {} flights for {}.",
+        response.positions,
+        response.flights,
+        provider
+    );
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::tests::{bearer, credential};
+
+    fn query(time_start: DateTime<Utc>, time_end: DateTime<Utc>) -> GetTrafficStatsRequest {
+        GetTrafficStatsRequest {
+            view: "52.3,4.8,52.4,5.0".to_string(),
+            time_start: Time::from(time_start).value,
+            time_end: Time::from(time_end).value,
+            resolution: 0.01,
+        }
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let now = Utc::now();
+        let (start, end) = parse_range(&query(now - Duration::hours(1), now)).unwrap();
+        assert!(start < end);
+
+        let mut invalid = query(now, now);
+        assert_eq!(parse_range(&invalid).unwrap_err(), StatusCode::BAD_REQUEST);
+        invalid.time_start = "yesterday".to_string();
+        assert_eq!(parse_range(&invalid).unwrap_err(), StatusCode::BAD_REQUEST);
+
+        let long = query(now - Duration::hours(MAX_RANGE_HOURS + 1), now);
+        assert_eq!(
+            parse_range(&long).unwrap_err(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_traffic_stats() {
+        let config = crate::config::Config::default();
+        let grpc_clients = Extension(GrpcClients::default(config));
+        let limits = Extension(Arc::new(QueryLimits::default()));
+        let credentials = Extension(Arc::new(ProviderCredentials::parse(&credential(
+            "planner", "secret",
+        ))));
+        let now = Utc::now();
+
+        let Json(response) = get_traffic_stats(
+            grpc_clients.clone(),
+            limits.clone(),
+            credentials.clone(),
+            bearer("secret"),
+            Query(query(now - Duration::minutes(2), now)),
+        )
+        .await
+        .unwrap();
+        assert_eq!((response.rows, response.cols), (10, 20));
+        assert_eq!(response.flights, 0);
+        assert!(response.aircraft_types.is_empty());
+
+        for headers in [HeaderMap::new(), bearer("wrong")] {
+            let e = get_traffic_stats(
+                grpc_clients.clone(),
+                limits.clone(),
+                credentials.clone(),
+                headers,
+                Query(query(now - Duration::minutes(2), now)),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(e, StatusCode::UNAUTHORIZED);
+        }
+
+        let mut invalid = query(now - Duration::minutes(2), now);
+        invalid.view = "52.3,4.8".to_string();
+        let e = get_traffic_stats(
+            grpc_clients.clone(),
+            limits.clone(),
+            credentials.clone(),
+            bearer("secret"),
+            Query(invalid),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        let mut fine = query(now - Duration::minutes(2), now);
+        fine.resolution = 0.0001;
+        let e = get_traffic_stats(
+            grpc_clients.clone(),
+            limits.clone(),
+            credentials.clone(),
+            bearer("secret"),
+            Query(fine),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::PAYLOAD_TOO_LARGE);
+
+        let mut world = query(now - Duration::minutes(2), now);
+        world.view = "-90,-180,90,180".to_string();
+        world.resolution = 10.0;
+        let e = get_traffic_stats(
+            grpc_clients,
+            limits,
+            credentials,
+            bearer("secret"),
+            Query(world),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
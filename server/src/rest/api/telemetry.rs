@@ -0,0 +1,285 @@
+//! REST API for the telemetry of partner aircraft
+//! Partners push the RID states of their flights, so the aircraft are
+//! reported by `/uss/flights` and forwarded to svc-gis.
+
+use super::rest_types::*;
+use crate::auth::ProviderCredentials;
+use crate::telemetry::forward::{GisBatch, GisForwarder};
+use crate::telemetry::store::PushedTelemetry;
+use crate::telemetry::{TelemetryError, MAX_BATCH_STATES};
+use axum::http::HeaderMap;
+use axum::{Extension, Json};
+use hyper::StatusCode;
+use lib_common::time::Utc;
+use std::sync::Arc;
+
+impl From<TelemetryError> for StatusCode {
+    fn from(e: TelemetryError) -> Self {
+        match e {
+            TelemetryError::InvalidState => StatusCode::BAD_REQUEST,
+            TelemetryError::OutOfOrder => StatusCode::CONFLICT,
+            TelemetryError::NotOwner => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+/// Get the pushing provider authenticated by its bearer token.
+/// Returns 401 if the provider could not be authenticated and 403 if it may
+///  not push telemetry.
+fn pushing_provider(
+    headers: &HeaderMap,
+    credentials: &ProviderCredentials,
+    telemetry: &PushedTelemetry,
+) -> Result<String, StatusCode> {
+    let provider = credentials.authenticated_provider(headers)?;
+    if !telemetry.is_provider(&provider) {
+        rest_error!("provider {} may not push telemetry.", provider);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(provider)
+}
+
+/// Push a batch of states of a flight, oldest first
+#[utoipa::path(
+    post,
+    path = "/telemetry",
+    tag = "svc-discovery",
+    request_body = PostTelemetryRequest,
+    responses(
+        (status = 200, description = "The states were accepted.", body = PostTelemetryResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid, or a state is out of range."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint, or the flight is reported by another provider."),
+        (status = 409, description = "A state is older than a state already received for the flight."),
+        (status = 413, description = "The batch has too many states.")
+    )
+)]
+pub async fn post_telemetry(
+    Extension(telemetry): Extension<Arc<PushedTelemetry>>,
+    Extension(credentials): Extension<Arc<ProviderCredentials>>,
+    Extension(forwarder): Extension<Arc<GisForwarder>>,
+    headers: HeaderMap,
+    Json(request): Json<PostTelemetryRequest>,
+) -> Result<Json<PostTelemetryResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let provider = pushing_provider(&headers, &credentials, &telemetry)?;
+    let flight_id = request.flight_id.trim();
+    if flight_id.is_empty() {
+        rest_error!("flight_id must not be empty.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if request.states.len() > MAX_BATCH_STATES {
+        rest_error!("batch exceeds the maximum of {} states.", MAX_BATCH_STATES);
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    let now = Utc::now();
+    let result = telemetry
+        .push(
+            &provider,
+            flight_id,
+            request.aircraft_type,
+            request.simulated,
+            request.states,
+            now,
+        )
+        .map_err(|e| {
+            rest_error!("could not push telemetry of {}: {}", flight_id, e);
+            StatusCode::from(e)
+        })?;
+
+    forwarder.forward(GisBatch::new(
+        flight_id,
+        request.aircraft_type,
+        request.simulated,
+        &result.states,
+        now,
+    ));
+
+    rest_debug!(
+        "accepted {} states of flight {}, {} duplicates.",
+        result.accepted,
+        flight_id,
+        result.duplicates
+    );
+    Ok(Json(PostTelemetryResponse {
+        accepted: result.accepted,
+        duplicates: result.duplicates,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::tests::{bearer, credential};
+    use crate::telemetry::parse_providers;
+    use crate::telemetry::tests::state;
+    use lib_common::time::Duration;
+
+    /// Every provider is issued the token 'token-<id>'
+    fn credentials() -> Extension<Arc<ProviderCredentials>> {
+        let value = ["acme", "skyways", "other"]
+            .iter()
+            .map(|id| credential(id, &format!("token-{}", id)))
+            .collect::<Vec<String>>()
+            .join(",");
+        Extension(Arc::new(ProviderCredentials::parse(&value)))
+    }
+
+    fn forwarder() -> Extension<Arc<GisForwarder>> {
+        Extension(Arc::new(GisForwarder::new(false)))
+    }
+
+    fn headers(provider: &str) -> HeaderMap {
+        bearer(&format!("token-{}", provider))
+    }
+
+    fn request(flight_id: &str, states: Vec<RIDAircraftState>) -> Json<PostTelemetryRequest> {
+        Json(PostTelemetryRequest {
+            flight_id: flight_id.to_string(),
+            aircraft_type: UAType::Helicopter,
+            simulated: false,
+            states,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_post_telemetry() {
+        let now = Utc::now();
+        let telemetry = Arc::new(PushedTelemetry::new(parse_providers("acme,skyways")));
+        let states = vec![
+            state(now - Duration::seconds(1), 52.37, 4.9),
+            state(now, 52.37, 4.91),
+        ];
+        let gis = Arc::new(GisForwarder::new(true));
+        let response = post_telemetry(
+            Extension(telemetry.clone()),
+            credentials(),
+            Extension(gis.clone()),
+            headers("acme"),
+            request("AC-1", states.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.accepted, 2);
+        assert_eq!(response.duplicates, 0);
+
+        let response = post_telemetry(
+            Extension(telemetry.clone()),
+            credentials(),
+            Extension(gis.clone()),
+            headers("acme"),
+            request("AC-1", states[1..].to_vec()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.accepted, 0);
+        assert_eq!(response.duplicates, 1);
+
+        // only the new states are forwarded to svc-gis
+        let mut receiver = gis.take_receiver().unwrap();
+        let batch = receiver.try_recv().unwrap();
+        assert_eq!(batch.ids.len(), 1);
+        assert_eq!(batch.positions.len(), 2);
+        assert!(receiver.try_recv().is_err());
+
+        let e = post_telemetry(
+            Extension(telemetry.clone()),
+            credentials(),
+            forwarder(),
+            headers("acme"),
+            request("AC-1", states[..1].to_vec()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::CONFLICT);
+
+        let e = post_telemetry(
+            Extension(telemetry.clone()),
+            credentials(),
+            forwarder(),
+            headers("skyways"),
+            request("AC-1", vec![state(now, 52.37, 4.9)]),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_post_telemetry_invalid() {
+        let now = Utc::now();
+        let telemetry = Arc::new(PushedTelemetry::new(parse_providers("acme")));
+
+        let e = post_telemetry(
+            Extension(telemetry.clone()),
+            credentials(),
+            forwarder(),
+            HeaderMap::new(),
+            request("AC-1", vec![state(now, 52.37, 4.9)]),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::UNAUTHORIZED);
+
+        let e = post_telemetry(
+            Extension(telemetry.clone()),
+            credentials(),
+            forwarder(),
+            bearer("forged"),
+            request("AC-1", vec![state(now, 52.37, 4.9)]),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::UNAUTHORIZED);
+
+        let e = post_telemetry(
+            Extension(telemetry.clone()),
+            credentials(),
+            forwarder(),
+            headers("other"),
+            request("AC-1", vec![state(now, 52.37, 4.9)]),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::FORBIDDEN);
+
+        let e = post_telemetry(
+            Extension(telemetry.clone()),
+            credentials(),
+            forwarder(),
+            headers("acme"),
+            request(" ", vec![state(now, 52.37, 4.9)]),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        let e = post_telemetry(
+            Extension(telemetry.clone()),
+            credentials(),
+            forwarder(),
+            headers("acme"),
+            request("AC-1", vec![state(now, 91.0, 4.9)]),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        let states = vec![state(now, 52.37, 4.9); MAX_BATCH_STATES + 1];
+        let e = post_telemetry(
+            Extension(telemetry.clone()),
+            credentials(),
+            forwarder(),
+            headers("acme"),
+            request("AC-1", states),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(telemetry.flight("AC-1").is_none());
+    }
+}
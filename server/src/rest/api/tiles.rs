@@ -0,0 +1,593 @@
+//! REST API for slippy map tiles of flights
+//! Map clients request flights by XYZ tile of the Web Mercator projection.
+//! Zoomed in tiles report individual flights, zoomed out tiles aggregate
+//! flights in a grid of cells so a view of a region stays light.
+//! Tiles are returned as JSON or as Mapbox Vector Tiles.
+
+use super::rest_types::*;
+use super::uss::{get_live_flights, with_local_flights, Window, MAX_DISPLAY_AREA_DIAGONAL_METERS};
+use crate::grpc::client::GrpcClients;
+use crate::odid::store::BroadcastFlights;
+use crate::rest::formats::if_none_match;
+use crate::rest::formats::mvt::{encode_tile, MVT_MEDIA_TYPE};
+use crate::telemetry::store::PushedTelemetry;
+use crate::traffic::index::LiveTraffic;
+use axum::extract::{Path, Query};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use hyper::header::{ACCEPT, CACHE_CONTROL, CONTENT_TYPE, ETAG, VARY};
+use hyper::StatusCode;
+use lib_common::time::Utc;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// Highest supported zoom level
+pub const MAX_ZOOM: u8 = 22;
+
+/// Lowest zoom level reporting individual flights, its tiles are about
+///  the largest `/uss/flights` view
+pub const MIN_FLIGHTS_ZOOM: u8 = 13;
+
+/// Number of cells per side of the grid aggregating flights
+pub const CLUSTER_GRID: u32 = 8;
+
+/// Duration of recent positions of zoomed in tiles if not requested, in
+///  seconds
+const DEFAULT_RECENT_POSITIONS_SECONDS: f32 = 5.0;
+
+/// Flights are aggregated if reported within this duration, in seconds
+const CLUSTER_DURATION_SECONDS: f32 = 5.0;
+
+/// Time zoomed in tiles may be cached, in seconds
+const FLIGHTS_TILE_MAX_AGE_SECONDS: u32 = 1;
+
+/// Time zoomed out tiles may be cached, in seconds
+const CLUSTERS_TILE_MAX_AGE_SECONDS: u32 = 5;
+
+/// A tile of the Web Mercator projection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    /// The zoom level
+    pub z: u8,
+
+    /// The column, from west to east
+    pub x: u32,
+
+    /// The row, from north to south
+    pub y: u32,
+}
+
+impl Tile {
+    /// A tile, rejected with 400 if the zoom level or position is invalid
+    pub fn new(z: u8, x: u32, y: u32) -> Result<Self, StatusCode> {
+        if z > MAX_ZOOM {
+            rest_error!("zoom level must be <= {}.", MAX_ZOOM);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let tiles = 1u32 << z;
+        if x >= tiles || y >= tiles {
+            rest_error!("tile {}/{}/{} does not exist.", z, x, y);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        Ok(Tile { z, x, y })
+    }
+
+    /// Number of tiles per side at the zoom level of the tile
+    fn tiles(&self) -> f64 {
+        (1u32 << self.z) as f64
+    }
+
+    /// If the tile reports individual flights rather than clusters
+    pub fn shows_flights(&self) -> bool {
+        self.z >= MIN_FLIGHTS_ZOOM
+    }
+
+    /// The window covered by the tile
+    pub fn window(&self) -> Window {
+        let lng = |x: u32| x as f64 / self.tiles() * 360.0 - 180.0;
+        let lat = |y: u32| {
+            (PI * (1.0 - 2.0 * y as f64 / self.tiles()))
+                .sinh()
+                .atan()
+                .to_degrees()
+        };
+
+        Window {
+            lon1: lng(self.x),
+            lat1: lat(self.y + 1),
+            lon2: lng(self.x + 1),
+            lat2: lat(self.y),
+        }
+    }
+
+    /// The position of a point relative to the tile, as fractions of the
+    ///  tile from its north-west corner
+    pub fn project(&self, lat: f64, lng: f64) -> (f64, f64) {
+        let lat = lat.to_radians();
+        let x = (lng + 180.0) / 360.0 * self.tiles() - self.x as f64;
+        let y =
+            (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0 * self.tiles() - self.y as f64;
+
+        (x, y)
+    }
+
+    /// The position of a point within the tile, as fractions of the tile
+    ///  from its north-west corner, if the point lies within the tile
+    pub fn offset(&self, lat: f64, lng: f64) -> Option<(f64, f64)> {
+        let (x, y) = self.project(lat, lng);
+        ((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y)).then_some((x, y))
+    }
+
+    /// Aggregate the flights with a current position in the tile by cell
+    ///  of the grid, from north-west to south-east
+    pub fn clusters(&self, flights: &[RIDFlight]) -> Vec<FlightCluster> {
+        let mut cells: BTreeMap<(u32, u32), (f64, f64, usize)> = BTreeMap::new();
+        for flight in flights {
+            let position = &flight.current_state.position;
+            let Some((x, y)) = self.offset(position.lat, position.lng) else {
+                continue;
+            };
+
+            let cell = (
+                (y * CLUSTER_GRID as f64) as u32,
+                (x * CLUSTER_GRID as f64) as u32,
+            );
+            let (lat, lng, count) = cells.entry(cell).or_default();
+            *lat += position.lat;
+            *lng += position.lng;
+            *count += 1;
+        }
+
+        cells
+            .into_values()
+            .map(|(lat, lng, count)| FlightCluster {
+                lat: lat / count as f64,
+                lng: lng / count as f64,
+                count,
+            })
+            .collect()
+    }
+}
+
+/// Formats of map tiles of flights
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileFormat {
+    /// A `GetFlightTileResponse` as JSON
+    Json,
+
+    /// A Mapbox Vector Tile
+    Mvt,
+}
+
+impl TileFormat {
+    /// The media type of the format
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            TileFormat::Json => "application/json",
+            TileFormat::Mvt => MVT_MEDIA_TYPE,
+        }
+    }
+
+    /// The format named by the `format` parameter, which takes precedence,
+    ///  or else the format accepted with the highest quality, JSON by
+    ///  default.
+    /// Returns 400 if the parameter names an unsupported format.
+    pub fn negotiate(format: Option<&str>, headers: &HeaderMap) -> Result<Self, StatusCode> {
+        if let Some(name) = format {
+            return match name.trim().to_ascii_lowercase().as_str() {
+                "json" => Ok(TileFormat::Json),
+                "mvt" => Ok(TileFormat::Mvt),
+                _ => {
+                    rest_error!("unsupported tile format: {}.", name);
+                    Err(StatusCode::BAD_REQUEST)
+                }
+            };
+        }
+
+        let accept = headers
+            .get(ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let mut best: Option<(f32, TileFormat)> = None;
+        for range in accept.split(',') {
+            let mut parts = range.split(';').map(str::trim);
+            let format = match parts.next().map(str::to_ascii_lowercase).as_deref() {
+                Some(MVT_MEDIA_TYPE) => TileFormat::Mvt,
+                Some("application/json" | "application/*" | "*/*") => TileFormat::Json,
+                _ => continue,
+            };
+
+            let quality = parts
+                .filter_map(|param| param.strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if quality > 0.0 && best.is_none_or(|(best, _)| quality > best) {
+                best = Some((quality, format));
+            }
+        }
+
+        Ok(best.map_or(TileFormat::Json, |(_, format)| format))
+    }
+}
+
+/// Render a tile with a weak ETag hashed from its format and its flights
+///  or clusters, and a Cache-Control header suited to its zoom level.
+/// Returns 304 if the request already has the tile.
+fn render_tile(
+    tile: &Tile,
+    format: TileFormat,
+    response: &GetFlightTileResponse,
+    headers: &HeaderMap,
+) -> Result<Response, StatusCode> {
+    let content = serde_json::to_vec(&(&response.flights, &response.clusters)).map_err(|e| {
+        rest_error!("could not hash tile: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut hasher = DefaultHasher::new();
+    format.media_type().hash(&mut hasher);
+    (tile.z, tile.x, tile.y).hash(&mut hasher);
+    content.hash(&mut hasher);
+    let etag = format!("W/\"{:016x}\"", hasher.finish());
+
+    let max_age = if tile.shows_flights() {
+        FLIGHTS_TILE_MAX_AGE_SECONDS
+    } else {
+        CLUSTERS_TILE_MAX_AGE_SECONDS
+    };
+    let cache_control = format!("public, max-age={}", max_age);
+
+    if if_none_match(headers, &etag) {
+        rest_debug!("tile not modified: {}", etag);
+        return Ok((
+            StatusCode::NOT_MODIFIED,
+            [
+                (ETAG, etag),
+                (VARY, ACCEPT.to_string()),
+                (CACHE_CONTROL, cache_control),
+            ],
+        )
+            .into_response());
+    }
+
+    let body = match format {
+        TileFormat::Json => serde_json::to_vec(response).map_err(|e| {
+            rest_error!("could not render tile: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?,
+        TileFormat::Mvt => encode_tile(tile, response),
+    };
+
+    Ok((
+        [
+            (CONTENT_TYPE, format.media_type().to_string()),
+            (VARY, ACCEPT.to_string()),
+            (ETAG, etag),
+            (CACHE_CONTROL, cache_control),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Get the flights of a map tile
+#[utoipa::path(
+    get,
+    path = "/tiles/flights/{z}/{x}/{y}",
+    tag = "svc-discovery",
+    params(
+        ("z" = u8, Path, description = "Zoom level, at most 22"),
+        ("x" = u32, Path, description = "Column of the tile"),
+        ("y" = u32, Path, description = "Row of the tile"),
+        GetFlightTileRequest
+    ),
+    responses(
+        (status = 200, description = "The flights of the tile were retrieved successfully, as JSON or as a Mapbox Vector Tile.", body = GetFlightTileResponse),
+        (status = 304, description = "The flights of the tile did not change."),
+        (status = 400, description = "The tile does not exist, or the duration or format is invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 413, description = "The tile is larger than a display area and not covered by the live traffic index.")
+    )
+)]
+pub async fn get_flight_tile(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(broadcast): Extension<Arc<BroadcastFlights>>,
+    Extension(telemetry): Extension<Arc<PushedTelemetry>>,
+    Extension(traffic): Extension<Arc<LiveTraffic>>,
+    headers: HeaderMap,
+    Path((z, x, y)): Path<(u8, u32, u32)>,
+    Query(query): Query<GetFlightTileRequest>,
+) -> Result<Response, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let tile = Tile::new(z, x, y)?;
+    let format = TileFormat::negotiate(query.format.as_deref(), &headers)?;
+    let duration_s = if tile.shows_flights() {
+        query
+            .recent_positions_duration
+            .unwrap_or(DEFAULT_RECENT_POSITIONS_SECONDS)
+    } else {
+        CLUSTER_DURATION_SECONDS
+    };
+
+    if !(0.0..=60.0).contains(&duration_s) {
+        rest_error!("recent_positions_duration must be between 0.0 and 60.0.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let window = tile.window();
+    let flights = if window.diagonal() <= MAX_DISPLAY_AREA_DIAGONAL_METERS {
+        get_live_flights(&grpc_clients, traffic, &window, duration_s).await?
+    } else {
+        // svc-gis is not queried for tiles larger than a display area
+        traffic
+            .flights_in(&window, duration_s, Utc::now())
+            .ok_or_else(|| {
+                rest_error!(
+                    "tile {}/{}/{} is not covered by the live traffic index.",
+                    z,
+                    x,
+                    y
+                );
+                StatusCode::PAYLOAD_TOO_LARGE
+            })?
+    };
+    let flights = with_local_flights(flights, &broadcast, &telemetry, &window, duration_s);
+    let (flights, clusters) = if tile.shows_flights() {
+        (flights, vec![])
+    } else {
+        (vec![], tile.clusters(&flights))
+    };
+
+    let response = GetFlightTileResponse {
+        z,
+        x,
+        y,
+        timestamp: Time::default(),
+        flights,
+        clusters,
+    };
+
+    render_tile(&tile, format, &response, &headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::tests::flight;
+    use hyper::header::IF_NONE_MATCH;
+    use prost::Message;
+
+    #[test]
+    fn test_tile() {
+        assert_eq!(Tile::new(23, 0, 0).unwrap_err(), StatusCode::BAD_REQUEST);
+        assert_eq!(Tile::new(1, 2, 0).unwrap_err(), StatusCode::BAD_REQUEST);
+        assert_eq!(Tile::new(1, 0, 2).unwrap_err(), StatusCode::BAD_REQUEST);
+        assert!(Tile::new(MAX_ZOOM, (1 << MAX_ZOOM) - 1, 0).is_ok());
+
+        let world = Tile::new(0, 0, 0).unwrap().window();
+        assert_eq!((world.lon1, world.lon2), (-180.0, 180.0));
+        assert!((world.lat2 - 85.0511).abs() < 1e-4);
+        assert!((world.lat1 + 85.0511).abs() < 1e-4);
+
+        // the tile of Amsterdam Centraal at zoom level 13
+        let tile = Tile::new(13, 4207, 2691).unwrap();
+        assert!(tile.shows_flights());
+        let window = tile.window();
+        assert!(window.lon1 < 4.9003 && 4.9003 < window.lon2);
+        assert!(window.lat1 < 52.3791 && 52.3791 < window.lat2);
+
+        let (x, y) = tile.offset(52.3791, 4.9003).unwrap();
+        assert!((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y));
+        let (x, _) = tile.offset(window.lat2 - 1e-9, window.lon1).unwrap();
+        assert!(x.abs() < 1e-6);
+        assert!(tile.offset(52.3791, window.lon2).is_none());
+        assert!(!Tile::new(12, 2103, 1345).unwrap().shows_flights());
+    }
+
+    #[test]
+    fn test_clusters() {
+        let now = Utc::now();
+        let tile = Tile::new(10, 525, 336).unwrap();
+        let window = tile.window();
+        let north_west = (window.lat2 - 0.01, window.lon1 + 0.01);
+        let south_east = (window.lat1 + 0.01, window.lon2 - 0.01);
+        let flights = vec![
+            flight("AC-1", now, north_west.0, north_west.1),
+            flight("AC-2", now, north_west.0 - 0.002, north_west.1 + 0.002),
+            flight("AC-3", now, south_east.0, south_east.1),
+            flight("AC-4", now, 0.0, 0.0),
+        ];
+
+        let clusters = tile.clusters(&flights);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].count, 2);
+        assert!((clusters[0].lat - (north_west.0 - 0.001)).abs() < 1e-9);
+        assert!((clusters[0].lng - (north_west.1 + 0.001)).abs() < 1e-9);
+        assert_eq!(clusters[1].count, 1);
+        assert_eq!((clusters[1].lat, clusters[1].lng), south_east);
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let accept = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(ACCEPT, value.parse().unwrap());
+            headers
+        };
+
+        let cases = [
+            (MVT_MEDIA_TYPE, TileFormat::Mvt),
+            ("application/json", TileFormat::Json),
+            ("*/*", TileFormat::Json),
+            ("image/png", TileFormat::Json),
+            (
+                "application/vnd.mapbox-vector-tile, */*;q=0.5",
+                TileFormat::Mvt,
+            ),
+            ("*/*, application/vnd.mapbox-vector-tile", TileFormat::Json),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(
+                TileFormat::negotiate(None, &accept(value)).unwrap(),
+                expected,
+                "{}",
+                value
+            );
+        }
+
+        let headers = accept("application/json");
+        assert_eq!(
+            TileFormat::negotiate(Some("MVT"), &headers).unwrap(),
+            TileFormat::Mvt
+        );
+        assert_eq!(
+            TileFormat::negotiate(Some("png"), &headers).unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            TileFormat::negotiate(None, &HeaderMap::new()).unwrap(),
+            TileFormat::Json
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_flight_tile() {
+        let config = crate::config::Config::default();
+        let grpc_clients = Extension(crate::grpc::client::GrpcClients::default(config));
+        let broadcast = Extension(Arc::new(BroadcastFlights::default()));
+        let telemetry = Extension(Arc::new(PushedTelemetry::new(Default::default())));
+        // the zoomed out tiles of Europe are answered from the index
+        let europe = Window {
+            lon1: -10.0,
+            lat1: 35.0,
+            lon2: 30.0,
+            lat2: 70.0,
+        };
+        let traffic = Arc::new(LiveTraffic::new(Some(europe), 1_000));
+        traffic.update(vec![flight("AC-1", Utc::now(), 52.37, 4.9)], Utc::now());
+        let traffic = Extension(traffic);
+
+        let e = get_flight_tile(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            traffic.clone(),
+            HeaderMap::new(),
+            Path((13, 4207, 2691)),
+            Query(GetFlightTileRequest {
+                recent_positions_duration: Some(61.0),
+                format: None,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        for (z, x, y, max_age) in [(13, 4207, 2691, 1), (5, 16, 10, 5)] {
+            let response = get_flight_tile(
+                grpc_clients.clone(),
+                broadcast.clone(),
+                telemetry.clone(),
+                traffic.clone(),
+                HeaderMap::new(),
+                Path((z, x, y)),
+                Query(GetFlightTileRequest::default()),
+            )
+            .await
+            .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response.headers().get(CACHE_CONTROL).unwrap(),
+                &format!("public, max-age={}", max_age)
+            );
+            let etag = response.headers().get(ETAG).unwrap().clone();
+
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let tile: GetFlightTileResponse = serde_json::from_slice(&body).unwrap();
+            assert_eq!((tile.z, tile.x, tile.y), (z, x, y));
+
+            let mut headers = HeaderMap::new();
+            headers.insert(IF_NONE_MATCH, etag);
+            let response = get_flight_tile(
+                grpc_clients.clone(),
+                broadcast.clone(),
+                telemetry.clone(),
+                traffic.clone(),
+                headers,
+                Path((z, x, y)),
+                Query(GetFlightTileRequest::default()),
+            )
+            .await
+            .unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        }
+
+        let response = get_flight_tile(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            traffic.clone(),
+            HeaderMap::new(),
+            Path((13, 4207, 2691)),
+            Query(GetFlightTileRequest {
+                recent_positions_duration: None,
+                format: Some("mvt".to_string()),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            MVT_MEDIA_TYPE
+        );
+        assert_eq!(response.headers().get(VARY).unwrap(), "accept");
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let tile = crate::rest::formats::mvt::vector_tile::Tile::decode(body.as_ref()).unwrap();
+        assert!(tile.layers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_flight_tile_too_large() {
+        let config = crate::config::Config::default();
+        let grpc_clients = Extension(crate::grpc::client::GrpcClients::default(config));
+        let broadcast = Extension(Arc::new(BroadcastFlights::default()));
+        let telemetry = Extension(Arc::new(PushedTelemetry::new(Default::default())));
+        let amsterdam = Window {
+            lon1: 4.7,
+            lat1: 52.2,
+            lon2: 5.1,
+            lat2: 52.5,
+        };
+
+        // the whole world, without an index or beyond the indexed region
+        for region in [None, Some(amsterdam)] {
+            let traffic = Arc::new(LiveTraffic::new(region, 1_000));
+            traffic.update(vec![flight("AC-1", Utc::now(), 52.37, 4.9)], Utc::now());
+            let e = get_flight_tile(
+                grpc_clients.clone(),
+                broadcast.clone(),
+                telemetry.clone(),
+                Extension(traffic),
+                HeaderMap::new(),
+                Path((0, 0, 0)),
+                Query(GetFlightTileRequest::default()),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(e, StatusCode::PAYLOAD_TOO_LARGE);
+        }
+    }
+}
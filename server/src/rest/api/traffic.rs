@@ -0,0 +1,45 @@
+//! REST API for the live traffic index
+//! The ops team checks the age of the index and how often it disagrees
+//! with svc-gis.
+
+use super::rest_types::*;
+use crate::traffic::index::LiveTraffic;
+use axum::{Extension, Json};
+use hyper::StatusCode;
+use lib_common::time::Utc;
+use std::sync::Arc;
+
+/// Get the state and consistency of the live traffic index
+#[utoipa::path(
+    get,
+    path = "/ops/traffic/live",
+    tag = "svc-discovery",
+    responses(
+        (status = 200, description = "Statistics were retrieved successfully.", body = LiveTrafficStats),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint.")
+    )
+)]
+pub async fn get_live_traffic_stats(
+    Extension(traffic): Extension<Arc<LiveTraffic>>,
+) -> Result<Json<LiveTrafficStats>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    Ok(Json(traffic.stats(Utc::now())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_live_traffic_stats() {
+        let traffic = Arc::new(LiveTraffic::new(None, 1_000));
+        let Json(stats) = get_live_traffic_stats(Extension(traffic)).await.unwrap();
+        assert!(!stats.enabled);
+        assert_eq!(stats.flights, 0);
+        assert_eq!(stats.age_ms, None);
+    }
+}
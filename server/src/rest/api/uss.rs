@@ -2,43 +2,110 @@
 //! Implements the ASTM Standard at <https://github.com/uastech/standards/blob/astm_rid_api_2.1/remoteid/canonical.yaml>
 
 use super::rest_types::*;
+use crate::cache::store::{CachedQuery, FlightsCache};
+use crate::cache::{get_flights_cache, narrow_flights, CacheKey, CACHE_TTL_MILLISECONDS};
 use crate::grpc::client::GrpcClients;
+use crate::odid::store::BroadcastFlights;
+use crate::rest::formats::FlightsFormat;
+use crate::telemetry::store::PushedTelemetry;
+use crate::traffic::index::LiveTraffic;
 use axum::extract::Query;
-use axum::{Extension, Json};
+use axum::http::HeaderMap;
+use axum::response::Response;
+use axum::Extension;
 use geo::algorithm::haversine_distance::HaversineDistance;
+use geo::{Contains, Intersects};
 use hyper::StatusCode;
 use lib_common::time::{DateTime, Duration, SecondsFormat, Utc};
 use num_traits::FromPrimitive;
+use std::str::FromStr;
+use std::sync::Arc;
 use svc_gis_client_grpc::client::GetFlightsRequest as GisFlightsRequest;
 use svc_gis_client_grpc::prelude::AircraftType;
 use svc_gis_client_grpc::prelude::GisServiceClient;
 use svc_gis_client_grpc::prelude::OperationalStatus;
-const MAX_DISPLAY_AREA_DIAGONAL_METERS: f64 = 7_000.0;
+pub(crate) const MAX_DISPLAY_AREA_DIAGONAL_METERS: f64 = 7_000.0;
 
 /// A window for a given area of interest defined by two opposite corners
-struct Window {
-    lon1: f64,
-    lat1: f64,
-    lon2: f64,
-    lat2: f64,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Window {
+    /// Longitude of the first corner
+    pub lon1: f64,
+    /// Latitude of the first corner
+    pub lat1: f64,
+    /// Longitude of the second corner
+    pub lon2: f64,
+    /// Latitude of the second corner
+    pub lat2: f64,
 }
 
 impl Window {
     /// Calculate the diagonal distance of the window
-    fn diagonal(&self) -> f64 {
+    pub fn diagonal(&self) -> f64 {
         let p1 = geo::Point::<f64>::new(self.lon1, self.lat1);
         let p2 = geo::Point::<f64>::new(self.lon2, self.lat2);
         p1.haversine_distance(&p2)
     }
+
+    /// Returns the (min_lon, min_lat, max_lon, max_lat) of the window,
+    ///  regardless of which corners were provided
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        (
+            self.lon1.min(self.lon2),
+            self.lat1.min(self.lat2),
+            self.lon1.max(self.lon2),
+            self.lat1.max(self.lat2),
+        )
+    }
+
+    /// Check if two windows overlap (touching edges count as overlap)
+    pub fn intersects(&self, other: &Window) -> bool {
+        let (a_min_lon, a_min_lat, a_max_lon, a_max_lat) = self.bounds();
+        let (b_min_lon, b_min_lat, b_max_lon, b_max_lat) = other.bounds();
+
+        a_min_lon <= b_max_lon
+            && b_min_lon <= a_max_lon
+            && a_min_lat <= b_max_lat
+            && b_min_lat <= a_max_lat
+    }
+
+    /// Check if the window overlaps a polygon (touching edges count as overlap)
+    pub fn intersects_polygon(&self, polygon: &Polygon) -> bool {
+        let (min_lon, min_lat, max_lon, max_lat) = self.bounds();
+        let rect = geo::Rect::new((min_lon, min_lat), (max_lon, max_lat));
+        let exterior = polygon
+            .vertices
+            .iter()
+            .map(|vertex| (vertex.lng, vertex.lat))
+            .collect::<Vec<(f64, f64)>>();
+        let polygon = geo::Polygon::new(exterior.into(), vec![]);
+
+        // a window on a single point has a degenerate rectangle
+        polygon.intersects(&rect) || polygon.contains(&rect.center())
+    }
 }
 
 /// Check if there are identification service areas for a given RID
-async fn check_isas(_grpc_clients: &mut GrpcClients, _window: &Window) -> Result<bool, StatusCode> {
+#[cfg(not(feature = "embedded_dss"))]
+pub(crate) async fn check_isas(
+    _grpc_clients: &mut GrpcClients,
+    _window: &Window,
+) -> Result<bool, StatusCode> {
     // TODO(R5): grpc call to svc-gis
     // with optional 'check' parameter to return no values
     Ok(false)
 }
 
+/// Check if there are identification service areas for a given RID
+///  in the embedded DSS
+#[cfg(feature = "embedded_dss")]
+pub(crate) async fn check_isas(
+    _grpc_clients: &mut GrpcClients,
+    window: &Window,
+) -> Result<bool, StatusCode> {
+    Ok(crate::dss::get_dss().await.has_isas(window, Utc::now()))
+}
+
 impl From<AircraftType> for UAType {
     fn from(t: AircraftType) -> Self {
         match t {
@@ -205,7 +272,7 @@ impl TryFrom<svc_gis_client_grpc::client::Flight> for RIDFlight {
 }
 
 /// Get recent flights for a given area from svc-gis
-async fn get_recent_flights(
+pub(crate) async fn get_recent_flights(
     grpc_clients: &mut GrpcClients,
     window: &Window,
     duration_s: f32,
@@ -226,8 +293,70 @@ async fn get_recent_flights(
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    let time_start = Utc::now() - Duration::milliseconds((duration_s * 1000.0) as i64);
-    let time_end = Utc::now();
+    let now = Utc::now();
+    let since = now - Duration::milliseconds((duration_s * 1000.0) as i64);
+    let cache = get_flights_cache().await;
+    let key = CacheKey::new(window, duration_s);
+    if let Some(query) = cache.get(&key, now) {
+        let flights = narrow_flights(&query.flights, window, since);
+        rest_debug!("returning {} cached flights.", flights.len());
+        return Ok(flights);
+    }
+
+    // Concurrent lookups of the same key share a single call to svc-gis
+    let call = cache.in_flight(&key);
+    let query = call
+        .get_or_init(|| query_flights(grpc_clients, &cache, key))
+        .await
+        .clone()?;
+
+    let flights = narrow_flights(&query.flights, window, since);
+    rest_debug!("returning {} flights.", flights.len());
+    Ok(flights)
+}
+
+/// Get recent flights for a given area from the live traffic index if it
+///  covers the area, or else from svc-gis.
+/// Some areas answered from the index are also queried from svc-gis in the
+///  background, to check both report the same flights.
+pub(crate) async fn get_live_flights(
+    grpc_clients: &GrpcClients,
+    traffic: Arc<LiveTraffic>,
+    window: &Window,
+    duration_s: f32,
+) -> Result<Vec<RIDFlight>, StatusCode> {
+    let indexed = (duration_s > 0.0)
+        .then(|| traffic.flights_in(window, duration_s, Utc::now()))
+        .flatten();
+    let Some(flights) = indexed else {
+        return get_recent_flights(&mut grpc_clients.clone(), window, duration_s).await;
+    };
+
+    if traffic.should_check() {
+        let mut grpc_clients = grpc_clients.clone();
+        let indexed = flights.clone();
+        let window = *window;
+        tokio::spawn(async move {
+            match get_recent_flights(&mut grpc_clients, &window, duration_s).await {
+                Ok(gis) => {
+                    traffic.check(&indexed, &gis);
+                }
+                Err(e) => rest_warn!("could not check the live traffic index: {}", e),
+            }
+        });
+    }
+
+    rest_debug!("returning {} indexed flights.", flights.len());
+    Ok(flights)
+}
+
+/// Query svc-gis for the flights within a window between two times
+pub(crate) async fn gis_flights(
+    grpc_clients: &mut GrpcClients,
+    window: &Window,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> Result<Vec<RIDFlight>, StatusCode> {
     let request = GisFlightsRequest {
         window_min_x: window.lon1,
         window_min_y: window.lat1,
@@ -237,7 +366,7 @@ async fn get_recent_flights(
         time_end: Some(time_end.into()),
     };
 
-    let flights = grpc_clients
+    grpc_clients
         .gis
         .get_flights(request)
         .await
@@ -249,14 +378,178 @@ async fn get_recent_flights(
         .flights
         .into_iter()
         .map(TryInto::<RIDFlight>::try_into)
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect::<Result<Vec<_>, _>>()
+}
 
-    rest_debug!("returning {} flights.", flights.len());
-    Ok(flights)
+/// Query svc-gis for the whole window of a cache key, with enough history
+///  to serve the key for as long as it is cached, and cache the flights
+async fn query_flights(
+    grpc_clients: &mut GrpcClients,
+    cache: &FlightsCache,
+    key: CacheKey,
+) -> Result<CachedQuery, StatusCode> {
+    let now = Utc::now();
+    let history =
+        Duration::seconds(key.duration_s as i64) + Duration::milliseconds(CACHE_TTL_MILLISECONDS);
+    let flights = gis_flights(grpc_clients, &key.window(), now - history, now).await?;
+
+    let query = CachedQuery {
+        time: now,
+        flights: Arc::new(flights),
+    };
+    cache.insert(key, query.clone(), Utc::now());
+    Ok(query)
+}
+
+/// Add the flights received over Broadcast Remote ID and the flights
+///  pushed by partners to the flights of svc-gis, unless a flight with
+///  the same ID is already reported
+pub(crate) fn with_local_flights(
+    mut flights: Vec<RIDFlight>,
+    broadcast: &BroadcastFlights,
+    telemetry: &PushedTelemetry,
+    window: &Window,
+    duration_s: f32,
+) -> Vec<RIDFlight> {
+    let now = Utc::now();
+    let local = telemetry
+        .flights_in(window, duration_s, now)
+        .into_iter()
+        .chain(broadcast.flights_in(window, duration_s, now));
+    for flight in local {
+        if !flights.iter().any(|f| f.id == flight.id) {
+            flights.push(flight);
+        }
+    }
+
+    flights
+}
+
+/// Filters of a flight query.
+/// svc-gis only filters by area and time, so the filters are applied to
+///  the converted flights.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct FlightFilter {
+    /// Only these aircraft types, if any
+    aircraft_types: Option<Vec<UAType>>,
+
+    /// Never these aircraft types
+    exclude_aircraft_types: Vec<UAType>,
+
+    /// Only these operational statuses, if any
+    operational_statuses: Option<Vec<RIDOperationalStatus>>,
+
+    /// Only simulated or real flights, if set
+    simulated: Option<bool>,
+
+    /// Lowest geodetic altitude in meters, if set
+    altitude_min: Option<f32>,
+
+    /// Highest geodetic altitude in meters, if set
+    altitude_max: Option<f32>,
+}
+
+/// Parse a comma separated list of enum values
+fn parse_list<T: FromStr>(name: &str, list: &str) -> Result<Vec<T>, StatusCode> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            T::from_str(value).map_err(|_| {
+                rest_error!("invalid {} value: {}", name, value);
+                StatusCode::BAD_REQUEST
+            })
+        })
+        .collect()
+}
+
+impl FlightFilter {
+    /// Parse and validate the filters of a flight query
+    pub(crate) fn try_from_request(request: &GetFlightsRequest) -> Result<Self, StatusCode> {
+        let aircraft_types = request
+            .aircraft_types
+            .as_deref()
+            .map(|list| parse_list("aircraft_types", list))
+            .transpose()?;
+        let exclude_aircraft_types = request
+            .exclude_aircraft_types
+            .as_deref()
+            .map(|list| parse_list("exclude_aircraft_types", list))
+            .transpose()?
+            .unwrap_or_default();
+        let operational_statuses = request
+            .operational_statuses
+            .as_deref()
+            .map(|list| parse_list("operational_statuses", list))
+            .transpose()?;
+
+        let altitudes = [request.altitude_min, request.altitude_max];
+        if altitudes
+            .iter()
+            .flatten()
+            .any(|altitude| !altitude.is_finite())
+        {
+            rest_error!("altitude_min and altitude_max must be finite.");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        if let [Some(min), Some(max)] = altitudes {
+            if min > max {
+                rest_error!("altitude_min must not exceed altitude_max.");
+                return Err(StatusCode::BAD_REQUEST);
+            }
+        }
+
+        Ok(FlightFilter {
+            aircraft_types,
+            exclude_aircraft_types,
+            operational_statuses,
+            simulated: request.simulated,
+            altitude_min: request.altitude_min,
+            altitude_max: request.altitude_max,
+        })
+    }
+
+    /// If the flight passes the filters, a flight with an unknown
+    ///  altitude is outside of any altitude band
+    pub(crate) fn matches(&self, flight: &RIDFlight) -> bool {
+        let state = &flight.current_state;
+        let altitude = state.position.alt;
+        let in_band = match (self.altitude_min, self.altitude_max) {
+            (None, None) => true,
+            (min, max) => {
+                altitude != UNKNOWN_ALTITUDE
+                    && min.is_none_or(|min| altitude >= min)
+                    && max.is_none_or(|max| altitude <= max)
+            }
+        };
+
+        in_band
+            && self
+                .aircraft_types
+                .as_ref()
+                .is_none_or(|types| types.contains(&flight.aircraft_type))
+            && !self.exclude_aircraft_types.contains(&flight.aircraft_type)
+            && self
+                .operational_statuses
+                .as_ref()
+                .is_none_or(|statuses| statuses.contains(&state.operational_status))
+            && self
+                .simulated
+                .is_none_or(|simulated| simulated == flight.simulated)
+    }
+
+    /// Keep the flights passing the filters
+    pub(crate) fn apply(&self, flights: Vec<RIDFlight>) -> Vec<RIDFlight> {
+        flights
+            .into_iter()
+            .filter(|flight| self.matches(flight))
+            .collect()
+    }
 }
 
 /// Parse a coordinate (float) from a string
-fn parse_coordinate(coordinate: &str, lat: bool) -> Result<f64, StatusCode> {
+pub(crate) fn parse_coordinate(coordinate: &str, lat: bool) -> Result<f64, StatusCode> {
     let value = coordinate.parse::<f64>().map_err(|e| {
         rest_error!("view must be a string of format 'lat1,lon1,lat2,lon2' with floating point values: {:?}", e);
         StatusCode::BAD_REQUEST
@@ -273,6 +566,22 @@ fn parse_coordinate(coordinate: &str, lat: bool) -> Result<f64, StatusCode> {
     Ok(value)
 }
 
+/// Parse a window from a view of format 'lat1,lon1,lat2,lon2'
+pub(crate) fn parse_view(view: &str) -> Result<Window, StatusCode> {
+    let values = view.split(',').collect::<Vec<&str>>();
+    if values.len() != 4 {
+        rest_error!("view must be a string of format 'lat1,lon1,lat2,lon2'.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(Window {
+        lat1: parse_coordinate(values[0], true)?,
+        lon1: parse_coordinate(values[1], false)?,
+        lat2: parse_coordinate(values[2], true)?,
+        lon2: parse_coordinate(values[3], false)?,
+    })
+}
+
 /// Validate the input for the get_flights endpoint
 fn validate_get_flights_request(
     payload: &GetFlightsRequest,
@@ -283,18 +592,7 @@ fn validate_get_flights_request(
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    let values = payload.view.split(',').collect::<Vec<&str>>();
-    if values.len() != 4 {
-        rest_error!("view must be a string of format 'lat1,lon1,lat2,lon2'.");
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
-    let window = Window {
-        lat1: parse_coordinate(values[0], true)?,
-        lon1: parse_coordinate(values[1], false)?,
-        lat2: parse_coordinate(values[2], true)?,
-        lon2: parse_coordinate(values[3], false)?,
-    };
+    let window = parse_view(&payload.view)?;
 
     if let Some(limit) = diagonal_limit_meters {
         let diagonal = window.diagonal();
@@ -317,9 +615,9 @@ fn validate_get_flights_request(
     get,
     path = "/uss/flights",
     tag = "svc-discovery",
-    request_body = GetFlightsRequest,
+    params(GetFlightsRequest),
     responses(
-        (status = 200, description = "Flight information was successfully retrieved.", body = String),
+        (status = 200, description = "Flight information was successfully retrieved, as JSON or as a GeoJSON FeatureCollection.", body = String),
         (status = 400, description = "One or more input parameters were missing or invalid."),
         (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
         (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
@@ -328,25 +626,39 @@ fn validate_get_flights_request(
 )]
 pub async fn get_flights(
     Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(broadcast): Extension<Arc<BroadcastFlights>>,
+    Extension(telemetry): Extension<Arc<PushedTelemetry>>,
+    Extension(traffic): Extension<Arc<LiveTraffic>>,
+    headers: HeaderMap,
     Query(query): Query<GetFlightsRequest>,
-) -> Result<Json<GetFlightsResponse>, StatusCode> {
+) -> Result<Response, StatusCode> {
     rest_debug!("entry.");
 
     // TODO(R5): 403 and 401 are not implemented yet
 
     let window = validate_get_flights_request(&query, Some(MAX_DISPLAY_AREA_DIAGONAL_METERS))?;
+    let filter = FlightFilter::try_from_request(&query)?;
+    let format = FlightsFormat::negotiate(query.format.as_deref(), &headers)?;
+    let flights = get_live_flights(
+        &grpc_clients,
+        traffic,
+        &window,
+        query.recent_positions_duration,
+    )
+    .await?;
     let response = GetFlightsResponse {
-        flights: get_recent_flights(
-            &mut grpc_clients.clone(),
+        flights: filter.apply(with_local_flights(
+            flights,
+            &broadcast,
+            &telemetry,
             &window,
             query.recent_positions_duration,
-        )
-        .await?,
+        )),
         no_isas_present: !check_isas(&mut grpc_clients.clone(), &window).await?,
         ..Default::default() // applies current timestamp
     };
 
-    Ok(Json(response))
+    format.render(&response, &headers)
 }
 
 /// Get flights for a given area
@@ -354,9 +666,9 @@ pub async fn get_flights(
     get,
     path = "/demo/flights",
     tag = "svc-discovery",
-    request_body = GetFlightsRequest,
+    params(GetFlightsRequest),
     responses(
-        (status = 200, description = "Flight information was successfully retrieved.", body = String),
+        (status = 200, description = "Flight information was successfully retrieved, as JSON or as a GeoJSON FeatureCollection.", body = String),
         (status = 400, description = "One or more input parameters were missing or invalid."),
         (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
         (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
@@ -365,25 +677,37 @@ pub async fn get_flights(
 )]
 pub async fn demo_flights(
     Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(broadcast): Extension<Arc<BroadcastFlights>>,
+    Extension(telemetry): Extension<Arc<PushedTelemetry>>,
+    headers: HeaderMap,
     Query(query): Query<GetFlightsRequest>,
-) -> Result<Json<GetFlightsResponse>, StatusCode> {
+) -> Result<Response, StatusCode> {
     rest_debug!("entry.");
 
     // TODO(R5): 403 and 401 are not implemented yet
 
     let window = validate_get_flights_request(&query, None)?;
+    let filter = FlightFilter::try_from_request(&query)?;
+    let format = FlightsFormat::negotiate(query.format.as_deref(), &headers)?;
+    let flights = get_recent_flights(
+        &mut grpc_clients.clone(),
+        &window,
+        query.recent_positions_duration,
+    )
+    .await?;
     let response = GetFlightsResponse {
-        flights: get_recent_flights(
-            &mut grpc_clients.clone(),
+        flights: filter.apply(with_local_flights(
+            flights,
+            &broadcast,
+            &telemetry,
             &window,
             query.recent_positions_duration,
-        )
-        .await?,
+        )),
         no_isas_present: !check_isas(&mut grpc_clients.clone(), &window).await?,
         ..Default::default() // applies current timestamp
     };
 
-    Ok(Json(response))
+    format.render(&response, &headers)
 }
 
 #[cfg(test)]
@@ -641,6 +965,233 @@ mod tests {
         assert_eq!(e, StatusCode::BAD_REQUEST);
     }
 
+    #[test]
+    fn test_parse_view() {
+        let window = parse_view("52.3,4.8,52.4,4.9").unwrap();
+        assert_eq!(window.lat1, 52.3);
+        assert_eq!(window.lon1, 4.8);
+        assert_eq!(window.lat2, 52.4);
+        assert_eq!(window.lon2, 4.9);
+
+        let e = parse_view("52.3,4.8,52.4").unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        let e = parse_view("52.3,4.8,92.4,4.9").unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+    }
+
+    fn rid_flight(
+        aircraft_type: UAType,
+        operational_status: RIDOperationalStatus,
+        simulated: bool,
+        alt: f32,
+    ) -> RIDFlight {
+        let mut flight = crate::telemetry::tests::flight("AC-1", Utc::now(), 52.37, 4.9);
+        flight.aircraft_type = aircraft_type;
+        flight.current_state.operational_status = operational_status;
+        flight.current_state.position.alt = alt;
+        flight.simulated = simulated;
+        flight
+    }
+
+    #[test]
+    fn test_flight_filter_parse() {
+        let request = GetFlightsRequest {
+            aircraft_types: Some("Helicopter, HybridLift".to_string()),
+            exclude_aircraft_types: Some("FreeBalloon,".to_string()),
+            operational_statuses: Some("Emergency".to_string()),
+            simulated: Some(false),
+            altitude_min: Some(50.0),
+            altitude_max: Some(150.0),
+            ..Default::default()
+        };
+        let filter = FlightFilter::try_from_request(&request).unwrap();
+        assert_eq!(
+            filter.aircraft_types,
+            Some(vec![UAType::Helicopter, UAType::HybridLift])
+        );
+        assert_eq!(filter.exclude_aircraft_types, vec![UAType::FreeBalloon]);
+        assert_eq!(
+            filter.operational_statuses,
+            Some(vec![RIDOperationalStatus::Emergency])
+        );
+        assert_eq!(
+            FlightFilter::try_from_request(&GetFlightsRequest::default()).unwrap(),
+            FlightFilter::default()
+        );
+
+        let invalid = [
+            GetFlightsRequest {
+                aircraft_types: Some("Helicopter,Zeppelin".to_string()),
+                ..Default::default()
+            },
+            GetFlightsRequest {
+                operational_statuses: Some("emergency".to_string()),
+                ..Default::default()
+            },
+            GetFlightsRequest {
+                altitude_min: Some(200.0),
+                altitude_max: Some(100.0),
+                ..Default::default()
+            },
+            GetFlightsRequest {
+                altitude_max: Some(f32::NAN),
+                ..Default::default()
+            },
+        ];
+        for request in invalid {
+            let e = FlightFilter::try_from_request(&request).unwrap_err();
+            assert_eq!(e, StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[test]
+    fn test_flight_filter_matches() {
+        let flight = rid_flight(
+            UAType::Helicopter,
+            RIDOperationalStatus::Airborne,
+            false,
+            120.0,
+        );
+        assert!(FlightFilter::default().matches(&flight));
+
+        let filter = |request: GetFlightsRequest| FlightFilter::try_from_request(&request).unwrap();
+        let excluded = filter(GetFlightsRequest {
+            exclude_aircraft_types: Some("FreeBalloon,GroundObstacle".to_string()),
+            ..Default::default()
+        });
+        assert!(excluded.matches(&flight));
+        let balloon = rid_flight(
+            UAType::FreeBalloon,
+            RIDOperationalStatus::Airborne,
+            false,
+            120.0,
+        );
+        assert!(!excluded.matches(&balloon));
+
+        let only = filter(GetFlightsRequest {
+            aircraft_types: Some("Aeroplane".to_string()),
+            ..Default::default()
+        });
+        assert!(!only.matches(&flight));
+
+        let emergency = filter(GetFlightsRequest {
+            operational_statuses: Some("Emergency".to_string()),
+            ..Default::default()
+        });
+        assert!(!emergency.matches(&flight));
+        assert!(emergency.matches(&rid_flight(
+            UAType::Helicopter,
+            RIDOperationalStatus::Emergency,
+            false,
+            120.0
+        )));
+
+        let real = filter(GetFlightsRequest {
+            simulated: Some(false),
+            ..Default::default()
+        });
+        assert!(real.matches(&flight));
+        assert!(!real.matches(&rid_flight(
+            UAType::Helicopter,
+            RIDOperationalStatus::Airborne,
+            true,
+            120.0
+        )));
+
+        let band = filter(GetFlightsRequest {
+            altitude_min: Some(100.0),
+            altitude_max: Some(120.0),
+            ..Default::default()
+        });
+        assert!(band.matches(&flight));
+        let above = rid_flight(
+            UAType::Helicopter,
+            RIDOperationalStatus::Airborne,
+            false,
+            120.5,
+        );
+        assert!(!band.matches(&above));
+
+        // unknown altitudes are outside of any band
+        let below = filter(GetFlightsRequest {
+            altitude_max: Some(500.0),
+            ..Default::default()
+        });
+        let unknown = rid_flight(
+            UAType::Helicopter,
+            RIDOperationalStatus::Airborne,
+            false,
+            UNKNOWN_ALTITUDE,
+        );
+        assert!(!below.matches(&unknown));
+        assert_eq!(below.apply(vec![flight, unknown]).len(), 1);
+    }
+
+    #[test]
+    fn test_with_local_flights() {
+        use crate::odid::decoder::decode_frames;
+        use crate::odid::decoder::tests::{location_at, pack, BASIC_ID};
+        use crate::telemetry::tests::state;
+
+        let now = Utc::now();
+        let broadcast = BroadcastFlights::default();
+        let messages = decode_frames(&pack(&[BASIC_ID, location_at(now)])).unwrap();
+        broadcast.ingest("aa:bb", messages, now);
+        let telemetry = PushedTelemetry::new(["acme".to_string()].into());
+        telemetry
+            .push(
+                "acme",
+                "AC-1",
+                UAType::Aeroplane,
+                false,
+                vec![state(now, 52.365, 4.905)],
+                now,
+            )
+            .unwrap();
+        let window = parse_view("52.36,4.90,52.37,4.91").unwrap();
+
+        let flights = with_local_flights(vec![], &broadcast, &telemetry, &window, 0.0);
+        assert_eq!(flights.len(), 2);
+        assert_eq!(flights[0].id, "AC-1");
+        assert_eq!(flights[1].id, "1596F3546SN001");
+
+        // svc-gis already reports the flights
+        let flights = with_local_flights(flights, &broadcast, &telemetry, &window, 0.0);
+        assert_eq!(flights.len(), 2);
+
+        let window = parse_view("0.0,0.0,0.1,0.1").unwrap();
+        assert!(with_local_flights(vec![], &broadcast, &telemetry, &window, 0.0).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_live_flights() {
+        let config = crate::config::Config::default();
+        let grpc_clients = crate::grpc::client::GrpcClients::default(config);
+        let window = parse_view("52.36,4.89,52.38,4.91").unwrap();
+        let now = Utc::now();
+        let region = parse_view("52.2,4.7,52.5,5.1").unwrap();
+        let traffic = Arc::new(LiveTraffic::new(Some(region), 60_000));
+        traffic.update(
+            vec![crate::telemetry::tests::flight("AC-1", now, 52.37, 4.9)],
+            now,
+        );
+
+        let flights = get_live_flights(&grpc_clients, traffic.clone(), &window, 10.0)
+            .await
+            .unwrap();
+        assert_eq!(flights.len(), 1);
+        assert_eq!(traffic.stats(now).served, 1);
+
+        // areas outside of the region are queried from svc-gis
+        let outside = parse_view("52.36,5.09,52.38,5.11").unwrap();
+        let flights = get_live_flights(&grpc_clients, traffic.clone(), &outside, 10.0)
+            .await
+            .unwrap();
+        assert!(flights.is_empty());
+        assert_eq!(traffic.stats(now).served, 1);
+    }
+
     #[tokio::test]
     async fn test_get_recent_flights() {
         let config = crate::config::Config::default();
@@ -710,46 +1261,95 @@ mod tests {
     async fn test_demo_flights() {
         let config = crate::config::Config::default();
         let grpc_clients = Extension(crate::grpc::client::GrpcClients::default(config));
+        let broadcast = Extension(Arc::new(BroadcastFlights::default()));
+        let telemetry = Extension(Arc::new(PushedTelemetry::new(Default::default())));
 
         // valid window
         let request = GetFlightsRequest {
             view: "0.0,0.0,0.0,0.0".to_string(),
             recent_positions_duration: 0.0,
+            ..Default::default()
         };
 
-        let _ = demo_flights(grpc_clients.clone(), Query(request))
-            .await
-            .unwrap();
+        let _ = demo_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap();
 
         // Invalid window
         let request = GetFlightsRequest {
             view: "0.0,0.0,0.0".to_string(),
             recent_positions_duration: 0.0,
+            ..Default::default()
         };
 
-        let e = demo_flights(grpc_clients.clone(), Query(request))
-            .await
-            .unwrap_err();
+        let e = demo_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap_err();
         assert_eq!(e, StatusCode::BAD_REQUEST);
 
         // Invalid window
         let request = GetFlightsRequest {
             view: "0.0,0.0,0.0,180.0001".to_string(),
             recent_positions_duration: 0.0,
+            ..Default::default()
         };
 
-        let e = demo_flights(grpc_clients.clone(), Query(request))
-            .await
-            .unwrap_err();
+        let e = demo_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap_err();
         assert_eq!(e, StatusCode::BAD_REQUEST);
 
         let request = GetFlightsRequest {
             view: "0.0,0.0,90.001,0.0".to_string(),
             recent_positions_duration: 0.0,
+            ..Default::default()
         };
-        let e = demo_flights(grpc_clients.clone(), Query(request))
-            .await
-            .unwrap_err();
+        let e = demo_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        // unsupported format
+        let request = GetFlightsRequest {
+            view: "0.0,0.0,0.0,0.0".to_string(),
+            recent_positions_duration: 0.0,
+            format: Some("shapefile".to_string()),
+            ..Default::default()
+        };
+
+        let e = demo_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap_err();
         assert_eq!(e, StatusCode::BAD_REQUEST);
     }
 
@@ -757,62 +1357,108 @@ mod tests {
     async fn test_get_flights_recent_positions() {
         let config = crate::config::Config::default();
         let grpc_clients = Extension(crate::grpc::client::GrpcClients::default(config));
+        let broadcast = Extension(Arc::new(BroadcastFlights::default()));
+        let telemetry = Extension(Arc::new(PushedTelemetry::new(Default::default())));
+        let traffic = Extension(Arc::new(LiveTraffic::new(None, 1_000)));
 
         let request = GetFlightsRequest {
             view: "0.0,0.0,0.0".to_string(),
             recent_positions_duration: -0.0001,
+            ..Default::default()
         };
 
-        let e = get_flights(grpc_clients.clone(), Query(request))
-            .await
-            .unwrap_err();
+        let e = get_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            traffic.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap_err();
         assert_eq!(e, StatusCode::BAD_REQUEST);
 
         let request = GetFlightsRequest {
             view: "0.0,0.0,0.0,0.0".to_string(),
             recent_positions_duration: 60.0001,
+            ..Default::default()
         };
 
-        let e = get_flights(grpc_clients.clone(), Query(request))
-            .await
-            .unwrap_err();
+        let e = get_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            traffic.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap_err();
         assert_eq!(e, StatusCode::BAD_REQUEST);
 
         // Valid request
         let request = GetFlightsRequest {
             view: "0.0,0.0,0.0,0.0".to_string(),
             recent_positions_duration: 0.0,
+            ..Default::default()
         };
-        let _ = get_flights(grpc_clients.clone(), Query(request))
-            .await
-            .unwrap();
+        let _ = get_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            traffic.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap();
     }
 
     #[tokio::test]
     async fn test_get_flights_view() {
         let config = crate::config::Config::default();
         let grpc_clients = Extension(crate::grpc::client::GrpcClients::default(config));
+        let broadcast = Extension(Arc::new(BroadcastFlights::default()));
+        let telemetry = Extension(Arc::new(PushedTelemetry::new(Default::default())));
+        let traffic = Extension(Arc::new(LiveTraffic::new(None, 1_000)));
 
         // invalid - too many coordinates
         let request = GetFlightsRequest {
             view: "0.0,0.0,0.0,0.0,0.0".to_string(),
             recent_positions_duration: 0.0,
+            ..Default::default()
         };
 
-        let e = get_flights(grpc_clients.clone(), Query(request))
-            .await
-            .unwrap_err();
+        let e = get_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            traffic.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap_err();
         assert_eq!(e, StatusCode::BAD_REQUEST);
 
         // invalid - too few coordinates
         let request = GetFlightsRequest {
             view: "0.0,0.0,0.0".to_string(),
             recent_positions_duration: 0.0,
+            ..Default::default()
         };
 
-        let e = get_flights(grpc_clients.clone(), Query(request))
-            .await
-            .unwrap_err();
+        let e = get_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            traffic.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap_err();
         assert_eq!(e, StatusCode::BAD_REQUEST);
 
         for i in [
@@ -828,11 +1474,19 @@ mod tests {
             let request = GetFlightsRequest {
                 view: i.to_string(),
                 recent_positions_duration: 0.0,
+                ..Default::default()
             };
 
-            let e = get_flights(grpc_clients.clone(), Query(request))
-                .await
-                .unwrap_err();
+            let e = get_flights(
+                grpc_clients.clone(),
+                broadcast.clone(),
+                telemetry.clone(),
+                traffic.clone(),
+                HeaderMap::new(),
+                Query(request),
+            )
+            .await
+            .unwrap_err();
             assert_eq!(e, StatusCode::BAD_REQUEST);
         }
 
@@ -840,21 +1494,58 @@ mod tests {
         let request = GetFlightsRequest {
             view: "52.392365,4.850067,52.364510,4.959106".to_string(),
             recent_positions_duration: 0.0,
+            ..Default::default()
         };
 
-        let e = get_flights(grpc_clients.clone(), Query(request))
-            .await
-            .unwrap_err();
+        let e = get_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            traffic.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap_err();
         assert_eq!(e, StatusCode::PAYLOAD_TOO_LARGE);
 
+        // invalid - filter
+        let request = GetFlightsRequest {
+            view: "52.392365,4.850067,52.371385,4.906068".to_string(),
+            recent_positions_duration: 0.0,
+            operational_statuses: Some("Lost".to_string()),
+            ..Default::default()
+        };
+
+        let e = get_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            traffic.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
         // valid request
         let request = GetFlightsRequest {
             view: "52.392365,4.850067,52.371385,4.906068".to_string(),
             recent_positions_duration: 0.0,
+            simulated: Some(false),
+            ..Default::default()
         };
 
-        let _ = get_flights(grpc_clients.clone(), Query(request))
-            .await
-            .unwrap();
+        let _ = get_flights(
+            grpc_clients.clone(),
+            broadcast.clone(),
+            telemetry.clone(),
+            traffic.clone(),
+            HeaderMap::new(),
+            Query(request),
+        )
+        .await
+        .unwrap();
     }
 }
@@ -0,0 +1,156 @@
+//! REST API for the vertiports of the Aetheric network
+//! Lets external service providers discover our vertiports.
+
+use super::rest_types::*;
+use super::uss::parse_view;
+use crate::vertiports::{VertiportError, VertiportSource};
+use axum::extract::{Path, Query};
+use axum::{Extension, Json};
+use hyper::StatusCode;
+use std::sync::Arc;
+
+impl From<VertiportError> for StatusCode {
+    fn from(e: VertiportError) -> Self {
+        match e {
+            VertiportError::NotFound => StatusCode::NOT_FOUND,
+            VertiportError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+/// Get the vertiports in an area
+#[utoipa::path(
+    get,
+    path = "/vertiports",
+    tag = "svc-discovery",
+    params(GetVertiportsRequest),
+    responses(
+        (status = 200, description = "Vertiports were retrieved successfully.", body = GetVertiportsResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 503, description = "Could not reach the vertiport source.")
+    )
+)]
+pub async fn get_vertiports(
+    Extension(source): Extension<Arc<dyn VertiportSource>>,
+    Query(query): Query<GetVertiportsRequest>,
+) -> Result<Json<GetVertiportsResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let window = parse_view(&query.view)?;
+    let vertiports = source.vertiports_in(&window).map_err(|e| {
+        rest_error!("could not get vertiports: {}", e);
+        StatusCode::from(e)
+    })?;
+
+    Ok(Json(GetVertiportsResponse { vertiports }))
+}
+
+/// Get a vertiport
+#[utoipa::path(
+    get,
+    path = "/vertiports/{id}",
+    tag = "svc-discovery",
+    params(
+        ("id" = String, Path, description = "ID of the vertiport")
+    ),
+    responses(
+        (status = 200, description = "The vertiport was retrieved successfully.", body = Vertiport),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 404, description = "The requested vertiport was not found."),
+        (status = 503, description = "Could not reach the vertiport source.")
+    )
+)]
+pub async fn get_vertiport(
+    Extension(source): Extension<Arc<dyn VertiportSource>>,
+    Path(id): Path<String>,
+) -> Result<Json<Vertiport>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let vertiport = source.vertiport(&id).map_err(|e| {
+        rest_error!("could not get vertiport {}: {}", id, e);
+        StatusCode::from(e)
+    })?;
+
+    Ok(Json(vertiport))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vertiports::file::tests::{vertiport, vertiports_file};
+    use crate::vertiports::file::FileVertiports;
+
+    /// A source that can never be reached
+    #[derive(Debug)]
+    struct UnavailableSource;
+
+    impl VertiportSource for UnavailableSource {
+        fn vertiports(&self) -> Result<Vec<Vertiport>, VertiportError> {
+            Err(VertiportError::Unavailable)
+        }
+    }
+
+    fn source() -> Arc<dyn VertiportSource> {
+        let path = vertiports_file(&[vertiport("a"), vertiport("b")]);
+        let source = FileVertiports::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        Arc::new(source)
+    }
+
+    #[tokio::test]
+    async fn test_get_vertiports() {
+        let query = GetVertiportsRequest {
+            view: "52.37,4.89,52.38,4.91".to_string(),
+        };
+        let response = get_vertiports(Extension(source()), Query(query))
+            .await
+            .unwrap();
+        assert_eq!(response.vertiports.len(), 2);
+        assert_eq!(response.vertiports[0].pad_count, 4);
+
+        let query = GetVertiportsRequest {
+            view: "52.30,4.80,52.31,4.81".to_string(),
+        };
+        let response = get_vertiports(Extension(source()), Query(query))
+            .await
+            .unwrap();
+        assert!(response.vertiports.is_empty());
+
+        let query = GetVertiportsRequest {
+            view: "52.37,4.89,52.38".to_string(),
+        };
+        let e = get_vertiports(Extension(source()), Query(query))
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        let query = GetVertiportsRequest {
+            view: "52.37,4.89,52.38,4.91".to_string(),
+        };
+        let e = get_vertiports(Extension(Arc::new(UnavailableSource)), Query(query))
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_get_vertiport() {
+        let response = get_vertiport(Extension(source()), Path("b".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.id, "b");
+        assert_eq!(response.status, VertiportStatus::Open);
+
+        let e = get_vertiport(Extension(source()), Path("c".to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,217 @@
+//! REST API for meteorological reports
+//! Shares the latest METAR and TAF reports by station and by area, and
+//! accepts reports pushed by weather providers.
+
+use super::rest_types::*;
+use super::uss::parse_view;
+use crate::auth::ProviderCredentials;
+use crate::weather::store::WeatherStore;
+use crate::weather::WeatherError;
+use axum::extract::{Path, Query};
+use axum::{Extension, Json};
+use hyper::{HeaderMap, StatusCode};
+use lib_common::time::Utc;
+use std::sync::Arc;
+
+impl From<WeatherError> for StatusCode {
+    fn from(e: WeatherError) -> Self {
+        match e {
+            WeatherError::InvalidReport => StatusCode::BAD_REQUEST,
+            WeatherError::NotFound => StatusCode::NOT_FOUND,
+        }
+    }
+}
+
+/// Get the latest reports of the stations in an area
+#[utoipa::path(
+    get,
+    path = "/weather",
+    tag = "svc-discovery",
+    params(GetWeatherRequest),
+    responses(
+        (status = 200, description = "Reports were retrieved successfully.", body = GetWeatherResponse),
+        (status = 400, description = "One or more input parameters were missing or invalid."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint.")
+    )
+)]
+pub async fn get_weather(
+    Extension(weather): Extension<Arc<WeatherStore>>,
+    Query(query): Query<GetWeatherRequest>,
+) -> Result<Json<GetWeatherResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let window = parse_view(&query.view)?;
+    Ok(Json(GetWeatherResponse {
+        stations: weather.stations_in(&window),
+    }))
+}
+
+/// Get the latest reports of a station
+#[utoipa::path(
+    get,
+    path = "/weather/{station}",
+    tag = "svc-discovery",
+    params(
+        ("station" = String, Path, description = "ICAO identifier of the station")
+    ),
+    responses(
+        (status = 200, description = "Reports were retrieved successfully.", body = StationWeather),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid."),
+        (status = 403, description = "The access token was decoded successfully but did not include a scope appropriate to this endpoint."),
+        (status = 404, description = "No reports are known for the station.")
+    )
+)]
+pub async fn get_station_weather(
+    Extension(weather): Extension<Arc<WeatherStore>>,
+    Path(station): Path<String>,
+) -> Result<Json<StationWeather>, StatusCode> {
+    rest_debug!("entry.");
+
+    // TODO(R5): 403 and 401 are not implemented yet
+
+    let station = weather.station(&station).map_err(|e| {
+        rest_error!("could not get weather of {}: {}", station, e);
+        StatusCode::from(e)
+    })?;
+
+    Ok(Json(station))
+}
+
+/// Push METAR and TAF reports, separated by '=' or by line
+#[utoipa::path(
+    post,
+    path = "/weather",
+    tag = "svc-discovery",
+    request_body(content = String, content_type = "text/plain"),
+    responses(
+        (status = 200, description = "Reports were processed, invalid reports were rejected.", body = PostWeatherResponse),
+        (status = 400, description = "None of the reports could be parsed."),
+        (status = 401, description = "Bearer access token was not provided in Authorization header, token could not be decoded, or token was invalid.")
+    )
+)]
+pub async fn post_weather(
+    Extension(weather): Extension<Arc<WeatherStore>>,
+    Extension(credentials): Extension<Arc<ProviderCredentials>>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<PostWeatherResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let provider = credentials.authenticated_provider(&headers)?;
+    let (accepted, rejected) = weather.ingest(&body, Utc::now());
+    if accepted.is_empty() {
+        rest_error!(
+            "none of the {} reports pushed by {} could be parsed.",
+            rejected,
+            provider
+        );
+        return Err(StatusCode::from(WeatherError::InvalidReport));
+    }
+
+    rest_info!(
+        "{} reports of {} stored, {} rejected.",
+        accepted.len(),
+        provider,
+        rejected
+    );
+    Ok(Json(PostWeatherResponse { accepted, rejected }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::tests::{bearer, credential};
+    use crate::weather::store::tests::{store, BULLETIN};
+
+    fn credentials() -> Extension<Arc<ProviderCredentials>> {
+        Extension(Arc::new(ProviderCredentials::parse(&credential(
+            "weather", "secret",
+        ))))
+    }
+
+    #[tokio::test]
+    async fn test_post_and_get_weather() {
+        let weather = Arc::new(store());
+
+        let e = post_weather(
+            Extension(weather.clone()),
+            credentials(),
+            bearer("secret"),
+            "not a report".to_string(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        let response = post_weather(
+            Extension(weather.clone()),
+            credentials(),
+            bearer("secret"),
+            BULLETIN.to_string(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.accepted.len(), 3);
+        assert_eq!(response.rejected, 1);
+
+        let query = GetWeatherRequest {
+            view: "52.0,4.5,52.5,5.0".to_string(),
+        };
+        let response = get_weather(Extension(weather.clone()), Query(query))
+            .await
+            .unwrap();
+        assert_eq!(response.stations.len(), 1);
+        assert!(response.stations[0]
+            .metar
+            .as_ref()
+            .unwrap()
+            .raw
+            .starts_with("METAR EHAM"));
+
+        let query = GetWeatherRequest {
+            view: "52.0,4.5".to_string(),
+        };
+        let e = get_weather(Extension(weather.clone()), Query(query))
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::BAD_REQUEST);
+
+        let response = get_station_weather(Extension(weather.clone()), Path("LFPG".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.metar.as_ref().unwrap().visibility_meters,
+            Some(10_000.0)
+        );
+
+        let e = get_station_weather(Extension(weather.clone()), Path("EGLL".to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_post_weather_unauthenticated() {
+        let weather = Arc::new(store());
+
+        for headers in [HeaderMap::new(), bearer("wrong")] {
+            let e = post_weather(
+                Extension(weather.clone()),
+                credentials(),
+                headers,
+                BULLETIN.to_string(),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(e, StatusCode::UNAUTHORIZED);
+        }
+
+        let e = get_station_weather(Extension(weather), Path("EHAM".to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,133 @@
+//! Encodings of flight responses
+//! The encoders are shared with the REST client crate, which decodes the
+//! responses.
+
+use crate::grpc::server::grpc_server as pb;
+use crate::rest::api::rest_types::*;
+
+include!("../../../../openapi/encodings.rs");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::Duration;
+
+    fn response() -> GetFlightsResponse {
+        let now = Utc::now();
+        let volume = Volume4D {
+            volume: Volume3D {
+                outline_circle: Some(Circle {
+                    center: LatLngPoint {
+                        lat: 52.37,
+                        lng: 4.9,
+                    },
+                    radius: Radius {
+                        value: 300.0,
+                        units: "M".to_string(),
+                    },
+                }),
+                outline_polygon: None,
+                altitude_lower: Some(Altitude {
+                    value: 100.0,
+                    ..Default::default()
+                }),
+                altitude_upper: None,
+            },
+            time_start: now.into(),
+            time_end: (now + Duration::minutes(10)).into(),
+        };
+
+        let mut flight = crate::telemetry::tests::flight("AC-1", now, 52.37, 4.91);
+        flight.aircraft_type = UAType::HybridLift;
+        flight.current_state.operational_status = RIDOperationalStatus::Emergency;
+        flight.current_state.position.height.reference = RIDHeightReference::GroundLevel;
+        flight.operating_area.volumes = vec![volume];
+        flight.simulated = true;
+        flight.recent_positions.drain(..9);
+        GetFlightsResponse {
+            timestamp: now.into(),
+            flights: vec![flight],
+            no_isas_present: true,
+        }
+    }
+
+    #[test]
+    fn test_from_media_type() {
+        let cases = [
+            ("application/json; charset=utf-8", Some(Encoding::Json)),
+            ("application/x-protobuf", Some(Encoding::Protobuf)),
+            ("application/protobuf", Some(Encoding::Protobuf)),
+            ("Application/MsgPack", Some(Encoding::MessagePack)),
+            ("application/x-msgpack", Some(Encoding::MessagePack)),
+            ("application/cbor", Some(Encoding::Cbor)),
+            ("application/xml", None),
+        ];
+
+        for (media_type, expected) in cases {
+            assert_eq!(Encoding::from_media_type(media_type), expected);
+        }
+
+        for encoding in [
+            Encoding::Json,
+            Encoding::Protobuf,
+            Encoding::MessagePack,
+            Encoding::Cbor,
+        ] {
+            assert_eq!(
+                Encoding::from_media_type(encoding.media_type()),
+                Some(encoding)
+            );
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let response = response();
+        let expected = serde_json::to_value(&response).unwrap();
+        let json = encode_flights(Encoding::Json, &response).unwrap().len();
+        for encoding in [
+            Encoding::Json,
+            Encoding::Protobuf,
+            Encoding::MessagePack,
+            Encoding::Cbor,
+        ] {
+            let bytes = encode_flights(encoding, &response).unwrap();
+            assert!(bytes.len() <= json, "{:?}", encoding);
+
+            let decoded = decode_flights(encoding, &bytes).unwrap();
+            assert_eq!(
+                serde_json::to_value(&decoded).unwrap(),
+                expected,
+                "{:?}",
+                encoding
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let bytes = encode_flights(Encoding::Protobuf, &response()).unwrap();
+        for encoding in [Encoding::Json, Encoding::MessagePack, Encoding::Cbor] {
+            assert_eq!(
+                decode_flights(encoding, &bytes).unwrap_err(),
+                EncodingError::Decode
+            );
+        }
+
+        // a flight without a current state
+        let mut message = pb::GetFlightsResponse::decode(bytes.as_slice()).unwrap();
+        message.flights[0].current_state = None;
+        assert_eq!(
+            decode_flights(Encoding::Protobuf, &message.encode_to_vec()).unwrap_err(),
+            EncodingError::Decode
+        );
+
+        // an aircraft type out of range
+        let mut message = pb::GetFlightsResponse::decode(bytes.as_slice()).unwrap();
+        message.flights[0].aircraft_type = 16;
+        assert_eq!(
+            decode_flights(Encoding::Protobuf, &message.encode_to_vec()).unwrap_err(),
+            EncodingError::Decode
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! KML and CZML exports of flights for 3D visualisation tools
+//! The renderers are shared with the REST client crate.
+
+use crate::rest::api::rest_types::*;
+
+include!("../../../../openapi/exports.rs");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::Duration;
+
+    /// A flight with two recent positions west of its current position
+    fn flight(id: &str, now: DateTime<Utc>) -> RIDFlight {
+        let mut flight = crate::telemetry::tests::flight(id, now, 52.37, 4.91);
+        let old = flight.recent_positions.len() - 2;
+        flight.recent_positions.drain(..old);
+        for recent in flight.recent_positions.iter_mut() {
+            recent.position.lng = 4.9;
+        }
+        flight
+    }
+
+    fn response(flights: Vec<RIDFlight>, now: DateTime<Utc>) -> GetFlightsResponse {
+        GetFlightsResponse {
+            timestamp: Time {
+                value: export_time(&now),
+                format: RFC3339_FORMAT_STRING.to_string(),
+            },
+            flights,
+            no_isas_present: false,
+        }
+    }
+
+    #[test]
+    fn test_flight_track() {
+        let now = Utc::now();
+        let mut flight = flight("AC-1", now);
+
+        // a recent position at the time of the current state
+        let mut recent = flight.recent_positions[1].clone();
+        recent.time = flight.current_state.timestamp.clone();
+        recent.position.lng = 0.0;
+        flight.recent_positions.push(recent);
+        flight.recent_positions[0].time.value = "invalid".to_string();
+
+        let track = flight_track(&flight);
+        assert_eq!(track.len(), 2);
+        assert!(track[0].0 < track[1].0);
+        assert_eq!(track[1].1.lng, 4.91);
+        assert!(has_altitudes(&track));
+
+        flight.current_state.position.alt = UNKNOWN_ALTITUDE;
+        assert!(!has_altitudes(&flight_track(&flight)));
+    }
+
+    #[test]
+    fn test_style() {
+        assert_eq!(
+            flight_color(RIDOperationalStatus::Emergency, false),
+            [213, 0, 0, 255]
+        );
+        assert_eq!(flight_color(RIDOperationalStatus::Airborne, true)[3], 128);
+        assert!(aircraft_icon(UAType::Helicopter).ends_with("/heliport.png"));
+        assert!(aircraft_icon(UAType::Other).ends_with("/placemark_circle.png"));
+    }
+
+    #[test]
+    fn test_flights_to_kml() {
+        let now = Utc::now();
+        let mut escaped = flight("AC<&>", now);
+        escaped.current_state.operational_status = RIDOperationalStatus::Emergency;
+        escaped.current_state.position.alt = UNKNOWN_ALTITUDE;
+        let kml = flights_to_kml(&response(vec![flight("AC-1", now), escaped], now));
+
+        assert!(kml.starts_with("<?xml"));
+        assert!(kml.ends_with("</Document></kml>"));
+        assert_eq!(kml.matches("<Placemark>").count(), 2);
+        assert_eq!(kml.matches("<when>").count(), 6);
+        assert!(kml.contains("<name>AC-1</name>"));
+        assert!(kml.contains("<name>AC&lt;&amp;&gt;</name>"));
+        assert!(kml.contains("<altitudeMode>absolute</altitudeMode>"));
+        assert!(kml.contains("<altitudeMode>clampToGround</altitudeMode>"));
+        assert!(kml.contains("<gx:coord>4.91 52.37 120</gx:coord>"));
+        assert!(kml.contains(&format!("<when>{}</when>", export_time(&now))));
+
+        // airborne in green and emergency in red, as aabbggrr
+        assert!(kml.contains("<color>ff53c800</color>"));
+        assert!(kml.contains("<color>ff0000d5</color>"));
+        assert!(kml.contains("<Data name=\"aircraft_type\"><value>Helicopter</value></Data>"));
+    }
+
+    #[test]
+    fn test_flights_to_czml() {
+        let now = Utc::now();
+        let czml = flights_to_czml(&response(vec![flight("AC-1", now)], now));
+        let packets = czml.as_array().unwrap();
+        assert_eq!(packets.len(), 2);
+
+        let start = export_time(&(now - Duration::seconds(2)));
+        let end = export_time(&now);
+        assert_eq!(packets[0]["id"], "document");
+        assert_eq!(
+            packets[0]["clock"]["interval"],
+            format!("{}/{}", start, end)
+        );
+
+        let packet = &packets[1];
+        assert_eq!(packet["id"], "AC-1");
+        assert_eq!(packet["availability"], format!("{}/{}", start, end));
+        assert_eq!(packet["position"]["epoch"], start);
+        assert_eq!(
+            packet["position"]["cartographicDegrees"],
+            json!([0.0, 4.9, 52.37, 120.0, 1.0, 4.9, 52.37, 120.0, 2.0, 4.91, 52.37, 120.0])
+        );
+        assert_eq!(
+            packet["billboard"]["color"]["rgba"],
+            json!([0, 200, 83, 255])
+        );
+        assert_eq!(packet["billboard"]["heightReference"], "NONE");
+        assert_eq!(packet["properties"]["aircraft_type"], "Helicopter");
+
+        let empty = flights_to_czml(&response(vec![], now));
+        assert_eq!(empty.as_array().unwrap().len(), 1);
+        assert_eq!(empty[0]["clock"]["interval"], format!("{}/{}", end, end));
+    }
+}
@@ -0,0 +1,201 @@
+//! GeoJSON (RFC 7946) rendering of flights
+//! Each flight is a Point feature at its current position with the RID
+//! state as flat properties, so GIS tools can style and filter on them.
+//! Flights with at least two recent positions also get a LineString
+//! feature of their track.
+
+use crate::rest::api::rest_types::*;
+use serde_json::{json, Value};
+
+/// Value of the `feature` property of current state features
+pub const CURRENT_STATE_FEATURE: &str = "current_state";
+
+/// Value of the `feature` property of recent positions features
+pub const RECENT_POSITIONS_FEATURE: &str = "recent_positions";
+
+/// The GeoJSON position of an aircraft, without altitude if it is unknown
+///  or not wanted
+fn coordinates(position: &RIDAircraftPosition, altitude: bool) -> Value {
+    if altitude && position.alt != UNKNOWN_ALTITUDE {
+        json!([position.lng, position.lat, position.alt])
+    } else {
+        json!([position.lng, position.lat])
+    }
+}
+
+/// The Point feature of the current state of a flight
+fn current_state_feature(flight: &RIDFlight) -> Value {
+    let state = &flight.current_state;
+    let position = &state.position;
+    json!({
+        "type": "Feature",
+        "id": flight.id,
+        "geometry": {
+            "type": "Point",
+            "coordinates": coordinates(position, true)
+        },
+        "properties": {
+            "feature": CURRENT_STATE_FEATURE,
+            "flight_id": flight.id,
+            "aircraft_type": flight.aircraft_type,
+            "simulated": flight.simulated,
+            "aircraft_count": flight.operating_area.aircraft_count,
+            "timestamp": state.timestamp.value,
+            "timestamp_accuracy": state.timestamp_accuracy,
+            "operational_status": state.operational_status,
+            "alt": position.alt,
+            "accuracy_h": position.accuracy_h,
+            "accuracy_v": position.accuracy_v,
+            "extrapolated": position.extrapolated,
+            "pressure_alt": position.pressure_alt,
+            "height": position.height.distance,
+            "height_reference": position.height.reference,
+            "track": state.track,
+            "speed": state.speed,
+            "speed_accuracy": state.speed_accuracy,
+            "vertical_speed": state.vertical_speed
+        }
+    })
+}
+
+/// The LineString feature of the recent positions of a flight, if there
+///  are enough positions to draw a line.
+/// Altitudes are left out unless known for every position, as a line
+///  must not mix 2D and 3D positions.
+fn recent_positions_feature(flight: &RIDFlight) -> Option<Value> {
+    let positions = &flight.recent_positions;
+    if positions.len() < 2 {
+        return None;
+    }
+
+    let altitude = positions
+        .iter()
+        .all(|recent| recent.position.alt != UNKNOWN_ALTITUDE);
+
+    Some(json!({
+        "type": "Feature",
+        "id": format!("{}/{}", flight.id, RECENT_POSITIONS_FEATURE),
+        "geometry": {
+            "type": "LineString",
+            "coordinates": positions
+                .iter()
+                .map(|recent| coordinates(&recent.position, altitude))
+                .collect::<Vec<Value>>()
+        },
+        "properties": {
+            "feature": RECENT_POSITIONS_FEATURE,
+            "flight_id": flight.id,
+            "aircraft_type": flight.aircraft_type,
+            "simulated": flight.simulated,
+            "times": positions
+                .iter()
+                .map(|recent| recent.time.value.as_str())
+                .collect::<Vec<&str>>()
+        }
+    }))
+}
+
+/// The FeatureCollection of a flights response, with the response time
+///  and ISA presence as foreign members
+pub fn feature_collection(response: &GetFlightsResponse) -> Value {
+    let features = response
+        .flights
+        .iter()
+        .flat_map(|flight| {
+            std::iter::once(current_state_feature(flight)).chain(recent_positions_feature(flight))
+        })
+        .collect::<Vec<Value>>();
+
+    json!({
+        "type": "FeatureCollection",
+        "timestamp": response.timestamp.value,
+        "no_isas_present": response.no_isas_present,
+        "features": features
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::Utc;
+
+    /// A simulated flight with the given number of recent positions
+    fn flight(id: &str, recent: usize) -> RIDFlight {
+        let mut flight = crate::telemetry::tests::flight(id, Utc::now(), 52.37, 4.91);
+        let old = flight.recent_positions.len() - recent;
+        flight.recent_positions.drain(..old);
+        for (i, recent) in flight.recent_positions.iter_mut().enumerate() {
+            recent.position.lng = 4.9 + i as f64 * 0.001;
+        }
+        flight.simulated = true;
+        flight
+    }
+
+    #[test]
+    fn test_feature_collection() {
+        let response = GetFlightsResponse {
+            flights: vec![flight("AC-1", 3), flight("AC-2", 1)],
+            no_isas_present: true,
+            ..Default::default()
+        };
+
+        let collection = feature_collection(&response);
+        assert_eq!(collection["type"], "FeatureCollection");
+        assert_eq!(collection["no_isas_present"], true);
+        assert_eq!(collection["timestamp"], response.timestamp.value);
+
+        // a single recent position does not make a line
+        let features = collection["features"].as_array().unwrap();
+        assert_eq!(features.len(), 3);
+
+        let point = &features[0];
+        assert_eq!(point["id"], "AC-1");
+        assert_eq!(point["geometry"]["type"], "Point");
+        assert_eq!(
+            point["geometry"]["coordinates"],
+            json!([4.91, 52.37, 120.0])
+        );
+        let properties = &point["properties"];
+        assert_eq!(properties["feature"], CURRENT_STATE_FEATURE);
+        assert_eq!(properties["aircraft_type"], "Helicopter");
+        assert_eq!(properties["operational_status"], "Airborne");
+        assert_eq!(properties["simulated"], true);
+        assert_eq!(properties["speed"], 20.0);
+        assert_eq!(
+            properties["timestamp"],
+            response.flights[0].current_state.timestamp.value
+        );
+
+        let line = &features[1];
+        assert_eq!(line["id"], "AC-1/recent_positions");
+        assert_eq!(line["geometry"]["type"], "LineString");
+        assert_eq!(line["geometry"]["coordinates"].as_array().unwrap().len(), 3);
+        assert_eq!(line["properties"]["flight_id"], "AC-1");
+        assert_eq!(line["properties"]["times"].as_array().unwrap().len(), 3);
+
+        assert_eq!(features[2]["id"], "AC-2");
+    }
+
+    #[test]
+    fn test_unknown_altitude() {
+        let mut flight = flight("AC-1", 2);
+        flight.current_state.position.alt = UNKNOWN_ALTITUDE;
+        flight.recent_positions[0].position.alt = UNKNOWN_ALTITUDE;
+        let response = GetFlightsResponse {
+            flights: vec![flight],
+            ..Default::default()
+        };
+
+        let collection = feature_collection(&response);
+        let features = collection["features"].as_array().unwrap();
+        assert_eq!(features[0]["geometry"]["coordinates"], json!([4.91, 52.37]));
+        let positions = &response.flights[0].recent_positions;
+        assert_eq!(
+            features[1]["geometry"]["coordinates"],
+            json!([
+                [positions[0].position.lng, 52.37],
+                [positions[1].position.lng, 52.37]
+            ])
+        );
+    }
+}
@@ -0,0 +1,382 @@
+//! Formats of flight query responses
+//! The format is chosen with the `format` query parameter, or negotiated
+//! with the Accept header. JSON is returned when neither asks for a
+//! supported format, so existing clients are not affected.
+//! Responses are tagged by the flights they report, so clients polling an
+//! unchanged area get a `304 NOT MODIFIED` without a body.
+
+pub mod encodings;
+pub mod exports;
+pub mod geojson;
+pub mod mvt;
+
+use crate::rest::api::rest_types::GetFlightsResponse;
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use encodings::{encode_flights, Encoding, EncodingError};
+use hyper::header::{ACCEPT, CONTENT_TYPE, ETAG, IF_NONE_MATCH, VARY};
+use hyper::StatusCode;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// A format of flight query responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlightsFormat {
+    /// `GetFlightsResponse` as JSON
+    Json,
+
+    /// A GeoJSON FeatureCollection (RFC 7946)
+    GeoJson,
+
+    /// A KML document of flight tracks
+    Kml,
+
+    /// A CZML document of flight tracks
+    Czml,
+
+    /// `GetFlightsResponse` as the Protobuf message of the gRPC API
+    Protobuf,
+
+    /// `GetFlightsResponse` as MessagePack
+    MessagePack,
+
+    /// `GetFlightsResponse` as CBOR
+    Cbor,
+}
+
+impl From<Encoding> for FlightsFormat {
+    fn from(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Json => FlightsFormat::Json,
+            Encoding::Protobuf => FlightsFormat::Protobuf,
+            Encoding::MessagePack => FlightsFormat::MessagePack,
+            Encoding::Cbor => FlightsFormat::Cbor,
+        }
+    }
+}
+
+impl FlightsFormat {
+    /// The media type of the format
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            FlightsFormat::Json => "application/json",
+            FlightsFormat::GeoJson => "application/geo+json",
+            FlightsFormat::Kml => exports::KML_MEDIA_TYPE,
+            FlightsFormat::Czml => exports::CZML_MEDIA_TYPE,
+            FlightsFormat::Protobuf => Encoding::Protobuf.media_type(),
+            FlightsFormat::MessagePack => Encoding::MessagePack.media_type(),
+            FlightsFormat::Cbor => Encoding::Cbor.media_type(),
+        }
+    }
+
+    /// The format of a `format` query parameter value
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "json" => Some(FlightsFormat::Json),
+            "geojson" => Some(FlightsFormat::GeoJson),
+            "kml" => Some(FlightsFormat::Kml),
+            "czml" => Some(FlightsFormat::Czml),
+            "protobuf" => Some(FlightsFormat::Protobuf),
+            "msgpack" => Some(FlightsFormat::MessagePack),
+            "cbor" => Some(FlightsFormat::Cbor),
+            _ => None,
+        }
+    }
+
+    /// The format of a media range of the Accept header
+    fn from_media_range(range: &str) -> Option<Self> {
+        match range.to_ascii_lowercase().as_str() {
+            "application/json" | "application/*" | "*/*" => Some(FlightsFormat::Json),
+            "application/geo+json" => Some(FlightsFormat::GeoJson),
+            exports::KML_MEDIA_TYPE => Some(FlightsFormat::Kml),
+            exports::CZML_MEDIA_TYPE => Some(FlightsFormat::Czml),
+            range => Encoding::from_media_type(range).map(FlightsFormat::from),
+        }
+    }
+
+    /// The supported format with the highest quality in an Accept header,
+    ///  the first one listed on a tie
+    fn from_accept(accept: &str) -> Option<Self> {
+        let mut best: Option<(f32, FlightsFormat)> = None;
+        for range in accept.split(',') {
+            let mut parts = range.split(';').map(str::trim);
+            let Some(format) = parts.next().and_then(FlightsFormat::from_media_range) else {
+                continue;
+            };
+
+            let quality = parts
+                .filter_map(|param| param.strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if quality > 0.0 && best.is_none_or(|(best, _)| quality > best) {
+                best = Some((quality, format));
+            }
+        }
+
+        best.map(|(_, format)| format)
+    }
+
+    /// Choose the format of a response, the `format` query parameter
+    ///  taking precedence over the Accept header
+    pub fn negotiate(format: Option<&str>, headers: &HeaderMap) -> Result<Self, StatusCode> {
+        if let Some(name) = format {
+            return FlightsFormat::from_name(name).ok_or_else(|| {
+                rest_error!("unsupported format: {}.", name);
+                StatusCode::BAD_REQUEST
+            });
+        }
+
+        Ok(headers
+            .get(ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .and_then(FlightsFormat::from_accept)
+            .unwrap_or(FlightsFormat::Json))
+    }
+
+    /// The weak entity tag of a response in this format, a hash of the
+    ///  media type, the flights and the presence of ISAs.
+    /// The time of the response is left out, so the tag only changes with
+    ///  the flight set.
+    pub fn etag(&self, response: &GetFlightsResponse) -> Result<String, StatusCode> {
+        let flights = serde_json::to_vec(&response.flights).map_err(|e| {
+            rest_error!("could not hash flights: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        let mut hasher = DefaultHasher::new();
+        self.media_type().hash(&mut hasher);
+        response.no_isas_present.hash(&mut hasher);
+        flights.hash(&mut hasher);
+        Ok(format!("W/\"{:016x}\"", hasher.finish()))
+    }
+
+    /// Render a response in this format, or `304 NOT MODIFIED` if the
+    ///  If-None-Match header of the request has its entity tag
+    pub fn render(
+        &self,
+        response: &GetFlightsResponse,
+        headers: &HeaderMap,
+    ) -> Result<Response, StatusCode> {
+        let etag = self.etag(response)?;
+        if if_none_match(headers, &etag) {
+            rest_debug!("flights not modified: {}", etag);
+            return Ok((
+                StatusCode::NOT_MODIFIED,
+                [(ETAG, etag), (VARY, ACCEPT.to_string())],
+            )
+                .into_response());
+        }
+
+        let body = match self {
+            FlightsFormat::Json => encode_flights(Encoding::Json, response),
+            FlightsFormat::GeoJson => serde_json::to_vec(&geojson::feature_collection(response))
+                .map_err(|_| EncodingError::Encode),
+            FlightsFormat::Kml => Ok(exports::flights_to_kml(response).into_bytes()),
+            FlightsFormat::Czml => serde_json::to_vec(&exports::flights_to_czml(response))
+                .map_err(|_| EncodingError::Encode),
+            FlightsFormat::Protobuf => encode_flights(Encoding::Protobuf, response),
+            FlightsFormat::MessagePack => encode_flights(Encoding::MessagePack, response),
+            FlightsFormat::Cbor => encode_flights(Encoding::Cbor, response),
+        }
+        .map_err(|e| {
+            rest_error!("could not render flights as {}: {}", self.media_type(), e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        Ok((
+            [
+                (CONTENT_TYPE, self.media_type().to_string()),
+                (VARY, ACCEPT.to_string()),
+                (ETAG, etag),
+            ],
+            body,
+        )
+            .into_response())
+    }
+}
+
+/// If an If-None-Match header has the entity tag, with the weak
+///  comparison of RFC 9110
+pub(crate) fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::rest_types::Time;
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let cases = [
+            ("application/geo+json", FlightsFormat::GeoJson),
+            ("application/json", FlightsFormat::Json),
+            ("*/*", FlightsFormat::Json),
+            ("text/html", FlightsFormat::Json),
+            (
+                "Application/Geo+JSON; charset=utf-8",
+                FlightsFormat::GeoJson,
+            ),
+            ("application/geo+json, */*", FlightsFormat::GeoJson),
+            ("*/*, application/geo+json", FlightsFormat::Json),
+            (
+                "application/json;q=0.5, application/geo+json",
+                FlightsFormat::GeoJson,
+            ),
+            ("application/geo+json;q=0, */*;q=0.1", FlightsFormat::Json),
+            (exports::KML_MEDIA_TYPE, FlightsFormat::Kml),
+            ("application/x-protobuf", FlightsFormat::Protobuf),
+            (
+                "application/x-msgpack, application/json;q=0.5",
+                FlightsFormat::MessagePack,
+            ),
+            ("application/cbor", FlightsFormat::Cbor),
+            (
+                "application/czml+json, application/json;q=0.9",
+                FlightsFormat::Czml,
+            ),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(
+                FlightsFormat::negotiate(None, &accept(value)).unwrap(),
+                expected,
+                "{}",
+                value
+            );
+        }
+
+        assert_eq!(
+            FlightsFormat::negotiate(None, &HeaderMap::new()).unwrap(),
+            FlightsFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_negotiate_parameter() {
+        let headers = accept("application/json");
+        assert_eq!(
+            FlightsFormat::negotiate(Some("GeoJSON"), &headers).unwrap(),
+            FlightsFormat::GeoJson
+        );
+        assert_eq!(
+            FlightsFormat::negotiate(Some("json"), &accept("application/geo+json")).unwrap(),
+            FlightsFormat::Json
+        );
+        assert_eq!(
+            FlightsFormat::negotiate(Some("shapefile"), &headers).unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_etag() {
+        let response = GetFlightsResponse::default();
+        let etag = FlightsFormat::Json.etag(&response).unwrap();
+        assert!(etag.starts_with("W/\""));
+
+        // the time of the response is not part of the tag
+        let later = GetFlightsResponse {
+            timestamp: Time {
+                value: "2030-01-01T00:00:00.000Z".to_string(),
+                format: response.timestamp.format.clone(),
+            },
+            ..response.clone()
+        };
+        assert_eq!(FlightsFormat::Json.etag(&later).unwrap(), etag);
+
+        let isas = GetFlightsResponse {
+            no_isas_present: true,
+            ..response.clone()
+        };
+        assert_ne!(FlightsFormat::Json.etag(&isas).unwrap(), etag);
+        assert_ne!(FlightsFormat::GeoJson.etag(&response).unwrap(), etag);
+    }
+
+    #[tokio::test]
+    async fn test_render_not_modified() {
+        let response = GetFlightsResponse::default();
+        let rendered = FlightsFormat::Json
+            .render(&response, &HeaderMap::new())
+            .unwrap();
+        let etag = rendered.headers().get(ETAG).unwrap().clone();
+
+        let strong = etag.to_str().unwrap().trim_start_matches("W/").to_string();
+        for tag in [
+            etag.to_str().unwrap().to_string(),
+            strong,
+            format!("\"other\", {}", etag.to_str().unwrap()),
+            "*".to_string(),
+        ] {
+            let mut headers = HeaderMap::new();
+            headers.insert(IF_NONE_MATCH, tag.parse().unwrap());
+            let rendered = FlightsFormat::Json.render(&response, &headers).unwrap();
+            assert_eq!(rendered.status(), StatusCode::NOT_MODIFIED, "{}", tag);
+            assert_eq!(rendered.headers().get(ETAG).unwrap(), etag);
+
+            let body = hyper::body::to_bytes(rendered.into_body()).await.unwrap();
+            assert!(body.is_empty());
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, "W/\"other\"".parse().unwrap());
+        let rendered = FlightsFormat::Json.render(&response, &headers).unwrap();
+        assert_eq!(rendered.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_render() {
+        let response = GetFlightsResponse::default();
+        for format in [FlightsFormat::Json, FlightsFormat::GeoJson] {
+            let rendered = format.render(&response, &HeaderMap::new()).unwrap();
+            assert_eq!(rendered.status(), StatusCode::OK);
+            assert_eq!(
+                rendered.headers().get(CONTENT_TYPE).unwrap(),
+                format.media_type()
+            );
+            assert_eq!(rendered.headers().get(VARY).unwrap(), "accept");
+
+            let body = hyper::body::to_bytes(rendered.into_body()).await.unwrap();
+            let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(value["no_isas_present"], false);
+        }
+
+        for format in [FlightsFormat::Kml, FlightsFormat::Czml] {
+            let rendered = format.render(&response, &HeaderMap::new()).unwrap();
+            assert_eq!(
+                rendered.headers().get(CONTENT_TYPE).unwrap(),
+                format.media_type()
+            );
+
+            let body = hyper::body::to_bytes(rendered.into_body()).await.unwrap();
+            assert!(body.contains(&b'<') == (format == FlightsFormat::Kml));
+        }
+
+        for encoding in [Encoding::Protobuf, Encoding::MessagePack, Encoding::Cbor] {
+            let format = FlightsFormat::from(encoding);
+            let rendered = format.render(&response, &HeaderMap::new()).unwrap();
+            assert_eq!(
+                rendered.headers().get(CONTENT_TYPE).unwrap(),
+                encoding.media_type()
+            );
+
+            let body = hyper::body::to_bytes(rendered.into_body()).await.unwrap();
+            let decoded = encodings::decode_flights(encoding, &body).unwrap();
+            assert_eq!(decoded.timestamp.value, response.timestamp.value);
+        }
+    }
+}
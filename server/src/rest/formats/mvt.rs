@@ -0,0 +1,491 @@
+//! Mapbox Vector Tile (MVT) encoding of flight tiles
+//! Zoomed in tiles have a `flights` layer of points at the current
+//! positions and a `trails` layer of lines through the recent positions,
+//! zoomed out tiles a `clusters` layer of points with their flight count.
+//! Geometries are in tile coordinates of the vector tile extent.
+
+use crate::rest::api::rest_types::*;
+use crate::rest::api::tiles::Tile;
+use prost::Message;
+use std::collections::HashMap;
+use vector_tile::tile::{Feature, GeomType, Layer, Value};
+
+/// module generated from proto/vector_tile.proto
+pub mod vector_tile {
+    #![allow(unused_qualifications, missing_docs)]
+    tonic::include_proto!("vector_tile");
+}
+
+/// The media type of Mapbox Vector Tiles
+pub const MVT_MEDIA_TYPE: &str = "application/vnd.mapbox-vector-tile";
+
+/// Version of the vector tile specification
+const MVT_VERSION: u32 = 2;
+
+/// Size of a tile in tile coordinates
+pub const EXTENT: u32 = 4096;
+
+/// Name of the layer of current positions
+pub const FLIGHTS_LAYER: &str = "flights";
+
+/// Name of the layer of recent position trails
+pub const TRAILS_LAYER: &str = "trails";
+
+/// Name of the layer of aggregated flights
+pub const CLUSTERS_LAYER: &str = "clusters";
+
+/// Geometry command to start a new point or line
+const MOVE_TO: u32 = 1;
+
+/// Geometry command to extend a line
+const LINE_TO: u32 = 2;
+
+/// An attribute of a feature
+#[derive(Debug, Clone, PartialEq)]
+enum Attribute {
+    /// A text attribute
+    Text(String),
+
+    /// A numeric attribute
+    Number(f64),
+
+    /// A count
+    Count(u64),
+
+    /// A boolean attribute
+    Flag(bool),
+}
+
+impl From<Attribute> for Value {
+    fn from(attribute: Attribute) -> Self {
+        let mut value = Value::default();
+        match attribute {
+            Attribute::Text(text) => value.string_value = Some(text),
+            Attribute::Number(number) => value.double_value = Some(number),
+            Attribute::Count(count) => value.uint_value = Some(count),
+            Attribute::Flag(flag) => value.bool_value = Some(flag),
+        }
+
+        value
+    }
+}
+
+/// A layer under construction, sharing its keys and values between
+///  features
+struct LayerBuilder {
+    /// The layer
+    layer: Layer,
+
+    /// Index of each key
+    keys: HashMap<&'static str, u32>,
+
+    /// Index of each value, by its debug representation as floats are not
+    ///  hashable
+    values: HashMap<String, u32>,
+}
+
+impl LayerBuilder {
+    /// An empty layer
+    fn new(name: &str) -> Self {
+        LayerBuilder {
+            layer: Layer {
+                version: MVT_VERSION,
+                name: name.to_string(),
+                extent: Some(EXTENT),
+                ..Default::default()
+            },
+            keys: HashMap::new(),
+            values: HashMap::new(),
+        }
+    }
+
+    /// Add a feature with its attributes
+    fn add(
+        &mut self,
+        geom_type: GeomType,
+        geometry: Vec<u32>,
+        attributes: Vec<(&'static str, Attribute)>,
+    ) {
+        let mut tags = Vec::with_capacity(attributes.len() * 2);
+        for (key, attribute) in attributes {
+            let keys = &mut self.layer.keys;
+            let key = *self.keys.entry(key).or_insert_with(|| {
+                keys.push(key.to_string());
+                keys.len() as u32 - 1
+            });
+
+            let value = Value::from(attribute);
+            let values = &mut self.layer.values;
+            let value = *self
+                .values
+                .entry(format!("{:?}", value))
+                .or_insert_with(|| {
+                    values.push(value);
+                    values.len() as u32 - 1
+                });
+
+            tags.extend([key, value]);
+        }
+
+        let id = self.layer.features.len() as u64 + 1;
+        self.layer.features.push(Feature {
+            id: Some(id),
+            tags,
+            r#type: Some(geom_type as i32),
+            geometry,
+        });
+    }
+
+    /// The layer, if it has features
+    fn build(self) -> Option<Layer> {
+        (!self.layer.features.is_empty()).then_some(self.layer)
+    }
+}
+
+/// A geometry command with its repeat count
+fn command(id: u32, count: u32) -> u32 {
+    (id & 0x7) | (count << 3)
+}
+
+/// Zigzag encoding of a geometry parameter
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+/// Maximum distance of tile coordinates from the tile, in tiles, so that
+///  the coordinates of far away points and their deltas fit in an i32
+const MAX_TILE_DISTANCE: f64 = 16.0;
+
+/// The position of a point in tile coordinates, which lie outside of the
+///  extent for points outside of the tile.
+/// Latitudes are clamped to the Web Mercator bounds and coordinates to
+///  `MAX_TILE_DISTANCE` tiles around the tile, so lines to far away points
+///  keep their direction within the tile.
+fn tile_coordinates(tile: &Tile, lat: f64, lng: f64) -> (i32, i32) {
+    let max_lat = std::f64::consts::PI.sinh().atan().to_degrees();
+    let (x, y) = tile.project(lat.clamp(-max_lat, max_lat), lng);
+    let extent = EXTENT as f64;
+    let limit = MAX_TILE_DISTANCE * extent;
+    let coordinate = |value: f64| (value * extent).round().clamp(-limit, limit) as i32;
+    (coordinate(x), coordinate(y))
+}
+
+/// The geometry of a single point
+fn point_geometry((x, y): (i32, i32)) -> Vec<u32> {
+    vec![command(MOVE_TO, 1), zigzag(x), zigzag(y)]
+}
+
+/// The geometry of a line, if it has at least two distinct points
+fn line_geometry(points: &[(i32, i32)]) -> Option<Vec<u32>> {
+    let mut points = points.to_vec();
+    points.dedup();
+    let (first, rest) = points.split_first()?;
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut geometry = Vec::with_capacity(points.len() * 2 + 2);
+    geometry.extend([command(MOVE_TO, 1), zigzag(first.0), zigzag(first.1)]);
+    geometry.push(command(LINE_TO, rest.len() as u32));
+    let mut cursor = *first;
+    for point in rest {
+        geometry.extend([zigzag(point.0 - cursor.0), zigzag(point.1 - cursor.1)]);
+        cursor = *point;
+    }
+
+    Some(geometry)
+}
+
+/// The attributes identifying a flight
+fn flight_attributes(flight: &RIDFlight) -> Vec<(&'static str, Attribute)> {
+    vec![
+        ("id", Attribute::Text(flight.id.clone())),
+        (
+            "aircraft_type",
+            Attribute::Text(flight.aircraft_type.to_string()),
+        ),
+        ("simulated", Attribute::Flag(flight.simulated)),
+    ]
+}
+
+/// The attributes of the current state of a flight, without unknown
+///  altitude
+fn state_attributes(flight: &RIDFlight) -> Vec<(&'static str, Attribute)> {
+    let state = &flight.current_state;
+    let mut attributes = flight_attributes(flight);
+    attributes.extend([
+        (
+            "operational_status",
+            Attribute::Text(state.operational_status.to_string()),
+        ),
+        ("timestamp", Attribute::Text(state.timestamp.value.clone())),
+        ("track", Attribute::Number(state.track as f64)),
+        ("speed", Attribute::Number(state.speed as f64)),
+        (
+            "vertical_speed",
+            Attribute::Number(state.vertical_speed as f64),
+        ),
+    ]);
+
+    if state.position.alt != UNKNOWN_ALTITUDE {
+        attributes.push(("alt", Attribute::Number(state.position.alt as f64)));
+    }
+
+    attributes
+}
+
+/// Encode a tile of flights as a Mapbox Vector Tile.
+/// Flights are points of the `flights` layer if their current position is
+///  within the tile, and lines of the `trails` layer through their recent
+///  and current positions. Clusters are points of the `clusters` layer.
+pub fn encode_tile(tile: &Tile, response: &GetFlightTileResponse) -> Vec<u8> {
+    let mut flights = LayerBuilder::new(FLIGHTS_LAYER);
+    let mut trails = LayerBuilder::new(TRAILS_LAYER);
+    let mut clusters = LayerBuilder::new(CLUSTERS_LAYER);
+
+    for flight in &response.flights {
+        let position = &flight.current_state.position;
+        if tile.offset(position.lat, position.lng).is_some() {
+            flights.add(
+                GeomType::Point,
+                point_geometry(tile_coordinates(tile, position.lat, position.lng)),
+                state_attributes(flight),
+            );
+        }
+
+        let points = flight
+            .recent_positions
+            .iter()
+            .map(|recent| &recent.position)
+            .chain(std::iter::once(position))
+            .map(|position| tile_coordinates(tile, position.lat, position.lng))
+            .collect::<Vec<(i32, i32)>>();
+        if let Some(geometry) = line_geometry(&points) {
+            trails.add(GeomType::Linestring, geometry, flight_attributes(flight));
+        }
+    }
+
+    for cluster in &response.clusters {
+        clusters.add(
+            GeomType::Point,
+            point_geometry(tile_coordinates(tile, cluster.lat, cluster.lng)),
+            vec![("count", Attribute::Count(cluster.count as u64))],
+        );
+    }
+
+    vector_tile::Tile {
+        layers: [flights, trails, clusters]
+            .into_iter()
+            .filter_map(LayerBuilder::build)
+            .collect(),
+    }
+    .encode_to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::tests::flight;
+    use lib_common::time::Utc;
+
+    /// Decode the points of a geometry, with the number of MoveTo commands
+    fn decode_geometry(geometry: &[u32]) -> (Vec<(i32, i32)>, usize) {
+        let unzigzag = |value: u32| ((value >> 1) as i32) ^ -((value & 1) as i32);
+        let mut points = vec![];
+        let mut moves = 0;
+        let mut cursor = (0, 0);
+        let mut values = geometry.iter();
+        while let Some(integer) = values.next() {
+            let (id, count) = (integer & 0x7, integer >> 3);
+            if id == MOVE_TO {
+                moves += 1;
+            }
+
+            for _ in 0..count {
+                let dx = unzigzag(*values.next().unwrap());
+                let dy = unzigzag(*values.next().unwrap());
+                cursor = (cursor.0 + dx, cursor.1 + dy);
+                points.push(cursor);
+            }
+        }
+
+        (points, moves)
+    }
+
+    /// The attributes of a feature by key
+    fn attributes(layer: &Layer, feature: &Feature) -> HashMap<String, Value> {
+        feature
+            .tags
+            .chunks(2)
+            .map(|tag| {
+                (
+                    layer.keys[tag[0] as usize].clone(),
+                    layer.values[tag[1] as usize].clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_zigzag() {
+        assert_eq!(zigzag(0), 0);
+        assert_eq!(zigzag(-1), 1);
+        assert_eq!(zigzag(1), 2);
+        assert_eq!(zigzag(-2), 3);
+        assert_eq!(command(MOVE_TO, 1), 9);
+        assert_eq!(command(LINE_TO, 3), 26);
+        assert!(line_geometry(&[(1, 1), (1, 1)]).is_none());
+    }
+
+    #[test]
+    fn test_encode_flights() {
+        let tile = Tile::new(13, 4207, 2691).unwrap();
+        let window = tile.window();
+        let (lat, lng) = (
+            (window.lat1 + window.lat2) / 2.0,
+            (window.lon1 + window.lon2) / 2.0,
+        );
+        let now = Utc::now();
+        let mut inside = flight("AC-1", now, lat, lng);
+        for (i, recent) in inside.recent_positions.iter_mut().enumerate() {
+            recent.position.lng = window.lon1 + (window.lon2 - window.lon1) * i as f64 / 20.0;
+        }
+        let mut outside = flight("AC-2", now, lat, window.lon2 + 0.01);
+        outside.recent_positions[9].position.lng = lng;
+        outside.current_state.position.alt = UNKNOWN_ALTITUDE;
+
+        let response = GetFlightTileResponse {
+            z: tile.z,
+            x: tile.x,
+            y: tile.y,
+            timestamp: Time::default(),
+            flights: vec![inside, outside],
+            clusters: vec![],
+        };
+
+        let bytes = encode_tile(&tile, &response);
+        let decoded = vector_tile::Tile::decode(bytes.as_slice()).unwrap();
+        assert_eq!(decoded.layers.len(), 2);
+
+        let layer = &decoded.layers[0];
+        assert_eq!(layer.name, FLIGHTS_LAYER);
+        assert_eq!(layer.version, MVT_VERSION);
+        assert_eq!(layer.extent(), EXTENT);
+        assert_eq!(layer.features.len(), 1);
+
+        let feature = &layer.features[0];
+        assert_eq!(feature.r#type(), GeomType::Point);
+        let (points, moves) = decode_geometry(&feature.geometry);
+        assert_eq!(moves, 1);
+        assert_eq!(points.len(), 1);
+        let (x, y) = points[0];
+        assert!((x - 2048).abs() <= 1, "{}", x);
+        assert!((y - 2048).abs() <= 40, "{}", y);
+
+        let values = attributes(layer, feature);
+        assert_eq!(values["id"].string_value.as_deref(), Some("AC-1"));
+        assert_eq!(
+            values["aircraft_type"].string_value.as_deref(),
+            Some("Helicopter")
+        );
+        assert_eq!(
+            values["operational_status"].string_value.as_deref(),
+            Some("Airborne")
+        );
+        assert_eq!(values["simulated"].bool_value, Some(false));
+        assert_eq!(values["speed"].double_value, Some(20.0));
+        assert_eq!(values["alt"].double_value, Some(120.0));
+
+        let layer = &decoded.layers[1];
+        assert_eq!(layer.name, TRAILS_LAYER);
+        assert_eq!(layer.features.len(), 2);
+        let feature = &layer.features[0];
+        assert_eq!(feature.r#type(), GeomType::Linestring);
+        let (points, moves) = decode_geometry(&feature.geometry);
+        assert_eq!(moves, 1);
+        assert_eq!(points.len(), 11);
+        assert_eq!(points[0].0, 0);
+        assert!(points.windows(2).take(9).all(|pair| pair[0].0 < pair[1].0));
+
+        // the trail of a flight that left the tile leaves the extent
+        let (points, _) = decode_geometry(&layer.features[1].geometry);
+        assert!(points.last().unwrap().0 > EXTENT as i32);
+        assert_eq!(
+            attributes(layer, &layer.features[1])["id"]
+                .string_value
+                .as_deref(),
+            Some("AC-2")
+        );
+    }
+
+    #[test]
+    fn test_encode_polar_trail() {
+        let tile = Tile::new(22, 2_153_486, 1_378_153).unwrap();
+        let window = tile.window();
+        let (lat, lng) = (
+            (window.lat1 + window.lat2) / 2.0,
+            (window.lon1 + window.lon2) / 2.0,
+        );
+        let mut polar = flight("AC-1", Utc::now(), lat, lng);
+        polar.recent_positions[0].position.lat = 90.0;
+        polar.recent_positions[1].position.lat = -90.0;
+        polar.recent_positions[2].position.lng = -180.0;
+
+        let response = GetFlightTileResponse {
+            z: tile.z,
+            x: tile.x,
+            y: tile.y,
+            timestamp: Time::default(),
+            flights: vec![polar],
+            clusters: vec![],
+        };
+
+        let bytes = encode_tile(&tile, &response);
+        let decoded = vector_tile::Tile::decode(bytes.as_slice()).unwrap();
+        let layer = &decoded.layers[1];
+        assert_eq!(layer.name, TRAILS_LAYER);
+        let (points, _) = decode_geometry(&layer.features[0].geometry);
+        let limit = MAX_TILE_DISTANCE as i32 * EXTENT as i32;
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[0].1, -limit);
+        assert_eq!(points[1].1, limit);
+        assert_eq!(points[2].0, -limit);
+        assert_eq!(points[0].0, points[3].0);
+        assert!((points[3].1 - 2048).abs() <= 1, "{:?}", points);
+    }
+
+    #[test]
+    fn test_encode_clusters() {
+        let tile = Tile::new(5, 16, 10).unwrap();
+        let window = tile.window();
+        let response = GetFlightTileResponse {
+            z: tile.z,
+            x: tile.x,
+            y: tile.y,
+            timestamp: Time::default(),
+            flights: vec![],
+            clusters: vec![FlightCluster {
+                lat: window.lat2,
+                lng: window.lon1,
+                count: 3,
+            }],
+        };
+
+        let bytes = encode_tile(&tile, &response);
+        let decoded = vector_tile::Tile::decode(bytes.as_slice()).unwrap();
+        assert_eq!(decoded.layers.len(), 1);
+
+        let layer = &decoded.layers[0];
+        assert_eq!(layer.name, CLUSTERS_LAYER);
+        let feature = &layer.features[0];
+        assert_eq!(decode_geometry(&feature.geometry).0, vec![(0, 0)]);
+        assert_eq!(attributes(layer, feature)["count"].uint_value, Some(3));
+
+        let empty = GetFlightTileResponse {
+            clusters: vec![],
+            ..response
+        };
+        let decoded = vector_tile::Tile::decode(encode_tile(&tile, &empty).as_slice()).unwrap();
+        assert!(decoded.layers.is_empty());
+    }
+}
@@ -4,6 +4,7 @@
 #[macro_use]
 pub mod macros;
 pub mod api;
+pub mod formats;
 pub mod server;
 
 use std::fmt::{self, Display, Formatter};
@@ -14,7 +15,37 @@ use utoipa::OpenApi;
 #[openapi(
     paths(
         api::uss::get_flights,
-        api::uss::demo_flights
+        api::uss::demo_flights,
+        api::operational_intents::get_operational_intent,
+        api::operational_intents::get_operational_intent_telemetry,
+        api::operational_intents::notify_operational_intent,
+        api::constraints::get_constraint,
+        api::constraints::notify_constraint,
+        api::reports::submit_report,
+        api::reports::query_reports,
+        api::availability::get_availability,
+        api::availability::set_availability,
+        api::registry::list_providers,
+        api::registry::create_provider,
+        api::registry::get_provider,
+        api::registry::update_provider,
+        api::registry::delete_provider,
+        api::registry::lookup_providers,
+        api::vertiports::get_vertiports,
+        api::vertiports::get_vertiport,
+        api::weather::get_weather,
+        api::weather::get_station_weather,
+        api::weather::post_weather,
+        api::schedules::get_vertiport_schedule,
+        api::schedules::get_flight_route,
+        api::broadcast::post_odid,
+        api::telemetry::post_telemetry,
+        api::areas::get_area_flights,
+        api::nearby::get_nearby_flights,
+        api::cache::get_flights_cache_stats,
+        api::traffic::get_live_traffic_stats,
+        api::tiles::get_flight_tile,
+        api::stats::get_traffic_stats
     ),
     components(
         schemas(
@@ -40,6 +71,74 @@ use utoipa::OpenApi;
             api::rest_types::HorizontalAccuracy,
             api::rest_types::VerticalAccuracy,
             api::rest_types::RIDHeightReference,
+            api::rest_types::SubscriptionState,
+            api::rest_types::UssAvailabilityState,
+            api::rest_types::OperationalIntentState,
+            api::rest_types::OperationalIntentReference,
+            api::rest_types::OperationalIntentDetails,
+            api::rest_types::OperationalIntent,
+            api::rest_types::GetOperationalIntentDetailsResponse,
+            api::rest_types::Position,
+            api::rest_types::Velocity,
+            api::rest_types::VehicleTelemetry,
+            api::rest_types::GetOperationalIntentTelemetryResponse,
+            api::rest_types::PutOperationalIntentDetailsParameters,
+            api::rest_types::ConstraintReference,
+            api::rest_types::ConstraintDetails,
+            api::rest_types::Constraint,
+            api::rest_types::GetConstraintDetailsResponse,
+            api::rest_types::PutConstraintDetailsParameters,
+            api::rest_types::RecorderRole,
+            api::rest_types::ExchangeRecord,
+            api::rest_types::ErrorReport,
+            api::rest_types::StoredErrorReport,
+            api::rest_types::QueryErrorReportsRequest,
+            api::rest_types::QueryErrorReportsResponse,
+            api::rest_types::UssAvailabilityStatus,
+            api::rest_types::UssAvailabilityStatusResponse,
+            api::rest_types::SetUssAvailabilityStatusParameters,
+            api::rest_types::ProviderService,
+            api::rest_types::ProviderRole,
+            api::rest_types::ProviderEndpoint,
+            api::rest_types::ProviderPublicKey,
+            api::rest_types::ServiceProvider,
+            api::rest_types::ListServiceProvidersResponse,
+            api::rest_types::LookupServiceProvidersRequest,
+            api::rest_types::VertiportStatus,
+            api::rest_types::DayOfWeek,
+            api::rest_types::OperatingHours,
+            api::rest_types::Vertiport,
+            api::rest_types::GetVertiportsRequest,
+            api::rest_types::GetVertiportsResponse,
+            api::rest_types::WeatherReportType,
+            api::rest_types::Wind,
+            api::rest_types::WeatherReport,
+            api::rest_types::StationWeather,
+            api::rest_types::GetWeatherRequest,
+            api::rest_types::GetWeatherResponse,
+            api::rest_types::PostWeatherResponse,
+            api::rest_types::ScheduleEntry,
+            api::rest_types::GetVertiportScheduleRequest,
+            api::rest_types::GetVertiportScheduleResponse,
+            api::rest_types::GetFlightRouteResponse,
+            api::rest_types::PostOdidRequest,
+            api::rest_types::PostOdidResponse,
+            api::rest_types::PostTelemetryRequest,
+            api::rest_types::PostTelemetryResponse,
+            api::rest_types::GetAreaFlightsRequest,
+            api::rest_types::GetNearbyFlightsRequest,
+            api::rest_types::NearbyFlight,
+            api::rest_types::GetNearbyFlightsResponse,
+            api::rest_types::FlightsCacheStats,
+            api::rest_types::LiveTrafficStats,
+            api::rest_types::GetFlightTileRequest,
+            api::rest_types::FlightCluster,
+            api::rest_types::GetFlightTileResponse,
+            api::rest_types::GetTrafficStatsRequest,
+            api::rest_types::DensityCell,
+            api::rest_types::AircraftTypeStats,
+            api::rest_types::OperationalStatusStats,
+            api::rest_types::GetTrafficStatsResponse,
         )
     ),
     tags(
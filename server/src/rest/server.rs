@@ -1,12 +1,27 @@
 //! Rest server implementation
 
 use super::api;
+use crate::auth::get_provider_credentials;
+use crate::cache::get_flights_cache;
 use crate::config::Config;
+use crate::f3548::reports::MAX_REPORT_BYTES;
+use crate::f3548::{
+    get_constraints, get_error_reports, get_flight_plans, get_peer_constraints, get_peer_intents,
+    get_uss_availability,
+};
 use crate::grpc::client::GrpcClients;
+use crate::odid::get_broadcast_flights;
+use crate::registry::get_registry;
+use crate::schedules::{get_schedule_access, get_schedules};
 use crate::shutdown_signal;
+use crate::stats::get_query_limits;
+use crate::telemetry::{get_gis_forwarder, get_pushed_telemetry};
+use crate::traffic::get_live_traffic;
+use crate::vertiports::get_vertiports;
+use crate::weather::get_weather;
 use axum::{
     error_handling::HandleErrorLayer,
-    extract::Extension,
+    extract::{DefaultBodyLimit, Extension},
     http::{HeaderValue, StatusCode},
     routing, BoxError, Router,
 };
@@ -16,6 +31,7 @@ use tower::{
     limit::{ConcurrencyLimitLayer, RateLimitLayer},
     ServiceBuilder,
 };
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 
@@ -78,6 +94,98 @@ pub async fn rest_server(
         .route("/health", routing::get(api::health::health_check)) // MUST HAVE
         .route("/uss/flights", routing::get(api::uss::get_flights))
         .route("/demo/flights", routing::get(api::uss::demo_flights))
+        .route(
+            "/uss/flights/area",
+            routing::post(api::areas::get_area_flights),
+        )
+        .route(
+            "/uss/flights/nearby",
+            routing::get(api::nearby::get_nearby_flights),
+        )
+        .route(
+            "/tiles/flights/:z/:x/:y",
+            routing::get(api::tiles::get_flight_tile),
+        )
+        .route(
+            "/stats/traffic",
+            routing::get(api::stats::get_traffic_stats),
+        )
+        .route(
+            "/uss/v1/operational_intents",
+            routing::post(api::operational_intents::notify_operational_intent),
+        )
+        .route(
+            "/uss/v1/operational_intents/:entityid",
+            routing::get(api::operational_intents::get_operational_intent),
+        )
+        .route(
+            "/uss/v1/operational_intents/:entityid/telemetry",
+            routing::get(api::operational_intents::get_operational_intent_telemetry),
+        )
+        .route(
+            "/uss/v1/constraints",
+            routing::post(api::constraints::notify_constraint),
+        )
+        .route(
+            "/uss/v1/constraints/:entityid",
+            routing::get(api::constraints::get_constraint),
+        )
+        .route(
+            "/uss/v1/reports",
+            routing::post(api::reports::submit_report)
+                .layer(DefaultBodyLimit::max(2 * MAX_REPORT_BYTES)),
+        )
+        .route(
+            "/uss/v1/uss_availability/:uss_id",
+            routing::get(api::availability::get_availability)
+                .put(api::availability::set_availability),
+        )
+        .route("/ops/reports", routing::get(api::reports::query_reports))
+        .route(
+            "/ops/cache/flights",
+            routing::get(api::cache::get_flights_cache_stats),
+        )
+        .route(
+            "/ops/traffic/live",
+            routing::get(api::traffic::get_live_traffic_stats),
+        )
+        .route(
+            "/registry/providers",
+            routing::get(api::registry::list_providers).post(api::registry::create_provider),
+        )
+        .route(
+            "/registry/providers/:id",
+            routing::get(api::registry::get_provider)
+                .put(api::registry::update_provider)
+                .delete(api::registry::delete_provider),
+        )
+        .route(
+            "/registry/lookup",
+            routing::get(api::registry::lookup_providers),
+        )
+        .route("/vertiports", routing::get(api::vertiports::get_vertiports))
+        .route(
+            "/vertiports/:id",
+            routing::get(api::vertiports::get_vertiport),
+        )
+        .route(
+            "/weather",
+            routing::get(api::weather::get_weather).post(api::weather::post_weather),
+        )
+        .route(
+            "/weather/:station",
+            routing::get(api::weather::get_station_weather),
+        )
+        .route("/broadcast/odid", routing::post(api::broadcast::post_odid))
+        .route("/telemetry", routing::post(api::telemetry::post_telemetry))
+        .route(
+            "/schedules/vertiports/:id",
+            routing::get(api::schedules::get_vertiport_schedule),
+        )
+        .route(
+            "/schedules/flights/:id/route",
+            routing::get(api::schedules::get_flight_route),
+        )
         // .route(
         //     "/uss/identification_service_areas/:id",
         //     routing::get(api::get_isas),
@@ -92,7 +200,26 @@ pub async fn rest_server(
                 .allow_headers(Any)
                 .allow_methods(Any),
         )
+        .layer(CompressionLayer::new())
         .layer(limit_middleware)
+        .layer(Extension(get_flight_plans().await))
+        .layer(Extension(get_peer_intents().await))
+        .layer(Extension(get_constraints().await))
+        .layer(Extension(get_peer_constraints().await))
+        .layer(Extension(get_error_reports().await))
+        .layer(Extension(get_uss_availability().await))
+        .layer(Extension(get_registry().await))
+        .layer(Extension(get_vertiports().await))
+        .layer(Extension(get_weather().await))
+        .layer(Extension(get_schedules().await))
+        .layer(Extension(get_schedule_access().await))
+        .layer(Extension(get_broadcast_flights().await))
+        .layer(Extension(get_pushed_telemetry().await))
+        .layer(Extension(get_flights_cache().await))
+        .layer(Extension(get_live_traffic().await))
+        .layer(Extension(get_query_limits().await))
+        .layer(Extension(get_provider_credentials().await))
+        .layer(Extension(get_gis_forwarder().await))
         .layer(Extension(grpc_clients)); // Extension layer must be last
 
     rest_info!("hosted at {:?}", full_rest_addr);
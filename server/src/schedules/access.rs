@@ -0,0 +1,133 @@
+//! Access of service providers to our schedules
+//! Each provider is granted either every vertiport or a list of vertiports.
+
+use std::collections::{BTreeSet, HashMap};
+
+/// The vertiports a provider may read the schedules of
+#[derive(Debug, Clone, PartialEq)]
+pub enum VertiportAccess {
+    /// The schedules of every vertiport and every route
+    All,
+
+    /// The schedules of the listed vertiports, and the routes of the
+    ///  flights departing from or arriving at them
+    Only(BTreeSet<String>),
+}
+
+/// Access of service providers to our schedules, by provider ID
+#[derive(Debug, Clone, Default)]
+pub struct ScheduleAccess {
+    /// The access of each provider
+    providers: HashMap<String, VertiportAccess>,
+}
+
+impl ScheduleAccess {
+    /// Parse the access from a comma separated list of 'id=vertiports',
+    ///  with vertiports either '*' or separated by '|'.
+    /// Invalid entries are skipped.
+    pub fn parse(value: &str) -> Self {
+        let providers = value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let Some((id, vertiports)) = entry.split_once('=') else {
+                    rest_warn!(
+                        "schedule access must be of format 'id=vertiports': {}",
+                        entry
+                    );
+                    return None;
+                };
+
+                let (id, vertiports) = (id.trim(), vertiports.trim());
+                if id.is_empty() || vertiports.is_empty() {
+                    rest_warn!("schedule access must have an ID and vertiports: {}", entry);
+                    return None;
+                }
+
+                let access = match vertiports {
+                    "*" => VertiportAccess::All,
+                    _ => VertiportAccess::Only(
+                        vertiports
+                            .split('|')
+                            .map(str::trim)
+                            .filter(|vertiport| !vertiport.is_empty())
+                            .map(str::to_string)
+                            .collect(),
+                    ),
+                };
+
+                Some((id.to_string(), access))
+            })
+            .collect();
+
+        ScheduleAccess { providers }
+    }
+
+    /// Whether the provider may read any schedule at all
+    pub fn is_known(&self, provider: &str) -> bool {
+        self.providers.contains_key(provider)
+    }
+
+    /// Whether the provider may read the schedule of the vertiport
+    pub fn allows(&self, provider: &str, vertiport_id: &str) -> bool {
+        match self.providers.get(provider) {
+            Some(VertiportAccess::All) => true,
+            Some(VertiportAccess::Only(vertiports)) => vertiports.contains(vertiport_id),
+            None => false,
+        }
+    }
+
+    /// Whether the provider may read the route of a flight between the
+    ///  vertiports. Flights outside of any vertiport are only shared with
+    ///  providers granted every vertiport.
+    pub fn allows_route(
+        &self,
+        provider: &str,
+        origin_vertiport_id: Option<&str>,
+        target_vertiport_id: Option<&str>,
+    ) -> bool {
+        match self.providers.get(provider) {
+            Some(VertiportAccess::All) => true,
+            Some(VertiportAccess::Only(_)) => [origin_vertiport_id, target_vertiport_id]
+                .into_iter()
+                .flatten()
+                .any(|vertiport_id| self.allows(provider, vertiport_id)),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let access = ScheduleAccess::parse(" a=* , b=VP-A|VP-B, c, =VP-A, d=, e=| ");
+        assert!(access.is_known("a"));
+        assert!(access.is_known("b"));
+        assert!(!access.is_known("c"));
+        assert!(!access.is_known("d"));
+        assert_eq!(
+            access.providers.get("e"),
+            Some(&VertiportAccess::Only(BTreeSet::new()))
+        );
+        assert!(ScheduleAccess::parse("").providers.is_empty());
+    }
+
+    #[test]
+    fn test_allows() {
+        let access = ScheduleAccess::parse("a=*,b=VP-A|VP-B");
+        assert!(access.allows("a", "VP-C"));
+        assert!(access.allows("b", "VP-A"));
+        assert!(!access.allows("b", "VP-C"));
+        assert!(!access.allows("c", "VP-A"));
+
+        assert!(access.allows_route("a", None, None));
+        assert!(access.allows_route("b", Some("VP-C"), Some("VP-B")));
+        assert!(!access.allows_route("b", Some("VP-C"), None));
+        assert!(!access.allows_route("b", None, None));
+        assert!(!access.allows_route("c", Some("VP-A"), Some("VP-B")));
+    }
+}
@@ -0,0 +1,244 @@
+//! Schedules of the Aetheric network
+//! Planned departures, arrivals and routes shared with other providers.
+//! Flight plans are read from a [`ScheduleBackend`], so the schedules can
+//! be served from the plans pushed by the scheduler.
+
+pub mod access;
+
+use crate::f3548::flight_plans::{FlightPlan, FlightPlans};
+use crate::f3548::{get_flight_plans, F3548Error};
+use access::ScheduleAccess;
+use lib_common::time::{DateTime, Utc};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Errors returned by a schedule backend
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScheduleError {
+    /// No flight exists with the provided ID
+    NotFound,
+
+    /// The backend could not be reached
+    Unavailable,
+}
+
+impl std::error::Error for ScheduleError {}
+
+impl Display for ScheduleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::NotFound => write!(f, "Flight not found"),
+            ScheduleError::Unavailable => write!(f, "Schedule backend unavailable"),
+        }
+    }
+}
+
+/// A source of planned flights
+pub trait ScheduleBackend: Debug + Send + Sync {
+    /// The flights planned to be airborne at some point of the time
+    ///  range, ordered by departure time
+    fn flights(
+        &self,
+        time_start: DateTime<Utc>,
+        time_end: DateTime<Utc>,
+    ) -> Result<Vec<FlightPlan>, ScheduleError>;
+
+    /// The planned flight with the given ID
+    fn flight(&self, id: &str) -> Result<FlightPlan, ScheduleError>;
+
+    /// The flights departing from the vertiport in the time range,
+    ///  ordered by departure time
+    fn departures(
+        &self,
+        vertiport_id: &str,
+        time_start: DateTime<Utc>,
+        time_end: DateTime<Utc>,
+    ) -> Result<Vec<FlightPlan>, ScheduleError> {
+        Ok(self
+            .flights(time_start, time_end)?
+            .into_iter()
+            .filter(|plan| plan.origin_vertiport_id.as_deref() == Some(vertiport_id))
+            .filter(|plan| plan.time_start >= time_start && plan.time_start <= time_end)
+            .collect())
+    }
+
+    /// The flights arriving at the vertiport in the time range,
+    ///  ordered by arrival time
+    fn arrivals(
+        &self,
+        vertiport_id: &str,
+        time_start: DateTime<Utc>,
+        time_end: DateTime<Utc>,
+    ) -> Result<Vec<FlightPlan>, ScheduleError> {
+        let mut arrivals = self
+            .flights(time_start, time_end)?
+            .into_iter()
+            .filter(|plan| plan.target_vertiport_id.as_deref() == Some(vertiport_id))
+            .filter(|plan| plan.time_end >= time_start && plan.time_end <= time_end)
+            .collect::<Vec<FlightPlan>>();
+        arrivals.sort_by(|a, b| a.time_end.cmp(&b.time_end).then(a.id.cmp(&b.id)));
+        Ok(arrivals)
+    }
+}
+
+impl ScheduleBackend for FlightPlans {
+    fn flights(
+        &self,
+        time_start: DateTime<Utc>,
+        time_end: DateTime<Utc>,
+    ) -> Result<Vec<FlightPlan>, ScheduleError> {
+        Ok(self
+            .list(Utc::now())
+            .into_iter()
+            .filter(|plan| plan.time_start <= time_end && plan.time_end >= time_start)
+            .collect())
+    }
+
+    fn flight(&self, id: &str) -> Result<FlightPlan, ScheduleError> {
+        self.get(id, Utc::now()).map_err(|e| match e {
+            F3548Error::NotFound => ScheduleError::NotFound,
+            _ => ScheduleError::Unavailable,
+        })
+    }
+}
+
+pub(crate) static SCHEDULES: OnceCell<Arc<dyn ScheduleBackend>> = OnceCell::const_new();
+pub(crate) static SCHEDULE_ACCESS: OnceCell<Arc<ScheduleAccess>> = OnceCell::const_new();
+
+/// Returns the schedule backend, serving the flight plans pushed by the
+///  scheduler.
+/// Initializes the backend if it hasn't been initialized yet.
+pub async fn get_schedules() -> Arc<dyn ScheduleBackend> {
+    SCHEDULES
+        .get_or_init(|| async move {
+            let backend: Arc<dyn ScheduleBackend> = get_flight_plans().await;
+            backend
+        })
+        .await
+        .clone()
+}
+
+/// Returns the providers allowed to read schedules, parsed from the
+///  schedule access of a Config object generated from environment variables.
+/// Initializes the access list if it hasn't been initialized yet.
+pub async fn get_schedule_access() -> Arc<ScheduleAccess> {
+    SCHEDULE_ACCESS
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            Arc::new(ScheduleAccess::parse(&config.schedule_access))
+        })
+        .await
+        .clone()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::f3548::flight_plans::tests::plan;
+    use lib_common::time::Duration;
+
+    /// Backend serving a fixed set of flight plans
+    #[derive(Debug)]
+    pub(crate) struct FixtureSchedules(pub(crate) Vec<FlightPlan>);
+
+    impl ScheduleBackend for FixtureSchedules {
+        fn flights(
+            &self,
+            time_start: DateTime<Utc>,
+            time_end: DateTime<Utc>,
+        ) -> Result<Vec<FlightPlan>, ScheduleError> {
+            let mut plans = self
+                .0
+                .iter()
+                .filter(|plan| plan.time_start <= time_end && plan.time_end >= time_start)
+                .cloned()
+                .collect::<Vec<FlightPlan>>();
+            plans.sort_by_key(|plan| plan.time_start);
+            Ok(plans)
+        }
+
+        fn flight(&self, id: &str) -> Result<FlightPlan, ScheduleError> {
+            self.0
+                .iter()
+                .find(|plan| plan.id == id)
+                .cloned()
+                .ok_or(ScheduleError::NotFound)
+        }
+    }
+
+    /// A flight from origin to target, departing `start` minutes from
+    ///  `now` and flying for 10 minutes
+    pub(crate) fn flight(
+        id: &str,
+        origin: &str,
+        target: &str,
+        now: DateTime<Utc>,
+        start: i64,
+    ) -> FlightPlan {
+        let mut p = plan(id, vec![(52.37, 4.89, 100.0), (52.38, 4.90, 100.0)]);
+        p.origin_vertiport_id = Some(origin.to_string());
+        p.target_vertiport_id = Some(target.to_string());
+        p.time_start = now + Duration::minutes(start);
+        p.time_end = p.time_start + Duration::minutes(10);
+        p
+    }
+
+    pub(crate) fn fixtures(now: DateTime<Utc>) -> FixtureSchedules {
+        FixtureSchedules(vec![
+            flight("c", "VP-B", "VP-A", now, 30),
+            flight("a", "VP-A", "VP-B", now, 0),
+            flight("b", "VP-A", "VP-C", now, 15),
+        ])
+    }
+
+    fn ids(plans: Vec<FlightPlan>) -> Vec<String> {
+        plans.into_iter().map(|p| p.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_get_schedules() {
+        let a = get_schedules().await;
+        let b = get_schedules().await;
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let a = get_schedule_access().await;
+        let b = get_schedule_access().await;
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+
+    #[test]
+    fn test_departures_arrivals() {
+        let now = Utc::now();
+        let backend = fixtures(now);
+        let end = now + Duration::hours(1);
+
+        assert_eq!(
+            ids(backend.departures("VP-A", now, end).unwrap()),
+            ["a", "b"]
+        );
+        assert_eq!(ids(backend.arrivals("VP-A", now, end).unwrap()), ["c"]);
+        assert_eq!(ids(backend.arrivals("VP-B", now, end).unwrap()), ["a"]);
+        assert!(backend.departures("VP-C", now, end).unwrap().is_empty());
+
+        // b departs before the range but arrives in it
+        let start = now + Duration::minutes(20);
+        assert!(ids(backend.departures("VP-A", start, end).unwrap()).is_empty());
+        assert_eq!(ids(backend.arrivals("VP-C", start, end).unwrap()), ["b"]);
+    }
+
+    #[test]
+    fn test_flight_plans_backend() {
+        let now = Utc::now();
+        let plans = FlightPlans::new("uss", "https://uss.example.com");
+        for plan in fixtures(now).0 {
+            plans.put(plan, now).unwrap();
+        }
+
+        let end = now + Duration::minutes(20);
+        assert_eq!(ids(plans.flights(now, end).unwrap()), ["a", "b"]);
+        assert_eq!(ids(plans.departures("VP-A", now, end).unwrap()), ["a", "b"]);
+        assert_eq!(plans.flight("c").unwrap().id, "c");
+        assert_eq!(plans.flight("d").unwrap_err(), ScheduleError::NotFound);
+    }
+}
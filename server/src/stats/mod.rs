@@ -0,0 +1,661 @@
+//! Traffic statistics of an area over a time range
+//! svc-gis is queried for one slice of the time range at a time, and each
+//! slice is folded into the counts before the next one is requested, so a
+//! long time range never holds more than a slice of positions in memory.
+//! A position is counted in the slice of its timestamp only.
+//! The view, the time range and the flights reported by svc-gis are
+//! bounded, queries run for a limited time and only a few of them run at
+//! the same time, so statistics can't keep svc-gis busy for long.
+
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::rest_types::*;
+use crate::rest::api::uss::{gis_flights, Window};
+use hyper::StatusCode;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::{OnceCell, Semaphore};
+use tokio::time::Instant;
+
+/// Duration of the time slices queried from svc-gis, in seconds
+pub const SLICE_SECONDS: i64 = 60;
+
+/// Maximum time range of a query in hours
+pub const MAX_RANGE_HOURS: i64 = 24;
+
+/// Maximum number of cells of a density grid
+pub const MAX_GRID_CELLS: u64 = 10_000;
+
+/// Maximum number of distinct flights counted by a query
+pub const MAX_FLIGHTS: usize = 50_000;
+
+/// Maximum diagonal of the view of a query in meters, about a city and its
+///  surroundings
+pub const MAX_VIEW_DIAGONAL_METERS: f64 = 20_000.0;
+
+/// Maximum number of flights reported by svc-gis to a query, a flight being
+///  reported again by every slice it is seen in
+pub const MAX_FLIGHT_REPORTS: usize = 1_000_000;
+
+/// Time a query has to complete, in seconds
+pub const MAX_QUERY_SECONDS: u64 = 60;
+
+/// Maximum number of queries running at the same time
+pub const MAX_CONCURRENT_QUERIES: usize = 2;
+
+/// Limits on the queries of traffic statistics running at the same time
+#[derive(Debug)]
+pub struct QueryLimits {
+    /// A permit for every query that can run at the same time
+    permits: Semaphore,
+
+    /// Time a query has to complete
+    timeout: std::time::Duration,
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        QueryLimits {
+            permits: Semaphore::new(MAX_CONCURRENT_QUERIES),
+            timeout: std::time::Duration::from_secs(MAX_QUERY_SECONDS),
+        }
+    }
+}
+
+pub(crate) static QUERY_LIMITS: OnceCell<Arc<QueryLimits>> = OnceCell::const_new();
+
+/// Returns the limits on the queries of traffic statistics.
+/// Initializes the limits if they haven't been initialized yet.
+pub async fn get_query_limits() -> Arc<QueryLimits> {
+    QUERY_LIMITS
+        .get_or_init(|| async move { Arc::new(QueryLimits::default()) })
+        .await
+        .clone()
+}
+
+/// A grid of square cells over a window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Grid {
+    /// Longitude of the west edge
+    min_lng: f64,
+
+    /// Latitude of the south edge
+    min_lat: f64,
+
+    /// Longitude of the east edge
+    max_lng: f64,
+
+    /// Latitude of the north edge
+    max_lat: f64,
+
+    /// Size of the cells in degrees
+    resolution: f64,
+
+    /// Number of rows
+    rows: u32,
+
+    /// Number of columns
+    cols: u32,
+}
+
+impl Grid {
+    /// A grid of cells of the given size in degrees covering a window.
+    /// Returns 400 if the size is not a positive number and 413 if the
+    ///  window is too large or the grid has too many cells.
+    pub fn new(window: &Window, resolution: f64) -> Result<Self, StatusCode> {
+        if !resolution.is_finite() || resolution <= 0.0 {
+            rest_error!("resolution must be a positive number of degrees.");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        if window.diagonal() > MAX_VIEW_DIAGONAL_METERS {
+            rest_error!(
+                "view diagonal exceeds the maximum of {} meters.",
+                MAX_VIEW_DIAGONAL_METERS
+            );
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+
+        let (min_lng, min_lat, max_lng, max_lat) = window.bounds();
+        // spans that are a multiple of the resolution don't get an extra
+        //  cell from rounding errors
+        let count = |span: f64| (span / resolution - 1e-9).ceil().max(1.0);
+        let (rows, cols) = (count(max_lat - min_lat), count(max_lng - min_lng));
+        if rows * cols > MAX_GRID_CELLS as f64 {
+            rest_error!(
+                "density grid exceeds the maximum of {} cells.",
+                MAX_GRID_CELLS
+            );
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+
+        Ok(Grid {
+            min_lng,
+            min_lat,
+            max_lng,
+            max_lat,
+            resolution,
+            rows: rows as u32,
+            cols: cols as u32,
+        })
+    }
+
+    /// The window covered by the grid
+    pub fn window(&self) -> Window {
+        Window {
+            lon1: self.min_lng,
+            lat1: self.min_lat,
+            lon2: self.max_lng,
+            lat2: self.max_lat,
+        }
+    }
+
+    /// The (row, column) of the cell of a position, None if the position is
+    ///  outside of the window
+    pub fn cell(&self, lat: f64, lng: f64) -> Option<(u32, u32)> {
+        if !(self.min_lat..=self.max_lat).contains(&lat)
+            || !(self.min_lng..=self.max_lng).contains(&lng)
+        {
+            return None;
+        }
+
+        // positions on the north and east edges are in the last cells
+        let index = |offset: f64, count: u32| ((offset / self.resolution) as u32).min(count - 1);
+        Some((
+            index(lat - self.min_lat, self.rows),
+            index(lng - self.min_lng, self.cols),
+        ))
+    }
+
+    /// The (lat, lng) center of a cell
+    fn center(&self, row: u32, col: u32) -> (f64, f64) {
+        (
+            self.min_lat + (row as f64 + 0.5) * self.resolution,
+            self.min_lng + (col as f64 + 0.5) * self.resolution,
+        )
+    }
+}
+
+/// The positions and distinct flights counted for a cell or a category
+#[derive(Debug, Default)]
+struct Counts {
+    /// Number of positions
+    positions: u64,
+
+    /// Index of each flight with a counted position
+    flights: HashSet<u32>,
+}
+
+impl Counts {
+    /// Count positions of a flight
+    fn add(&mut self, flight: u32, positions: u64) {
+        self.positions += positions;
+        self.flights.insert(flight);
+    }
+}
+
+/// The counts of a category, added if nothing was counted for it yet
+fn category<T: PartialEq>(categories: &mut Vec<(T, Counts)>, key: T) -> &mut Counts {
+    let index = match categories.iter().position(|(category, _)| *category == key) {
+        Some(index) => index,
+        None => {
+            categories.push((key, Counts::default()));
+            categories.len() - 1
+        }
+    };
+
+    &mut categories[index].1
+}
+
+/// Traffic statistics under aggregation
+#[derive(Debug)]
+pub struct TrafficStats {
+    /// The density grid
+    grid: Grid,
+
+    /// Index of each flight with a counted position
+    flights: HashMap<String, u32>,
+
+    /// Number of flights reported by svc-gis, over all slices
+    reports: usize,
+
+    /// Number of counted positions
+    positions: u64,
+
+    /// Counts of the cells with traffic by (row, column)
+    cells: BTreeMap<(u32, u32), Counts>,
+
+    /// Counts by aircraft type
+    aircraft_types: Vec<(UAType, Counts)>,
+
+    /// Counts by operational status
+    operational_statuses: Vec<(RIDOperationalStatus, Counts)>,
+}
+
+impl TrafficStats {
+    /// Empty statistics over a grid
+    pub fn new(grid: Grid) -> Self {
+        TrafficStats {
+            grid,
+            flights: HashMap::new(),
+            reports: 0,
+            positions: 0,
+            cells: BTreeMap::new(),
+            aircraft_types: vec![],
+            operational_statuses: vec![],
+        }
+    }
+
+    /// The index of a flight, added if not counted yet.
+    /// Returns 413 if too many flights were counted.
+    fn flight_index(&mut self, id: &str) -> Result<u32, StatusCode> {
+        if let Some(index) = self.flights.get(id) {
+            return Ok(*index);
+        }
+
+        if self.flights.len() >= MAX_FLIGHTS {
+            rest_error!(
+                "traffic statistics exceed the maximum of {} flights.",
+                MAX_FLIGHTS
+            );
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+
+        let index = self.flights.len() as u32;
+        self.flights.insert(id.to_string(), index);
+        Ok(index)
+    }
+
+    /// Count the recent and current positions of flights reported within
+    ///  the grid in a time slice, from its start inclusive to its end
+    ///  exclusive. Positions are counted under the operational status of
+    ///  the current state of their flight in the slice.
+    /// Returns 413 if too many flights were reported or counted.
+    pub fn add(
+        &mut self,
+        flights: &[RIDFlight],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<(), StatusCode> {
+        self.reports += flights.len();
+        if self.reports > MAX_FLIGHT_REPORTS {
+            rest_error!(
+                "traffic statistics exceed the maximum of {} flight reports.",
+                MAX_FLIGHT_REPORTS
+            );
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+
+        let grid = self.grid;
+        for flight in flights {
+            let current = &flight.current_state;
+            let mut times = HashSet::new();
+            let cells = flight
+                .recent_positions
+                .iter()
+                .map(|recent| (&recent.time, &recent.position))
+                .chain(std::iter::once((&current.timestamp, &current.position)))
+                .filter(|(time, _)| times.insert(time.value.as_str()))
+                .filter(|(time, _)| {
+                    time.to_datetime()
+                        .is_some_and(|time| start <= time && time < end)
+                })
+                .filter_map(|(_, position)| grid.cell(position.lat, position.lng))
+                .collect::<Vec<(u32, u32)>>();
+            if cells.is_empty() {
+                continue;
+            }
+
+            let index = self.flight_index(&flight.id)?;
+            let positions = cells.len() as u64;
+            for cell in cells {
+                self.cells.entry(cell).or_default().add(index, 1);
+            }
+
+            self.positions += positions;
+            category(&mut self.aircraft_types, flight.aircraft_type).add(index, positions);
+            category(&mut self.operational_statuses, current.operational_status)
+                .add(index, positions);
+        }
+
+        Ok(())
+    }
+
+    /// The statistics of a time range, breakdowns by decreasing number of
+    ///  positions
+    pub fn response(
+        self,
+        time_start: DateTime<Utc>,
+        time_end: DateTime<Utc>,
+    ) -> GetTrafficStatsResponse {
+        let grid = self.grid;
+        let cells = self
+            .cells
+            .iter()
+            .map(|(&(row, col), counts)| {
+                let (lat, lng) = grid.center(row, col);
+                DensityCell {
+                    row,
+                    col,
+                    lat,
+                    lng,
+                    positions: counts.positions,
+                    flights: counts.flights.len() as u64,
+                }
+            })
+            .collect();
+
+        let mut aircraft_types = self
+            .aircraft_types
+            .iter()
+            .map(|(aircraft_type, counts)| AircraftTypeStats {
+                aircraft_type: *aircraft_type,
+                positions: counts.positions,
+                flights: counts.flights.len() as u64,
+            })
+            .collect::<Vec<AircraftTypeStats>>();
+        aircraft_types.sort_by_key(|stats| Reverse(stats.positions));
+
+        let mut operational_statuses = self
+            .operational_statuses
+            .iter()
+            .map(|(operational_status, counts)| OperationalStatusStats {
+                operational_status: *operational_status,
+                positions: counts.positions,
+                flights: counts.flights.len() as u64,
+            })
+            .collect::<Vec<OperationalStatusStats>>();
+        operational_statuses.sort_by_key(|stats| Reverse(stats.positions));
+
+        GetTrafficStatsResponse {
+            time_start: time_start.into(),
+            time_end: time_end.into(),
+            min_lat: grid.min_lat,
+            min_lng: grid.min_lng,
+            resolution: grid.resolution,
+            rows: grid.rows,
+            cols: grid.cols,
+            positions: self.positions,
+            flights: self.flights.len() as u64,
+            cells,
+            aircraft_types,
+            operational_statuses,
+        }
+    }
+}
+
+/// The traffic statistics of the window of a grid over a time range,
+///  querying svc-gis one slice at a time up to now.
+/// Returns 503 if the maximum number of queries is already running and
+///  504 if the query doesn't complete in time.
+pub async fn traffic_stats(
+    grpc_clients: &mut GrpcClients,
+    limits: &QueryLimits,
+    grid: Grid,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> Result<GetTrafficStatsResponse, StatusCode> {
+    let Ok(_permit) = limits.permits.try_acquire() else {
+        rest_error!(
+            "the maximum of {} traffic statistics queries is already running.",
+            MAX_CONCURRENT_QUERIES
+        );
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    };
+
+    let deadline = Instant::now() + limits.timeout;
+    let timed_out = || {
+        rest_error!(
+            "traffic statistics did not complete in {} seconds.",
+            limits.timeout.as_secs()
+        );
+        StatusCode::GATEWAY_TIMEOUT
+    };
+
+    let window = grid.window();
+    let end = time_end.min(Utc::now());
+    let mut stats = TrafficStats::new(grid);
+    let mut start = time_start;
+    while start < end {
+        if Instant::now() >= deadline {
+            return Err(timed_out());
+        }
+
+        let slice_end = (start + Duration::seconds(SLICE_SECONDS)).min(end);
+        let flights = tokio::time::timeout_at(
+            deadline,
+            gis_flights(grpc_clients, &window, start, slice_end),
+        )
+        .await
+        .map_err(|_| timed_out())??;
+        stats.add(&flights, start, slice_end)?;
+        start = slice_end;
+    }
+
+    Ok(stats.response(time_start, time_end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::tests::flight;
+
+    fn window() -> Window {
+        Window {
+            lon1: 4.8,
+            lat1: 52.3,
+            lon2: 5.0,
+            lat2: 52.4,
+        }
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid = Grid::new(&window(), 0.01).unwrap();
+        assert_eq!((grid.rows, grid.cols), (10, 20));
+        assert_eq!(grid.cell(52.3, 4.8), Some((0, 0)));
+        assert_eq!(grid.cell(52.4, 5.0), Some((9, 19)));
+        assert_eq!(grid.cell(52.355, 4.905), Some((5, 10)));
+        assert_eq!(grid.cell(52.41, 4.9), None);
+
+        let (lat, lng) = grid.center(0, 0);
+        assert!((lat - 52.305).abs() < 1e-9 && (lng - 4.805).abs() < 1e-9);
+
+        // a point window has a single cell
+        let point = Window {
+            lon1: 4.9,
+            lat1: 52.37,
+            lon2: 4.9,
+            lat2: 52.37,
+        };
+        let grid = Grid::new(&point, 0.01).unwrap();
+        assert_eq!((grid.rows, grid.cols), (1, 1));
+        assert_eq!(grid.cell(52.37, 4.9), Some((0, 0)));
+
+        assert_eq!(
+            Grid::new(&window(), 0.0).unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            Grid::new(&window(), f64::NAN).unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            Grid::new(&window(), 0.0001).unwrap_err(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        // the whole world, even with few cells
+        let world = Window {
+            lon1: -180.0,
+            lat1: -90.0,
+            lon2: 180.0,
+            lat2: 90.0,
+        };
+        assert_eq!(
+            Grid::new(&world, 10.0).unwrap_err(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[test]
+    fn test_add() {
+        let now = Utc::now();
+        let grid = Grid::new(&window(), 0.01).unwrap();
+        let mut stats = TrafficStats::new(grid);
+
+        let mut emergency = flight("AC-2", now, 52.355, 4.905);
+        emergency.aircraft_type = UAType::Aeroplane;
+        emergency.current_state.operational_status = RIDOperationalStatus::Emergency;
+        let flights = vec![
+            flight("AC-1", now, 52.355, 4.905),
+            emergency,
+            flight("AC-3", now, 52.5, 4.9),
+        ];
+
+        // two slices splitting the positions, and the current state
+        //  reported again with the recent positions of the next slice
+        let middle = now - Duration::seconds(5);
+        stats
+            .add(&flights, now - Duration::seconds(60), middle)
+            .unwrap();
+        let mut repeated = flights.clone();
+        for flight in repeated.iter_mut() {
+            let current = flight.current_state.clone();
+            flight.recent_positions.push(RIDRecentAircraftPosition {
+                time: current.timestamp,
+                position: current.position,
+            });
+        }
+        stats
+            .add(&repeated, middle, now + Duration::seconds(1))
+            .unwrap();
+
+        let response = stats.response(now - Duration::seconds(60), now);
+        assert_eq!(response.flights, 2);
+        assert_eq!(response.positions, 22);
+        assert_eq!((response.rows, response.cols), (10, 20));
+        assert_eq!(response.cells.len(), 1);
+        assert_eq!((response.cells[0].row, response.cells[0].col), (5, 10));
+        assert_eq!(response.cells[0].positions, 22);
+        assert_eq!(response.cells[0].flights, 2);
+
+        assert_eq!(response.aircraft_types.len(), 2);
+        assert_eq!(response.aircraft_types[0].positions, 11);
+        assert_eq!(response.aircraft_types[0].flights, 1);
+        let statuses = response
+            .operational_statuses
+            .iter()
+            .map(|stats| (stats.operational_status, stats.flights))
+            .collect::<Vec<_>>();
+        assert!(statuses.contains(&(RIDOperationalStatus::Emergency, 1)));
+        assert!(statuses.contains(&(RIDOperationalStatus::Airborne, 1)));
+    }
+
+    #[test]
+    fn test_max_flights() {
+        let now = Utc::now();
+        let grid = Grid::new(&window(), 0.1).unwrap();
+        let mut stats = TrafficStats::new(grid);
+        let mut flights = [flight("AC-0", now, 52.35, 4.9)];
+        let start = now - Duration::seconds(1);
+        for i in 0..MAX_FLIGHTS {
+            flights[0].id = format!("AC-{}", i);
+            stats.add(&flights, start, now).unwrap();
+        }
+
+        // flights already counted are still counted
+        flights[0].id = "AC-0".to_string();
+        stats.add(&flights, start, now).unwrap();
+
+        flights[0].id = "AC-MORE".to_string();
+        assert_eq!(
+            stats.add(&flights, start, now).unwrap_err(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[test]
+    fn test_max_flight_reports() {
+        let now = Utc::now();
+        let grid = Grid::new(&window(), 0.1).unwrap();
+        let mut stats = TrafficStats::new(grid);
+        let flights = [flight("AC-1", now, 52.35, 4.9)];
+        let start = now - Duration::seconds(1);
+        stats.reports = MAX_FLIGHT_REPORTS - 1;
+        stats.add(&flights, start, now).unwrap();
+
+        // flights outside of the slice were still reported
+        assert_eq!(
+            stats.add(&flights, now, now).unwrap_err(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_traffic_stats() {
+        let config = crate::config::Config::default();
+        let mut grpc_clients = GrpcClients::default(config);
+        let grid = Grid::new(&window(), 0.01).unwrap();
+        let now = Utc::now();
+
+        let limits = QueryLimits::default();
+        let response = traffic_stats(
+            &mut grpc_clients,
+            &limits,
+            grid,
+            now - Duration::seconds(150),
+            now,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.flights, 0);
+        assert!(response.cells.is_empty());
+        assert_eq!(response.time_end.value, Time::from(now).value);
+
+        // future time ranges have no traffic yet
+        let future = now + Duration::hours(1);
+        let response = traffic_stats(
+            &mut grpc_clients,
+            &limits,
+            grid,
+            future,
+            future + Duration::hours(1),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.positions, 0);
+    }
+
+    #[tokio::test]
+    async fn test_traffic_stats_limits() {
+        let config = crate::config::Config::default();
+        let mut grpc_clients = GrpcClients::default(config);
+        let grid = Grid::new(&window(), 0.01).unwrap();
+        let now = Utc::now();
+        let start = now - Duration::hours(MAX_RANGE_HOURS);
+
+        let limits = QueryLimits {
+            timeout: std::time::Duration::ZERO,
+            ..Default::default()
+        };
+        let e = traffic_stats(&mut grpc_clients, &limits, grid, start, now)
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::GATEWAY_TIMEOUT);
+
+        // the permit of a query is released once it completes
+        assert_eq!(limits.permits.available_permits(), MAX_CONCURRENT_QUERIES);
+
+        let limits = QueryLimits::default();
+        let _permits = limits
+            .permits
+            .try_acquire_many(MAX_CONCURRENT_QUERIES as u32)
+            .unwrap();
+        let e = traffic_stats(&mut grpc_clients, &limits, grid, start, now)
+            .await
+            .unwrap_err();
+        assert_eq!(e, StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
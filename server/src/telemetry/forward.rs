@@ -0,0 +1,374 @@
+//! Forwarding of pushed telemetry to svc-gis
+//! svc-gis ingests aircraft updates from Redis queues. Accepted states are
+//! queued in memory and pushed to Redis by a background task, so a slow or
+//! unreachable Redis never holds up the pushing provider.
+
+use crate::config::RedisConfig;
+use crate::odid::decoder::{
+    UNKNOWN_ALTITUDE, UNKNOWN_DIRECTION, UNKNOWN_SPEED, UNKNOWN_VERTICAL_SPEED,
+};
+use crate::rest::api::rest_types::{RIDAircraftState, UAType};
+use lib_common::time::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::Serialize;
+use std::sync::{Mutex, MutexGuard};
+use svc_gis_client_grpc::prelude::AircraftType;
+use tokio::sync::mpsc;
+
+/// Redis queue of the aircraft identifiers ingested by svc-gis
+pub const GIS_ID_QUEUE: &str = "gis:id";
+
+/// Redis queue of the aircraft positions ingested by svc-gis
+pub const GIS_POSITION_QUEUE: &str = "gis:position";
+
+/// Redis queue of the aircraft velocities ingested by svc-gis
+pub const GIS_VELOCITY_QUEUE: &str = "gis:velocity";
+
+/// Maximum number of batches waiting to be pushed to Redis, newer batches
+///  are dropped while the queue is full
+pub const MAX_PENDING_BATCHES: usize = 1_000;
+
+impl From<UAType> for AircraftType {
+    fn from(t: UAType) -> Self {
+        match t {
+            UAType::NotDeclared => AircraftType::Undeclared,
+            UAType::Aeroplane => AircraftType::Aeroplane,
+            UAType::Helicopter => AircraftType::Rotorcraft,
+            UAType::Gyroplane => AircraftType::Gyroplane,
+            UAType::HybridLift => AircraftType::Hybridlift,
+            UAType::Ornithopter => AircraftType::Ornithopter,
+            UAType::Glider => AircraftType::Glider,
+            UAType::Kite => AircraftType::Kite,
+            UAType::FreeBalloon => AircraftType::Freeballoon,
+            UAType::CaptiveBalloon => AircraftType::Captiveballoon,
+            UAType::Airship => AircraftType::Airship,
+            UAType::FreeFallOrParachute => AircraftType::Unpowered,
+            UAType::Rocket => AircraftType::Rocket,
+            UAType::TetheredPoweredAircraft => AircraftType::Tethered,
+            UAType::GroundObstacle => AircraftType::Groundobstacle,
+            UAType::Other => AircraftType::Other,
+        }
+    }
+}
+
+/// The identifier of an aircraft, as ingested by svc-gis
+#[derive(Debug, Serialize)]
+struct GisAircraftId {
+    identifier: String,
+    aircraft_type: i32,
+    simulated: bool,
+    timestamp_network: DateTime<Utc>,
+}
+
+/// A 3D position, as ingested by svc-gis
+#[derive(Debug, Serialize)]
+struct GisPosition {
+    latitude: f64,
+    longitude: f64,
+    altitude_meters: f64,
+}
+
+/// The position of an aircraft, as ingested by svc-gis
+#[derive(Debug, Serialize)]
+struct GisAircraftPosition {
+    identifier: String,
+    position: GisPosition,
+    timestamp_asset: DateTime<Utc>,
+    timestamp_network: DateTime<Utc>,
+}
+
+/// The velocity of an aircraft, as ingested by svc-gis
+#[derive(Debug, Serialize)]
+struct GisAircraftVelocity {
+    identifier: String,
+    velocity_horizontal_ground_mps: f32,
+    velocity_vertical_mps: f32,
+    track_angle_degrees: f32,
+    timestamp_asset: DateTime<Utc>,
+    timestamp_network: DateTime<Utc>,
+}
+
+/// The serialized updates of a pushed batch, by Redis queue
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GisBatch {
+    /// Updates of [`GIS_ID_QUEUE`]
+    pub ids: Vec<String>,
+
+    /// Updates of [`GIS_POSITION_QUEUE`]
+    pub positions: Vec<String>,
+
+    /// Updates of [`GIS_VELOCITY_QUEUE`]
+    pub velocities: Vec<String>,
+}
+
+impl GisBatch {
+    /// The updates of accepted states of a flight.
+    /// Positions with an unknown altitude and unknown velocities are not
+    ///  forwarded, as svc-gis has no unknown values.
+    pub fn new(
+        flight_id: &str,
+        aircraft_type: UAType,
+        simulated: bool,
+        states: &[RIDAircraftState],
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut batch = GisBatch::default();
+        if states.is_empty() {
+            return batch;
+        }
+
+        let id = GisAircraftId {
+            identifier: flight_id.to_string(),
+            aircraft_type: AircraftType::from(aircraft_type) as i32,
+            simulated,
+            timestamp_network: now,
+        };
+        batch.ids.extend(serde_json::to_string(&id).ok());
+
+        for state in states {
+            let Some(timestamp_asset) = state.timestamp.to_datetime() else {
+                continue;
+            };
+
+            let position = &state.position;
+            if position.alt != UNKNOWN_ALTITUDE {
+                let update = GisAircraftPosition {
+                    identifier: flight_id.to_string(),
+                    position: GisPosition {
+                        latitude: position.lat,
+                        longitude: position.lng,
+                        altitude_meters: position.alt as f64,
+                    },
+                    timestamp_asset,
+                    timestamp_network: now,
+                };
+                batch.positions.extend(serde_json::to_string(&update).ok());
+            }
+
+            if state.speed != UNKNOWN_SPEED
+                && state.track != UNKNOWN_DIRECTION
+                && state.vertical_speed != UNKNOWN_VERTICAL_SPEED
+            {
+                let update = GisAircraftVelocity {
+                    identifier: flight_id.to_string(),
+                    velocity_horizontal_ground_mps: state.speed,
+                    velocity_vertical_mps: state.vertical_speed,
+                    track_angle_degrees: state.track,
+                    timestamp_asset,
+                    timestamp_network: now,
+                };
+                batch.velocities.extend(serde_json::to_string(&update).ok());
+            }
+        }
+
+        batch
+    }
+
+    /// The non empty queues and their updates
+    fn queues(&self) -> impl Iterator<Item = (&'static str, &Vec<String>)> {
+        [
+            (GIS_ID_QUEUE, &self.ids),
+            (GIS_POSITION_QUEUE, &self.positions),
+            (GIS_VELOCITY_QUEUE, &self.velocities),
+        ]
+        .into_iter()
+        .filter(|(_, updates)| !updates.is_empty())
+    }
+}
+
+/// Queue of the batches waiting to be pushed to the svc-gis Redis
+#[derive(Debug)]
+pub struct GisForwarder {
+    /// Sending end of the queue, None if forwarding is disabled
+    sender: Option<mpsc::Sender<GisBatch>>,
+
+    /// Receiving end of the queue, until taken by [`forward_to_gis`]
+    receiver: Mutex<Option<mpsc::Receiver<GisBatch>>>,
+}
+
+impl GisForwarder {
+    /// Create a forwarder, dropping every batch if disabled
+    pub fn new(enabled: bool) -> Self {
+        if !enabled {
+            return GisForwarder {
+                sender: None,
+                receiver: Mutex::new(None),
+            };
+        }
+
+        let (sender, receiver) = mpsc::channel(MAX_PENDING_BATCHES);
+        GisForwarder {
+            sender: Some(sender),
+            receiver: Mutex::new(Some(receiver)),
+        }
+    }
+
+    /// Lock the receiver, recovering from a poisoned lock as it is only
+    ///  ever taken
+    fn lock(&self) -> MutexGuard<'_, Option<mpsc::Receiver<GisBatch>>> {
+        self.receiver.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// If batches are forwarded to svc-gis
+    pub fn is_enabled(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    /// Queue a batch for svc-gis.
+    /// Returns false if the batch was dropped because forwarding is
+    ///  disabled or the queue is full.
+    pub fn forward(&self, batch: GisBatch) -> bool {
+        let Some(sender) = &self.sender else {
+            return false;
+        };
+
+        if batch.queues().next().is_none() {
+            return true;
+        }
+
+        sender
+            .try_send(batch)
+            .map_err(|e| {
+                rest_warn!("could not queue telemetry for svc-gis: {}", e);
+            })
+            .is_ok()
+    }
+
+    /// Take the receiving end of the queue, only once
+    pub(crate) fn take_receiver(&self) -> Option<mpsc::Receiver<GisBatch>> {
+        self.lock().take()
+    }
+}
+
+/// Push a batch to the svc-gis Redis
+async fn push_batch(
+    connection: &mut ConnectionManager,
+    batch: &GisBatch,
+) -> redis::RedisResult<()> {
+    for (queue, updates) in batch.queues() {
+        connection.rpush::<_, _, ()>(queue, updates).await?;
+    }
+
+    Ok(())
+}
+
+/// Push the queued batches to the svc-gis Redis, until the forwarder is
+///  dropped.
+/// Batches are dropped while Redis can't be reached.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) needs a Redis server, GisBatch and GisForwarder are
+//  unit tested
+pub async fn forward_to_gis(forwarder: std::sync::Arc<GisForwarder>, redis: RedisConfig) {
+    let Some(mut receiver) = forwarder.take_receiver() else {
+        return;
+    };
+
+    let client = match redis::Client::open(redis.url.as_str()) {
+        Ok(client) => client,
+        Err(e) => {
+            rest_error!("invalid svc-gis Redis url, forwarding disabled: {}", e);
+            return;
+        }
+    };
+
+    rest_info!("forwarding pushed telemetry to svc-gis.");
+    let mut connection = None;
+    while let Some(batch) = receiver.recv().await {
+        if connection.is_none() {
+            connection = ConnectionManager::new(client.clone())
+                .await
+                .map_err(|e| rest_warn!("could not connect to the svc-gis Redis: {}", e))
+                .ok();
+        }
+
+        let Some(connection) = connection.as_mut() else {
+            continue;
+        };
+
+        if let Err(e) = push_batch(connection, &batch).await {
+            rest_warn!("could not forward telemetry to svc-gis: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::tests::state;
+    use lib_common::time::Duration;
+
+    #[test]
+    fn test_gis_batch() {
+        let now = Utc::now();
+        let mut unknown = state(now, 52.37, 4.91);
+        unknown.position.alt = UNKNOWN_ALTITUDE;
+        unknown.speed = UNKNOWN_SPEED;
+        let states = vec![state(now - Duration::seconds(1), 52.37, 4.9), unknown];
+
+        let batch = GisBatch::new("AC-1", UAType::Helicopter, true, &states, now);
+        assert_eq!(batch.ids.len(), 1);
+        assert_eq!(batch.positions.len(), 1);
+        assert_eq!(batch.velocities.len(), 1);
+
+        let id: serde_json::Value = serde_json::from_str(&batch.ids[0]).unwrap();
+        assert_eq!(id["identifier"], "AC-1");
+        assert_eq!(id["aircraft_type"], AircraftType::Rotorcraft as i32);
+        assert_eq!(id["simulated"], true);
+
+        let position: serde_json::Value = serde_json::from_str(&batch.positions[0]).unwrap();
+        assert_eq!(position["position"]["latitude"], 52.37);
+        assert_eq!(position["position"]["altitude_meters"], 120.0);
+
+        let velocity: serde_json::Value = serde_json::from_str(&batch.velocities[0]).unwrap();
+        assert_eq!(velocity["velocity_horizontal_ground_mps"], 20.0);
+        assert_eq!(velocity["track_angle_degrees"], 90.0);
+
+        let empty = GisBatch::new("AC-1", UAType::Helicopter, true, &[], now);
+        assert_eq!(empty, GisBatch::default());
+        assert_eq!(empty.queues().count(), 0);
+    }
+
+    #[test]
+    fn test_aircraft_type_round_trip() {
+        for t in [
+            UAType::Aeroplane,
+            UAType::Helicopter,
+            UAType::TetheredPoweredAircraft,
+            UAType::FreeFallOrParachute,
+        ] {
+            assert_eq!(UAType::from(AircraftType::from(t)), t);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_forward() {
+        let now = Utc::now();
+        let batch = GisBatch::new(
+            "AC-1",
+            UAType::Helicopter,
+            false,
+            &[state(now, 52.37, 4.9)],
+            now,
+        );
+
+        let disabled = GisForwarder::new(false);
+        assert!(!disabled.is_enabled());
+        assert!(!disabled.forward(batch.clone()));
+        assert!(disabled.take_receiver().is_none());
+
+        let forwarder = GisForwarder::new(true);
+        assert!(forwarder.is_enabled());
+        assert!(forwarder.forward(batch.clone()));
+        assert!(forwarder.forward(GisBatch::default()));
+        let mut receiver = forwarder.take_receiver().unwrap();
+        assert!(forwarder.take_receiver().is_none());
+        assert_eq!(receiver.recv().await.unwrap(), batch);
+        assert!(receiver.try_recv().is_err());
+
+        for _ in 0..MAX_PENDING_BATCHES {
+            assert!(forwarder.forward(batch.clone()));
+        }
+        assert!(!forwarder.forward(batch));
+    }
+}
@@ -0,0 +1,292 @@
+//! Telemetry pushed by partners
+//! Partners that would rather push the RID states of their flights than be
+//! polled send them in batches per flight. States are validated and
+//! deduplicated, so the aircraft are reported by `/uss/flights` with the
+//! network flights, and forwarded to svc-gis.
+
+pub mod forward;
+pub mod store;
+
+use crate::odid::decoder::{
+    UNKNOWN_ALTITUDE, UNKNOWN_DIRECTION, UNKNOWN_SPEED, UNKNOWN_VERTICAL_SPEED,
+};
+use crate::rest::api::rest_types::RIDAircraftState;
+use forward::GisForwarder;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::BTreeSet;
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use store::PushedTelemetry;
+use tokio::sync::OnceCell;
+
+/// Maximum number of states in a single batch
+pub const MAX_BATCH_STATES: usize = 100;
+
+/// States older than this are rejected, in seconds
+pub const MAX_STATE_AGE_SECONDS: i64 = 60;
+
+/// States ahead of our clock by more than this are rejected, in seconds
+pub const MAX_CLOCK_SKEW_SECONDS: i64 = 5;
+
+/// Highest known speed in meters per second
+const MAX_SPEED: f32 = 254.25;
+
+/// Highest known vertical speed in meters per second
+const MAX_VERTICAL_SPEED: f32 = 62.0;
+
+/// Errors with pushed telemetry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TelemetryError {
+    /// A state has a missing, out of range or invalid value
+    InvalidState,
+
+    /// A state is older than a state already received for the flight
+    OutOfOrder,
+
+    /// The flight is reported by another provider
+    NotOwner,
+}
+
+impl std::error::Error for TelemetryError {}
+
+impl Display for TelemetryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TelemetryError::InvalidState => write!(f, "Invalid aircraft state"),
+            TelemetryError::OutOfOrder => write!(f, "Aircraft state out of order"),
+            TelemetryError::NotOwner => write!(f, "Flight reported by another provider"),
+        }
+    }
+}
+
+pub(crate) static PUSHED_TELEMETRY: OnceCell<Arc<PushedTelemetry>> = OnceCell::const_new();
+
+/// Returns the flights pushed by partners, shared by the REST and gRPC
+///  servers.
+/// Initializes the storage with the providers of the configuration if it
+///  hasn't been initialized yet.
+pub async fn get_pushed_telemetry() -> Arc<PushedTelemetry> {
+    PUSHED_TELEMETRY
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            Arc::new(PushedTelemetry::new(parse_providers(
+                &config.telemetry_providers,
+            )))
+        })
+        .await
+        .clone()
+}
+
+pub(crate) static GIS_FORWARDER: OnceCell<Arc<GisForwarder>> = OnceCell::const_new();
+
+/// Returns the queue of telemetry forwarded to svc-gis, shared by the REST
+///  and gRPC servers.
+/// Initializes the queue if it hasn't been initialized yet. Forwarding is
+///  disabled if no Redis is configured.
+pub async fn get_gis_forwarder() -> Arc<GisForwarder> {
+    GIS_FORWARDER
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            Arc::new(GisForwarder::new(config.redis.is_some()))
+        })
+        .await
+        .clone()
+}
+
+/// Parse a comma separated list of provider IDs
+pub fn parse_providers(providers: &str) -> BTreeSet<String> {
+    providers
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Validate the values of a state.
+/// Returns the time of the state.
+pub fn validate_state(
+    state: &RIDAircraftState,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, TelemetryError> {
+    let time = state
+        .timestamp
+        .to_datetime()
+        .ok_or(TelemetryError::InvalidState)?;
+    if time < now - Duration::seconds(MAX_STATE_AGE_SECONDS)
+        || time > now + Duration::seconds(MAX_CLOCK_SKEW_SECONDS)
+    {
+        return Err(TelemetryError::InvalidState);
+    }
+
+    let position = &state.position;
+    let valid = (-90.0..=90.0).contains(&position.lat)
+        && (-180.0..=180.0).contains(&position.lng)
+        && position.alt.is_finite()
+        && position.alt >= UNKNOWN_ALTITUDE
+        && ((0.0..360.0).contains(&state.track) || state.track == UNKNOWN_DIRECTION)
+        && ((0.0..=MAX_SPEED).contains(&state.speed) || state.speed == UNKNOWN_SPEED)
+        && ((-MAX_VERTICAL_SPEED..=MAX_VERTICAL_SPEED).contains(&state.vertical_speed)
+            || state.vertical_speed == UNKNOWN_VERTICAL_SPEED)
+        && state.timestamp_accuracy >= 0.0;
+
+    if !valid {
+        return Err(TelemetryError::InvalidState);
+    }
+
+    Ok(time)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::rest::api::rest_types::*;
+
+    /// A valid state at the given time and position
+    pub(crate) fn state(time: DateTime<Utc>, lat: f64, lng: f64) -> RIDAircraftState {
+        RIDAircraftState {
+            timestamp: time.into(),
+            timestamp_accuracy: 0.1,
+            operational_status: RIDOperationalStatus::Airborne,
+            position: RIDAircraftPosition {
+                lat,
+                lng,
+                alt: 120.0,
+                accuracy_h: HorizontalAccuracy::HA10m,
+                accuracy_v: VerticalAccuracy::VA10m,
+                extrapolated: false,
+                pressure_alt: UNKNOWN_ALTITUDE,
+                height: RIDHeight {
+                    distance: UNKNOWN_ALTITUDE,
+                    reference: RIDHeightReference::TakeoffLocation,
+                },
+            },
+            track: 90.0,
+            speed: 20.0,
+            speed_accuracy: SpeedAccuracy::SA1mps,
+            vertical_speed: 0.0,
+        }
+    }
+
+    /// A flight at the given position with a recent position every second
+    pub(crate) fn flight(id: &str, now: DateTime<Utc>, lat: f64, lng: f64) -> RIDFlight {
+        let recent_positions = (1..=10)
+            .rev()
+            .map(|seconds| {
+                let state = state(now - Duration::seconds(seconds), lat, lng);
+                RIDRecentAircraftPosition {
+                    time: state.timestamp,
+                    position: state.position,
+                }
+            })
+            .collect();
+
+        RIDFlight {
+            id: id.to_string(),
+            aircraft_type: UAType::Helicopter,
+            current_state: state(now, lat, lng),
+            operating_area: OperatingArea {
+                aircraft_count: 1,
+                volumes: vec![],
+            },
+            simulated: false,
+            recent_positions,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_pushed_telemetry() {
+        let a = get_pushed_telemetry().await;
+        let b = get_pushed_telemetry().await;
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+
+    #[tokio::test]
+    async fn test_get_gis_forwarder() {
+        let a = get_gis_forwarder().await;
+        let b = get_gis_forwarder().await;
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            TelemetryError::OutOfOrder.to_string(),
+            "Aircraft state out of order"
+        );
+        assert_eq!(
+            TelemetryError::NotOwner.to_string(),
+            "Flight reported by another provider"
+        );
+    }
+
+    #[test]
+    fn test_parse_providers() {
+        let providers = parse_providers(" acme, ,skyways,acme");
+        assert_eq!(providers.len(), 2);
+        assert!(providers.contains("acme"));
+        assert!(providers.contains("skyways"));
+        assert!(parse_providers("").is_empty());
+    }
+
+    #[test]
+    fn test_validate_state() {
+        let now = Utc::now();
+        let valid = state(now, 52.37, 4.9);
+        assert_eq!(
+            validate_state(&valid, now).unwrap(),
+            valid.timestamp.to_datetime().unwrap()
+        );
+
+        let mut unknown = valid.clone();
+        unknown.track = UNKNOWN_DIRECTION;
+        unknown.speed = UNKNOWN_SPEED;
+        unknown.vertical_speed = UNKNOWN_VERTICAL_SPEED;
+        unknown.position.alt = UNKNOWN_ALTITUDE;
+        assert!(validate_state(&unknown, now).is_ok());
+
+        let invalid = [
+            state(
+                now - Duration::seconds(MAX_STATE_AGE_SECONDS + 1),
+                52.37,
+                4.9,
+            ),
+            state(
+                now + Duration::seconds(MAX_CLOCK_SKEW_SECONDS + 1),
+                52.37,
+                4.9,
+            ),
+            state(now, 90.1, 4.9),
+            state(now, 52.37, -180.1),
+            RIDAircraftState {
+                track: 360.0,
+                ..valid.clone()
+            },
+            RIDAircraftState {
+                speed: 254.5,
+                ..valid.clone()
+            },
+            RIDAircraftState {
+                vertical_speed: -62.5,
+                ..valid.clone()
+            },
+            RIDAircraftState {
+                timestamp: Time {
+                    value: "yesterday".to_string(),
+                    format: valid.timestamp.format.clone(),
+                },
+                ..valid.clone()
+            },
+        ];
+        for state in invalid {
+            assert_eq!(
+                validate_state(&state, now).unwrap_err(),
+                TelemetryError::InvalidState
+            );
+        }
+
+        let mut below = valid;
+        below.position.alt = -1000.5;
+        assert!(validate_state(&below, now).is_err());
+    }
+}
@@ -0,0 +1,354 @@
+//! Flights pushed by partners
+//! The states of a flight are kept oldest first. A batch is applied as a
+//! whole, or rejected as a whole if a state is invalid or out of order.
+
+use super::{validate_state, TelemetryError, MAX_BATCH_STATES, MAX_STATE_AGE_SECONDS};
+use crate::rest::api::rest_types::*;
+use crate::rest::api::uss::Window;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+
+/// Flights that did not push a state for this long are no longer
+///  reported, in seconds
+pub const TELEMETRY_TIMEOUT_SECONDS: i64 = 10;
+
+/// Maximum number of states kept per flight
+pub const MAX_STATES: usize = 600;
+
+/// A flight pushed by a partner
+#[derive(Debug, Clone)]
+pub struct PushedFlight {
+    /// The provider pushing the flight
+    pub provider: String,
+
+    /// The type of aircraft
+    pub aircraft_type: UAType,
+
+    /// If this is a simulated flight
+    pub simulated: bool,
+
+    /// Recent states with their time, oldest first
+    states: VecDeque<(DateTime<Utc>, RIDAircraftState)>,
+}
+
+impl PushedFlight {
+    /// Time of the latest state
+    pub fn latest(&self) -> Option<DateTime<Utc>> {
+        self.states.back().map(|(time, _)| *time)
+    }
+
+    /// The flight reported by `/uss/flights`, if a state was received
+    pub fn flight(&self, id: &str, recent_duration_s: f32) -> Option<RIDFlight> {
+        let (latest, state) = self.states.back()?;
+        let since = *latest - Duration::milliseconds((recent_duration_s * 1000.0) as i64);
+        let recent_positions = self
+            .states
+            .iter()
+            .filter(|(time, _)| *time >= since)
+            .map(|(time, state)| RIDRecentAircraftPosition {
+                time: (*time).into(),
+                position: state.position,
+            })
+            .collect();
+
+        Some(RIDFlight {
+            id: id.to_string(),
+            aircraft_type: self.aircraft_type,
+            current_state: state.clone(),
+            operating_area: OperatingArea {
+                aircraft_count: 1,
+                volumes: vec![],
+            },
+            simulated: self.simulated,
+            recent_positions,
+        })
+    }
+}
+
+/// The result of a pushed batch
+#[derive(Debug, Clone)]
+pub struct PushResult {
+    /// Number of new states
+    pub accepted: usize,
+
+    /// Number of states already received
+    pub duplicates: usize,
+
+    /// The new states, oldest first
+    pub states: Vec<RIDAircraftState>,
+}
+
+/// Flights pushed by partners, by flight ID
+#[derive(Debug)]
+pub struct PushedTelemetry {
+    /// Providers allowed to push telemetry
+    providers: BTreeSet<String>,
+
+    /// Flights by ID
+    flights: Mutex<HashMap<String, PushedFlight>>,
+}
+
+impl PushedTelemetry {
+    /// Create an empty store accepting telemetry of the providers
+    pub fn new(providers: BTreeSet<String>) -> Self {
+        PushedTelemetry {
+            providers,
+            flights: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lock the flights, recovering from a poisoned lock as every
+    ///  modification leaves the map in a consistent state
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, PushedFlight>> {
+        self.flights.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// If the provider may push telemetry
+    pub fn is_provider(&self, provider: &str) -> bool {
+        self.providers.contains(provider)
+    }
+
+    /// Apply a batch of states of a flight, oldest first, dropping the
+    ///  flights without recent states.
+    /// States with the time of an already received state are duplicates
+    ///  and ignored.
+    pub fn push(
+        &self,
+        provider: &str,
+        flight_id: &str,
+        aircraft_type: UAType,
+        simulated: bool,
+        states: Vec<RIDAircraftState>,
+        now: DateTime<Utc>,
+    ) -> Result<PushResult, TelemetryError> {
+        if states.is_empty() || states.len() > MAX_BATCH_STATES {
+            return Err(TelemetryError::InvalidState);
+        }
+
+        let mut timed = Vec::with_capacity(states.len());
+        for state in states {
+            timed.push((validate_state(&state, now)?, state));
+        }
+
+        let mut flights = self.lock();
+        let oldest = now - Duration::seconds(MAX_STATE_AGE_SECONDS);
+        flights.retain(|_, f| f.latest().is_some_and(|latest| latest >= oldest));
+
+        let existing = flights.get(flight_id);
+        if existing.is_some_and(|f| f.provider != provider) {
+            return Err(TelemetryError::NotOwner);
+        }
+
+        let mut latest = existing.and_then(PushedFlight::latest);
+        let mut accepted = Vec::with_capacity(timed.len());
+        let mut duplicates = 0;
+        for (time, state) in timed {
+            match latest {
+                Some(latest) if time == latest => duplicates += 1,
+                Some(latest) if time < latest => return Err(TelemetryError::OutOfOrder),
+                _ => {
+                    latest = Some(time);
+                    accepted.push((time, state));
+                }
+            }
+        }
+
+        let result = PushResult {
+            accepted: accepted.len(),
+            duplicates,
+            states: accepted.iter().map(|(_, state)| state.clone()).collect(),
+        };
+
+        let flight = flights
+            .entry(flight_id.to_string())
+            .or_insert_with(|| PushedFlight {
+                provider: provider.to_string(),
+                aircraft_type,
+                simulated,
+                states: VecDeque::new(),
+            });
+        flight.aircraft_type = aircraft_type;
+        flight.simulated = simulated;
+        flight.states.extend(accepted);
+        while flight.states.len() > MAX_STATES
+            || flight
+                .states
+                .front()
+                .is_some_and(|(time, _)| *time < oldest)
+        {
+            flight.states.pop_front();
+        }
+
+        Ok(result)
+    }
+
+    /// The flight with the given ID
+    pub fn flight(&self, flight_id: &str) -> Option<PushedFlight> {
+        self.lock().get(flight_id).cloned()
+    }
+
+    /// The flights located in the window that pushed a state recently,
+    ///  ordered by ID
+    pub fn flights_in(
+        &self,
+        window: &Window,
+        recent_duration_s: f32,
+        now: DateTime<Utc>,
+    ) -> Vec<RIDFlight> {
+        let (min_lon, min_lat, max_lon, max_lat) = window.bounds();
+        let timeout = now - Duration::seconds(TELEMETRY_TIMEOUT_SECONDS);
+        let mut flights = self
+            .lock()
+            .iter()
+            .filter(|(_, f)| f.latest().is_some_and(|latest| latest >= timeout))
+            .filter_map(|(id, f)| f.flight(id, recent_duration_s))
+            .filter(|f| {
+                let p = &f.current_state.position;
+                (min_lat..=max_lat).contains(&p.lat) && (min_lon..=max_lon).contains(&p.lng)
+            })
+            .collect::<Vec<RIDFlight>>();
+        flights.sort_by(|a, b| a.id.cmp(&b.id));
+        flights
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::state;
+    use super::*;
+
+    fn telemetry() -> PushedTelemetry {
+        PushedTelemetry::new(super::super::parse_providers("acme,skyways"))
+    }
+
+    fn window() -> Window {
+        Window {
+            lon1: 4.8,
+            lat1: 52.3,
+            lon2: 5.0,
+            lat2: 52.4,
+        }
+    }
+
+    #[test]
+    fn test_push() {
+        let now = Utc::now();
+        let telemetry = telemetry();
+        assert!(telemetry.is_provider("acme"));
+        assert!(!telemetry.is_provider("other"));
+
+        let states = vec![
+            state(now - Duration::seconds(2), 52.35, 4.9),
+            state(now - Duration::seconds(1), 52.36, 4.9),
+        ];
+        let result = telemetry
+            .push("acme", "AC-1", UAType::Helicopter, false, states, now)
+            .unwrap();
+        assert_eq!(result.accepted, 2);
+        assert_eq!(result.duplicates, 0);
+
+        // retransmitted states are ignored
+        let states = vec![
+            state(now - Duration::seconds(1), 52.36, 4.9),
+            state(now, 52.37, 4.9),
+            state(now, 52.37, 4.9),
+        ];
+        let result = telemetry
+            .push("acme", "AC-1", UAType::Helicopter, false, states, now)
+            .unwrap();
+        assert_eq!(result.accepted, 1);
+        assert_eq!(result.duplicates, 2);
+        assert_eq!(result.states.len(), 1);
+        assert_eq!(result.states[0].position.lat, 52.37);
+
+        let flights = telemetry.flights_in(&window(), 1.5, now);
+        assert_eq!(flights.len(), 1);
+        assert_eq!(flights[0].id, "AC-1");
+        assert_eq!(flights[0].aircraft_type, UAType::Helicopter);
+        assert_eq!(flights[0].current_state.position.lat, 52.37);
+        assert_eq!(flights[0].recent_positions.len(), 2);
+
+        // outside the window or timed out
+        let far = Window {
+            lon1: 5.0,
+            lat1: 53.0,
+            lon2: 5.1,
+            lat2: 53.1,
+        };
+        assert!(telemetry.flights_in(&far, 1.5, now).is_empty());
+        let later = now + Duration::seconds(TELEMETRY_TIMEOUT_SECONDS + 1);
+        assert!(telemetry.flights_in(&window(), 1.5, later).is_empty());
+    }
+
+    #[test]
+    fn test_push_rejected() {
+        let now = Utc::now();
+        let telemetry = telemetry();
+        let states = vec![state(now, 52.37, 4.9)];
+        telemetry
+            .push("acme", "AC-1", UAType::Aeroplane, true, states, now)
+            .unwrap();
+
+        // older than the latest state
+        let states = vec![state(now - Duration::seconds(1), 52.37, 4.9)];
+        let e = telemetry
+            .push("acme", "AC-1", UAType::Aeroplane, true, states, now)
+            .unwrap_err();
+        assert_eq!(e, TelemetryError::OutOfOrder);
+
+        // a batch out of order is rejected as a whole
+        let states = vec![
+            state(now + Duration::seconds(2), 52.37, 4.9),
+            state(now + Duration::seconds(1), 52.37, 4.9),
+        ];
+        let e = telemetry
+            .push("acme", "AC-1", UAType::Aeroplane, true, states, now)
+            .unwrap_err();
+        assert_eq!(e, TelemetryError::OutOfOrder);
+        let flight = telemetry.flight("AC-1").unwrap();
+        assert_eq!(
+            flight.latest(),
+            state(now, 0.0, 0.0).timestamp.to_datetime()
+        );
+        assert!(flight.simulated);
+
+        let states = vec![state(now + Duration::seconds(1), 52.37, 4.9)];
+        let e = telemetry
+            .push("skyways", "AC-1", UAType::Aeroplane, true, states, now)
+            .unwrap_err();
+        assert_eq!(e, TelemetryError::NotOwner);
+
+        let e = telemetry
+            .push("acme", "AC-2", UAType::Aeroplane, true, vec![], now)
+            .unwrap_err();
+        assert_eq!(e, TelemetryError::InvalidState);
+
+        let states = vec![state(now, 52.37, 4.9); MAX_BATCH_STATES + 1];
+        let e = telemetry
+            .push("acme", "AC-2", UAType::Aeroplane, true, states, now)
+            .unwrap_err();
+        assert_eq!(e, TelemetryError::InvalidState);
+        assert!(telemetry.flight("AC-2").is_none());
+    }
+
+    #[test]
+    fn test_states_expire() {
+        let now = Utc::now();
+        let telemetry = telemetry();
+        let states = vec![state(now, 52.37, 4.9)];
+        telemetry
+            .push("acme", "AC-1", UAType::Aeroplane, false, states, now)
+            .unwrap();
+
+        // the flight timed out, so another provider may use the ID
+        let later = now + Duration::seconds(MAX_STATE_AGE_SECONDS + 1);
+        let states = vec![state(later, 52.37, 4.9)];
+        telemetry
+            .push("skyways", "AC-1", UAType::Aeroplane, false, states, later)
+            .unwrap();
+        let flight = telemetry.flight("AC-1").unwrap();
+        assert_eq!(flight.provider, "skyways");
+        assert_eq!(flight.states.len(), 1);
+    }
+}
@@ -0,0 +1,325 @@
+//! R-tree of the live traffic
+//! Each flight is indexed by the bounding box of its track, so a view finds
+//! the flights that are or were recently within it. The index is replaced as
+//! a whole on every poll.
+
+use super::HISTORY_SECONDS;
+use crate::cache::narrow_flights;
+use crate::rest::api::rest_types::*;
+use crate::rest::api::uss::Window;
+use lib_common::time::{DateTime, Duration, Utc};
+use rstar::{RTree, RTreeObject, AABB};
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{RwLock, RwLockReadGuard};
+
+/// A flight with the bounding box of its track
+#[derive(Debug, Clone)]
+pub struct IndexedFlight {
+    /// Bounding box of the current and recent positions, as (lng, lat)
+    envelope: AABB<[f64; 2]>,
+
+    /// The flight reported by svc-gis
+    pub flight: RIDFlight,
+}
+
+impl From<RIDFlight> for IndexedFlight {
+    fn from(flight: RIDFlight) -> Self {
+        let points = flight
+            .recent_positions
+            .iter()
+            .map(|recent| &recent.position)
+            .chain(std::iter::once(&flight.current_state.position))
+            .map(|position| [position.lng, position.lat])
+            .collect::<Vec<[f64; 2]>>();
+
+        IndexedFlight {
+            envelope: AABB::from_points(&points),
+            flight,
+        }
+    }
+}
+
+impl RTreeObject for IndexedFlight {
+    type Envelope = AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        self.envelope
+    }
+}
+
+/// The flights of the service region at the time of a poll
+#[derive(Debug)]
+struct Snapshot {
+    /// End of the time range of the poll
+    time: DateTime<Utc>,
+
+    /// The flights by the bounding box of their track
+    tree: RTree<IndexedFlight>,
+}
+
+/// The live traffic of the service region
+#[derive(Debug)]
+pub struct LiveTraffic {
+    /// The indexed region, disabled if None
+    region: Option<Window>,
+
+    /// Interval between polls of svc-gis, in milliseconds
+    poll_ms: u32,
+
+    /// The latest snapshot, if a poll succeeded
+    snapshot: RwLock<Option<Snapshot>>,
+
+    /// Number of views answered from the index
+    served: AtomicU64,
+
+    /// Number of views checked against svc-gis
+    checks: AtomicU64,
+
+    /// Number of checked views for which svc-gis reported other flights
+    mismatches: AtomicU64,
+}
+
+impl LiveTraffic {
+    /// The live traffic of a region, polled every given milliseconds
+    pub fn new(region: Option<Window>, poll_ms: u32) -> Self {
+        LiveTraffic {
+            region,
+            poll_ms: poll_ms.max(1),
+            snapshot: RwLock::new(None),
+            served: AtomicU64::new(0),
+            checks: AtomicU64::new(0),
+            mismatches: AtomicU64::new(0),
+        }
+    }
+
+    /// Read the snapshot, recovering from a poisoned lock as the snapshot
+    ///  is replaced as a whole
+    fn read(&self) -> RwLockReadGuard<'_, Option<Snapshot>> {
+        self.snapshot.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The indexed region, None if the index is disabled
+    pub fn region(&self) -> Option<Window> {
+        self.region
+    }
+
+    /// Interval between polls of svc-gis
+    pub fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.poll_ms as u64)
+    }
+
+    /// A snapshot is served until a second poll was missed
+    fn max_age(&self) -> Duration {
+        Duration::milliseconds(2 * self.poll_ms as i64)
+    }
+
+    /// Replace the index with the flights of a poll ending at the given
+    ///  time
+    pub fn update(&self, flights: Vec<RIDFlight>, time: DateTime<Utc>) {
+        let tree = RTree::bulk_load(flights.into_iter().map(IndexedFlight::from).collect());
+        let mut snapshot = self.snapshot.write().unwrap_or_else(|e| e.into_inner());
+        *snapshot = Some(Snapshot { time, tree });
+    }
+
+    /// The flights that are or were within a window for the given duration,
+    ///  as svc-gis would report them.
+    /// Returns None if the index is disabled or stale, or if the window is
+    ///  not within the region.
+    pub fn flights_in(
+        &self,
+        window: &Window,
+        duration_s: f32,
+        now: DateTime<Utc>,
+    ) -> Option<Vec<RIDFlight>> {
+        let (min_lon, min_lat, max_lon, max_lat) = window.bounds();
+        let (region_min_lon, region_min_lat, region_max_lon, region_max_lat) =
+            self.region?.bounds();
+        let within = min_lon >= region_min_lon
+            && min_lat >= region_min_lat
+            && max_lon <= region_max_lon
+            && max_lat <= region_max_lat;
+        if !within || duration_s > HISTORY_SECONDS as f32 {
+            return None;
+        }
+
+        let snapshot = self.read();
+        let snapshot = snapshot
+            .as_ref()
+            .filter(|snapshot| now - snapshot.time < self.max_age())?;
+
+        let envelope = AABB::from_corners([min_lon, min_lat], [max_lon, max_lat]);
+        let candidates = snapshot
+            .tree
+            .locate_in_envelope_intersecting(&envelope)
+            .map(|indexed| indexed.flight.clone())
+            .collect::<Vec<RIDFlight>>();
+
+        let since = now - Duration::milliseconds((duration_s * 1000.0) as i64);
+        self.served.fetch_add(1, Ordering::Relaxed);
+        Some(narrow_flights(&candidates, window, since))
+    }
+
+    /// If the latest view answered from the index should be checked
+    ///  against svc-gis
+    pub fn should_check(&self) -> bool {
+        self.served.load(Ordering::Relaxed) % super::CONSISTENCY_CHECK_INTERVAL == 1
+    }
+
+    /// Compare the flights of a view answered from the index with those
+    ///  reported by svc-gis.
+    /// Returns if both report the same flights.
+    pub fn check(&self, indexed: &[RIDFlight], gis: &[RIDFlight]) -> bool {
+        let ids = |flights: &[RIDFlight]| {
+            flights
+                .iter()
+                .map(|flight| flight.id.clone())
+                .collect::<BTreeSet<String>>()
+        };
+        let indexed = ids(indexed);
+        let gis = ids(gis);
+
+        self.checks.fetch_add(1, Ordering::Relaxed);
+        if indexed == gis {
+            return true;
+        }
+
+        self.mismatches.fetch_add(1, Ordering::Relaxed);
+        rest_warn!(
+            "live traffic index and svc-gis disagree, only indexed: {:?}, only in svc-gis: {:?}",
+            indexed.difference(&gis).collect::<Vec<&String>>(),
+            gis.difference(&indexed).collect::<Vec<&String>>()
+        );
+        false
+    }
+
+    /// The state and consistency of the index
+    pub fn stats(&self, now: DateTime<Utc>) -> LiveTrafficStats {
+        let snapshot = self.read();
+        LiveTrafficStats {
+            enabled: self.region.is_some(),
+            flights: snapshot.as_ref().map_or(0, |snapshot| snapshot.tree.size()),
+            age_ms: snapshot
+                .as_ref()
+                .map(|snapshot| (now - snapshot.time).num_milliseconds()),
+            served: self.served.load(Ordering::Relaxed),
+            checks: self.checks.load(Ordering::Relaxed),
+            mismatches: self.mismatches.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::tests::flight;
+
+    fn region() -> Window {
+        Window {
+            lon1: 4.7,
+            lat1: 52.2,
+            lon2: 5.1,
+            lat2: 52.5,
+        }
+    }
+
+    fn view() -> Window {
+        Window {
+            lon1: 4.89,
+            lat1: 52.36,
+            lon2: 4.91,
+            lat2: 52.38,
+        }
+    }
+
+    #[test]
+    fn test_flights_in() {
+        let traffic = LiveTraffic::new(Some(region()), 1_000);
+        let now = Utc::now();
+        assert!(traffic.flights_in(&view(), 10.0, now).is_none());
+
+        traffic.update(
+            vec![
+                flight("AC-1", now, 52.37, 4.90),
+                flight("AC-2", now, 52.45, 4.90),
+            ],
+            now,
+        );
+
+        let flights = traffic.flights_in(&view(), 5.5, now).unwrap();
+        assert_eq!(flights.len(), 1);
+        assert_eq!(flights[0].id, "AC-1");
+        assert_eq!(flights[0].recent_positions.len(), 5);
+
+        // flights that recently left the view are found by their track
+        let mut moved = flight("AC-3", now, 52.45, 4.90);
+        moved.recent_positions[9].position.lat = 52.37;
+        traffic.update(vec![moved], now);
+        let flights = traffic.flights_in(&view(), 5.0, now).unwrap();
+        assert_eq!(flights.len(), 1);
+
+        let stats = traffic.stats(now);
+        assert!(stats.enabled);
+        assert_eq!(stats.flights, 1);
+        assert_eq!(stats.age_ms, Some(0));
+        assert_eq!(stats.served, 2);
+    }
+
+    #[test]
+    fn test_fallback() {
+        let now = Utc::now();
+        let disabled = LiveTraffic::new(None, 1_000);
+        disabled.update(vec![], now);
+        assert!(disabled.flights_in(&view(), 10.0, now).is_none());
+        assert!(!disabled.stats(now).enabled);
+
+        let traffic = LiveTraffic::new(Some(region()), 1_000);
+        traffic.update(vec![], now);
+        assert!(traffic.flights_in(&view(), 10.0, now).is_some());
+        assert!(traffic.flights_in(&view(), 60.5, now).is_none());
+
+        // views reaching outside of the region
+        let outside = Window {
+            lon1: 5.0,
+            lat1: 52.4,
+            lon2: 5.2,
+            lat2: 52.45,
+        };
+        assert!(traffic.flights_in(&outside, 10.0, now).is_none());
+
+        // stale index
+        let later = now + Duration::milliseconds(2_000);
+        assert!(traffic.flights_in(&view(), 10.0, later).is_none());
+    }
+
+    #[test]
+    fn test_check() {
+        let traffic = LiveTraffic::new(Some(region()), 1_000);
+        let now = Utc::now();
+        let a = flight("AC-1", now, 52.37, 4.90);
+        let b = flight("AC-2", now, 52.37, 4.90);
+
+        assert!(traffic.check(&[a.clone(), b.clone()], &[b.clone(), a.clone()]));
+        assert!(!traffic.check(&[a], &[b]));
+
+        let stats = traffic.stats(now);
+        assert_eq!(stats.checks, 2);
+        assert_eq!(stats.mismatches, 1);
+        assert_eq!(stats.age_ms, None);
+    }
+
+    #[test]
+    fn test_should_check() {
+        let traffic = LiveTraffic::new(Some(region()), 1_000);
+        let now = Utc::now();
+        traffic.update(vec![], now);
+
+        let checked = (0..super::super::CONSISTENCY_CHECK_INTERVAL * 2)
+            .filter(|_| {
+                traffic.flights_in(&view(), 1.0, now);
+                traffic.should_check()
+            })
+            .count();
+        assert_eq!(checked, 2);
+    }
+}
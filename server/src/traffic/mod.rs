@@ -0,0 +1,81 @@
+//! Live traffic of the service region
+//! When a service region is configured, svc-gis is polled for the latest
+//! state and recent positions of every flight in the region, which are kept
+//! in an R-tree. Views within the region are answered from the index without
+//! a round trip to svc-gis, falling back to svc-gis while the index is stale.
+
+pub mod index;
+
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::uss::{gis_flights, parse_view};
+use index::LiveTraffic;
+use lib_common::time::{Duration, Utc};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Duration of the recent positions kept of every flight, in seconds, the
+///  longest duration of a view
+pub const HISTORY_SECONDS: i64 = 60;
+
+/// One in this many views answered from the index is also queried from
+///  svc-gis to check both agree
+pub const CONSISTENCY_CHECK_INTERVAL: u64 = 100;
+
+pub(crate) static LIVE_TRAFFIC: OnceCell<Arc<LiveTraffic>> = OnceCell::const_new();
+
+/// Returns the live traffic index, shared by the REST and gRPC servers.
+/// Initializes the index with the region and poll interval of the
+///  configuration if it hasn't been initialized yet. The index is
+///  disabled if the region is empty or invalid.
+pub async fn get_live_traffic() -> Arc<LiveTraffic> {
+    LIVE_TRAFFIC
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            let region = match config.live_traffic_region.as_str() {
+                "" => None,
+                region => parse_view(region)
+                    .map_err(|_| {
+                        rest_error!("invalid live traffic region, index disabled: {}", region);
+                    })
+                    .ok(),
+            };
+
+            Arc::new(LiveTraffic::new(region, config.live_traffic_poll_ms))
+        })
+        .await
+        .clone()
+}
+
+/// Poll svc-gis for the flights of the service region, forever
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) endless loop, LiveTraffic is unit tested
+pub async fn poll_gis(traffic: Arc<LiveTraffic>, mut grpc_clients: GrpcClients) {
+    let Some(region) = traffic.region() else {
+        return;
+    };
+
+    rest_info!("indexing the live traffic of {:?}.", region);
+    let mut interval = tokio::time::interval(traffic.poll_interval());
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let now = Utc::now();
+        let history = Duration::seconds(HISTORY_SECONDS);
+        match gis_flights(&mut grpc_clients, &region, now - history, now).await {
+            Ok(flights) => traffic.update(flights, now),
+            Err(e) => rest_warn!("could not poll the live traffic: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_live_traffic() {
+        let a = get_live_traffic().await;
+        let b = get_live_traffic().await;
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+}
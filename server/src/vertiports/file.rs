@@ -0,0 +1,159 @@
+//! Vertiports read from a JSON file at startup
+
+use super::{validate, VertiportError, VertiportSource};
+use crate::rest::api::rest_types::*;
+
+/// Vertiports loaded from a JSON file, ordered by ID
+#[derive(Debug)]
+pub struct FileVertiports {
+    /// The valid vertiports of the file
+    vertiports: Vec<Vertiport>,
+}
+
+impl FileVertiports {
+    /// Load the vertiports stored in the given file.
+    /// A missing or invalid file results in no vertiports,
+    ///  invalid vertiports are skipped.
+    pub fn load(path: &str) -> Self {
+        let vertiports = match std::fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str::<Vec<Vertiport>>(&content) {
+                Ok(vertiports) => vertiports,
+                Err(e) => {
+                    rest_error!("invalid vertiports file {}: {}", path, e);
+                    vec![]
+                }
+            },
+            Err(e) => {
+                rest_info!("no vertiports loaded from {}: {}", path, e);
+                vec![]
+            }
+        };
+
+        let mut vertiports = vertiports
+            .into_iter()
+            .filter(validate)
+            .collect::<Vec<Vertiport>>();
+        vertiports.sort_by(|a, b| a.id.cmp(&b.id));
+        vertiports.dedup_by(|a, b| a.id == b.id);
+
+        rest_info!("loaded {} vertiports from {}.", vertiports.len(), path);
+        FileVertiports { vertiports }
+    }
+}
+
+impl VertiportSource for FileVertiports {
+    fn vertiports(&self) -> Result<Vec<Vertiport>, VertiportError> {
+        Ok(self.vertiports.clone())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A vertiport in the center of Amsterdam
+    pub(crate) fn vertiport(id: &str) -> Vertiport {
+        Vertiport {
+            id: id.to_string(),
+            name: "Amsterdam Centraal".to_string(),
+            location: LatLngPoint {
+                lat: 52.3785,
+                lng: 4.9005,
+            },
+            footprint: Polygon {
+                vertices: vec![
+                    LatLngPoint {
+                        lat: 52.378,
+                        lng: 4.900,
+                    },
+                    LatLngPoint {
+                        lat: 52.378,
+                        lng: 4.901,
+                    },
+                    LatLngPoint {
+                        lat: 52.379,
+                        lng: 4.901,
+                    },
+                    LatLngPoint {
+                        lat: 52.379,
+                        lng: 4.900,
+                    },
+                ],
+            },
+            pad_count: 4,
+            operating_hours: vec![OperatingHours {
+                day: DayOfWeek::Monday,
+                open: "06:00".to_string(),
+                close: "22:00".to_string(),
+            }],
+            status: VertiportStatus::Open,
+        }
+    }
+
+    /// Write vertiports to a new temporary file
+    pub(crate) fn vertiports_file(vertiports: &[Vertiport]) -> String {
+        let path = std::env::temp_dir()
+            .join(format!(
+                "svc-discovery-vertiports-{}.json",
+                rand::random::<u64>()
+            ))
+            .to_string_lossy()
+            .to_string();
+        std::fs::write(&path, serde_json::to_string(vertiports).unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load() {
+        let mut invalid = vertiport("invalid");
+        invalid.footprint.vertices.clear();
+        let path = vertiports_file(&[vertiport("b"), invalid, vertiport("a"), vertiport("a")]);
+
+        let source = FileVertiports::load(&path);
+        let ids = source
+            .vertiports()
+            .unwrap()
+            .into_iter()
+            .map(|v| v.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        assert_eq!(source.vertiport("b").unwrap().id, "b");
+        assert_eq!(
+            source.vertiport("invalid").unwrap_err(),
+            VertiportError::NotFound
+        );
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(FileVertiports::load(&path).vertiports().unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(FileVertiports::load("/nonsense/vertiports.json")
+            .vertiports()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_vertiports_in() {
+        let path = vertiports_file(&[vertiport("a")]);
+        let source = FileVertiports::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let window = crate::rest::api::uss::Window {
+            lat1: 52.37,
+            lon1: 4.89,
+            lat2: 52.38,
+            lon2: 4.91,
+        };
+        assert_eq!(source.vertiports_in(&window).unwrap().len(), 1);
+
+        let window = crate::rest::api::uss::Window {
+            lat1: 52.30,
+            lon1: 4.80,
+            lat2: 52.31,
+            lon2: 4.81,
+        };
+        assert!(source.vertiports_in(&window).unwrap().is_empty());
+    }
+}
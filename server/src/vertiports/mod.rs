@@ -0,0 +1,214 @@
+//! Vertiports of the Aetheric network
+//! Vertiports are read from a [`VertiportSource`], so they can be served
+//! from a file until they are available from svc-storage.
+
+pub mod file;
+pub mod partners;
+
+use crate::rest::api::rest_types::{LatLngPoint, Vertiport};
+use crate::rest::api::uss::Window;
+use file::FileVertiports;
+use partners::{parse_partners, PartnerVertiports};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Errors returned by a vertiport source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VertiportError {
+    /// No vertiport exists with the provided ID
+    NotFound,
+
+    /// The source could not be reached
+    Unavailable,
+}
+
+impl std::error::Error for VertiportError {}
+
+impl Display for VertiportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VertiportError::NotFound => write!(f, "Vertiport not found"),
+            VertiportError::Unavailable => write!(f, "Vertiport source unavailable"),
+        }
+    }
+}
+
+/// Parse a time of day of format 'HH:MM' into minutes since midnight
+fn parse_time_of_day(value: &str) -> Option<u32> {
+    let (hours, minutes) = value.split_once(':')?;
+    if hours.len() != 2 || minutes.len() != 2 {
+        return None;
+    }
+
+    let hours = hours.parse::<u32>().ok()?;
+    let minutes = minutes.parse::<u32>().ok()?;
+    if hours > 24 || minutes > 59 || (hours == 24 && minutes > 0) {
+        return None;
+    }
+
+    Some(hours * 60 + minutes)
+}
+
+/// Validate the fields of a vertiport
+pub(crate) fn validate(vertiport: &Vertiport) -> bool {
+    if vertiport.id.trim().is_empty() {
+        rest_warn!("vertiport ID must not be empty.");
+        return false;
+    }
+
+    if !vertiport.location.is_valid() {
+        rest_warn!("invalid location of vertiport {}.", vertiport.id);
+        return false;
+    }
+
+    let footprint = &vertiport.footprint.vertices;
+    if footprint.len() < 3 || !footprint.iter().all(LatLngPoint::is_valid) {
+        rest_warn!(
+            "footprint of vertiport {} must have at least 3 valid vertices.",
+            vertiport.id
+        );
+        return false;
+    }
+
+    for hours in &vertiport.operating_hours {
+        let (Some(open), Some(close)) = (
+            parse_time_of_day(&hours.open),
+            parse_time_of_day(&hours.close),
+        ) else {
+            rest_warn!(
+                "operating hours of vertiport {} must be of format 'HH:MM'.",
+                vertiport.id
+            );
+            return false;
+        };
+
+        if open >= close {
+            rest_warn!(
+                "vertiport {} must open before it closes on {}.",
+                vertiport.id,
+                hours.day
+            );
+            return false;
+        }
+    }
+
+    true
+}
+
+/// A source of vertiports
+pub trait VertiportSource: Debug + Send + Sync {
+    /// All vertiports, ordered by ID
+    fn vertiports(&self) -> Result<Vec<Vertiport>, VertiportError>;
+
+    /// The vertiport with the given ID
+    fn vertiport(&self, id: &str) -> Result<Vertiport, VertiportError> {
+        self.vertiports()?
+            .into_iter()
+            .find(|vertiport| vertiport.id == id)
+            .ok_or(VertiportError::NotFound)
+    }
+
+    /// The vertiports with a footprint overlapping the window
+    fn vertiports_in(&self, window: &Window) -> Result<Vec<Vertiport>, VertiportError> {
+        Ok(self
+            .vertiports()?
+            .into_iter()
+            .filter(|vertiport| window.intersects_polygon(&vertiport.footprint))
+            .collect())
+    }
+}
+
+pub(crate) static VERTIPORTS: OnceCell<Arc<dyn VertiportSource>> = OnceCell::const_new();
+
+/// Returns the vertiport source, loaded from the vertiports file
+///  of a Config object generated from environment variables.
+/// Initializes the source if it hasn't been initialized yet.
+pub async fn get_vertiports() -> Arc<dyn VertiportSource> {
+    VERTIPORTS
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            let source: Arc<dyn VertiportSource> =
+                Arc::new(FileVertiports::load(&config.vertiports_file));
+            source
+        })
+        .await
+        .clone()
+}
+
+pub(crate) static PARTNER_VERTIPORTS: OnceCell<Arc<PartnerVertiports>> = OnceCell::const_new();
+
+/// Returns the client for the vertiports of partner networks, using the
+///  partners of a Config object generated from environment variables.
+/// Initializes the client if it hasn't been initialized yet.
+pub async fn get_partner_vertiports() -> Arc<PartnerVertiports> {
+    PARTNER_VERTIPORTS
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            Arc::new(PartnerVertiports::new(parse_partners(
+                &config.partner_vertiport_providers,
+            )))
+        })
+        .await
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use file::tests::vertiport;
+
+    #[test]
+    fn test_parse_time_of_day() {
+        assert_eq!(parse_time_of_day("00:00"), Some(0));
+        assert_eq!(parse_time_of_day("06:30"), Some(390));
+        assert_eq!(parse_time_of_day("24:00"), Some(1440));
+        assert_eq!(parse_time_of_day("24:01"), None);
+        assert_eq!(parse_time_of_day("12:60"), None);
+        assert_eq!(parse_time_of_day("6:30"), None);
+        assert_eq!(parse_time_of_day("0630"), None);
+        assert_eq!(parse_time_of_day("ab:cd"), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&vertiport("a")));
+        assert!(!validate(&vertiport(" ")));
+
+        let mut invalid = vertiport("a");
+        invalid.location.lat = 90.1;
+        assert!(!validate(&invalid));
+
+        invalid = vertiport("a");
+        invalid.footprint.vertices.truncate(2);
+        assert!(!validate(&invalid));
+
+        invalid = vertiport("a");
+        invalid.operating_hours[0].open = "6am".to_string();
+        assert!(!validate(&invalid));
+
+        invalid = vertiport("a");
+        invalid.operating_hours[0].close = "05:00".to_string();
+        assert!(!validate(&invalid));
+    }
+
+    #[tokio::test]
+    async fn test_get_vertiports() {
+        let a = get_vertiports().await;
+        let b = get_vertiports().await;
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let a = get_partner_vertiports().await;
+        let b = get_partner_vertiports().await;
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(VertiportError::NotFound.to_string(), "Vertiport not found");
+        assert_eq!(
+            VertiportError::Unavailable.to_string(),
+            "Vertiport source unavailable"
+        );
+    }
+}
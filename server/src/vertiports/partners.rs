@@ -0,0 +1,523 @@
+//! Vertiports of partner networks
+//! Partner providers expose the same `/vertiports` API as this service, and
+//! are either configured or registered with a `Vertiports` endpoint.
+//! Their vertiports are validated and namespaced by provider, so they can
+//! be used next to our own.
+//! Registered partners are only queried on public addresses, so a registry
+//! entry can't make this service query loopback or internal hosts.
+
+use super::validate;
+use crate::registry::store::ServiceProviders;
+use crate::rest::api::rest_types::*;
+use crate::rest::api::uss::Window;
+use futures::future::join_all;
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method, Request, Uri};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Time a partner has to answer a query, in seconds
+pub const PARTNER_TIMEOUT_SECONDS: u64 = 5;
+
+/// A partner provider of vertiports
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartnerProvider {
+    /// Identifier of the provider, used to namespace its vertiports
+    pub id: String,
+
+    /// Base URL of the vertiport API of the provider
+    pub base_url: String,
+}
+
+/// A vertiport of a partner network
+#[derive(Debug, Clone)]
+pub struct PartnerVertiport {
+    /// Identifier of the provider of the vertiport
+    pub provider: String,
+
+    /// The vertiport, with an ID of format 'provider:id'
+    pub vertiport: Vertiport,
+}
+
+/// Check if the base URL of a partner can be queried
+fn valid_base_url(base_url: &str) -> bool {
+    base_url.starts_with("http://") || base_url.starts_with("https://")
+}
+
+/// Check if an address is reachable on the public internet, rejecting
+///  unspecified, loopback, private, shared, link-local and broadcast addresses
+fn public_address(address: &IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            let [a, b, ..] = address.octets();
+            !(address.is_unspecified()
+                || address.is_loopback()
+                || address.is_private()
+                || address.is_link_local()
+                || address.is_broadcast()
+                // shared address space, 100.64.0.0/10
+                || (a == 100 && (b & 0xc0) == 64))
+        }
+        IpAddr::V6(address) => {
+            if let Some(mapped) = address.to_ipv4_mapped() {
+                return public_address(&IpAddr::V4(mapped));
+            }
+
+            let first = address.segments()[0];
+            !(address.is_unspecified()
+                || address.is_loopback()
+                // unique local, fc00::/7
+                || (first & 0xfe00) == 0xfc00
+                // link-local, fe80::/10
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// Check if the host of a base URL only resolves to public addresses
+async fn public_host(base_url: &str) -> bool {
+    let Ok(uri) = base_url.parse::<Uri>() else {
+        return false;
+    };
+
+    let Some(host) = uri.host() else {
+        return false;
+    };
+
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(address) = host.parse::<IpAddr>() {
+        return public_address(&address);
+    }
+
+    let port = uri
+        .port_u16()
+        .unwrap_or(if uri.scheme_str() == Some("https") {
+            443
+        } else {
+            80
+        });
+    let Ok(addresses) = tokio::net::lookup_host((host, port)).await else {
+        rest_warn!("could not resolve the host of {}.", base_url);
+        return false;
+    };
+
+    let mut addresses = addresses.peekable();
+    addresses.peek().is_some() && addresses.all(|address| public_address(&address.ip()))
+}
+
+/// Parse partner providers from a comma separated list of 'id=base_url'.
+/// Entries without an ID or an http:// or https:// URL are skipped.
+pub fn parse_partners(value: &str) -> Vec<PartnerProvider> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let Some((id, base_url)) = entry.split_once('=') else {
+                rest_warn!("partner must be of format 'id=base_url': {}", entry);
+                return None;
+            };
+
+            let (id, base_url) = (id.trim(), base_url.trim().trim_end_matches('/'));
+            if id.is_empty() || !valid_base_url(base_url) {
+                rest_warn!("partner must have an ID and an HTTP(S) URL: {}", entry);
+                return None;
+            }
+
+            Some(PartnerProvider {
+                id: id.to_string(),
+                base_url: base_url.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The partners registered with a `Vertiports` endpoint and a region
+///  overlapping the window
+pub fn registered_partners(registry: &ServiceProviders, window: &Window) -> Vec<PartnerProvider> {
+    registry
+        .lookup(window, ProviderService::Vertiports)
+        .into_iter()
+        .filter_map(|provider| {
+            let endpoint = provider
+                .endpoints
+                .iter()
+                .find(|endpoint| endpoint.service == ProviderService::Vertiports)?;
+
+            Some(PartnerProvider {
+                base_url: endpoint.base_url.trim_end_matches('/').to_string(),
+                id: provider.id,
+            })
+        })
+        .collect()
+}
+
+/// Client querying the vertiports of partner providers
+#[derive(Debug, Clone)]
+pub struct PartnerVertiports {
+    /// The configured partner providers
+    partners: Vec<PartnerProvider>,
+
+    /// Time a partner has to answer a query
+    timeout: Duration,
+
+    /// If registered partners must have a public address
+    public_only: bool,
+
+    /// HTTP(S) client shared by the queries
+    client: Client<HttpsConnector<HttpConnector>>,
+}
+
+impl PartnerVertiports {
+    /// Create a client for the given partners
+    pub fn new(partners: Vec<PartnerProvider>) -> Self {
+        let connector = HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_or_http()
+            .enable_http1()
+            .build();
+
+        PartnerVertiports {
+            partners,
+            timeout: Duration::from_secs(PARTNER_TIMEOUT_SECONDS),
+            public_only: true,
+            client: Client::builder().build(connector),
+        }
+    }
+
+    /// The configured partners queried by this client
+    pub fn partners(&self) -> &[PartnerProvider] {
+        &self.partners
+    }
+
+    /// Query a single partner, returns its vertiports as provided
+    async fn query(&self, partner: &PartnerProvider, window: &Window) -> Option<Vec<Vertiport>> {
+        let uri = format!(
+            "{}/vertiports?view={},{},{},{}",
+            partner.base_url, window.lat1, window.lon1, window.lat2, window.lon2
+        );
+
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri(&uri)
+            .header(hyper::header::ACCEPT, "application/json")
+            .body(Body::empty())
+            .map_err(|e| rest_error!("could not build request for {}: {}", uri, e))
+            .ok()?;
+
+        let response = tokio::time::timeout(self.timeout, self.client.request(request))
+            .await
+            .map_err(|_| rest_warn!("partner {} did not answer in time.", partner.id))
+            .ok()?
+            .map_err(|e| rest_warn!("could not query partner {}: {}", partner.id, e))
+            .ok()?;
+
+        if !response.status().is_success() {
+            rest_warn!("partner {} responded {}.", partner.id, response.status());
+            return None;
+        }
+
+        let body = tokio::time::timeout(self.timeout, hyper::body::to_bytes(response.into_body()))
+            .await
+            .map_err(|_| rest_warn!("partner {} did not answer in time.", partner.id))
+            .ok()?
+            .map_err(|e| rest_warn!("could not read response of partner {}: {}", partner.id, e))
+            .ok()?;
+
+        serde_json::from_slice::<GetVertiportsResponse>(&body)
+            .map(|response| response.vertiports)
+            .map_err(|e| rest_warn!("invalid response of partner {}: {}", partner.id, e))
+            .ok()
+    }
+
+    /// The vertiports of the configured and registered partners overlapping
+    ///  the window, ordered by ID.
+    /// A registered partner with the ID of a configured partner, or with a
+    ///  host that isn't public, is ignored.
+    /// Partners that fail to answer are skipped.
+    pub async fn vertiports_in(
+        &self,
+        window: &Window,
+        registered: &[PartnerProvider],
+    ) -> Vec<PartnerVertiport> {
+        let registered = join_all(
+            registered
+                .iter()
+                .filter(|partner| valid_base_url(&partner.base_url))
+                .filter(|partner| self.partners.iter().all(|p| p.id != partner.id))
+                .map(|partner| async move {
+                    let public = !self.public_only || public_host(&partner.base_url).await;
+                    if !public {
+                        rest_warn!("partner {} is not on a public host, skipped.", partner.id);
+                    }
+
+                    public.then_some(partner)
+                }),
+        )
+        .await
+        .into_iter()
+        .flatten();
+        let results = join_all(
+            self.partners
+                .iter()
+                .chain(registered)
+                .map(|partner| async move { (partner, self.query(partner, window).await) }),
+        )
+        .await;
+
+        let mut vertiports = results
+            .into_iter()
+            .filter_map(|(partner, vertiports)| Some((partner, vertiports?)))
+            .flat_map(|(partner, vertiports)| {
+                vertiports
+                    .into_iter()
+                    .filter(validate)
+                    .filter(|vertiport| window.intersects_polygon(&vertiport.footprint))
+                    .map(|vertiport| PartnerVertiport {
+                        provider: partner.id.clone(),
+                        vertiport: Vertiport {
+                            id: format!("{}:{}", partner.id, vertiport.id),
+                            ..vertiport
+                        },
+                    })
+            })
+            .collect::<Vec<PartnerVertiport>>();
+
+        vertiports.sort_by(|a, b| a.vertiport.id.cmp(&b.vertiport.id));
+        vertiports
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::registry::store::tests::{provider, temp_path};
+    use crate::vertiports::file::tests::vertiport;
+    use axum::extract::Query;
+    use axum::routing;
+    use axum::{Json, Router};
+    use hyper::StatusCode;
+
+    /// Start a mock partner on a free local port, returns its base URL
+    pub(crate) fn mock_partner(vertiports: Vec<Vertiport>, status: StatusCode) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new().route(
+            "/vertiports",
+            routing::get(
+                move |Query(query): Query<GetVertiportsRequest>| async move {
+                    assert_eq!(query.view.split(',').count(), 4);
+                    (
+                        status,
+                        Json(GetVertiportsResponse {
+                            vertiports: vertiports.clone(),
+                        }),
+                    )
+                },
+            ),
+        );
+
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        format!("http://{}", address)
+    }
+
+    pub(crate) fn window() -> Window {
+        Window {
+            lat1: 52.37,
+            lon1: 4.89,
+            lat2: 52.38,
+            lon2: 4.91,
+        }
+    }
+
+    #[test]
+    fn test_parse_partners() {
+        assert!(parse_partners("").is_empty());
+
+        let partners = parse_partners(
+            " a=http://a.example.com/ ,b,=http://c.example.com,d=https://d.example.com,e=http://e.example.com",
+        );
+        assert_eq!(
+            partners,
+            vec![
+                PartnerProvider {
+                    id: "a".to_string(),
+                    base_url: "http://a.example.com".to_string(),
+                },
+                PartnerProvider {
+                    id: "d".to_string(),
+                    base_url: "https://d.example.com".to_string(),
+                },
+                PartnerProvider {
+                    id: "e".to_string(),
+                    base_url: "http://e.example.com".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_registered_partners() {
+        let path = temp_path("registry-partners");
+        let registry = ServiceProviders::load(&path);
+        registry.create(provider("rid")).unwrap();
+        let mut partner = provider("partner");
+        partner.endpoints.push(ProviderEndpoint {
+            service: ProviderService::Vertiports,
+            base_url: "https://vertiports.example.com/".to_string(),
+        });
+        registry.create(partner).unwrap();
+
+        assert_eq!(
+            registered_partners(&registry, &window()),
+            vec![PartnerProvider {
+                id: "partner".to_string(),
+                base_url: "https://vertiports.example.com".to_string(),
+            }]
+        );
+
+        let elsewhere = Window {
+            lat1: 10.0,
+            lon1: 10.0,
+            lat2: 10.1,
+            lon2: 10.1,
+        };
+        assert!(registered_partners(&registry, &elsewhere).is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_vertiports_in() {
+        let mut invalid = vertiport("invalid");
+        invalid.footprint.vertices.clear();
+        let mut elsewhere = vertiport("elsewhere");
+        for vertex in elsewhere.footprint.vertices.iter_mut() {
+            vertex.lat -= 1.0;
+        }
+
+        let partners = vec![
+            PartnerProvider {
+                id: "b".to_string(),
+                base_url: mock_partner(vec![vertiport("vp")], StatusCode::OK),
+            },
+            PartnerProvider {
+                id: "a".to_string(),
+                base_url: mock_partner(vec![vertiport("vp"), invalid, elsewhere], StatusCode::OK),
+            },
+            PartnerProvider {
+                id: "failing".to_string(),
+                base_url: mock_partner(vec![vertiport("vp")], StatusCode::INTERNAL_SERVER_ERROR),
+            },
+            PartnerProvider {
+                id: "unreachable".to_string(),
+                base_url: "http://127.0.0.1:1".to_string(),
+            },
+        ];
+
+        let mut client = PartnerVertiports::new(partners);
+        assert_eq!(client.partners().len(), 4);
+
+        let vertiports = client.vertiports_in(&window(), &[]).await;
+        let ids = vertiports
+            .iter()
+            .map(|v| v.vertiport.id.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a:vp", "b:vp"]);
+        assert_eq!(vertiports[0].provider, "a");
+        assert_eq!(vertiports[0].vertiport.pad_count, 4);
+
+        // registered partners are queried too, unless configured
+        let registered = vec![
+            PartnerProvider {
+                id: "c".to_string(),
+                base_url: mock_partner(vec![vertiport("vp")], StatusCode::OK),
+            },
+            PartnerProvider {
+                id: "b".to_string(),
+                base_url: mock_partner(vec![vertiport("other")], StatusCode::OK),
+            },
+            PartnerProvider {
+                id: "d".to_string(),
+                base_url: "ftp://d.example.com".to_string(),
+            },
+        ];
+        let ids = client
+            .vertiports_in(&window(), &registered)
+            .await
+            .into_iter()
+            .map(|v| v.vertiport.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a:vp", "b:vp"]);
+
+        // the mock partners are on a loopback address
+        client.public_only = false;
+        let ids = client
+            .vertiports_in(&window(), &registered)
+            .await
+            .into_iter()
+            .map(|v| v.vertiport.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a:vp", "b:vp", "c:vp"]);
+    }
+
+    #[tokio::test]
+    async fn test_public_host() {
+        for base_url in [
+            "http://localhost:8080",
+            "http://127.0.0.1",
+            "http://0.0.0.0",
+            "http://10.1.2.3",
+            "http://172.16.0.1",
+            "http://192.168.1.1",
+            "http://100.64.0.1",
+            "http://169.254.169.254",
+            "http://255.255.255.255",
+            "http://[::1]:8080",
+            "http://[::]",
+            "http://[fd00::1]",
+            "http://[fe80::1]",
+            "http://[::ffff:127.0.0.1]",
+            "not a url",
+        ] {
+            assert!(!public_host(base_url).await, "{}", base_url);
+        }
+
+        for base_url in [
+            "http://93.184.216.34",
+            "https://8.8.8.8:8443",
+            "http://[2606:4700::1111]",
+        ] {
+            assert!(public_host(base_url).await, "{}", base_url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vertiports_in_timeout() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new().route(
+            "/vertiports",
+            routing::get(|| async {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                StatusCode::OK
+            }),
+        );
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+
+        let mut client = PartnerVertiports::new(vec![PartnerProvider {
+            id: "slow".to_string(),
+            base_url: format!("http://{}", address),
+        }]);
+        client.timeout = Duration::from_millis(100);
+        assert!(client.vertiports_in(&window(), &[]).await.is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+//! Meteorological reports
+//! METAR and TAF reports are ingested from a drop directory or pushed to
+//! the REST API, and shared with U-space providers by station and by area.
+
+pub mod parser;
+pub mod store;
+
+use lib_common::time::Utc;
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+use std::sync::Arc;
+use store::WeatherStore;
+use tokio::sync::OnceCell;
+
+/// Interval between two scans of the drop directory, in seconds
+pub const DROP_DIR_SCAN_INTERVAL_SECONDS: u64 = 10;
+
+/// Extension of completed files in the drop directory
+pub const DROP_FILE_EXTENSION: &str = "txt";
+
+/// Errors with meteorological reports
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeatherError {
+    /// The report could not be parsed
+    InvalidReport,
+
+    /// No reports are known for the station
+    NotFound,
+}
+
+impl std::error::Error for WeatherError {}
+
+impl Display for WeatherError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            WeatherError::InvalidReport => write!(f, "Invalid report"),
+            WeatherError::NotFound => write!(f, "No reports for station"),
+        }
+    }
+}
+
+pub(crate) static WEATHER: OnceCell<Arc<WeatherStore>> = OnceCell::const_new();
+
+/// Returns the latest meteorological reports, shared by the REST and
+///  gRPC servers and the drop directory scanner.
+/// Uses the station locations from a Config object generated from
+///  environment variables.
+/// Initializes the storage if it hasn't been initialized yet.
+pub async fn get_weather() -> Arc<WeatherStore> {
+    WEATHER
+        .get_or_init(|| async move {
+            let config = crate::Config::try_from_env().unwrap_or_default();
+            Arc::new(WeatherStore::load(&config.weather_stations_file))
+        })
+        .await
+        .clone()
+}
+
+/// Ingest and remove the completed files in the drop directory.
+/// Files are written under another name and renamed to the completed
+///  extension once written, so partially written files are never read.
+/// Returns the number of ingested files.
+pub async fn scan_drop_dir(store: &WeatherStore, dir: &Path) -> usize {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) => {
+            rest_warn!("could not read weather drop directory {:?}: {}", dir, e);
+            return 0;
+        }
+    };
+
+    let mut ingested = 0;
+    loop {
+        let entry = match entries.next_entry().await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => break,
+            Err(e) => {
+                rest_warn!("could not read weather drop directory {:?}: {}", dir, e);
+                break;
+            }
+        };
+
+        let path = entry.path();
+        let completed = path
+            .extension()
+            .is_some_and(|extension| extension == DROP_FILE_EXTENSION);
+        let is_file = entry
+            .file_type()
+            .await
+            .is_ok_and(|file_type| file_type.is_file());
+        if !completed || !is_file {
+            continue;
+        }
+
+        let text = match tokio::fs::read_to_string(&path).await {
+            Ok(text) => text,
+            Err(e) => {
+                rest_warn!("could not read weather file {:?}: {}", path, e);
+                continue;
+            }
+        };
+
+        let (accepted, rejected) = store.ingest(&text, Utc::now());
+        rest_info!(
+            "ingested {} reports from {:?}, {} rejected.",
+            accepted.len(),
+            path,
+            rejected
+        );
+
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            rest_error!("could not remove weather file {:?}: {}", path, e);
+        }
+        ingested += 1;
+    }
+
+    ingested
+}
+
+/// Scan the drop directory at a fixed interval, forever
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) endless loop, scan_drop_dir is unit tested
+pub async fn watch_drop_dir(store: Arc<WeatherStore>, dir: String) {
+    rest_info!("watching weather drop directory {}.", dir);
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+        DROP_DIR_SCAN_INTERVAL_SECONDS,
+    ));
+    loop {
+        interval.tick().await;
+        scan_drop_dir(&store, Path::new(&dir)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use store::tests::{store, BULLETIN};
+
+    #[tokio::test]
+    async fn test_get_weather() {
+        let a = get_weather().await;
+        let b = get_weather().await;
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(WeatherError::InvalidReport.to_string(), "Invalid report");
+        assert_eq!(WeatherError::NotFound.to_string(), "No reports for station");
+    }
+
+    #[tokio::test]
+    async fn test_scan_drop_dir() {
+        let dir = std::env::temp_dir().join(format!(
+            "svc-discovery-weather-drop-{}",
+            rand::random::<u64>()
+        ));
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::create_dir(dir.join("subdir")).unwrap();
+        std::fs::write(dir.join("bulletin.txt"), BULLETIN).unwrap();
+        std::fs::write(dir.join("partial.txt.tmp"), "METAR EHRD 0109").unwrap();
+
+        let store = store();
+        assert_eq!(scan_drop_dir(&store, &dir).await, 1);
+        assert!(store.station("EHAM").is_ok());
+        assert!(!dir.join("bulletin.txt").exists());
+
+        // files still being written are left alone
+        assert!(dir.join("partial.txt.tmp").exists());
+        assert_eq!(scan_drop_dir(&store, &dir).await, 0);
+
+        std::fs::rename(dir.join("partial.txt.tmp"), dir.join("partial.txt")).unwrap();
+        assert_eq!(scan_drop_dir(&store, &dir).await, 1);
+        assert!(!dir.join("partial.txt").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(scan_drop_dir(&store, &dir).await, 0);
+    }
+}
@@ -0,0 +1,642 @@
+//! Parser of METAR and TAF reports
+//!
+//! Only the groups needed for flight planning are decoded: wind,
+//! visibility, ceiling, QNH and temperature. For a TAF, only the base
+//! forecast is decoded, change groups (FM, BECMG, TEMPO, PROB) are kept in
+//! the raw report only.
+
+use super::WeatherError;
+use crate::rest::api::rest_types::*;
+use lib_common::time::{DateTime, Duration, Utc};
+
+/// Knots per meter per second
+const KNOTS_PER_MPS: f32 = 1.943_844;
+
+/// Hectopascal per inch of mercury
+const HPA_PER_INHG: f32 = 33.863_89;
+
+/// Meters per statute mile
+const METERS_PER_STATUTE_MILE: f32 = 1_609.344;
+
+/// Visibility reported for 10 km or more
+const MAX_VISIBILITY_METERS: f32 = 10_000.0;
+
+/// Groups ending the part of a report that is decoded
+const END_GROUPS: [&str; 6] = ["RMK", "TEMPO", "BECMG", "NOSIG", "FM", "PROB"];
+
+/// Parse digits only, fails on signs and other characters
+fn parse_digits<T: std::str::FromStr>(value: &str) -> Option<T> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    value.parse::<T>().ok()
+}
+
+/// Resolve a day of the month and a time to the matching date closest
+///  to the reference, as reports do not include the month.
+/// An hour of 24 is the end of the day.
+fn resolve_day_time(
+    day: u32,
+    hour: u32,
+    minute: u32,
+    reference: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if !(1..=31).contains(&day) || hour > 24 || minute > 59 {
+        return None;
+    }
+
+    let year = reference.format("%Y").to_string().parse::<i32>().ok()?;
+    let month = reference.format("%m").to_string().parse::<i32>().ok()?;
+
+    [-1, 0, 1]
+        .iter()
+        .filter_map(|offset| {
+            let index = year * 12 + (month - 1) + offset;
+            let (year, month) = (index.div_euclid(12), index.rem_euclid(12) + 1);
+            let (hour, extra) = match hour {
+                24 => (0, Duration::days(1)),
+                _ => (hour, Duration::zero()),
+            };
+
+            let value = format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:00Z",
+                year, month, day, hour, minute
+            );
+            DateTime::parse_from_rfc3339(&value)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc) + extra)
+        })
+        .min_by_key(|dt| (*dt - reference).num_seconds().abs())
+}
+
+/// Parse an issue time of format 'DDHHMMZ'
+fn parse_issue_time(group: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let digits = group.strip_suffix('Z')?;
+    if digits.len() != 6 || !digits.is_ascii() {
+        return None;
+    }
+
+    resolve_day_time(
+        parse_digits(&digits[0..2])?,
+        parse_digits(&digits[2..4])?,
+        parse_digits(&digits[4..6])?,
+        now,
+    )
+}
+
+/// Parse a validity period of format 'DDHH/DDHH'
+fn parse_validity(group: &str, issued: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let (from, to) = group.split_once('/')?;
+    if from.len() != 4 || to.len() != 4 || !group.is_ascii() {
+        return None;
+    }
+
+    let from = resolve_day_time(
+        parse_digits(&from[0..2])?,
+        parse_digits(&from[2..4])?,
+        0,
+        issued,
+    )?;
+    let to = resolve_day_time(parse_digits(&to[0..2])?, parse_digits(&to[2..4])?, 0, from)?;
+
+    (from < to).then_some((from, to))
+}
+
+/// Parse a wind group, for example '24015G25KT', 'VRB03KT' or '18005MPS'
+fn parse_wind(group: &str) -> Option<Wind> {
+    let (value, factor) = match group.strip_suffix("KT") {
+        Some(value) => (value, 1.0),
+        None => (group.strip_suffix("MPS")?, KNOTS_PER_MPS),
+    };
+
+    if value.len() < 5 || !value.is_ascii() {
+        return None;
+    }
+
+    let direction_degrees = match &value[0..3] {
+        "VRB" => None,
+        direction => Some(parse_digits::<u16>(direction).filter(|d| *d <= 360)?),
+    };
+
+    let (speed, gust) = match value[3..].split_once('G') {
+        Some((speed, gust)) => (speed, Some(gust)),
+        None => (&value[3..], None),
+    };
+
+    let speed_knots = parse_digits::<f32>(speed)? * factor;
+    let gust_knots = match gust {
+        Some(gust) => Some(parse_digits::<f32>(gust)? * factor),
+        None => None,
+    };
+
+    Some(Wind {
+        direction_degrees,
+        speed_knots,
+        gust_knots,
+    })
+}
+
+/// Parse a visibility in statute miles, for example '10SM', 'P6SM' or '1/2SM'
+fn parse_statute_miles(group: &str) -> Option<f32> {
+    let value = group.strip_suffix("SM")?;
+    let value = value.strip_prefix('P').unwrap_or(value);
+    let miles = match value.split_once('/') {
+        Some((numerator, denominator)) => {
+            parse_digits::<f32>(numerator)?
+                / parse_digits::<f32>(denominator).filter(|d| *d > 0.0)?
+        }
+        None => parse_digits::<f32>(value)?,
+    };
+
+    Some(miles * METERS_PER_STATUTE_MILE)
+}
+
+/// Parse a visibility in meters, for example '0800', '9999' is 10 km or more
+fn parse_meters(group: &str) -> Option<f32> {
+    match group {
+        "9999" => Some(MAX_VISIBILITY_METERS),
+        _ if group.len() == 4 => parse_digits::<f32>(group),
+        _ => None,
+    }
+}
+
+/// Parse a cloud layer, for example 'BKN015CB', returns the height in feet
+///  if the layer counts as a ceiling
+fn parse_ceiling(group: &str) -> Option<Option<u32>> {
+    if !group.is_ascii() {
+        return None;
+    }
+
+    let (cover, rest) = if let Some(rest) = group.strip_prefix("VV") {
+        ("VV", rest)
+    } else if group.len() >= 3 {
+        (&group[0..3], &group[3..])
+    } else {
+        return None;
+    };
+
+    if !["FEW", "SCT", "BKN", "OVC", "VV"].contains(&cover) || rest.len() < 3 {
+        return None;
+    }
+
+    let height = parse_digits::<u32>(&rest[0..3]).map(|hundreds| hundreds * 100);
+    if height.is_none() && &rest[0..3] != "///" {
+        return None;
+    }
+
+    match cover {
+        "BKN" | "OVC" | "VV" => Some(height),
+        _ => Some(None),
+    }
+}
+
+/// Parse a temperature, for example '12' or 'M03'
+fn parse_temperature(value: &str) -> Option<i32> {
+    if value.len() < 2 {
+        return None;
+    }
+
+    match value.strip_prefix('M') {
+        Some(value) => parse_digits::<i32>(value).map(|t| -t),
+        None => parse_digits::<i32>(value),
+    }
+}
+
+/// Parse the temperature and dew point group, for example '12/M03'
+fn parse_temperatures(group: &str) -> Option<(i32, Option<i32>)> {
+    let (temperature, dew_point) = group.split_once('/')?;
+    let temperature = parse_temperature(temperature)?;
+    let dew_point = match dew_point {
+        "" | "//" => None,
+        dew_point => Some(parse_temperature(dew_point)?),
+    };
+
+    Some((temperature, dew_point))
+}
+
+/// Parse an altimeter setting, for example 'Q1013' or 'A2992', in hectopascal
+fn parse_qnh(group: &str) -> Option<f32> {
+    if group.len() != 5 || !group.is_ascii() {
+        return None;
+    }
+
+    match &group[0..1] {
+        "Q" => parse_digits::<f32>(&group[1..]),
+        "A" => parse_digits::<f32>(&group[1..]).map(|hundredths| hundredths / 100.0 * HPA_PER_INHG),
+        _ => None,
+    }
+}
+
+/// Parse a METAR, SPECI or TAF.
+/// Day and time of the report are resolved to the closest date to `now`.
+pub fn parse_report(raw: &str, now: DateTime<Utc>) -> Result<WeatherReport, WeatherError> {
+    let raw = raw.split_whitespace().collect::<Vec<&str>>().join(" ");
+    let raw = raw.trim_end_matches('=').trim_end().to_string();
+    let mut groups = raw.split(' ').peekable();
+
+    let report_type = match groups.peek() {
+        Some(&"TAF") => {
+            groups.next();
+            WeatherReportType::Taf
+        }
+        Some(&"METAR") | Some(&"SPECI") => {
+            groups.next();
+            WeatherReportType::Metar
+        }
+        _ => WeatherReportType::Metar,
+    };
+
+    // amended or corrected reports
+    while let Some(&("AMD" | "COR")) = groups.peek() {
+        groups.next();
+    }
+
+    let station = groups
+        .next()
+        .filter(|station| station.len() == 4 && station.bytes().all(|b| b.is_ascii_alphanumeric()))
+        .ok_or(WeatherError::InvalidReport)?
+        .to_ascii_uppercase();
+
+    let issued = groups
+        .next()
+        .and_then(|group| parse_issue_time(group, now))
+        .ok_or(WeatherError::InvalidReport)?;
+
+    let validity = match report_type {
+        WeatherReportType::Taf => Some(
+            groups
+                .next()
+                .and_then(|group| parse_validity(group, issued))
+                .ok_or(WeatherError::InvalidReport)?,
+        ),
+        WeatherReportType::Metar => None,
+    };
+
+    let mut report = WeatherReport {
+        report_type,
+        station,
+        issued: issued.into(),
+        valid_from: validity.map(|(from, _)| from.into()),
+        valid_to: validity.map(|(_, to)| to.into()),
+        wind: None,
+        visibility_meters: None,
+        ceiling_feet: None,
+        qnh_hpa: None,
+        temperature_celsius: None,
+        dew_point_celsius: None,
+        raw: raw.clone(),
+    };
+
+    let mut whole_miles: Option<f32> = None;
+    for group in groups {
+        if END_GROUPS.iter().any(|end| group.starts_with(end)) {
+            break;
+        }
+
+        if group == "CAVOK" {
+            report.visibility_meters = Some(MAX_VISIBILITY_METERS);
+            continue;
+        }
+
+        if report.wind.is_none() {
+            if let Some(wind) = parse_wind(group) {
+                report.wind = Some(wind);
+                continue;
+            }
+        }
+
+        if report.visibility_meters.is_none() {
+            // whole miles followed by a fraction, for example '1 1/2SM'
+            if group.len() == 1 && whole_miles.is_none() {
+                if let Some(miles) = parse_digits::<f32>(group) {
+                    whole_miles = Some(miles);
+                    continue;
+                }
+            }
+
+            let visibility = parse_statute_miles(group)
+                .map(|meters| meters + whole_miles.unwrap_or(0.0) * METERS_PER_STATUTE_MILE)
+                .or_else(|| parse_meters(group));
+            if let Some(visibility) = visibility {
+                report.visibility_meters = Some(visibility.min(MAX_VISIBILITY_METERS));
+                continue;
+            }
+        }
+
+        if let Some(ceiling) = parse_ceiling(group) {
+            report.ceiling_feet = match (report.ceiling_feet, ceiling) {
+                (Some(current), Some(ceiling)) => Some(current.min(ceiling)),
+                (current, ceiling) => current.or(ceiling),
+            };
+            continue;
+        }
+
+        if report.temperature_celsius.is_none() {
+            if let Some((temperature, dew_point)) = parse_temperatures(group) {
+                report.temperature_celsius = Some(temperature);
+                report.dew_point_celsius = dew_point;
+                continue;
+            }
+        }
+
+        if report.qnh_hpa.is_none() {
+            report.qnh_hpa = parse_qnh(group);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Split a bulletin into reports.
+/// Reports end with '=', without it every line is a report and indented
+///  lines continue the previous report.
+pub fn split_reports(text: &str) -> Vec<String> {
+    if text.contains('=') {
+        return text
+            .split('=')
+            .map(|report| report.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .filter(|report| !report.is_empty())
+            .collect();
+    }
+
+    let mut reports: Vec<String> = vec![];
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match reports.last_mut() {
+            Some(report) if line.starts_with(char::is_whitespace) => {
+                report.push(' ');
+                report.push_str(line.trim());
+            }
+            _ => reports.push(line.trim().to_string()),
+        }
+    }
+
+    reports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-03-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn time(value: &str) -> Option<DateTime<Utc>> {
+        Some(
+            DateTime::parse_from_rfc3339(value)
+                .unwrap()
+                .with_timezone(&Utc),
+        )
+    }
+
+    #[test]
+    fn test_resolve_day_time() {
+        assert_eq!(
+            resolve_day_time(1, 9, 55, now()),
+            time("2024-03-01T09:55:00Z")
+        );
+
+        // end of the previous month
+        assert_eq!(
+            resolve_day_time(29, 23, 50, now()),
+            time("2024-02-29T23:50:00Z")
+        );
+
+        // the end of a day
+        assert_eq!(
+            resolve_day_time(1, 24, 0, now()),
+            time("2024-03-02T00:00:00Z")
+        );
+
+        assert_eq!(resolve_day_time(32, 0, 0, now()), None);
+        assert_eq!(resolve_day_time(1, 25, 0, now()), None);
+        assert_eq!(resolve_day_time(1, 0, 60, now()), None);
+    }
+
+    #[test]
+    fn test_parse_issue_time_and_validity() {
+        assert_eq!(
+            parse_issue_time("010925Z", now()),
+            time("2024-03-01T09:25:00Z")
+        );
+        assert_eq!(parse_issue_time("aébéZ", now()), None);
+        assert_eq!(parse_issue_time("0é0925Z", now()), None);
+
+        let issued = time("2024-03-01T08:00:00Z").unwrap();
+        assert_eq!(
+            parse_validity("0109/0215", issued),
+            Some((
+                time("2024-03-01T09:00:00Z").unwrap(),
+                time("2024-03-02T15:00:00Z").unwrap()
+            ))
+        );
+        assert_eq!(parse_validity("é12/0112", issued), None);
+        assert_eq!(parse_validity("0109/é12", issued), None);
+    }
+
+    #[test]
+    fn test_parse_wind() {
+        assert_eq!(
+            parse_wind("24015G25KT"),
+            Some(Wind {
+                direction_degrees: Some(240),
+                speed_knots: 15.0,
+                gust_knots: Some(25.0),
+            })
+        );
+        assert_eq!(
+            parse_wind("VRB03KT"),
+            Some(Wind {
+                direction_degrees: None,
+                speed_knots: 3.0,
+                gust_knots: None,
+            })
+        );
+
+        let wind = parse_wind("18010MPS").unwrap();
+        assert!((wind.speed_knots - 19.44).abs() < 0.01);
+
+        assert_eq!(parse_wind("37010KT"), None);
+        assert_eq!(parse_wind("240KT"), None);
+        assert_eq!(parse_wind("9999"), None);
+        assert_eq!(parse_wind("24é15KT"), None);
+        assert_eq!(parse_wind("éé15KT"), None);
+    }
+
+    #[test]
+    fn test_parse_visibility() {
+        assert_eq!(parse_meters("0800"), Some(800.0));
+        assert_eq!(parse_meters("9999"), Some(10_000.0));
+        assert_eq!(parse_meters("800"), None);
+        assert_eq!(
+            parse_statute_miles("P6SM"),
+            Some(6.0 * METERS_PER_STATUTE_MILE)
+        );
+        assert_eq!(
+            parse_statute_miles("1/2SM"),
+            Some(0.5 * METERS_PER_STATUTE_MILE)
+        );
+        assert_eq!(parse_statute_miles("1/0SM"), None);
+        assert_eq!(parse_statute_miles("10KM"), None);
+    }
+
+    #[test]
+    fn test_parse_ceiling() {
+        assert_eq!(parse_ceiling("FEW020"), Some(None));
+        assert_eq!(parse_ceiling("BKN015CB"), Some(Some(1_500)));
+        assert_eq!(parse_ceiling("OVC///"), Some(None));
+        assert_eq!(parse_ceiling("VV002"), Some(Some(200)));
+        assert_eq!(parse_ceiling("NSC"), None);
+        assert_eq!(parse_ceiling("BKN"), None);
+        assert_eq!(parse_ceiling("BKé015"), None);
+        assert_eq!(parse_ceiling("BKN0é5"), None);
+    }
+
+    #[test]
+    fn test_parse_temperatures_and_qnh() {
+        assert_eq!(parse_temperatures("12/M03"), Some((12, Some(-3))));
+        assert_eq!(parse_temperatures("M01/M05"), Some((-1, Some(-5))));
+        assert_eq!(parse_temperatures("12/"), Some((12, None)));
+        assert_eq!(parse_temperatures("1/2SM"), None);
+
+        assert_eq!(parse_qnh("Q1013"), Some(1013.0));
+        let qnh = parse_qnh("A2992").unwrap();
+        assert!((qnh - 1013.2).abs() < 0.1);
+        assert_eq!(parse_qnh("Q101"), None);
+        assert_eq!(parse_qnh("é013"), None);
+        assert_eq!(parse_qnh("Q10é"), None);
+    }
+
+    #[test]
+    fn test_parse_metar() {
+        let report = parse_report(
+            "METAR EHAM 010925Z 24015G25KT 9999 FEW020 BKN035 OVC080 12/M03 Q1013 NOSIG=",
+            now(),
+        )
+        .unwrap();
+        assert_eq!(report.report_type, WeatherReportType::Metar);
+        assert_eq!(report.station, "EHAM");
+        assert_eq!(report.issued.to_datetime(), time("2024-03-01T09:25:00Z"));
+        assert!(report.valid_from.is_none());
+        assert_eq!(report.wind.unwrap().gust_knots, Some(25.0));
+        assert_eq!(report.visibility_meters, Some(10_000.0));
+        assert_eq!(report.ceiling_feet, Some(3_500));
+        assert_eq!(report.temperature_celsius, Some(12));
+        assert_eq!(report.dew_point_celsius, Some(-3));
+        assert_eq!(report.qnh_hpa, Some(1013.0));
+        assert!(report.raw.ends_with("NOSIG"));
+    }
+
+    #[test]
+    fn test_parse_us_metar() {
+        let report = parse_report(
+            "KJFK 010951Z 31008KT 1 1/2SM BR OVC004 M01/M02 A2992 RMK AO2 SLP133",
+            now(),
+        )
+        .unwrap();
+        assert_eq!(report.station, "KJFK");
+        let visibility = report.visibility_meters.unwrap();
+        assert!((visibility - 1.5 * METERS_PER_STATUTE_MILE).abs() < 0.1);
+        assert_eq!(report.ceiling_feet, Some(400));
+        assert_eq!(report.temperature_celsius, Some(-1));
+        assert!((report.qnh_hpa.unwrap() - 1013.2).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_parse_lowercase_station() {
+        let report = parse_report("METAR eham 010925Z 24015KT 9999 Q1013", now()).unwrap();
+        assert_eq!(report.station, "EHAM");
+    }
+
+    #[test]
+    fn test_parse_cavok() {
+        let report = parse_report("SPECI LFPG 010930Z 18005KT CAVOK 15/08 Q1020", now()).unwrap();
+        assert_eq!(report.visibility_meters, Some(10_000.0));
+        assert_eq!(report.ceiling_feet, None);
+    }
+
+    #[test]
+    fn test_parse_taf() {
+        let report = parse_report(
+            "TAF AMD EHAM 010800Z 0109/0215 23012KT 8000 BKN012
+                TEMPO 0112/0116 4000 SHRA BKN008",
+            now(),
+        )
+        .unwrap();
+        assert_eq!(report.report_type, WeatherReportType::Taf);
+        assert_eq!(report.station, "EHAM");
+        assert_eq!(
+            report.valid_from.unwrap().to_datetime(),
+            time("2024-03-01T09:00:00Z")
+        );
+        assert_eq!(
+            report.valid_to.unwrap().to_datetime(),
+            time("2024-03-02T15:00:00Z")
+        );
+        assert_eq!(report.visibility_meters, Some(8_000.0));
+
+        // change groups are not decoded
+        assert_eq!(report.ceiling_feet, Some(1_200));
+        assert!(report.raw.contains("TEMPO"));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(
+            parse_report("", now()).unwrap_err(),
+            WeatherError::InvalidReport
+        );
+        assert_eq!(
+            parse_report("METAR EHAM", now()).unwrap_err(),
+            WeatherError::InvalidReport
+        );
+        assert_eq!(
+            parse_report("METAR EHAM 011025 24015KT", now()).unwrap_err(),
+            WeatherError::InvalidReport
+        );
+        assert_eq!(
+            parse_report("TAF EHAM 010800Z 23012KT", now()).unwrap_err(),
+            WeatherError::InvalidReport
+        );
+        assert_eq!(
+            parse_report("METAR EHAM aébéZ", now()).unwrap_err(),
+            WeatherError::InvalidReport
+        );
+        assert_eq!(
+            parse_report("TAF EHAM 010800Z é123/0112", now()).unwrap_err(),
+            WeatherError::InvalidReport
+        );
+
+        // multibyte groups in the body are skipped
+        let report = parse_report("METAR EHAM 011000Z é123 24é15KT BKé015 Qé013", now()).unwrap();
+        assert!(report.wind.is_none());
+        assert!(report.ceiling_feet.is_none());
+        assert!(report.qnh_hpa.is_none());
+    }
+
+    #[test]
+    fn test_split_reports() {
+        let reports = split_reports("METAR EHAM 010925Z 24015KT=\nMETAR EHRD\n 010925Z 22010KT=\n");
+        assert_eq!(
+            reports,
+            vec!["METAR EHAM 010925Z 24015KT", "METAR EHRD 010925Z 22010KT"]
+        );
+
+        let reports =
+            split_reports("EHAM 010925Z 24015KT\n\nTAF EHAM 010800Z 0109/0215\n  23012KT 8000\n");
+        assert_eq!(
+            reports,
+            vec![
+                "EHAM 010925Z 24015KT",
+                "TAF EHAM 010800Z 0109/0215 23012KT 8000"
+            ]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Storage of the latest meteorological reports by station
+
+use super::parser::{parse_report, split_reports};
+use super::WeatherError;
+use crate::rest::api::rest_types::*;
+use crate::rest::api::uss::Window;
+use lib_common::time::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+
+/// The location of a reporting station
+#[derive(Debug, Clone, Deserialize)]
+struct WeatherStationLocation {
+    /// ICAO identifier of the station
+    station: String,
+
+    /// Location of the station
+    location: LatLngPoint,
+}
+
+/// The latest METAR and TAF of every station
+#[derive(Debug)]
+pub struct WeatherStore {
+    /// Locations of the known stations
+    locations: HashMap<String, LatLngPoint>,
+
+    /// Latest reports by station
+    stations: Mutex<BTreeMap<String, StationWeather>>,
+}
+
+impl WeatherStore {
+    /// Create an empty storage with the locations of the stations
+    pub fn new(locations: HashMap<String, LatLngPoint>) -> Self {
+        WeatherStore {
+            locations,
+            stations: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Create an empty storage with the station locations stored in the
+    ///  given file. Without locations, weather can only be requested by station.
+    pub fn load(path: &str) -> Self {
+        let locations = match std::fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str::<Vec<WeatherStationLocation>>(&content) {
+                Ok(locations) => locations,
+                Err(e) => {
+                    rest_error!("invalid weather stations file {}: {}", path, e);
+                    vec![]
+                }
+            },
+            Err(e) => {
+                rest_info!("no weather stations loaded from {}: {}", path, e);
+                vec![]
+            }
+        };
+
+        let locations = locations
+            .into_iter()
+            .map(|location| (location.station.to_uppercase(), location.location))
+            .collect::<HashMap<String, LatLngPoint>>();
+
+        rest_info!("loaded {} weather stations from {}.", locations.len(), path);
+        Self::new(locations)
+    }
+
+    /// Lock the stations, recovering from a poisoned lock as every
+    ///  modification leaves the map in a consistent state
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, StationWeather>> {
+        self.stations.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Store a report, unless a more recent report of the same kind is known
+    pub fn insert(&self, report: WeatherReport) {
+        let mut stations = self.lock();
+        let station = stations
+            .entry(report.station.clone())
+            .or_insert_with(|| StationWeather {
+                station: report.station.clone(),
+                location: self.locations.get(&report.station).copied(),
+                metar: None,
+                taf: None,
+            });
+
+        let current = match report.report_type {
+            WeatherReportType::Metar => &mut station.metar,
+            WeatherReportType::Taf => &mut station.taf,
+        };
+
+        let newer = match current {
+            Some(current) => report.issued.to_datetime() >= current.issued.to_datetime(),
+            None => true,
+        };
+
+        if newer {
+            *current = Some(report);
+        }
+    }
+
+    /// Parse and store the reports of a bulletin.
+    /// Returns the stored reports and the number of invalid reports.
+    pub fn ingest(&self, text: &str, now: DateTime<Utc>) -> (Vec<WeatherReport>, usize) {
+        let mut accepted = vec![];
+        let mut rejected = 0;
+        for raw in split_reports(text) {
+            match parse_report(&raw, now) {
+                Ok(report) => {
+                    self.insert(report.clone());
+                    accepted.push(report);
+                }
+                Err(e) => {
+                    rest_warn!("skipping report '{}': {}", raw, e);
+                    rejected += 1;
+                }
+            }
+        }
+
+        (accepted, rejected)
+    }
+
+    /// The latest reports of a station
+    pub fn station(&self, station: &str) -> Result<StationWeather, WeatherError> {
+        self.lock()
+            .get(&station.to_uppercase())
+            .cloned()
+            .ok_or(WeatherError::NotFound)
+    }
+
+    /// The latest reports of the stations located in the window, ordered by station
+    pub fn stations_in(&self, window: &Window) -> Vec<StationWeather> {
+        let (min_lon, min_lat, max_lon, max_lat) = window.bounds();
+        self.lock()
+            .values()
+            .filter(|station| {
+                station.location.iter().any(|location| {
+                    (min_lon..=max_lon).contains(&location.lng)
+                        && (min_lat..=max_lat).contains(&location.lat)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn store() -> WeatherStore {
+        let locations = HashMap::from([(
+            "EHAM".to_string(),
+            LatLngPoint {
+                lat: 52.31,
+                lng: 4.76,
+            },
+        )]);
+        WeatherStore::new(locations)
+    }
+
+    pub(crate) const BULLETIN: &str = "METAR EHAM 010925Z 24015G25KT 9999 BKN035 12/M03 Q1013=
+TAF EHAM 010800Z 0109/0215 23012KT 8000 BKN012=
+METAR LFPG 010930Z 18005KT CAVOK 15/08 Q1020=
+METAR XX=";
+
+    #[test]
+    fn test_ingest() {
+        let store = store();
+        let (accepted, rejected) = store.ingest(BULLETIN, Utc::now());
+        assert_eq!(accepted.len(), 3);
+        assert_eq!(rejected, 1);
+
+        let eham = store.station("eham").unwrap();
+        assert_eq!(eham.location.unwrap().lat, 52.31);
+        assert_eq!(eham.metar.unwrap().qnh_hpa, Some(1013.0));
+        assert_eq!(eham.taf.unwrap().visibility_meters, Some(8000.0));
+
+        let lfpg = store.station("LFPG").unwrap();
+        assert!(lfpg.location.is_none());
+        assert!(lfpg.taf.is_none());
+
+        assert_eq!(store.station("EGLL").unwrap_err(), WeatherError::NotFound);
+    }
+
+    #[test]
+    fn test_insert_keeps_latest() {
+        let store = store();
+        let now = Utc::now();
+        let (newer, _) = store.ingest("METAR EHAM 010925Z 24015KT Q1013", now);
+        store.ingest("METAR EHAM 010855Z 24015KT Q1010", now);
+        assert_eq!(
+            store.station("EHAM").unwrap().metar.unwrap().raw,
+            newer[0].raw
+        );
+
+        store.ingest("METAR EHAM 010955Z 24015KT Q1015", now);
+        assert_eq!(
+            store.station("EHAM").unwrap().metar.unwrap().qnh_hpa,
+            Some(1015.0)
+        );
+    }
+
+    #[test]
+    fn test_stations_in() {
+        let store = store();
+        store.ingest(BULLETIN, Utc::now());
+
+        let window = Window {
+            lat1: 52.0,
+            lon1: 4.5,
+            lat2: 52.5,
+            lon2: 5.0,
+        };
+        let stations = store.stations_in(&window);
+        assert_eq!(stations.len(), 1);
+        assert_eq!(stations[0].station, "EHAM");
+
+        let window = Window {
+            lat1: 48.0,
+            lon1: 2.0,
+            lat2: 49.0,
+            lon2: 3.0,
+        };
+        assert!(store.stations_in(&window).is_empty());
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir()
+            .join(format!(
+                "svc-discovery-weather-stations-{}.json",
+                rand::random::<u64>()
+            ))
+            .to_string_lossy()
+            .to_string();
+        std::fs::write(
+            &path,
+            r#"[{"station": "eham", "location": {"lat": 52.31, "lng": 4.76}}]"#,
+        )
+        .unwrap();
+
+        let store = WeatherStore::load(&path);
+        store.ingest("METAR EHAM 010925Z 24015KT", Utc::now());
+        assert!(store.station("EHAM").unwrap().location.is_some());
+        std::fs::remove_file(&path).unwrap();
+
+        let store = WeatherStore::load("/nonsense/stations.json");
+        store.ingest("METAR EHAM 010925Z 24015KT", Utc::now());
+        assert!(store.station("EHAM").unwrap().location.is_none());
+    }
+}